@@ -0,0 +1,279 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! autofs wraps the kernel autofs interface used by the automount unit.
+//! The autofs filesystem is mounted on Where= in direct mode, the kernel writes a packet
+//! to the pipe when Where= is accessed or expires, and waits for the answer sent by the
+//! miscellaneous device /dev/autofs.
+//! For the definition of the structures and constant values, refer to <linux/auto_fs.h>
+//! and <linux/auto_dev-ioctl.h>.
+use nix::errno::Errno;
+use nix::fcntl::{self, OFlag};
+use nix::mount::{self, MntFlags, MsFlags};
+use nix::sys::stat::{self, Mode};
+use nix::unistd;
+use nix::{ioctl_readwrite, ioctl_write_ptr};
+use std::os::unix::prelude::RawFd;
+
+const DEV_AUTOFS: &str = "/dev/autofs";
+
+pub(super) const AUTOFS_PROTO_VERSION: i32 = 5;
+
+/// the mount point is accessed, the automount unit is asked to mount it
+pub(super) const AUTOFS_PTYPE_MISSING_DIRECT: i32 = 5;
+/// the mount point is idle, the automount unit is asked to unmount it
+pub(super) const AUTOFS_PTYPE_EXPIRE_DIRECT: i32 = 6;
+
+/// the size of struct autofs_v5_packet, which is padded to 8 bytes
+pub(super) const AUTOFS_PACKET_SIZE: usize = 304;
+
+const AUTOFS_IOCTL: u8 = 0x93;
+const AUTOFS_IOC_EXPIRE_MULTI: u8 = 0x66;
+const AUTOFS_DEV_IOCTL_OPENMOUNT: u8 = 0x74;
+const AUTOFS_DEV_IOCTL_READY: u8 = 0x76;
+const AUTOFS_DEV_IOCTL_FAIL: u8 = 0x77;
+const AUTOFS_DEV_IOCTL_TIMEOUT: u8 = 0x7a;
+
+const AUTOFS_DEV_IOCTL_VERSION_MAJOR: u32 = 1;
+const AUTOFS_DEV_IOCTL_VERSION_MINOR: u32 = 1;
+
+/// only expire the leaves of the mount tree, the mount point itself for the direct mount
+const AUTOFS_EXP_LEAVES: libc::c_int = 0x02;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+union AutofsDevIoctlArgs {
+    /// devid of openmount, token of ready, token and status of fail
+    arg: [u32; 2],
+    /// timeout of the mount point in seconds
+    timeout: u64,
+}
+
+/// struct autofs_dev_ioctl, the path is appended after it for openmount
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct AutofsDevIoctl {
+    ver_major: u32,
+    ver_minor: u32,
+    size: u32,
+    ioctlfd: i32,
+    args: AutofsDevIoctlArgs,
+}
+
+#[repr(C)]
+struct AutofsDevIoctlPath {
+    param: AutofsDevIoctl,
+    path: [u8; libc::PATH_MAX as usize],
+}
+
+impl AutofsDevIoctl {
+    fn new(ioctlfd: RawFd, arg: [u32; 2]) -> Self {
+        AutofsDevIoctl {
+            ver_major: AUTOFS_DEV_IOCTL_VERSION_MAJOR,
+            ver_minor: AUTOFS_DEV_IOCTL_VERSION_MINOR,
+            size: std::mem::size_of::<AutofsDevIoctl>() as u32,
+            ioctlfd,
+            args: AutofsDevIoctlArgs { arg },
+        }
+    }
+}
+
+ioctl_readwrite!(
+    /// open the ioctl fd of the autofs mount point
+    autofs_dev_ioctl_openmount,
+    AUTOFS_IOCTL,
+    AUTOFS_DEV_IOCTL_OPENMOUNT,
+    AutofsDevIoctl
+);
+
+ioctl_readwrite!(
+    /// wake up the processes waiting for the token successfully
+    autofs_dev_ioctl_ready,
+    AUTOFS_IOCTL,
+    AUTOFS_DEV_IOCTL_READY,
+    AutofsDevIoctl
+);
+
+ioctl_readwrite!(
+    /// wake up the processes waiting for the token with an error
+    autofs_dev_ioctl_fail,
+    AUTOFS_IOCTL,
+    AUTOFS_DEV_IOCTL_FAIL,
+    AutofsDevIoctl
+);
+
+ioctl_readwrite!(
+    /// set the expire timeout of the mount point
+    autofs_dev_ioctl_timeout,
+    AUTOFS_IOCTL,
+    AUTOFS_DEV_IOCTL_TIMEOUT,
+    AutofsDevIoctl
+);
+
+ioctl_write_ptr!(
+    /// ask the kernel to send the expire packets of the idle mount points
+    autofs_ioc_expire_multi,
+    AUTOFS_IOCTL,
+    AUTOFS_IOC_EXPIRE_MULTI,
+    libc::c_int
+);
+
+/// run the command of /dev/autofs
+fn dev_autofs_ioctl<F>(f: F) -> nix::Result<()>
+where
+    F: FnOnce(RawFd) -> nix::Result<libc::c_int>,
+{
+    let fd = fcntl::open(
+        DEV_AUTOFS,
+        OFlag::O_RDONLY | OFlag::O_CLOEXEC,
+        Mode::empty(),
+    )?;
+    let ret = f(fd);
+    let _ = unistd::close(fd);
+    ret.map(|_| ())
+}
+
+/// mount the autofs on the mount point in direct mode, the packets are written to the pipe
+pub(super) fn autofs_mount(mount_where: &str, pipe_fd: RawFd) -> nix::Result<()> {
+    let options = format!(
+        "fd={},pgrp={},minproto={},maxproto={},direct",
+        pipe_fd,
+        unistd::getpgrp(),
+        AUTOFS_PROTO_VERSION,
+        AUTOFS_PROTO_VERSION
+    );
+    mount::mount(
+        Some("sysmaster"),
+        mount_where,
+        Some("autofs"),
+        MsFlags::empty(),
+        Some(options.as_str()),
+    )
+}
+
+/// unmount the autofs and the filesystems mounted on it
+pub(super) fn autofs_umount(mount_where: &str) {
+    loop {
+        match mount::umount2(
+            mount_where,
+            MntFlags::MNT_DETACH | MntFlags::UMOUNT_NOFOLLOW,
+        ) {
+            Ok(_) => continue,
+            Err(Errno::EINVAL) | Err(Errno::ENOENT) => break,
+            Err(e) => {
+                log::warn!("Failed to unmount {}: {}", mount_where, e);
+                break;
+            }
+        }
+    }
+}
+
+/// open the ioctl fd of the autofs mounted on the mount point
+pub(super) fn autofs_open_ioctl_fd(mount_where: &str) -> nix::Result<RawFd> {
+    let dev_id = stat::stat(mount_where)?.st_dev as u32;
+    let bytes = mount_where.as_bytes();
+    if bytes.len() >= libc::PATH_MAX as usize {
+        return Err(Errno::ENAMETOOLONG);
+    }
+
+    let mut param = AutofsDevIoctlPath {
+        param: AutofsDevIoctl::new(-1, [dev_id, 0]),
+        path: [0; libc::PATH_MAX as usize],
+    };
+    param.param.size += bytes.len() as u32 + 1;
+    param.path[..bytes.len()].copy_from_slice(bytes);
+
+    dev_autofs_ioctl(|fd| unsafe {
+        autofs_dev_ioctl_openmount(fd, &mut param as *mut AutofsDevIoctlPath as *mut _)
+    })?;
+    Ok(param.param.ioctlfd)
+}
+
+/// set the idle timeout of the mount point, it is expired after it is not used for the timeout
+pub(super) fn autofs_set_timeout(ioctl_fd: RawFd, sec: u64) -> nix::Result<()> {
+    let mut param = AutofsDevIoctl::new(ioctl_fd, [0, 0]);
+    param.args.timeout = sec;
+    dev_autofs_ioctl(|fd| unsafe { autofs_dev_ioctl_timeout(fd, &mut param) })
+}
+
+/// answer the packet of the token, status is 0 for success, or the negative errno
+pub(super) fn autofs_send_ready(ioctl_fd: RawFd, token: u32, status: i32) -> nix::Result<()> {
+    if status == 0 {
+        let mut param = AutofsDevIoctl::new(ioctl_fd, [token, 0]);
+        dev_autofs_ioctl(|fd| unsafe { autofs_dev_ioctl_ready(fd, &mut param) })
+    } else {
+        let mut param = AutofsDevIoctl::new(ioctl_fd, [token, status as u32]);
+        dev_autofs_ioctl(|fd| unsafe { autofs_dev_ioctl_fail(fd, &mut param) })
+    }
+}
+
+/// ask the kernel to expire the mount point if it is idle, it is blocked until
+/// the expire packet is answered, and returns EAGAIN if nothing is expired.
+pub(super) fn autofs_expire(ioctl_fd: RawFd) -> nix::Result<()> {
+    unsafe { autofs_ioc_expire_multi(ioctl_fd, &AUTOFS_EXP_LEAVES) }.map(|_| ())
+}
+
+/// the fields of struct autofs_v5_packet used by the automount unit
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct AutofsPacket {
+    pub(super) proto_version: i32,
+    pub(super) ptype: i32,
+    pub(super) token: u32,
+    pub(super) pid: u32,
+}
+
+impl AutofsPacket {
+    /// parse the packet read from the pipe
+    pub(super) fn parse(buf: &[u8]) -> Option<AutofsPacket> {
+        if buf.len() < AUTOFS_PACKET_SIZE {
+            return None;
+        }
+
+        let u32_at = |off: usize| u32::from_ne_bytes(buf[off..off + 4].try_into().unwrap());
+        Some(AutofsPacket {
+            proto_version: u32_at(0) as i32,
+            ptype: u32_at(4) as i32,
+            token: u32_at(8),
+            pid: u32_at(32),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autofs_packet_parse() {
+        assert_eq!(AutofsPacket::parse(&[0; 16]), None);
+
+        let mut buf = [0u8; AUTOFS_PACKET_SIZE];
+        buf[0..4].copy_from_slice(&5i32.to_ne_bytes());
+        buf[4..8].copy_from_slice(&AUTOFS_PTYPE_MISSING_DIRECT.to_ne_bytes());
+        buf[8..12].copy_from_slice(&42u32.to_ne_bytes());
+        buf[32..36].copy_from_slice(&1000u32.to_ne_bytes());
+        assert_eq!(
+            AutofsPacket::parse(&buf),
+            Some(AutofsPacket {
+                proto_version: 5,
+                ptype: AUTOFS_PTYPE_MISSING_DIRECT,
+                token: 42,
+                pid: 1000,
+            })
+        );
+    }
+
+    #[test]
+    fn test_autofs_dev_ioctl_size() {
+        assert_eq!(std::mem::size_of::<AutofsDevIoctl>(), 24);
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! automount_config mod load the conf file list and convert it to structure which is defined in this mod.
+//!
+#![allow(non_snake_case)]
+use super::comm::AutomountUnitComm;
+use super::rentry::SectionAutomount;
+use basic::unit_name::unit_name_from_path;
+use confique::Config;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use sysmaster::error::*;
+use sysmaster::rel::ReStation;
+
+#[derive(Config, Default, Debug)]
+pub(super) struct AutomountConfigData {
+    #[config(nested)]
+    pub Automount: SectionAutomount,
+}
+
+impl AutomountConfigData {
+    pub(self) fn new(Automount: SectionAutomount) -> AutomountConfigData {
+        AutomountConfigData { Automount }
+    }
+}
+
+pub(super) struct AutomountConfig {
+    // associated objects
+    comm: Rc<AutomountUnitComm>,
+
+    // owned objects
+    data: Rc<RefCell<AutomountConfigData>>,
+}
+
+impl ReStation for AutomountConfig {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, reload: bool) {
+        if reload {
+            return;
+        }
+        if let Some(conf) = self.comm.rentry_conf_get() {
+            self.data.replace(AutomountConfigData::new(conf));
+        }
+    }
+
+    fn db_insert(&self) {
+        self.comm.rentry_conf_insert(&self.data.borrow().Automount);
+    }
+
+    // reload: no external connections, no entry
+}
+
+impl AutomountConfig {
+    pub(super) fn new(commr: &Rc<AutomountUnitComm>) -> Self {
+        AutomountConfig {
+            comm: Rc::clone(commr),
+            data: Rc::new(RefCell::new(AutomountConfigData::default())),
+        }
+    }
+
+    pub(super) fn load(&self, paths: Vec<PathBuf>, update: bool) -> Result<()> {
+        let mut builder = AutomountConfigData::builder().env();
+        for v in paths {
+            builder = builder.file(v);
+        }
+        let data = builder.load().context(ConfiqueSnafu)?;
+        self.verify(&data.Automount)?;
+
+        *self.data.borrow_mut() = data;
+        if !self.comm.um().load_unit_success(&self.mount_unit()) {
+            return Err(format!("failed to load unit {}", self.mount_unit()).into());
+        }
+
+        if update {
+            self.db_update();
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<AutomountConfigData>> {
+        self.data.clone()
+    }
+
+    pub(super) fn automount_where(&self) -> String {
+        self.data.borrow().Automount.Where.clone()
+    }
+
+    /// the mount unit started on access, like /mnt/data => mnt-data.mount
+    pub(super) fn mount_unit(&self) -> String {
+        unit_name_from_path(&self.automount_where(), ".mount")
+    }
+
+    fn verify(&self, automount: &SectionAutomount) -> Result<()> {
+        if !automount.Where.starts_with('/') {
+            return Err(format!("Where={} is not an absolute path", automount.Where).into());
+        }
+        if automount.Where.trim_end_matches('/').is_empty() {
+            return Err("Where=/ can't be automounted".to_string().into());
+        }
+
+        let unit_name = unit_name_from_path(&automount.Where, ".automount");
+        let owner_id = self
+            .comm
+            .owner()
+            .map_or(String::new(), |u| u.id().to_string());
+        if unit_name != owner_id {
+            return Err(format!(
+                "Where={} doesn't match the unit name {}",
+                automount.Where, owner_id
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! # Automount is used to mount a filesystem on demand.
+//!  The autofs filesystem is mounted on Where= when the automount is started, the mount unit
+//!  of Where= is started when it is accessed for the first time, like mnt-data.automount starts
+//!  mnt-data.mount. The mount point is unmounted after it is idle for TimeoutIdleSec=.
+//! #  Example:
+//! ``` toml
+//!  [Unit]
+//!  Description="automount the data disk"
+//!
+//!  [Automount]
+//!  Where="/mnt/data"
+//!  TimeoutIdleSec=300
+//! ```
+//! ##  Automatic dependency
+//!
+//! ###  Implicit dependency
+//!  The automount has Before= and Triggers= dependencies on the mount unit of Where=.
+//!
+//! ###  Default Dependency
+//!  If DefaultDependencies=true is set, the following dependencies will be added by default:
+//!  Conflicts="shutdown.target", Before="shutdown.target", Before="local-fs.target"
+
+// dependency: automount_base -> automount_rentry -> automount_comm -> {automount_mng, automount_config} -> automount_unit -> automount_manager
+mod autofs;
+mod base;
+mod comm;
+mod config;
+mod manager;
+mod mng;
+mod rentry;
+mod unit;
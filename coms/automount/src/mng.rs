@@ -0,0 +1,751 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! is the core of the automount unit, mount the autofs on Where= and start the mount unit on access
+//!
+use super::autofs::{
+    self, AutofsPacket, AUTOFS_PACKET_SIZE, AUTOFS_PROTO_VERSION, AUTOFS_PTYPE_EXPIRE_DIRECT,
+    AUTOFS_PTYPE_MISSING_DIRECT,
+};
+use super::comm::AutomountUnitComm;
+use super::config::AutomountConfig;
+use super::rentry::{AutomountResult, AutomountState};
+use basic::{fd_util, IN_SET};
+use event::{EventState, EventType, Events, Source};
+use nix::errno::Errno;
+use nix::fcntl::OFlag;
+use nix::unistd;
+use std::cell::RefCell;
+use std::fs;
+use std::os::unix::prelude::RawFd;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use sysmaster::error::*;
+use sysmaster::rel::ReStation;
+use sysmaster::unit::{UnitActiveState, UnitNotifyFlags};
+
+const USEC_PER_SEC: u64 = 1_000_000;
+
+impl AutomountState {
+    fn to_unit_state(self) -> UnitActiveState {
+        match self {
+            AutomountState::Dead | AutomountState::StateMax => UnitActiveState::UnitInActive,
+            AutomountState::Waiting | AutomountState::Running => UnitActiveState::UnitActive,
+            AutomountState::Failed => UnitActiveState::UnitFailed,
+        }
+    }
+}
+
+pub(super) struct AutomountMng {
+    // associated objects
+    comm: Rc<AutomountUnitComm>,
+    config: Rc<AutomountConfig>,
+
+    // owned objects
+    state: RefCell<AutomountState>,
+    result: RefCell<AutomountResult>,
+    // the ioctl fd of the autofs, the read end of the pipe is owned by the pipe source
+    ioctl_fd: RefCell<RawFd>,
+    // the tokens of the packets waiting for the mount unit to be mounted
+    tokens: RefCell<Vec<u32>>,
+    // the tokens of the packets waiting for the mount unit to be unmounted
+    expire_tokens: RefCell<Vec<u32>>,
+    pipe: Rc<AutomountPipe>,
+    pipe_added: RefCell<bool>,
+    timer: Rc<AutomountTimer>,
+    timer_armed: RefCell<bool>,
+    expiring: Arc<AtomicBool>,
+}
+
+impl ReStation for AutomountMng {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, _reload: bool) {
+        if let Some((state, result, pipe_fd, ioctl_fd, tokens, expire_tokens)) =
+            self.comm.rentry_mng_get()
+        {
+            *self.state.borrow_mut() = state;
+            *self.result.borrow_mut() = result;
+            self.pipe.set_fd(self.take_fd(pipe_fd));
+            *self.ioctl_fd.borrow_mut() = self.take_fd(ioctl_fd);
+            *self.tokens.borrow_mut() = tokens;
+            *self.expire_tokens.borrow_mut() = expire_tokens;
+        }
+    }
+
+    fn db_insert(&self) {
+        self.comm.rentry_mng_insert(
+            self.state(),
+            self.result(),
+            self.pipe.fd(),
+            *self.ioctl_fd.borrow(),
+            self.tokens.borrow().clone(),
+            self.expire_tokens.borrow().clone(),
+        );
+    }
+
+    // reload: entry-only
+    fn entry_coldplug(&self) {
+        if !IN_SET!(
+            self.state(),
+            AutomountState::Waiting,
+            AutomountState::Running
+        ) {
+            return;
+        }
+
+        if let Err(e) = self.watch_pipe() {
+            log::error!("Failed to rewatch the autofs pipe of {}: {}", self.id(), e);
+        }
+        if self.state() == AutomountState::Running {
+            self.start_expire();
+        }
+    }
+
+    // the fds are retained across reloading and reexecuting, only release the event sources here,
+    // they are closed when the unit is stopped.
+    fn entry_clear(&self) {
+        self.unwatch_pipe();
+        self.stop_expire();
+    }
+}
+
+impl AutomountMng {
+    pub(super) fn new(commr: &Rc<AutomountUnitComm>, configr: &Rc<AutomountConfig>) -> Self {
+        AutomountMng {
+            comm: Rc::clone(commr),
+            config: Rc::clone(configr),
+            state: RefCell::new(AutomountState::StateMax),
+            result: RefCell::new(AutomountResult::Success),
+            ioctl_fd: RefCell::new(-1),
+            tokens: RefCell::new(Vec::new()),
+            expire_tokens: RefCell::new(Vec::new()),
+            pipe: Rc::new(AutomountPipe::new()),
+            pipe_added: RefCell::new(false),
+            timer: Rc::new(AutomountTimer::new(0)),
+            timer_armed: RefCell::new(false),
+            expiring: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub(super) fn attach_mng(&self, mng: Weak<AutomountMng>) {
+        self.pipe.attach_mng(mng.clone());
+        self.timer.attach_mng(mng);
+    }
+
+    pub(super) fn start_check(&self) -> Result<()> {
+        if IN_SET!(
+            self.state(),
+            AutomountState::Waiting,
+            AutomountState::Running
+        ) {
+            return Err(Error::UnitActionEAgain);
+        }
+        Ok(())
+    }
+
+    pub(super) fn start_action(&self) {
+        *self.result.borrow_mut() = AutomountResult::Success;
+        self.enter_waiting();
+    }
+
+    pub(super) fn stop_check(&self) -> Result<()> {
+        if IN_SET!(self.state(), AutomountState::Dead) {
+            return Err(Error::UnitActionEAgain);
+        }
+        Ok(())
+    }
+
+    pub(super) fn stop_action(&self) {
+        self.enter_dead(AutomountResult::Success);
+    }
+
+    pub(super) fn reset_failed(&self) {
+        if self.state() == AutomountState::Failed {
+            self.set_state(AutomountState::Dead);
+        }
+        *self.result.borrow_mut() = AutomountResult::Success;
+    }
+
+    /// the state of the mount unit is changed, answer the waiting packets
+    pub(super) fn trigger_notify(&self, other: &str) {
+        if self.config.mount_unit() != other {
+            return;
+        }
+        if !IN_SET!(
+            self.state(),
+            AutomountState::Waiting,
+            AutomountState::Running
+        ) {
+            return;
+        }
+
+        let mount_state = self.comm.um().current_active_state(other);
+        if mount_state.is_active_or_reloading() {
+            log::debug!("{} got notified about {} mounted.", self.id(), other);
+            self.send_ready(false, 0);
+            self.set_state(AutomountState::Running);
+            self.start_expire();
+        } else if mount_state.is_inactive_or_failed() {
+            log::debug!("{} got notified about {} unmounted.", self.id(), other);
+            // the processes accessing the mount point are waked up with an error if it is failed to mount
+            self.send_ready(false, -libc::ENODEV);
+            self.send_ready(true, 0);
+            self.stop_expire();
+            self.set_state(AutomountState::Waiting);
+        }
+    }
+
+    fn dispatch_io(&self) {
+        if !IN_SET!(
+            self.state(),
+            AutomountState::Waiting,
+            AutomountState::Running
+        ) {
+            return;
+        }
+
+        let mut buf = [0u8; AUTOFS_PACKET_SIZE];
+        let packet = match unistd::read(self.pipe.fd(), &mut buf) {
+            Ok(n) => AutofsPacket::parse(&buf[..n]),
+            Err(Errno::EAGAIN) | Err(Errno::EINTR) => return,
+            Err(e) => {
+                log::error!("Failed to read the autofs packet of {}: {}", self.id(), e);
+                None
+            }
+        };
+        let packet = match packet {
+            None => {
+                self.enter_dead(AutomountResult::FailureResources);
+                return;
+            }
+            Some(p) => p,
+        };
+        if packet.proto_version != AUTOFS_PROTO_VERSION {
+            log::warn!(
+                "Unexpected autofs protocol version {} of {}",
+                packet.proto_version,
+                self.id()
+            );
+        }
+
+        match packet.ptype {
+            AUTOFS_PTYPE_MISSING_DIRECT => {
+                log::info!(
+                    "Got automount request for {}, triggered by {}",
+                    self.config.automount_where(),
+                    packet.pid
+                );
+                self.add_token(false, packet.token);
+                self.enter_running();
+            }
+            AUTOFS_PTYPE_EXPIRE_DIRECT => {
+                log::debug!("Got expire request for {}", self.config.automount_where());
+                self.add_token(true, packet.token);
+                self.enter_expire();
+            }
+            ptype => {
+                log::warn!("Received unknown automount request {}", ptype);
+            }
+        }
+    }
+
+    fn dispatch_timer(&self) {
+        if self.state() != AutomountState::Running {
+            return;
+        }
+
+        // the expire ioctl is blocked until the expire packet is answered by the event loop,
+        // so it is run in another thread.
+        if !self.expiring.swap(true, Ordering::SeqCst) {
+            match unistd::dup(*self.ioctl_fd.borrow()) {
+                Ok(fd) => {
+                    let expiring = Arc::clone(&self.expiring);
+                    thread::spawn(move || {
+                        while autofs::autofs_expire(fd).is_ok() {}
+                        fd_util::close(fd);
+                        expiring.store(false, Ordering::SeqCst);
+                    });
+                }
+                Err(e) => {
+                    log::warn!("Failed to duplicate the autofs ioctl fd: {}", e);
+                    self.expiring.store(false, Ordering::SeqCst);
+                }
+            }
+        }
+
+        self.start_expire();
+    }
+
+    fn enter_waiting(&self) {
+        let mount_unit = self.config.mount_unit();
+        if !self
+            .comm
+            .um()
+            .current_active_state(&mount_unit)
+            .is_inactive_or_failed()
+        {
+            log::error!(
+                "{} is already mounted, refusing to start {}.",
+                mount_unit,
+                self.id()
+            );
+            self.enter_dead(AutomountResult::FailureResources);
+            return;
+        }
+
+        if let Err(e) = self.mount_autofs() {
+            log::error!("Failed to mount the autofs of {}: {}", self.id(), e);
+            self.enter_dead(AutomountResult::FailureResources);
+            return;
+        }
+
+        self.set_state(AutomountState::Waiting);
+    }
+
+    fn enter_running(&self) {
+        let mount_unit = self.config.mount_unit();
+        let um = self.comm.um();
+
+        // the mount point may be mounted already, answer the packets directly
+        if um
+            .current_active_state(&mount_unit)
+            .is_active_or_reloading()
+        {
+            self.send_ready(false, 0);
+            self.set_state(AutomountState::Running);
+            return;
+        }
+
+        if let Err(e) = um.unit_start_by_job(&mount_unit) {
+            log::warn!("Failed to start unit {}: {:?}", mount_unit, e);
+            self.send_ready(false, -libc::EIO);
+            self.enter_dead(AutomountResult::FailureResources);
+            return;
+        }
+
+        self.set_state(AutomountState::Running);
+    }
+
+    fn enter_expire(&self) {
+        let mount_unit = self.config.mount_unit();
+        let um = self.comm.um();
+
+        if um.current_active_state(&mount_unit).is_inactive_or_failed() {
+            self.send_ready(true, 0);
+            return;
+        }
+
+        if let Err(e) = um.unit_stop_by_job(&mount_unit) {
+            log::warn!("Failed to stop unit {}: {:?}", mount_unit, e);
+            self.send_ready(true, -libc::EIO);
+        }
+    }
+
+    fn enter_dead(&self, res: AutomountResult) {
+        if self.result() == AutomountResult::Success {
+            *self.result.borrow_mut() = res;
+        }
+
+        // wake up the processes accessing the mount point before unmounting it
+        self.send_ready(false, -libc::EHOSTDOWN);
+        self.send_ready(true, -libc::EHOSTDOWN);
+        self.umount_autofs();
+
+        if self.result() == AutomountResult::Success {
+            self.set_state(AutomountState::Dead);
+        } else {
+            self.set_state(AutomountState::Failed);
+        }
+    }
+
+    fn mount_autofs(&self) -> Result<()> {
+        let mount_where = self.config.automount_where();
+        if let Err(e) = fs::create_dir_all(&mount_where) {
+            log::warn!("Failed to create directory {}: {}", mount_where, e);
+        }
+
+        let (rfd, wfd) = unistd::pipe2(OFlag::O_CLOEXEC)?;
+        self.pipe.set_fd(rfd);
+        fd_util::fd_nonblock(rfd, true)?;
+
+        // the kernel holds the write end after mounting
+        let ret = autofs::autofs_mount(&mount_where, wfd);
+        fd_util::close(wfd);
+        if let Err(e) = ret {
+            self.umount_autofs();
+            return Err(e.into());
+        }
+
+        if let Err(e) = self.setup_autofs(&mount_where) {
+            self.umount_autofs();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn setup_autofs(&self, mount_where: &str) -> Result<()> {
+        let ioctl_fd = autofs::autofs_open_ioctl_fd(mount_where)?;
+        *self.ioctl_fd.borrow_mut() = ioctl_fd;
+
+        let timeout = self.timeout_sec();
+        if timeout > 0 {
+            autofs::autofs_set_timeout(ioctl_fd, timeout)?;
+        }
+
+        // retain the fds across reexecuting
+        let reli = self.comm.reli();
+        reli.fd_cloexec(self.pipe.fd(), false)?;
+        reli.fd_cloexec(ioctl_fd, false)?;
+
+        self.watch_pipe()
+    }
+
+    fn umount_autofs(&self) {
+        self.unwatch_pipe();
+        self.stop_expire();
+
+        let reli = self.comm.reli();
+        for fd in [self.pipe.fd(), *self.ioctl_fd.borrow()] {
+            if fd >= 0 {
+                let _ = reli.fd_cloexec(fd, true);
+                fd_util::close(fd);
+            }
+        }
+        self.pipe.set_fd(-1);
+        *self.ioctl_fd.borrow_mut() = -1;
+
+        autofs::autofs_umount(&self.config.automount_where());
+    }
+
+    fn watch_pipe(&self) -> Result<()> {
+        if self.pipe.fd() < 0 || *self.pipe_added.borrow() {
+            return Ok(());
+        }
+
+        let events = self.comm.um().events();
+        let source: Rc<dyn Source> = self.pipe.clone();
+        events.add_source(source.clone())?;
+        events.set_enabled(source, EventState::On)?;
+        *self.pipe_added.borrow_mut() = true;
+        Ok(())
+    }
+
+    fn unwatch_pipe(&self) {
+        if !*self.pipe_added.borrow() {
+            return;
+        }
+
+        let events = self.comm.um().events();
+        let source: Rc<dyn Source> = self.pipe.clone();
+        if let Err(e) = events.set_enabled(source.clone(), EventState::Off) {
+            log::warn!("Failed to disable the autofs pipe of {}: {}", self.id(), e);
+        }
+        if let Err(e) = events.del_source(source) {
+            log::warn!("Failed to delete the autofs pipe of {}: {}", self.id(), e);
+        }
+        *self.pipe_added.borrow_mut() = false;
+    }
+
+    /// check whether the mount point is idle periodically, the kernel sends the expire packet if it is
+    fn start_expire(&self) {
+        let timeout = self.timeout_sec();
+        if timeout == 0 || *self.ioctl_fd.borrow() < 0 {
+            return;
+        }
+
+        let events = self.comm.um().events();
+        let timer: Rc<dyn Source> = self.timer.clone();
+        let ret = if *self.timer_armed.borrow() {
+            events.set_enabled(timer.clone(), EventState::Off)
+        } else {
+            events.add_source(timer.clone())
+        };
+        if let Err(e) = ret {
+            log::warn!("Failed to set the expire timer of {}: {}", self.id(), e);
+            return;
+        }
+        *self.timer_armed.borrow_mut() = true;
+
+        self.timer
+            .set_time(expire_interval_usec(timeout * USEC_PER_SEC));
+        if let Err(e) = events.set_enabled(timer, EventState::OneShot) {
+            log::warn!("Failed to enable the expire timer of {}: {}", self.id(), e);
+        }
+    }
+
+    fn stop_expire(&self) {
+        if !*self.timer_armed.borrow() {
+            return;
+        }
+
+        let events = self.comm.um().events();
+        let timer: Rc<dyn Source> = self.timer.clone();
+        let _ = events.set_enabled(timer.clone(), EventState::Off);
+        let _ = events.del_source(timer);
+        *self.timer_armed.borrow_mut() = false;
+    }
+
+    fn add_token(&self, expire: bool, token: u32) {
+        let tokens = if expire {
+            &self.expire_tokens
+        } else {
+            &self.tokens
+        };
+        if !tokens.borrow().contains(&token) {
+            tokens.borrow_mut().push(token);
+        }
+        self.db_update();
+    }
+
+    /// answer all the waiting packets, status is 0 for success, or the negative errno
+    fn send_ready(&self, expire: bool, status: i32) {
+        let tokens = if expire {
+            self.expire_tokens.take()
+        } else {
+            self.tokens.take()
+        };
+        if tokens.is_empty() {
+            return;
+        }
+
+        let ioctl_fd = *self.ioctl_fd.borrow();
+        if ioctl_fd >= 0 {
+            for token in tokens {
+                if let Err(e) = autofs::autofs_send_ready(ioctl_fd, token, status) {
+                    log::warn!(
+                        "Failed to answer the autofs request of {}: {}",
+                        self.id(),
+                        e
+                    );
+                }
+            }
+        }
+        self.db_update();
+    }
+
+    fn take_fd(&self, fd: RawFd) -> RawFd {
+        if fd < 0 {
+            return fd;
+        }
+        self.comm.reli().fd_take(fd)
+    }
+
+    fn timeout_sec(&self) -> u64 {
+        self.config.config_data().borrow().Automount.TimeoutIdleSec
+    }
+
+    pub fn get_state(&self) -> String {
+        let state = *self.state.borrow();
+        state.to_string()
+    }
+
+    fn set_state(&self, new_state: AutomountState) {
+        let old_state = self.state();
+        self.state.replace(new_state);
+
+        if new_state != AutomountState::Running {
+            self.stop_expire();
+        }
+
+        if new_state != old_state {
+            log::debug!(
+                "{} original state[{:?}] ->new state[{:?}]",
+                self.id(),
+                old_state,
+                new_state,
+            );
+        }
+
+        if let Some(unit) = self.comm.owner() {
+            unit.notify(
+                old_state.to_unit_state(),
+                new_state.to_unit_state(),
+                UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE,
+            );
+        }
+
+        self.db_update();
+    }
+
+    fn state(&self) -> AutomountState {
+        *self.state.borrow()
+    }
+
+    fn result(&self) -> AutomountResult {
+        *self.result.borrow()
+    }
+
+    fn id(&self) -> String {
+        self.comm
+            .owner()
+            .map_or(String::new(), |u| u.id().to_string())
+    }
+
+    pub fn to_unit_state(&self) -> UnitActiveState {
+        self.state().to_unit_state()
+    }
+}
+
+/// check the mount point three times in the idle timeout, but not more than once a second
+fn expire_interval_usec(timeout_usec: u64) -> u64 {
+    (timeout_usec / 3).max(USEC_PER_SEC)
+}
+
+/// the read end of the pipe receiving the autofs packets
+struct AutomountPipe {
+    fd: RefCell<RawFd>,
+    mng: RefCell<Weak<AutomountMng>>,
+}
+
+impl AutomountPipe {
+    fn new() -> Self {
+        AutomountPipe {
+            fd: RefCell::new(-1),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    fn attach_mng(&self, mng: Weak<AutomountMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    fn set_fd(&self, fd: RawFd) {
+        *self.fd.borrow_mut() = fd;
+    }
+
+    fn mng(&self) -> Rc<AutomountMng> {
+        self.mng.borrow().clone().upgrade().unwrap()
+    }
+}
+
+impl Source for AutomountPipe {
+    fn fd(&self) -> RawFd {
+        *self.fd.borrow()
+    }
+
+    fn event_type(&self) -> EventType {
+        EventType::Io
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn dispatch(&self, _: &Events) -> i32 {
+        self.mng().dispatch_io();
+        0
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+/// the timer checking whether the mount point is idle
+struct AutomountTimer {
+    time: RefCell<u64>,
+    mng: RefCell<Weak<AutomountMng>>,
+}
+
+impl AutomountTimer {
+    fn new(usec: u64) -> Self {
+        AutomountTimer {
+            time: RefCell::new(usec),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    fn attach_mng(&self, mng: Weak<AutomountMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    fn set_time(&self, usec: u64) {
+        *self.time.borrow_mut() = usec
+    }
+
+    fn mng(&self) -> Rc<AutomountMng> {
+        self.mng.borrow().clone().upgrade().unwrap()
+    }
+}
+
+impl Source for AutomountTimer {
+    fn fd(&self) -> RawFd {
+        0
+    }
+
+    fn event_type(&self) -> EventType {
+        EventType::TimerMonotonic
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn time_relative(&self) -> u64 {
+        *self.time.borrow()
+    }
+
+    fn dispatch(&self, _: &Events) -> i32 {
+        self.mng().dispatch_timer();
+        0
+    }
+
+    fn priority(&self) -> i8 {
+        0
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expire_interval_usec, AutomountState, USEC_PER_SEC};
+    use sysmaster::unit::UnitActiveState;
+
+    #[test]
+    fn test_automount_active_state() {
+        assert_eq!(
+            AutomountState::Dead.to_unit_state(),
+            UnitActiveState::UnitInActive
+        );
+        assert_eq!(
+            AutomountState::Waiting.to_unit_state(),
+            UnitActiveState::UnitActive
+        );
+        assert_eq!(
+            AutomountState::Running.to_unit_state(),
+            UnitActiveState::UnitActive
+        );
+        assert_eq!(
+            AutomountState::Failed.to_unit_state(),
+            UnitActiveState::UnitFailed
+        );
+    }
+
+    #[test]
+    fn test_expire_interval() {
+        assert_eq!(expire_interval_usec(USEC_PER_SEC), USEC_PER_SEC);
+        assert_eq!(expire_interval_usec(30 * USEC_PER_SEC), 10 * USEC_PER_SEC);
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+#![allow(non_snake_case)]
+use confique::Config;
+use macros::EnumDisplay;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use sysmaster::rel::{ReDb, ReDbRoTxn, ReDbRwTxn, ReDbTable, Reliability};
+use sysmaster::serialize::deserialize_str_or_value;
+
+const RELI_DB_HAUTOMOUNT_CONF: &str = "amntconf";
+const RELI_DB_HAUTOMOUNT_MNG: &str = "amntmng";
+
+#[derive(Config, Default, Clone, Debug, Serialize, Deserialize)]
+pub(super) struct SectionAutomount {
+    #[config(default = "")]
+    pub Where: String,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    #[config(default = 0)]
+    pub TimeoutIdleSec: u64,
+}
+
+// Waiting: the autofs is mounted on Where, Running: the mount unit is started on access,
+// both of them are the active states of the unit
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Serialize, Deserialize, EnumDisplay)]
+pub(super) enum AutomountState {
+    Dead,
+    Waiting,
+    Running,
+    Failed,
+    StateMax,
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Serialize, Deserialize, EnumDisplay)]
+pub(super) enum AutomountResult {
+    Success,
+    FailureResources,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct AutomountReMng {
+    state: AutomountState,
+    result: AutomountResult,
+    pipe_fd: i32,
+    ioctl_fd: i32,
+    tokens: Vec<u32>,
+    expire_tokens: Vec<u32>,
+}
+
+struct AutomountReDb<K, V>(ReDb<K, V>);
+
+pub(super) struct AutomountRe {
+    // database: multi-instance(N)
+    conf: Rc<AutomountReDb<String, SectionAutomount>>, // RELI_DB_HAUTOMOUNT_CONF; key: unit_id, data: config;
+    mng: Rc<AutomountReDb<String, AutomountReMng>>, // RELI_DB_HAUTOMOUNT_MNG; key: unit_id, data: state+result+fds+tokens;
+}
+
+impl AutomountRe {
+    pub(super) fn new(relir: &Reliability) -> AutomountRe {
+        let conf = Rc::new(AutomountReDb(ReDb::new(relir, RELI_DB_HAUTOMOUNT_CONF)));
+        let mng = Rc::new(AutomountReDb(ReDb::new(relir, RELI_DB_HAUTOMOUNT_MNG)));
+        let rentry = AutomountRe { conf, mng };
+        rentry.register(relir);
+        rentry
+    }
+
+    pub(super) fn conf_insert(&self, unit_id: &str, automount: &SectionAutomount) {
+        self.conf.0.insert(unit_id.to_string(), automount.clone());
+    }
+
+    pub(super) fn conf_get(&self, unit_id: &String) -> Option<SectionAutomount> {
+        self.conf.0.get(unit_id)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn mng_insert(
+        &self,
+        unit_id: &str,
+        state: AutomountState,
+        result: AutomountResult,
+        pipe_fd: i32,
+        ioctl_fd: i32,
+        tokens: Vec<u32>,
+        expire_tokens: Vec<u32>,
+    ) {
+        let mng = AutomountReMng {
+            state,
+            result,
+            pipe_fd,
+            ioctl_fd,
+            tokens,
+            expire_tokens,
+        };
+        self.mng.0.insert(unit_id.to_string(), mng);
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub(super) fn mng_get(
+        &self,
+        unit_id: &String,
+    ) -> Option<(
+        AutomountState,
+        AutomountResult,
+        i32,
+        i32,
+        Vec<u32>,
+        Vec<u32>,
+    )> {
+        let mng = self.mng.0.get(unit_id);
+        mng.map(|m| {
+            (
+                m.state,
+                m.result,
+                m.pipe_fd,
+                m.ioctl_fd,
+                m.tokens,
+                m.expire_tokens,
+            )
+        })
+    }
+
+    fn register(&self, relir: &Reliability) {
+        // rel-db: RELI_DB_HAUTOMOUNT_CONF
+        let db = Rc::clone(&self.conf);
+        relir.history_db_register(RELI_DB_HAUTOMOUNT_CONF, db);
+
+        // rel-db: RELI_DB_HAUTOMOUNT_MNG
+        let db = Rc::clone(&self.mng);
+        relir.history_db_register(RELI_DB_HAUTOMOUNT_MNG, db);
+    }
+}
+
+impl ReDbTable for AutomountReDb<String, SectionAutomount> {
+    fn clear(&self, wtxn: &mut ReDbRwTxn) {
+        self.0.do_clear(wtxn);
+    }
+
+    fn export(&self, db_wtxn: &mut ReDbRwTxn) {
+        self.0.cache_2_db(db_wtxn);
+    }
+
+    fn import<'a>(&self, db_rtxn: &ReDbRoTxn) {
+        self.0.db_2_cache(db_rtxn);
+    }
+
+    fn ignore_set(&self, ignore: bool) {
+        self.0.set_ignore(ignore);
+    }
+}
+
+impl ReDbTable for AutomountReDb<String, AutomountReMng> {
+    fn clear(&self, wtxn: &mut ReDbRwTxn) {
+        self.0.do_clear(wtxn);
+    }
+
+    fn export(&self, db_wtxn: &mut ReDbRwTxn) {
+        self.0.cache_2_db(db_wtxn);
+    }
+
+    fn import<'a>(&self, db_rtxn: &ReDbRoTxn) {
+        self.0.db_2_cache(db_rtxn);
+    }
+
+    fn ignore_set(&self, ignore: bool) {
+        self.0.set_ignore(ignore);
+    }
+}
@@ -0,0 +1,23 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+pub(super) const PLUGIN_NAME: &str = "DeviceUnit";
+
+/// the devices with one of these tags are exposed as device units,
+/// "systemd" is accepted for the compatibility with the existing rules
+pub(super) const DEVICE_TAGS: [&str; 2] = ["sysmaster", "systemd"];
+
+/// the properties listing the units wanted by the device
+pub(super) const DEVICE_WANTS: [&str; 2] = ["SYSMASTER_WANTS", "SYSTEMD_WANTS"];
+
+/// the properties marking the device not ready when set to "0"
+pub(super) const DEVICE_READY: [&str; 2] = ["SYSMASTER_READY", "SYSTEMD_READY"];
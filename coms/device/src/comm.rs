@@ -0,0 +1,210 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! device_comm is shared by the device units and the device manager,
+//! besides the associated objects, it records the devices which are plugged
+//! currently, so the unit can tell whether its device is present.
+use super::rentry::{DeviceRe, DeviceState};
+use once_cell::sync::Lazy;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+use std::sync::{Arc, RwLock};
+use sysmaster::rel::Reliability;
+use sysmaster::unit::{UmIf, UnitBase};
+
+pub(super) struct DeviceUnitComm {
+    owner: RefCell<Option<Rc<dyn UnitBase>>>,
+    umcomm: Arc<DeviceUmComm>,
+}
+
+impl DeviceUnitComm {
+    pub(super) fn new() -> Self {
+        DeviceUnitComm {
+            owner: RefCell::new(None),
+            umcomm: DeviceUmComm::get_instance(),
+        }
+    }
+
+    pub(super) fn attach_unit(&self, unit: Rc<dyn UnitBase>) {
+        self.owner.replace(Some(unit));
+    }
+
+    pub(super) fn attach_um(&self, um: Rc<dyn UmIf>) {
+        self.umcomm.attach_um(um)
+    }
+
+    pub(super) fn attach_reli(&self, reli: Rc<Reliability>) {
+        self.umcomm.attach_reli(reli)
+    }
+
+    pub(super) fn owner(&self) -> Option<Rc<dyn UnitBase>> {
+        if let Some(ref unit) = *self.owner.borrow() {
+            Some(Rc::clone(unit))
+        } else {
+            None
+        }
+    }
+
+    pub(super) fn get_owner_id(&self) -> String {
+        self.owner()
+            .map_or_else(|| "None".to_string(), |u| u.id().to_string())
+    }
+
+    /// the sysfs path of the device if the device of the owner is plugged
+    pub(super) fn plugged_sysfs(&self) -> Option<String> {
+        self.umcomm.plugged_sysfs(&self.get_owner_id())
+    }
+
+    pub(super) fn rentry_mng_insert(&self, state: DeviceState, sysfs: &str) {
+        if let Some(u) = self.owner() {
+            self.rentry().mng_insert(u.id(), state, sysfs)
+        }
+    }
+
+    pub(super) fn rentry_mng_get(&self) -> Option<(DeviceState, String)> {
+        let ret = self.owner().map(|u| self.rentry().mng_get(u.id()));
+        ret.unwrap_or(None)
+    }
+
+    fn rentry(&self) -> Rc<DeviceRe> {
+        self.umcomm.rentry()
+    }
+}
+
+static DEVICE_UM_COMM: Lazy<Arc<DeviceUmComm>> = Lazy::new(|| {
+    let comm = DeviceUmComm::new();
+    Arc::new(comm)
+});
+
+pub(super) struct DeviceUmComm {
+    data: RwLock<DeviceUmCommData>,
+}
+
+unsafe impl Send for DeviceUmComm {}
+
+unsafe impl Sync for DeviceUmComm {}
+
+impl DeviceUmComm {
+    pub(super) fn new() -> Self {
+        DeviceUmComm {
+            data: RwLock::new(DeviceUmCommData::new()),
+        }
+    }
+
+    pub(super) fn attach_um(&self, um: Rc<dyn UmIf>) {
+        let mut wdata = self.data.write().unwrap();
+        wdata.attach_um(um);
+    }
+
+    pub(super) fn attach_reli(&self, reli: Rc<Reliability>) {
+        let mut wdata = self.data.write().unwrap();
+        wdata.attach_reli(reli);
+    }
+
+    pub(super) fn get_instance() -> Arc<DeviceUmComm> {
+        DEVICE_UM_COMM.clone()
+    }
+
+    pub(super) fn reli(&self) -> Rc<Reliability> {
+        let rdata = self.data.read().unwrap();
+        rdata.reli()
+    }
+
+    pub(super) fn um(&self) -> Rc<dyn UmIf> {
+        let rdata = self.data.read().unwrap();
+        rdata.um().unwrap()
+    }
+
+    pub(super) fn rentry(&self) -> Rc<DeviceRe> {
+        let rdata = self.data.read().unwrap();
+        rdata.rentry()
+    }
+
+    /// record the unit as plugged by the device with the sysfs path
+    pub(super) fn plug(&self, unit_id: &str, sysfs: &str) {
+        let mut wdata = self.data.write().unwrap();
+        wdata.plugged.insert(unit_id.to_string(), sysfs.to_string());
+    }
+
+    /// forget the unit, return the sysfs path of the device which plugged it
+    pub(super) fn unplug(&self, unit_id: &str) -> Option<String> {
+        let mut wdata = self.data.write().unwrap();
+        wdata.plugged.remove(unit_id)
+    }
+
+    pub(super) fn plugged_sysfs(&self, unit_id: &str) -> Option<String> {
+        let rdata = self.data.read().unwrap();
+        rdata.plugged.get(unit_id).cloned()
+    }
+
+    /// the units plugged by the device with the sysfs path, or by any device if None
+    pub(super) fn plugged_units(&self, sysfs: Option<&str>) -> Vec<String> {
+        let rdata = self.data.read().unwrap();
+        rdata
+            .plugged
+            .iter()
+            .filter(|(_, s)| sysfs.map_or(true, |sysfs| s.as_str() == sysfs))
+            .map(|(u, _)| u.to_string())
+            .collect()
+    }
+}
+
+struct DeviceUmCommData {
+    // associated objects
+    um: Option<Rc<dyn UmIf>>,
+    reli: Weak<Reliability>,
+    rentry: Option<Rc<DeviceRe>>,
+
+    // owned objects
+    plugged: HashMap<String, String>, // key: unit_id, data: sysfs path
+}
+
+// the declaration "pub(self)" is for identification only.
+impl DeviceUmCommData {
+    pub(self) fn new() -> DeviceUmCommData {
+        DeviceUmCommData {
+            um: None,
+            reli: Weak::new(),
+            rentry: None,
+            plugged: HashMap::new(),
+        }
+    }
+
+    pub(self) fn attach_um(&mut self, um: Rc<dyn UmIf>) {
+        if self.um.is_none() {
+            log::debug!("DeviceUmComm attach_um action.");
+            self.um = Some(um);
+        }
+    }
+
+    pub(self) fn attach_reli(&mut self, reli: Rc<Reliability>) {
+        let old = self.reli.clone().upgrade();
+        if old.is_none() {
+            log::debug!("DeviceUmComm attach_reli action.");
+            self.reli = Rc::downgrade(&reli);
+            self.rentry.replace(Rc::new(DeviceRe::new(&reli)));
+        }
+    }
+
+    pub(self) fn um(&self) -> Option<Rc<dyn UmIf>> {
+        self.um.as_ref().cloned()
+    }
+
+    pub(self) fn reli(&self) -> Rc<Reliability> {
+        self.reli.clone().upgrade().unwrap()
+    }
+
+    pub(self) fn rentry(&self) -> Rc<DeviceRe> {
+        self.rentry.as_ref().cloned().unwrap()
+    }
+}
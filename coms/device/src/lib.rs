@@ -0,0 +1,37 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! # Device exposes the devices managed by devmaster as device units.
+//!  The devices with the tag "sysmaster" (or "systemd") are exposed by their sysfs path, device node and symlinks,
+//!  like sys-devices-...-sda1.device, dev-sda1.device and dev-disk-by\x2dlabel-root.device.
+//!  The device units are plugged when the devices appear and dead when they disappear, they are not started or stopped by themselves.
+//!  A job starting a device unit waits until the device appears, and the units with BindsTo= on a device unit are stopped when the device disappears.
+//! #  Example:
+//! ``` text
+//!  SUBSYSTEM=="block", KERNEL=="sda1", TAG+="sysmaster", ENV{SYSTEMD_WANTS}+="foo.service"
+//! ```
+//! ##  Automatic dependency
+//!
+//! ###  Implicit dependency
+//!  The device unit has Wants= dependencies on the units in the property SYSMASTER_WANTS or SYSTEMD_WANTS of the device,
+//!  a template is instantiated with the escaped sysfs path. The device is not ready if SYSMASTER_READY or SYSTEMD_READY is "0".
+//!
+//! ###  Default Dependency
+//!  NA
+
+// dependency: device_base -> device_rentry -> device_comm -> device_mng -> device_unit -> device_manager
+mod base;
+mod comm;
+mod manager;
+mod mng;
+mod rentry;
+mod unit;
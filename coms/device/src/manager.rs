@@ -0,0 +1,491 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+use super::base::{DEVICE_READY, DEVICE_TAGS, DEVICE_WANTS, PLUGIN_NAME};
+use super::comm::DeviceUmComm;
+use super::rentry::{DeviceRe, DeviceReFrame};
+use basic::logger;
+use basic::unit_name::{unit_name_from_path, unit_name_path_escape, unit_name_replace_instance};
+use device::device_enumerator::DeviceEnumerator;
+use device::device_monitor::{DeviceMonitor, MonitorNetlinkGroup};
+use device::{Device, DeviceAction};
+use event::{EventState, EventType, Events, Source};
+use std::collections::HashMap;
+use std::os::unix::io::RawFd;
+use std::rc::Rc;
+use std::sync::Arc;
+use sysmaster::rel::{ReStation, ReliLastFrame, Reliability};
+use sysmaster::unit::{
+    UmIf, UnitActiveState, UnitDependencyMask, UnitManagerObj, UnitMngUtil, UnitRelations, UnitType,
+};
+
+struct DeviceManager {
+    // owned objects
+    // data
+    comm: Arc<DeviceUmComm>,
+    monitor: UeventMonitor,
+}
+
+impl ReStation for DeviceManager {
+    // input
+    fn input_rebuild(&self) {
+        self.monitor.defer_enable(true);
+    }
+
+    // compensate
+    fn do_compensate_last(&self, _lframe: (u32, Option<u32>, Option<u32>), lunit: Option<&String>) {
+        assert!(lunit.is_some());
+
+        let frame = self.comm.rentry().last_frame();
+        if frame.is_none() {
+            // debug
+            return;
+        }
+
+        match frame.unwrap() {
+            // the devices are enumerated again to reconcile the device units
+            DeviceReFrame::Monitor => self.monitor.defer_enable(true),
+        };
+    }
+
+    // no data
+
+    // reload
+    fn register_ex(&self) {
+        self.monitor.register_ex();
+    }
+}
+
+impl UnitManagerObj for DeviceManager {}
+
+// the declaration "pub(self)" is for identification only.
+impl DeviceManager {
+    pub(self) fn new() -> DeviceManager {
+        let _comm = DeviceUmComm::get_instance();
+        DeviceManager {
+            comm: Arc::clone(&_comm),
+            monitor: UeventMonitor::new(&_comm),
+        }
+    }
+
+    fn register(&self) {
+        self.monitor.register();
+    }
+}
+
+struct UeventMonitor {
+    // owned objects
+    data: Rc<UeventMonitorData>,
+    defer: Rc<UeventMonitorDefer>,
+    io: Rc<UeventMonitorIo>,
+}
+
+// the declaration "pub(self)" is for identification only.
+impl UeventMonitor {
+    pub(self) fn new(commr: &Arc<DeviceUmComm>) -> UeventMonitor {
+        let _data = Rc::new(UeventMonitorData::new(commr));
+        UeventMonitor {
+            data: Rc::clone(&_data),
+            defer: Rc::new(UeventMonitorDefer::new(&_data)),
+            io: Rc::new(UeventMonitorIo::new(&_data)),
+        }
+    }
+
+    pub(self) fn register_ex(&self) {
+        let events = self.data.comm.um().events();
+
+        // io
+        let io = Rc::clone(&self.io);
+        events.add_source(io.clone()).unwrap();
+        events.set_enabled(io, EventState::On).unwrap();
+
+        // the devices plugged before are enumerated once
+        self.defer_enable(true);
+    }
+
+    pub(self) fn defer_enable(&self, enable: bool) -> i32 {
+        let source = Rc::clone(&self.defer);
+        let state = match enable {
+            true => EventState::OneShot,
+            false => EventState::Off,
+        };
+        let events = self.data.comm.um().events();
+        match events.set_enabled(source, state) {
+            Ok(_) => 0,
+            Err(_) => -1,
+        }
+    }
+
+    fn register(&self) {
+        let events = self.data.comm.um().events();
+
+        // defer
+        let defer = Rc::clone(&self.defer);
+        events.add_source(defer).unwrap();
+    }
+}
+
+struct UeventMonitorIo {
+    // associated objects
+    data: Rc<UeventMonitorData>,
+}
+
+// the declaration "pub(self)" is for identification only.
+impl UeventMonitorIo {
+    pub(self) fn new(datar: &Rc<UeventMonitorData>) -> UeventMonitorIo {
+        UeventMonitorIo {
+            data: Rc::clone(datar),
+        }
+    }
+
+    fn reli(&self) -> Rc<Reliability> {
+        self.data.comm.reli()
+    }
+
+    fn rentry(&self) -> Rc<DeviceRe> {
+        self.data.comm.rentry()
+    }
+}
+
+impl Source for UeventMonitorIo {
+    fn event_type(&self) -> EventType {
+        EventType::Io
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn dispatch(&self, _e: &Events) -> i32 {
+        let mut device = match self.data.monitor.receive_device() {
+            Ok(device) => device,
+            Err(e) => {
+                log::debug!("Failed to receive device, ignoring: {}", e);
+                return 0;
+            }
+        };
+
+        self.reli().set_last_frame2(
+            ReliLastFrame::SubManager as u32,
+            UnitType::UnitDevice as u32,
+        );
+        self.rentry().set_last_frame(DeviceReFrame::Monitor);
+        self.data.dispatch_uevent(&mut device);
+        self.rentry().clear_last_frame();
+        self.reli().clear_last_frame();
+        0
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+
+    fn fd(&self) -> RawFd {
+        self.data.monitor.fd()
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+}
+
+struct UeventMonitorDefer {
+    // associated objects
+    data: Rc<UeventMonitorData>,
+}
+
+impl UeventMonitorDefer {
+    pub(self) fn new(datar: &Rc<UeventMonitorData>) -> UeventMonitorDefer {
+        UeventMonitorDefer {
+            data: Rc::clone(datar),
+        }
+    }
+
+    fn reli(&self) -> Rc<Reliability> {
+        self.data.comm.reli()
+    }
+
+    fn rentry(&self) -> Rc<DeviceRe> {
+        self.data.comm.rentry()
+    }
+}
+
+impl Source for UeventMonitorDefer {
+    fn event_type(&self) -> EventType {
+        EventType::Defer
+    }
+
+    fn epoll_event(&self) -> u32 {
+        0
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+
+    fn dispatch(&self, _event: &Events) -> i32 {
+        self.reli().set_last_frame2(
+            ReliLastFrame::SubManager as u32,
+            UnitType::UnitDevice as u32,
+        );
+        self.rentry().set_last_frame(DeviceReFrame::Monitor);
+        self.data.dispatch_enumerate();
+        self.rentry().clear_last_frame();
+        self.reli().clear_last_frame();
+        0
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+}
+
+/// the device which is exposed as device units
+struct DeviceInfo {
+    sysfs: String,
+    units: Vec<String>,
+    wants: Vec<String>,
+}
+
+impl DeviceInfo {
+    /// None is returned if the device is not tagged for sysmaster or not ready
+    fn from_device(device: &mut Device) -> Option<DeviceInfo> {
+        let sysfs = device.get_syspath()?.to_string();
+
+        if !DEVICE_TAGS
+            .iter()
+            .any(|tag| device.has_tag(tag.to_string()).unwrap_or(false))
+        {
+            return None;
+        }
+
+        if DEVICE_READY.iter().any(|key| {
+            device
+                .get_property_value(key.to_string())
+                .map_or(false, |v| v == "0")
+        }) {
+            return None;
+        }
+
+        // the device is exposed by its sysfs path, its device node and all the symlinks
+        let mut paths = vec![sysfs.clone()];
+        if let Ok(devname) = device.get_devname() {
+            paths.push(devname);
+        }
+        if let Ok(devlinks) = device.get_property_value("DEVLINKS".to_string()) {
+            paths.extend(devlinks.split_whitespace().map(|s| s.to_string()));
+        }
+        let mut units: Vec<String> = Vec::new();
+        for unit in paths.iter().map(|p| unit_name_from_path(p, ".device")) {
+            if !units.contains(&unit) {
+                units.push(unit);
+            }
+        }
+
+        let mut wants = Vec::new();
+        for key in DEVICE_WANTS {
+            if let Ok(value) = device.get_property_value(key.to_string()) {
+                wants.extend(
+                    value
+                        .split_whitespace()
+                        .map(|s| device_wants_unit(s, &sysfs)),
+                );
+            }
+        }
+
+        Some(DeviceInfo {
+            sysfs,
+            units,
+            wants,
+        })
+    }
+}
+
+/// the template in SYSTEMD_WANTS= is instantiated with the escaped sysfs path
+fn device_wants_unit(name: &str, sysfs: &str) -> String {
+    unit_name_replace_instance(name, &unit_name_path_escape(sysfs))
+        .unwrap_or_else(|| name.to_string())
+}
+
+struct UeventMonitorData {
+    // associated objects
+    comm: Arc<DeviceUmComm>,
+
+    // owned objects
+    monitor: DeviceMonitor,
+}
+
+// the declaration "pub(self)" is for identification only.
+impl UeventMonitorData {
+    pub(self) fn new(commr: &Arc<DeviceUmComm>) -> UeventMonitorData {
+        // the uevents are received after being processed by devmaster
+        let monitor = DeviceMonitor::new(MonitorNetlinkGroup::Userspace, None);
+        log::debug!("Monitoring the uevents by netlink fd {}", monitor.fd());
+        UeventMonitorData {
+            comm: Arc::clone(commr),
+            monitor,
+        }
+    }
+
+    pub(self) fn dispatch_uevent(&self, device: &mut Device) {
+        let sysfs = match device.get_syspath() {
+            None => return,
+            Some(sysfs) => sysfs.to_string(),
+        };
+
+        let info = match device.get_action() {
+            Ok(DeviceAction::Remove) => None,
+            _ => DeviceInfo::from_device(device),
+        };
+
+        // the units which are not exposed by the device any more, like the removed symlinks
+        for unit in self.comm.plugged_units(Some(sysfs.as_str())) {
+            if !info.as_ref().map_or(false, |i| i.units.contains(&unit)) {
+                self.unplug_unit(&unit);
+            }
+        }
+
+        if let Some(info) = info {
+            self.plug_device(&info);
+        }
+    }
+
+    pub(self) fn dispatch_enumerate(&self) {
+        let mut devices: HashMap<String, DeviceInfo> = HashMap::new();
+        for tag in DEVICE_TAGS {
+            let mut enumerator = DeviceEnumerator::new();
+            if let Err(e) = enumerator.add_match_tag(tag.to_string()) {
+                log::error!("Failed to enumerate the devices with tag {}: {}", tag, e);
+                continue;
+            }
+            for device in enumerator.iter_mut() {
+                if let Some(info) = DeviceInfo::from_device(&mut device.lock().unwrap()) {
+                    devices.insert(info.sysfs.clone(), info);
+                }
+            }
+        }
+
+        // the devices removed while we are not monitoring, like during the reexecution
+        let mut plugged = self.comm.plugged_units(None);
+        for unit in self.comm.um().units_get_all(Some(UnitType::UnitDevice)) {
+            if self.comm.um().current_active_state(&unit) == UnitActiveState::UnitActive
+                && !plugged.contains(&unit)
+            {
+                plugged.push(unit);
+            }
+        }
+        for unit in plugged.iter() {
+            if !devices.values().any(|i| i.units.contains(unit)) {
+                self.unplug_unit(unit);
+            }
+        }
+
+        for info in devices.values() {
+            self.plug_device(info);
+        }
+    }
+
+    fn plug_device(&self, info: &DeviceInfo) {
+        let um = self.comm.um();
+        for unit in info.units.iter() {
+            self.comm.plug(unit, &info.sysfs);
+            if !um.load_unit_success(unit) {
+                log::error!("Failed to load device unit {}", unit);
+                continue;
+            }
+
+            // the wanted units are started when the device unit becomes active
+            for wants in info.wants.iter() {
+                if let Err(e) = um.unit_add_dependency(
+                    unit,
+                    UnitRelations::UnitWants,
+                    wants,
+                    true,
+                    UnitDependencyMask::Implicit,
+                ) {
+                    log::error!("Failed to add the dependency {} of {}: {}", wants, unit, e);
+                }
+            }
+
+            if um.current_active_state(unit) == UnitActiveState::UnitActive {
+                continue;
+            }
+
+            // record + action
+            self.comm.reli().set_last_unit(unit);
+            let ret = um.unit_start_directly(unit);
+            self.comm.reli().clear_last_unit();
+
+            match ret {
+                Ok(_) => log::debug!("{} change to plugged.", unit),
+                Err(e) => log::error!("Failed to plug {}: {}", unit, e),
+            }
+        }
+    }
+
+    fn unplug_unit(&self, unit: &str) {
+        self.comm.unplug(unit);
+        let um = self.comm.um();
+        if um.current_active_state(unit) != UnitActiveState::UnitActive {
+            return;
+        }
+
+        // record + action
+        self.comm.reli().set_last_unit(unit);
+        let ret = um.unit_stop(unit, false);
+        self.comm.reli().clear_last_unit();
+
+        match ret {
+            Ok(_) => log::debug!("{} change to dead.", unit),
+            Err(e) => log::error!("Failed to unplug {}: {}", unit, e),
+        }
+    }
+}
+
+impl UnitMngUtil for DeviceManager {
+    fn attach_um(&self, um: Rc<dyn UmIf>) {
+        self.comm.attach_um(um);
+        self.register();
+    }
+
+    fn attach_reli(&self, reli: Rc<Reliability>) {
+        self.comm.attach_reli(reli);
+    }
+}
+
+impl Default for DeviceManager {
+    fn default() -> Self {
+        DeviceManager::new()
+    }
+}
+
+use sysmaster::declure_umobj_plugin;
+declure_umobj_plugin!(DeviceManager, DeviceManager::default, PLUGIN_NAME);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_wants_unit() {
+        assert_eq!(
+            device_wants_unit("foo.service", "/sys/devices/virtual/net/lo"),
+            "foo.service"
+        );
+        assert_eq!(
+            device_wants_unit("ifup@.service", "/sys/devices/virtual/net/lo"),
+            "ifup@sys-devices-virtual-net-lo.service"
+        );
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! device_mng is the core of the device unit, the device unit is plugged
+//! only when its device is reported by devmaster, it can not be started
+//! or stopped by itself.
+//!
+use super::comm::DeviceUnitComm;
+use super::rentry::DeviceState;
+use std::{cell::RefCell, rc::Rc};
+use sysmaster::error::*;
+use sysmaster::rel::ReStation;
+use sysmaster::unit::{UnitActiveState, UnitNotifyFlags};
+
+impl DeviceState {
+    fn to_unit_state(self) -> UnitActiveState {
+        match self {
+            DeviceState::Dead => UnitActiveState::UnitInActive,
+            DeviceState::Plugged => UnitActiveState::UnitActive,
+        }
+    }
+}
+
+pub(super) struct DeviceMng {
+    comm: Rc<DeviceUnitComm>,
+    state: RefCell<DeviceState>,
+    sysfs: RefCell<String>,
+}
+
+impl ReStation for DeviceMng {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, _reload: bool) {
+        if let Some((state, sysfs)) = self.comm.rentry_mng_get() {
+            *self.state.borrow_mut() = state;
+            *self.sysfs.borrow_mut() = sysfs;
+        }
+    }
+
+    fn db_insert(&self) {
+        self.comm
+            .rentry_mng_insert(self.state(), &self.sysfs.borrow());
+    }
+
+    // reload: no external connections, no entry
+}
+
+impl DeviceMng {
+    pub(super) fn new(_comm: &Rc<DeviceUnitComm>) -> Self {
+        DeviceMng {
+            comm: Rc::clone(_comm),
+            state: RefCell::new(DeviceState::Dead),
+            sysfs: RefCell::new(String::new()),
+        }
+    }
+
+    /// the device unit becomes plugged once its device appears,
+    /// the start job waits for that by returning UnitActionEAgain.
+    pub(super) fn start_action(&self, notify: bool) -> Result<()> {
+        let sysfs = match self.comm.plugged_sysfs() {
+            None => return Err(Error::UnitActionEAgain),
+            Some(sysfs) => sysfs,
+        };
+
+        *self.sysfs.borrow_mut() = sysfs;
+        self.set_state(DeviceState::Plugged, notify);
+        Ok(())
+    }
+
+    /// the device unit becomes dead only when its device disappears,
+    /// stopping it while the device is still present is skipped.
+    pub(super) fn stop_action(&self, notify: bool) -> Result<()> {
+        if self.comm.plugged_sysfs().is_some() {
+            return Err(Error::UnitActionEBadR);
+        }
+
+        self.set_state(DeviceState::Dead, notify);
+        Ok(())
+    }
+
+    pub(super) fn get_state(&self) -> String {
+        self.state().to_string()
+    }
+
+    pub(super) fn sysfs(&self) -> String {
+        self.sysfs.borrow().clone()
+    }
+
+    fn set_state(&self, new_state: DeviceState, notify: bool) {
+        let old_state = self.state();
+        self.state.replace(new_state);
+
+        if notify {
+            self.state_notify(new_state, old_state);
+        }
+    }
+
+    fn state_notify(&self, new_state: DeviceState, old_state: DeviceState) {
+        let unit = self.comm.owner().unwrap();
+        if new_state != old_state {
+            log::debug!(
+                "{} original state[{:?}] ->new state[{:?}]",
+                unit.id(),
+                old_state,
+                new_state,
+            );
+        }
+
+        unit.notify(
+            old_state.to_unit_state(),
+            new_state.to_unit_state(),
+            UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE,
+        );
+
+        self.db_update();
+    }
+
+    fn state(&self) -> DeviceState {
+        *self.state.borrow()
+    }
+
+    pub(super) fn to_unit_state(&self) -> UnitActiveState {
+        self.state().to_unit_state()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comm::DeviceUmComm;
+
+    #[test]
+    fn test_device_start_wait_for_plug() {
+        let _comm = Rc::new(DeviceUnitComm::new());
+        let dm = DeviceMng::new(&_comm);
+        assert!(matches!(
+            dm.start_action(false),
+            Err(Error::UnitActionEAgain)
+        ));
+        assert_eq!(dm.to_unit_state(), UnitActiveState::UnitInActive);
+
+        // the owner of the comm without unit is "None"
+        let umcomm = DeviceUmComm::get_instance();
+        umcomm.plug("None", "/sys/devices/virtual/block/loop0");
+        assert!(dm.start_action(false).is_ok());
+        assert_eq!(dm.to_unit_state(), UnitActiveState::UnitActive);
+        assert_eq!(dm.sysfs(), "/sys/devices/virtual/block/loop0");
+
+        // the device unit can not be stopped until the device is removed
+        assert!(matches!(dm.stop_action(false), Err(Error::UnitActionEBadR)));
+        assert_eq!(
+            umcomm.unplug("None"),
+            Some("/sys/devices/virtual/block/loop0".to_string())
+        );
+        assert!(dm.stop_action(false).is_ok());
+        assert_eq!(dm.get_state(), "dead");
+    }
+}
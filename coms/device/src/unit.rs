@@ -0,0 +1,114 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! DeviceUnit is the entrance of the sub unit，implement the trait UnitObj,UnitMngUtil and UnitSubClass.
+//! Trait UnitObj defines the behavior of the sub unit.
+//! Trait UnitMngUtil is used to attach the Unitmanager to the sub unit.
+//! Trait UnitSubClass implement the convert from sub unit to UnitObj.
+use super::base::PLUGIN_NAME;
+use super::comm::DeviceUnitComm;
+use super::mng::DeviceMng;
+use basic::logger;
+use std::{path::PathBuf, rc::Rc};
+use sysmaster::error::*;
+use sysmaster::rel::{ReStation, Reliability};
+use sysmaster::unit::{SubUnit, UmIf, UnitActiveState, UnitBase, UnitMngUtil};
+
+struct DeviceUnit {
+    comm: Rc<DeviceUnitComm>,
+    mng: Rc<DeviceMng>,
+}
+
+impl ReStation for DeviceUnit {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, reload: bool) {
+        self.mng.db_map(reload);
+    }
+
+    fn db_insert(&self) {
+        self.mng.db_insert();
+    }
+
+    // reload: entry-only
+    fn entry_coldplug(&self) {
+        // the state is reconciled by the device manager after enumerating the devices
+    }
+
+    fn entry_clear(&self) {
+        // no external connection
+    }
+}
+
+impl DeviceUnit {
+    fn new(_um: Rc<dyn UmIf>) -> DeviceUnit {
+        let _comm = Rc::new(DeviceUnitComm::new());
+        DeviceUnit {
+            comm: Rc::clone(&_comm),
+            mng: Rc::new(DeviceMng::new(&_comm)),
+        }
+    }
+}
+
+impl SubUnit for DeviceUnit {
+    fn load(&self, _paths: Vec<PathBuf>) -> Result<()> {
+        // the device unit has no section of its own, only [Unit] is used,
+        // and stopping the device unit on isolate makes no sense.
+        if let Some(u) = self.comm.owner() {
+            u.set_ignore_on_isolate(true);
+        }
+        Ok(())
+    }
+
+    fn current_active_state(&self) -> UnitActiveState {
+        self.mng.to_unit_state()
+    }
+
+    fn get_subunit_state(&self) -> String {
+        self.mng.get_state()
+    }
+
+    fn attach_unit(&self, unit: Rc<dyn UnitBase>) {
+        self.comm.attach_unit(unit);
+        self.db_insert();
+    }
+
+    fn dump(&self) {
+        log::debug!(
+            "device {} sysfs: {}",
+            self.comm.get_owner_id(),
+            self.mng.sysfs()
+        );
+    }
+
+    fn start(&self) -> Result<()> {
+        self.mng.start_action(true)
+    }
+
+    fn stop(&self, _force: bool) -> Result<()> {
+        self.mng.stop_action(true)
+    }
+}
+
+impl UnitMngUtil for DeviceUnit {
+    fn attach_um(&self, um: Rc<dyn UmIf>) {
+        self.comm.attach_um(um);
+    }
+
+    fn attach_reli(&self, reli: Rc<Reliability>) {
+        self.comm.attach_reli(reli);
+    }
+}
+
+use sysmaster::declure_unitobj_plugin_with_param;
+declure_unitobj_plugin_with_param!(DeviceUnit, DeviceUnit::new, PLUGIN_NAME);
@@ -11,3 +11,14 @@
 // See the Mulan PSL v2 for more details.
 
 pub(super) const PLUGIN_NAME: &str = "MountUnit";
+
+pub(super) const MOUNT_BIN: &str = "/usr/bin/mount";
+pub(super) const UMOUNT_BIN: &str = "/usr/bin/umount";
+
+pub(super) fn mount_point_to_unit_name(mount_point: &str) -> String {
+    let mut res = String::from(mount_point).replace('/', "-") + ".mount";
+    if res != "-.mount" {
+        res = String::from(&res[1..])
+    }
+    res
+}
@@ -15,7 +15,8 @@
 //! *  Get the attributes of the unit object
 //! *  Call relation: mount_ unit->mount_ mng->mount_ comm
 
-use super::rentry::{MountRe, MountState};
+use super::rentry::{MountRe, MountResult, MountState, SectionMount};
+use nix::unistd::Pid;
 use once_cell::sync::Lazy;
 use std::cell::RefCell;
 use std::rc::{Rc, Weak};
@@ -67,11 +68,33 @@ impl MountUnitComm {
         u
     }
 
-    pub(super) fn rentry_mng_insert(&self, state: MountState) {
-        self.rentry().mng_insert(&self.get_owner_id(), state)
+    pub(super) fn rentry_conf_insert(&self, mount: &SectionMount) {
+        self.rentry().conf_insert(&self.get_owner_id(), mount)
     }
 
-    pub(super) fn rentry_mng_get(&self) -> Option<MountState> {
+    pub(super) fn rentry_conf_get(&self) -> Option<SectionMount> {
+        self.rentry().conf_get(&self.get_owner_id())
+    }
+
+    pub(super) fn rentry_mng_insert(
+        &self,
+        state: MountState,
+        result: MountResult,
+        reload_result: MountResult,
+        control_pid: Option<Pid>,
+    ) {
+        self.rentry().mng_insert(
+            &self.get_owner_id(),
+            state,
+            result,
+            reload_result,
+            control_pid,
+        )
+    }
+
+    pub(super) fn rentry_mng_get(
+        &self,
+    ) -> Option<(MountState, MountResult, MountResult, Option<Pid>)> {
         self.rentry().mng_get(&self.get_owner_id())
     }
 
@@ -79,7 +102,7 @@ impl MountUnitComm {
         self.umcomm.rentry()
     }
 
-    pub(super) fn _um(&self) -> Rc<dyn UmIf> {
+    pub(super) fn um(&self) -> Rc<dyn UmIf> {
         self.umcomm.um()
     }
 }
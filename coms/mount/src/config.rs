@@ -0,0 +1,134 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! mount_config mod load the conf file list and convert it to structure which is defined in this mod.
+//!
+#![allow(non_snake_case)]
+use super::base::mount_point_to_unit_name;
+use super::comm::MountUnitComm;
+use super::rentry::SectionMount;
+use confique::Config;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use sysmaster::error::*;
+use sysmaster::rel::ReStation;
+
+#[derive(Config, Default, Debug)]
+pub(super) struct MountConfigData {
+    #[config(nested)]
+    pub Mount: SectionMount,
+}
+
+impl MountConfigData {
+    pub(self) fn new(Mount: SectionMount) -> MountConfigData {
+        MountConfigData { Mount }
+    }
+}
+
+pub(super) struct MountConfig {
+    // associated objects
+    comm: Rc<MountUnitComm>,
+
+    // owned objects
+    data: Rc<RefCell<MountConfigData>>,
+    // the mount unit is generated from /proc/self/mountinfo, no configuration file
+    fragment: RefCell<bool>,
+}
+
+impl ReStation for MountConfig {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, reload: bool) {
+        if reload {
+            return;
+        }
+        if let Some(conf) = self.comm.rentry_conf_get() {
+            *self.fragment.borrow_mut() = !conf.Where.is_empty();
+            self.data.replace(MountConfigData::new(conf));
+        }
+    }
+
+    fn db_insert(&self) {
+        self.comm.rentry_conf_insert(&self.data.borrow().Mount);
+    }
+
+    // reload: no external connections, no entry
+}
+
+impl MountConfig {
+    pub(super) fn new(commr: &Rc<MountUnitComm>) -> Self {
+        MountConfig {
+            comm: Rc::clone(commr),
+            data: Rc::new(RefCell::new(MountConfigData::default())),
+            fragment: RefCell::new(false),
+        }
+    }
+
+    pub(super) fn load(&self, paths: Vec<PathBuf>, update: bool) -> Result<()> {
+        if paths.is_empty() {
+            // only monitored, nothing to load
+            return Ok(());
+        }
+
+        let mut builder = MountConfigData::builder().env();
+        for v in paths {
+            builder = builder.file(v);
+        }
+        let data = builder.load().context(ConfiqueSnafu)?;
+        self.verify(&data.Mount)?;
+
+        *self.data.borrow_mut() = data;
+        *self.fragment.borrow_mut() = true;
+
+        if update {
+            self.db_update();
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<MountConfigData>> {
+        self.data.clone()
+    }
+
+    /// whether the unit is loaded from a configuration file
+    pub(super) fn has_fragment(&self) -> bool {
+        *self.fragment.borrow()
+    }
+
+    pub(super) fn mount_where(&self) -> String {
+        self.data.borrow().Mount.Where.clone()
+    }
+
+    fn verify(&self, mount: &SectionMount) -> Result<()> {
+        if mount.What.is_empty() {
+            return Err("What= is not configured in [Mount] section".to_string().into());
+        }
+        if !mount.Where.starts_with('/') {
+            return Err(format!("Where={} is not an absolute path", mount.Where).into());
+        }
+
+        let unit_name = mount_point_to_unit_name(mount.Where.trim_end_matches('/'));
+        let owner_id = self.comm.get_owner_id();
+        if unit_name != owner_id {
+            return Err(format!(
+                "Where={} doesn't match the unit name {}",
+                mount.Where, owner_id
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+}
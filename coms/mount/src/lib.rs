@@ -10,9 +10,29 @@
 // NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
 // See the Mulan PSL v2 for more details.
 
-//! Mount is the entry for the mount point monitoring of sysmaster. sysmaster refers to systemd, but it is not the same.
-//! sysmaster mainly provides the monitoring function and does not actively mount/unmount the mount point, which is implemented by other processes.
-//! Mount does not support configuration files.
+//! Mount is the entry for the mount point management of sysmaster. sysmaster refers to systemd, but it is not the same.
+//! The mount points in /proc/self/mountinfo are monitored and mirrored into mount units.
+//! A mount unit can also be configured by a configuration file, and then sysmaster mounts/unmounts the mount point actively.
+//! The configuration file of mount unit is like this:
+//!
+//! ```toml
+//! [Mount]
+//! What="/dev/sdb1"
+//! Where="/mnt/data"
+//! Type="ext4"
+//! Options="defaults"
+//! TimeoutSec=90
+//! ```
+//!
+//! Mount unit supports the following options:
+//!
+//! | Option | Type | Description |
+//! |---|---|---|
+//! | What | String | the device, file or resource to mount |
+//! | Where | String | the absolute path of the mount point, must match the unit name |
+//! | Type | String | the file system type, optional |
+//! | Options | String | the mount options, split by ',' |
+//! | TimeoutSec | u64 | the timeout of the mount/umount command, default is 90s |
 //!
 //! ## Automatic dependency
 //! NA
@@ -21,10 +41,12 @@
 //! ### Default Dependency
 //! NA
 
-// dependency: mount_base -> mount_rentry -> mount_comm -> {mount_mng -> mount_unit} -> mount_manager
+// dependency: mount_base -> mount_rentry -> mount_comm -> mount_config -> mount_spawn -> {mount_mng -> mount_unit} -> mount_manager
 mod base;
 mod comm;
+mod config;
 mod manager;
 mod mng;
 mod rentry;
+mod spawn;
 mod unit;
@@ -10,10 +10,10 @@
 // NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
 // See the Mulan PSL v2 for more details.
 
-use super::base::PLUGIN_NAME;
+use super::base::{mount_point_to_unit_name, PLUGIN_NAME};
 use super::comm::MountUmComm;
 use super::rentry::{MountRe, MountReFrame};
-use basic::logger;
+use basic::{logger, IN_SET};
 use event::{EventState, EventType, Events, Source};
 use libmount::mountinfo;
 use std::collections::HashSet;
@@ -362,6 +362,15 @@ impl MountMonitorData {
                     if dead_mount_set.contains(unit_name.as_str()) {
                         dead_mount_set.remove(unit_name.as_str());
                     } else if self.comm.um().load_unit_success(unit_name.as_str()) {
+                        // The configured mount unit may be mounting or unmounting
+                        // by itself, the control process will reconcile its state.
+                        if !IN_SET!(
+                            self.comm.um().current_active_state(&unit_name),
+                            UnitActiveState::UnitInActive,
+                            UnitActiveState::UnitFailed
+                        ) {
+                            continue;
+                        }
                         // record + action
                         self.comm.reli().set_last_unit(&unit_name);
                         let start_ok = self.comm.um().unit_start_directly(&unit_name).is_ok();
@@ -404,14 +413,6 @@ fn drain_out(epfd: i32) {
     while epoll::wait(epfd, 0, &mut me_events).unwrap() > 0 {}
 }
 
-fn mount_point_to_unit_name(mount_point: &str) -> String {
-    let mut res = String::from(mount_point).replace('/', "-") + ".mount";
-    if res != "-.mount" {
-        res = String::from(&res[1..])
-    }
-    res
-}
-
 impl UnitMngUtil for MountManager {
     fn attach_um(&self, um: Rc<dyn UmIf>) {
         self.comm.attach_um(um);
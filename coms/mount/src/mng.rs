@@ -11,10 +11,24 @@
 // See the Mulan PSL v2 for more details.
 
 //!  The core logic of the mount subclass
+use super::base::{MOUNT_BIN, UMOUNT_BIN};
 use super::comm::MountUnitComm;
-use super::rentry::MountState;
-use std::{cell::RefCell, rc::Rc};
+use super::config::MountConfig;
+use super::rentry::{MountResult, MountState};
+use super::spawn::MountSpawn;
+use basic::{process_util, IN_SET};
+use event::{EventState, EventType, Events, Source};
+use libmount::mountinfo;
+use nix::sys::signal::Signal;
+use nix::sys::wait::WaitStatus;
+use nix::unistd::Pid;
+use std::fs::File;
+use std::io::Read;
+use std::os::unix::prelude::RawFd;
+use std::rc::{Rc, Weak};
+use std::{cell::RefCell, path::Path};
 use sysmaster::error::*;
+use sysmaster::exec::{ExecCommand, ExecContext};
 use sysmaster::rel::ReStation;
 use sysmaster::unit::{UnitActiveState, UnitNotifyFlags};
 
@@ -22,14 +36,35 @@ impl MountState {
     fn mount_state_to_unit_state(&self) -> UnitActiveState {
         match *self {
             MountState::Dead => UnitActiveState::UnitInActive,
+            MountState::Mounting => UnitActiveState::UnitActivating,
             MountState::Mounted => UnitActiveState::UnitActive,
+            MountState::Remounting => UnitActiveState::UnitReloading,
+            MountState::Unmounting => UnitActiveState::UnitDeActivating,
+            MountState::Failed => UnitActiveState::UnitFailed,
         }
     }
+
+    fn with_control_process(&self) -> bool {
+        matches!(
+            *self,
+            MountState::Mounting | MountState::Remounting | MountState::Unmounting
+        )
+    }
 }
 
 pub(super) struct MountMng {
+    // associated objects
     comm: Rc<MountUnitComm>,
+    config: Rc<MountConfig>,
+
+    // owned objects
+    spawn: MountSpawn,
     state: RefCell<MountState>,
+    result: RefCell<MountResult>,
+    reload_result: RefCell<MountResult>,
+    control_pid: RefCell<Option<Pid>>,
+    timer: Rc<MountTimer>,
+    timer_armed: RefCell<bool>,
 }
 
 impl ReStation for MountMng {
@@ -37,62 +72,387 @@ impl ReStation for MountMng {
 
     // data
     fn db_map(&self, _reload: bool) {
-        if let Some(state) = self.comm.rentry_mng_get() {
+        if let Some((state, result, reload_result, control_pid)) = self.comm.rentry_mng_get() {
             *self.state.borrow_mut() = state;
+            *self.result.borrow_mut() = result;
+            *self.reload_result.borrow_mut() = reload_result;
+            *self.control_pid.borrow_mut() = control_pid;
         }
     }
 
     fn db_insert(&self) {
-        self.comm.rentry_mng_insert(self.state());
+        self.comm.rentry_mng_insert(
+            self.state(),
+            self.result(),
+            self.reload_result(),
+            self.control_pid(),
+        );
+    }
+
+    // reload: entry-only
+    fn entry_coldplug(&self) {
+        if self.state().with_control_process() {
+            if let Err(e) = self.enable_timer(self.timeout_usec()) {
+                log::error!("Failed to rearm the timer of {}: {}", self.id(), e);
+            }
+        }
     }
 
-    // reload: no external connections, no entry
+    fn entry_clear(&self) {
+        self.delete_timer();
+    }
 }
 
 impl MountMng {
-    pub(super) fn new(_comm: &Rc<MountUnitComm>) -> Self {
+    pub(super) fn new(
+        commr: &Rc<MountUnitComm>,
+        configr: &Rc<MountConfig>,
+        exec_ctx: &Rc<ExecContext>,
+    ) -> Self {
         MountMng {
-            comm: Rc::clone(_comm),
+            comm: Rc::clone(commr),
+            config: Rc::clone(configr),
+            spawn: MountSpawn::new(commr, exec_ctx),
             state: RefCell::new(MountState::Dead),
+            result: RefCell::new(MountResult::Success),
+            reload_result: RefCell::new(MountResult::Success),
+            control_pid: RefCell::new(None),
+            timer: Rc::new(MountTimer::new(0)),
+            timer_armed: RefCell::new(false),
         }
     }
 
-    // process doesn't support manually mount/umount like systemd.
-    // We only monitor the state of mountpoint.
-
-    pub(super) fn enter_dead(&self, notify: bool) {
-        self.set_state(MountState::Dead, notify);
-    }
-
-    pub(super) fn enter_mounted(&self, notify: bool) {
-        self.set_state(MountState::Mounted, notify);
+    pub(super) fn attach_timer(&self, mng: Weak<MountMng>) {
+        self.timer.attach_mng(mng);
     }
 
     pub(super) fn start_check(&self) -> Result<bool> {
+        if self.state() == MountState::Unmounting {
+            return Err(Error::UnitActionEAgain);
+        }
+
+        if IN_SET!(self.state(), MountState::Mounting, MountState::Remounting) {
+            return Ok(true);
+        }
+
         let ret = self.comm.owner().map_or(false, |u| u.test_start_limit());
         if !ret {
-            self.enter_dead(true);
+            self.enter_dead(MountResult::FailureStartLimitHit);
             return Err(Error::UnitActionECanceled);
         }
 
         Ok(false)
     }
 
+    pub(super) fn start_action(&self) {
+        /* make sure the former failure doesn't disturb later action. */
+        self.set_result(MountResult::Success);
+        self.set_reload_result(MountResult::Success);
+
+        // The mount unit without configuration is started by the monitor,
+        // which means the mount point is already mounted.
+        if !self.config.has_fragment() || self.is_mounted() {
+            self.enter_mounted(MountResult::Success);
+            return;
+        }
+
+        self.enter_mounting();
+    }
+
+    pub(super) fn stop_check(&self) -> Result<bool> {
+        if self.state() == MountState::Unmounting {
+            return Ok(true);
+        }
+
+        if IN_SET!(self.state(), MountState::Mounting, MountState::Remounting) {
+            // the result is reconciled when the control process exits
+            self.kill_control_process();
+            return Err(Error::UnitActionEAgain);
+        }
+
+        Ok(false)
+    }
+
+    pub(super) fn stop_action(&self) {
+        // Nothing to umount: the mount point is gone or we don't know where it is.
+        if !self.config.has_fragment() || !self.is_mounted() {
+            self.enter_dead(MountResult::Success);
+            return;
+        }
+
+        self.enter_unmounting();
+    }
+
+    pub(super) fn can_reload(&self) -> bool {
+        self.config.has_fragment()
+    }
+
+    pub(super) fn reload_action(&self) -> Result<()> {
+        if self.state() != MountState::Mounted {
+            return Err(Error::UnitActionEAgain);
+        }
+
+        self.set_reload_result(MountResult::Success);
+        self.enter_remounting();
+        Ok(())
+    }
+
+    fn enter_mounting(&self) {
+        let argv = {
+            let config = self.config.config_data();
+            let mount = &config.borrow().Mount;
+            if let Err(e) = std::fs::create_dir_all(&mount.Where) {
+                log::warn!("Failed to create mount point {}: {}", mount.Where, e);
+            }
+
+            let mut argv = vec![mount.What.clone(), mount.Where.clone()];
+            if !mount.Type.is_empty() {
+                argv.push("-t".to_string());
+                argv.push(mount.Type.clone());
+            }
+            if !mount.Options.is_empty() {
+                argv.push("-o".to_string());
+                argv.push(mount.Options.clone());
+            }
+            argv
+        };
+
+        let cmd = ExecCommand::new(MOUNT_BIN.to_string(), argv);
+        match self.spawn_control(&cmd) {
+            Ok(_) => self.set_state(MountState::Mounting),
+            Err(e) => {
+                log::error!("Failed to run 'mount' task for {}: {}", self.id(), e);
+                self.enter_dead(MountResult::FailureResources);
+            }
+        }
+    }
+
+    fn enter_remounting(&self) {
+        let argv = {
+            let config = self.config.config_data();
+            let mount = &config.borrow().Mount;
+
+            let mut options = String::from("remount");
+            if !mount.Options.is_empty() {
+                options = options + "," + &mount.Options;
+            }
+            let mut argv = vec![
+                mount.What.clone(),
+                mount.Where.clone(),
+                "-o".to_string(),
+                options,
+            ];
+            if !mount.Type.is_empty() {
+                argv.push("-t".to_string());
+                argv.push(mount.Type.clone());
+            }
+            argv
+        };
+
+        let cmd = ExecCommand::new(MOUNT_BIN.to_string(), argv);
+        match self.spawn_control(&cmd) {
+            Ok(_) => self.set_state(MountState::Remounting),
+            Err(e) => {
+                log::error!("Failed to run 'remount' task for {}: {}", self.id(), e);
+                self.set_reload_result(MountResult::FailureResources);
+                self.enter_mounted(MountResult::Success);
+            }
+        }
+    }
+
+    fn enter_unmounting(&self) {
+        let argv = vec![self.config.mount_where()];
+        let cmd = ExecCommand::new(UMOUNT_BIN.to_string(), argv);
+        match self.spawn_control(&cmd) {
+            Ok(_) => self.set_state(MountState::Unmounting),
+            Err(e) => {
+                log::error!("Failed to run 'umount' task for {}: {}", self.id(), e);
+                self.enter_mounted(MountResult::FailureResources);
+            }
+        }
+    }
+
+    pub(super) fn enter_dead(&self, res: MountResult) {
+        if self.result() == MountResult::Success {
+            self.set_result(res);
+        }
+
+        let state = if self.result() == MountResult::Success {
+            MountState::Dead
+        } else {
+            MountState::Failed
+        };
+        self.set_state(state);
+    }
+
+    pub(super) fn enter_mounted(&self, res: MountResult) {
+        if self.result() == MountResult::Success {
+            self.set_result(res);
+        }
+
+        self.set_state(MountState::Mounted);
+    }
+
+    fn spawn_control(&self, cmd: &ExecCommand) -> Result<()> {
+        self.unwatch_control();
+        let pid = self.spawn.spawn_cmd(cmd)?;
+        *self.control_pid.borrow_mut() = Some(pid);
+        self.enable_timer(self.timeout_usec())?;
+        Ok(())
+    }
+
+    fn sigchld_result(&self, wait_status: WaitStatus) -> MountResult {
+        match wait_status {
+            WaitStatus::Exited(_, status) => {
+                if status == 0 {
+                    MountResult::Success
+                } else {
+                    MountResult::FailureExitCode
+                }
+            }
+            WaitStatus::Signaled(_, _, core_dump) => {
+                if core_dump {
+                    MountResult::FailureCoreDump
+                } else {
+                    MountResult::FailureSignal
+                }
+            }
+            _ => MountResult::Success,
+        }
+    }
+
+    pub(super) fn sigchld_event(&self, wait_status: WaitStatus) {
+        if wait_status.pid() != self.control_pid() {
+            return;
+        }
+        *self.control_pid.borrow_mut() = None;
+
+        let res = self.sigchld_result(wait_status);
+        log::debug!(
+            "Control process of {} exited in state {:?}, result: {:?}",
+            self.id(),
+            self.state(),
+            res
+        );
+
+        // the state of the mount point is the final judgement.
+        match self.state() {
+            MountState::Mounting => {
+                if self.is_mounted() {
+                    self.enter_mounted(res);
+                } else if res == MountResult::Success {
+                    log::error!("Mount process of {} finished, but there is no mount.", self.id());
+                    self.enter_dead(MountResult::FailureProtocol);
+                } else {
+                    self.enter_dead(res);
+                }
+            }
+            MountState::Remounting => {
+                if self.reload_result() == MountResult::Success {
+                    self.set_reload_result(res);
+                }
+                if self.is_mounted() {
+                    self.enter_mounted(MountResult::Success);
+                } else {
+                    self.enter_dead(MountResult::Success);
+                }
+            }
+            MountState::Unmounting => {
+                if self.is_mounted() {
+                    log::error!("Failed to umount {}, it is still mounted.", self.id());
+                    self.enter_mounted(res);
+                } else {
+                    self.enter_dead(res);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn dispatch_timer(&self) {
+        log::warn!("{} operation of {} timed out.", self.state(), self.id());
+        match self.state() {
+            MountState::Mounting | MountState::Unmounting => {
+                self.set_result(MountResult::FailureTimeout);
+                self.kill_control_process();
+            }
+            MountState::Remounting => {
+                self.set_reload_result(MountResult::FailureTimeout);
+                self.kill_control_process();
+            }
+            _ => {}
+        }
+    }
+
+    fn kill_control_process(&self) {
+        if let Some(pid) = self.control_pid() {
+            if let Err(e) = process_util::kill_and_cont(pid, Signal::SIGKILL) {
+                log::warn!("Failed to kill control process {}: {}", pid, e);
+            }
+        }
+    }
+
+    fn unwatch_control(&self) {
+        if let Some(pid) = self.control_pid.take() {
+            if let Some(u) = self.comm.owner() {
+                self.comm.um().child_unwatch_pid(u.id(), pid)
+            }
+        }
+    }
+
+    fn is_mounted(&self) -> bool {
+        mount_point_is_mounted(&self.config.mount_where())
+    }
+
+    fn timeout_usec(&self) -> u64 {
+        self.config.config_data().borrow().Mount.TimeoutSec
+    }
+
+    fn enable_timer(&self, usec: u64) -> Result<()> {
+        let events = self.comm.um().events();
+        if usec == 0 || usec == u64::MAX {
+            self.delete_timer();
+            return Ok(());
+        }
+
+        let timer = Rc::clone(&self.timer);
+        if *self.timer_armed.borrow() {
+            events.set_enabled(timer.clone(), EventState::Off)?;
+        } else {
+            events.add_source(timer.clone())?;
+            *self.timer_armed.borrow_mut() = true;
+        }
+        timer.set_time(usec);
+        events.set_enabled(timer, EventState::OneShot)?;
+        Ok(())
+    }
+
+    fn delete_timer(&self) {
+        if !*self.timer_armed.borrow() {
+            return;
+        }
+
+        let events = self.comm.um().events();
+        let timer = Rc::clone(&self.timer);
+        events.set_enabled(timer.clone(), EventState::Off).unwrap();
+        events.del_source(timer).unwrap();
+        *self.timer_armed.borrow_mut() = false;
+    }
+
     pub fn get_state(&self) -> String {
         let state = *self.state.borrow();
         state.to_string()
     }
 
-    fn set_state(&self, new_state: MountState, notify: bool) {
+    fn set_state(&self, new_state: MountState) {
         let old_state = self.state();
         self.change_state(new_state);
 
-        if notify {
-            self.state_notify(new_state, old_state);
+        if !new_state.with_control_process() {
+            self.delete_timer();
+            self.unwatch_control();
         }
-    }
 
-    fn state_notify(&self, new_state: MountState, old_state: MountState) {
         if new_state != old_state {
             log::debug!(
                 "{} original state[{:?}] -> new state[{:?}]",
@@ -102,17 +462,18 @@ impl MountMng {
             );
         }
 
-        let old_unit_state = old_state.mount_state_to_unit_state();
-        let new_unit_state = new_state.mount_state_to_unit_state();
         if let Some(u) = self.comm.owner() {
+            let mut flags = UnitNotifyFlags::UNIT_NOTIFY_SUCCESS;
+            if self.reload_result() != MountResult::Success {
+                flags |= UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE;
+            }
             u.notify(
-                old_unit_state,
-                new_unit_state,
-                UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE,
-            )
+                old_state.mount_state_to_unit_state(),
+                new_state.mount_state_to_unit_state(),
+                flags,
+            );
+            self.db_update();
         }
-
-        self.db_update();
     }
 
     fn change_state(&self, new_state: MountState) {
@@ -123,39 +484,181 @@ impl MountMng {
         *self.state.borrow()
     }
 
+    fn result(&self) -> MountResult {
+        *self.result.borrow()
+    }
+
+    fn set_result(&self, res: MountResult) {
+        *self.result.borrow_mut() = res;
+    }
+
+    fn reload_result(&self) -> MountResult {
+        *self.reload_result.borrow()
+    }
+
+    fn set_reload_result(&self, res: MountResult) {
+        *self.reload_result.borrow_mut() = res;
+    }
+
+    pub(super) fn control_pid(&self) -> Option<Pid> {
+        *self.control_pid.borrow()
+    }
+
+    fn id(&self) -> String {
+        self.comm.get_owner_id()
+    }
+
     pub(super) fn mount_state_to_unit_state(&self) -> UnitActiveState {
         self.state().mount_state_to_unit_state()
     }
 }
 
+fn mount_point_is_mounted(mount_point: &str) -> bool {
+    if mount_point.is_empty() {
+        return false;
+    }
+    let target = Path::new(mount_point);
+
+    let mut mountinfo_content = String::new();
+    if let Err(e) =
+        File::open("/proc/self/mountinfo").and_then(|mut f| f.read_to_string(&mut mountinfo_content))
+    {
+        log::error!("Failed to read /proc/self/mountinfo: {}", e);
+        return false;
+    }
+
+    let parser = mountinfo::Parser::new(mountinfo_content.as_bytes());
+    for mount in parser.flatten() {
+        if Path::new(&mount.mount_point) == target {
+            return true;
+        }
+    }
+    false
+}
+
+pub(super) struct MountTimer {
+    time: RefCell<u64>,
+    mng: RefCell<Weak<MountMng>>,
+}
+
+impl MountTimer {
+    pub fn new(usec: u64) -> Self {
+        MountTimer {
+            time: RefCell::new(usec),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    pub(super) fn attach_mng(&self, mng: Weak<MountMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    pub(super) fn set_time(&self, usec: u64) {
+        *self.time.borrow_mut() = usec
+    }
+
+    pub(self) fn mng(&self) -> Rc<MountMng> {
+        self.mng.borrow().clone().upgrade().unwrap()
+    }
+}
+
+impl Source for MountTimer {
+    fn fd(&self) -> RawFd {
+        0
+    }
+
+    fn event_type(&self) -> EventType {
+        EventType::TimerMonotonic
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn time_relative(&self) -> u64 {
+        *self.time.borrow()
+    }
+
+    fn dispatch(&self, _: &Events) -> i32 {
+        self.mng().dispatch_timer();
+        0
+    }
+
+    fn priority(&self) -> i8 {
+        0
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::MountMng;
+    use super::MountResult;
     use super::MountState;
     use super::MountUnitComm;
+    use crate::config::MountConfig;
     use std::rc::Rc;
+    use sysmaster::exec::ExecContext;
+    use sysmaster::unit::UnitActiveState;
+
+    fn new_mng() -> MountMng {
+        let _comm = Rc::new(MountUnitComm::new());
+        let _config = Rc::new(MountConfig::new(&_comm));
+        let _ctx = Rc::new(ExecContext::new());
+        MountMng::new(&_comm, &_config, &_ctx)
+    }
 
     #[test]
     fn test_mount_set_state() {
-        let _comm = Rc::new(MountUnitComm::new());
-        let tm = MountMng::new(&_comm);
-        tm.set_state(MountState::Mounted, false);
+        let tm = new_mng();
+        tm.set_state(MountState::Mounted);
         assert_eq!(tm.state(), MountState::Mounted)
     }
 
     #[test]
     fn test_mount_enter_dead() {
-        let _comm = Rc::new(MountUnitComm::new());
-        let tm = MountMng::new(&_comm);
-        tm.enter_dead(false);
-        assert_eq!(tm.state(), MountState::Dead)
+        let tm = new_mng();
+        tm.enter_dead(MountResult::Success);
+        assert_eq!(tm.state(), MountState::Dead);
+
+        tm.enter_dead(MountResult::FailureExitCode);
+        assert_eq!(tm.state(), MountState::Failed);
     }
 
     #[test]
     fn test_mount_enter_mounted() {
-        let _comm = Rc::new(MountUnitComm::new());
-        let tm = MountMng::new(&_comm);
-        tm.enter_mounted(false);
+        let tm = new_mng();
+        tm.enter_mounted(MountResult::Success);
         assert_eq!(tm.state(), MountState::Mounted)
     }
+
+    #[test]
+    fn test_mount_active_state() {
+        assert_eq!(
+            MountState::Mounting.mount_state_to_unit_state(),
+            UnitActiveState::UnitActivating
+        );
+        assert_eq!(
+            MountState::Remounting.mount_state_to_unit_state(),
+            UnitActiveState::UnitReloading
+        );
+        assert_eq!(
+            MountState::Unmounting.mount_state_to_unit_state(),
+            UnitActiveState::UnitDeActivating
+        );
+        assert_eq!(
+            MountState::Failed.mount_state_to_unit_state(),
+            UnitActiveState::UnitFailed
+        );
+    }
+
+    #[test]
+    fn test_mount_point_is_mounted() {
+        assert!(super::mount_point_is_mounted("/"));
+        assert!(!super::mount_point_is_mounted(""));
+    }
 }
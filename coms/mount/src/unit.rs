@@ -15,17 +15,22 @@
 
 use super::base::PLUGIN_NAME;
 use super::comm::MountUnitComm;
+use super::config::MountConfig;
 use super::mng::MountMng;
 use basic::logger;
 use nix::sys::wait::WaitStatus;
+use nix::unistd::Pid;
 use std::path::PathBuf;
 use std::rc::Rc;
 use sysmaster::error::*;
+use sysmaster::exec::ExecContext;
 use sysmaster::rel::{ReStation, Reliability};
+use sysmaster::serialize::section_properties;
 use sysmaster::unit::{SubUnit, UmIf, UnitActiveState, UnitBase, UnitMngUtil};
 
 struct MountUnit {
     comm: Rc<MountUnitComm>,
+    config: Rc<MountConfig>,
     mng: Rc<MountMng>,
 }
 
@@ -34,39 +39,51 @@ impl ReStation for MountUnit {
 
     // data
     fn db_map(&self, reload: bool) {
+        self.config.db_map(reload);
         self.mng.db_map(reload);
     }
 
     fn db_insert(&self) {
+        self.config.db_insert();
         self.mng.db_insert();
     }
 
     // reload: no external connections, entry-only
     fn entry_coldplug(&self) {
         // rebuild external connections, like: timer, ...
-        // do nothing now
+        self.mng.entry_coldplug();
     }
 
     fn entry_clear(&self) {
         // release external connection, like: timer, ...
-        // do nothing now
+        self.mng.entry_clear();
     }
 }
 
 impl MountUnit {
     fn new(_um: Rc<dyn UmIf>) -> MountUnit {
+        let context = Rc::new(ExecContext::new());
         let _comm = Rc::new(MountUnitComm::new());
+        let _config = Rc::new(MountConfig::new(&_comm));
+        let _mng = Rc::new(MountMng::new(&_comm, &_config, &context));
+        _mng.attach_timer(Rc::downgrade(&_mng));
         MountUnit {
             comm: Rc::clone(&_comm),
-            mng: Rc::new(MountMng::new(&_comm)),
+            config: Rc::clone(&_config),
+            mng: _mng,
         }
     }
 }
 
 impl SubUnit for MountUnit {
-    fn load(&self, _paths: Vec<PathBuf>) -> Result<()> {
-        if let Some(u) = self.comm.owner() {
-            u.set_ignore_on_isolate(true)
+    fn load(&self, paths: Vec<PathBuf>) -> Result<()> {
+        self.config.load(paths, true)?;
+
+        // the mount points that only monitored are not affected by isolate
+        if !self.config.has_fragment() {
+            if let Some(u) = self.comm.owner() {
+                u.set_ignore_on_isolate(true)
+            }
         }
         Ok(())
     }
@@ -79,6 +96,10 @@ impl SubUnit for MountUnit {
         self.mng.get_state()
     }
 
+    fn properties(&self) -> Vec<(String, String)> {
+        section_properties(&self.config.config_data().borrow().Mount)
+    }
+
     fn attach_unit(&self, unit: Rc<dyn UnitBase>) {
         self.comm.attach_unit(unit);
         self.db_insert();
@@ -97,21 +118,43 @@ impl SubUnit for MountUnit {
             return Ok(());
         }
 
-        self.mng.enter_mounted(true);
+        self.mng.start_action();
 
         Ok(())
     }
 
-    fn stop(&self, _force: bool) -> Result<()> {
-        self.mng.enter_dead(true);
+    fn stop(&self, force: bool) -> Result<()> {
+        if !force {
+            let stopping = self.mng.stop_check()?;
+            if stopping {
+                log::debug!("mount already in stopping, just return immediately");
+                return Ok(());
+            }
+        }
+
+        self.mng.stop_action();
         Ok(())
     }
 
+    fn reload(&self) -> Result<()> {
+        self.mng.reload_action()
+    }
+
+    fn can_reload(&self) -> bool {
+        self.mng.can_reload()
+    }
+
     fn kill(&self) {}
 
     fn release_resources(&self) {}
 
-    fn sigchld_events(&self, _wait_status: WaitStatus) {}
+    fn sigchld_events(&self, wait_status: WaitStatus) {
+        self.mng.sigchld_event(wait_status)
+    }
+
+    fn control_pid(&self) -> Option<Pid> {
+        self.mng.control_pid()
+    }
 
     fn reset_failed(&self) {}
 }
@@ -0,0 +1,163 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! path_config mod load the conf file list and convert it to structure which is defined in this mod.
+//!
+#![allow(non_snake_case)]
+use super::comm::PathUnitComm;
+use super::rentry::SectionPath;
+use confique::Config;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use sysmaster::error::*;
+use sysmaster::rel::ReStation;
+
+#[derive(Config, Default, Debug)]
+pub(super) struct PathConfigData {
+    #[config(nested)]
+    pub Path: SectionPath,
+}
+
+impl PathConfigData {
+    pub(self) fn new(Path: SectionPath) -> PathConfigData {
+        PathConfigData { Path }
+    }
+}
+
+/// the condition of the watched path
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub(super) enum PathType {
+    Exists,
+    ExistsGlob,
+    DirectoryNotEmpty,
+    Changed,
+    Modified,
+}
+
+pub(super) struct PathConfig {
+    // associated objects
+    comm: Rc<PathUnitComm>,
+
+    // owned objects
+    data: Rc<RefCell<PathConfigData>>,
+    unit: RefCell<Option<String>>,
+}
+
+impl ReStation for PathConfig {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, reload: bool) {
+        if reload {
+            return;
+        }
+        if let Some((data, unit)) = self.comm.rentry_conf_get() {
+            self.data.replace(PathConfigData::new(data));
+            self.unit.replace(unit);
+        }
+    }
+
+    fn db_insert(&self) {
+        self.comm
+            .rentry_conf_insert(&self.data.borrow().Path, self.unit());
+    }
+
+    // reload: no external connections, no entry
+}
+
+impl PathConfig {
+    pub(super) fn new(commr: &Rc<PathUnitComm>) -> Self {
+        PathConfig {
+            comm: Rc::clone(commr),
+            data: Rc::new(RefCell::new(PathConfigData::default())),
+            unit: RefCell::new(None),
+        }
+    }
+
+    pub(super) fn load(&self, paths: Vec<PathBuf>, update: bool) -> Result<()> {
+        let mut builder = PathConfigData::builder().env();
+        for v in paths {
+            builder = builder.file(v);
+        }
+
+        *self.data.borrow_mut() = builder.load().context(ConfiqueSnafu)?;
+        let specs = self.specs();
+        if specs.is_empty() {
+            return Err("path unit has no path configured".to_string().into());
+        }
+        if let Some((_, p)) = specs.iter().find(|(_, p)| !p.is_absolute()) {
+            return Err(format!("path {p:?} is not absolute").into());
+        }
+        self.parse_unit()?;
+
+        if update {
+            self.db_update();
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<PathConfigData>> {
+        self.data.clone()
+    }
+
+    /// all the watched paths and their conditions
+    pub(super) fn specs(&self) -> Vec<(PathType, PathBuf)> {
+        let data = self.data.borrow();
+        let path = &data.Path;
+        let mut specs = Vec::new();
+        for (p_type, paths) in [
+            (PathType::Exists, &path.PathExists),
+            (PathType::ExistsGlob, &path.PathExistsGlob),
+            (PathType::DirectoryNotEmpty, &path.DirectoryNotEmpty),
+            (PathType::Changed, &path.PathChanged),
+            (PathType::Modified, &path.PathModified),
+        ] {
+            for p in paths.iter().flatten() {
+                specs.push((p_type, PathBuf::from(p)));
+            }
+        }
+        specs
+    }
+
+    /// the unit triggered by the path
+    pub(super) fn unit(&self) -> Option<String> {
+        self.unit.borrow().clone()
+    }
+
+    fn parse_unit(&self) -> Result<()> {
+        let owner = match self.comm.owner() {
+            None => return Ok(()),
+            Some(u) => u,
+        };
+
+        // the unit with the same name as the path is triggered by default
+        let unit = match self.data.borrow().Path.Unit.clone() {
+            Some(unit) => unit,
+            None => {
+                let stem = Path::new(owner.id()).file_stem().unwrap().to_str().unwrap();
+                format!("{stem}.service")
+            }
+        };
+
+        if unit.ends_with(".path") {
+            return Err(format!("path can't trigger another path unit {unit}").into());
+        }
+        if !self.comm.um().load_unit_success(&unit) {
+            return Err(format!("failed to load unit {unit}").into());
+        }
+
+        self.unit.replace(Some(unit));
+        Ok(())
+    }
+}
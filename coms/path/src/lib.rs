@@ -0,0 +1,43 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! # Path is used to activate another unit when the watched path changes.
+//!  The paths are watched by inotify, when the condition of any path becomes true, the unit with the same name as the path is started,
+//!  like foo.path starts foo.service. The triggered unit can be changed with Unit=.
+//!  The paths are watched again after the triggered unit is stopped.
+//! #  Example:
+//! ``` toml
+//!  [Unit]
+//!  Description="foo"
+//!
+//!  [Path]
+//!  PathExists="/run/foo/ready"
+//!  DirectoryNotEmpty="/var/spool/foo"
+//!  MakeDirectory=true
+//! ```
+//! ##  Automatic dependency
+//!
+//! ###  Implicit dependency
+//!  The path has Before= and Triggers= dependencies on the unit it triggers.
+//!
+//! ###  Default Dependency
+//!  If DefaultDependencies=true is set, the following dependencies will be added by default:
+//!  Requires="sysinit.target", After="sysinit.target", Conflicts="shutdown.target", Before="shutdown.target", Before="paths.target"
+
+// dependency: path_base -> path_rentry -> path_comm -> {path_mng, path_config} -> path_unit -> path_manager
+mod base;
+mod comm;
+mod config;
+mod manager;
+mod mng;
+mod rentry;
+mod unit;
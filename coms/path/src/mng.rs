@@ -0,0 +1,645 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! is the core of the path unit, watch the paths by inotify and trigger the unit
+//!
+use super::comm::PathUnitComm;
+use super::config::{PathConfig, PathType};
+use super::rentry::{PathResult, PathState};
+use basic::{fd_util, IN_SET};
+use event::{EventState, EventType, Events, Source};
+use nix::errno::Errno;
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify, WatchDescriptor};
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::fs;
+use std::os::unix::prelude::{AsRawFd, FromRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+use std::time::Instant;
+use sysmaster::error::*;
+use sysmaster::rel::ReStation;
+use sysmaster::unit::{UnitActiveState, UnitNotifyFlags};
+
+impl PathState {
+    fn to_unit_state(self) -> UnitActiveState {
+        match self {
+            PathState::Dead | PathState::StateMax => UnitActiveState::UnitInActive,
+            PathState::Waiting | PathState::Running => UnitActiveState::UnitActive,
+            PathState::Failed => UnitActiveState::UnitFailed,
+        }
+    }
+}
+
+pub(super) struct PathMng {
+    // associated objects
+    comm: Rc<PathUnitComm>,
+    config: Rc<PathConfig>,
+
+    // owned objects
+    state: RefCell<PathState>,
+    result: RefCell<PathResult>,
+    specs: RefCell<Vec<Rc<PathSpec>>>,
+    trigger_limit: RefCell<TriggerLimit>,
+    this: RefCell<Weak<PathMng>>,
+}
+
+impl ReStation for PathMng {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, _reload: bool) {
+        if let Some((state, result)) = self.comm.rentry_mng_get() {
+            *self.state.borrow_mut() = state;
+            *self.result.borrow_mut() = result;
+        }
+    }
+
+    fn db_insert(&self) {
+        self.comm.rentry_mng_insert(self.state(), self.result());
+    }
+
+    // reload: entry-only
+    fn entry_coldplug(&self) {
+        if self.state() == PathState::Waiting {
+            if let Err(e) = self.watch() {
+                log::error!("Failed to rewatch the paths of {}: {}", self.id(), e);
+            }
+        }
+    }
+
+    fn entry_clear(&self) {
+        let events = self.comm.um().events();
+        for spec in self.specs.borrow().iter() {
+            spec.delete_source(&events);
+        }
+    }
+}
+
+impl PathMng {
+    pub(super) fn new(commr: &Rc<PathUnitComm>, configr: &Rc<PathConfig>) -> Self {
+        PathMng {
+            comm: Rc::clone(commr),
+            config: Rc::clone(configr),
+            state: RefCell::new(PathState::StateMax),
+            result: RefCell::new(PathResult::Success),
+            specs: RefCell::new(Vec::new()),
+            trigger_limit: RefCell::new(TriggerLimit::new(0, 0)),
+            this: RefCell::new(Weak::new()),
+        }
+    }
+
+    pub(super) fn attach_mng(&self, mng: Weak<PathMng>) {
+        *self.this.borrow_mut() = mng;
+    }
+
+    /// build the watched paths from the configuration
+    pub(super) fn build_specs(&self) {
+        let events = self.comm.um().events();
+        for spec in self.specs.borrow().iter() {
+            spec.delete_source(&events);
+        }
+
+        let mng = self.this.borrow().clone();
+        let specs = self
+            .config
+            .specs()
+            .into_iter()
+            .map(|(p_type, path)| Rc::new(PathSpec::new(path, p_type, mng.clone())))
+            .collect();
+        *self.specs.borrow_mut() = specs;
+
+        let data = self.config.config_data();
+        let data = data.borrow();
+        let path = &data.Path;
+        *self.trigger_limit.borrow_mut() =
+            TriggerLimit::new(path.TriggerLimitIntervalSec, path.TriggerLimitBurst);
+    }
+
+    pub(super) fn start_check(&self) -> Result<()> {
+        if IN_SET!(self.state(), PathState::Waiting, PathState::Running) {
+            return Err(Error::UnitActionEAgain);
+        }
+        Ok(())
+    }
+
+    pub(super) fn start_action(&self) {
+        if self.config.config_data().borrow().Path.MakeDirectory {
+            self.make_directory();
+        }
+
+        *self.result.borrow_mut() = PathResult::Success;
+        self.enter_waiting(true, false);
+    }
+
+    pub(super) fn stop_check(&self) -> Result<()> {
+        if IN_SET!(self.state(), PathState::Dead) {
+            return Err(Error::UnitActionEAgain);
+        }
+        Ok(())
+    }
+
+    pub(super) fn stop_action(&self) {
+        self.enter_dead(PathResult::Success);
+    }
+
+    pub(super) fn reset_failed(&self) {
+        if self.state() == PathState::Failed {
+            self.set_state(PathState::Dead);
+        }
+        *self.result.borrow_mut() = PathResult::Success;
+    }
+
+    /// the state of the triggered unit is changed, watch the paths again after it is stopped
+    pub(super) fn trigger_notify(&self, other: &str) {
+        if self.config.unit().as_deref() != Some(other) {
+            return;
+        }
+
+        let um = self.comm.um();
+        if self.state() == PathState::Running
+            && um.current_active_state(other).is_inactive_or_failed()
+        {
+            log::debug!("{} got notified about unit deactivation.", self.id());
+            self.enter_waiting(false, true);
+        }
+    }
+
+    fn dispatch_io(&self, spec: &PathSpec) {
+        if !IN_SET!(self.state(), PathState::Waiting, PathState::Running) {
+            return;
+        }
+
+        let changed = match spec.read_fd_event() {
+            Ok(changed) => changed,
+            Err(e) => {
+                log::error!("Failed to read the inotify event of {:?}: {}", spec.path, e);
+                self.enter_dead(PathResult::FailureResources);
+                return;
+            }
+        };
+
+        if changed {
+            self.enter_running();
+        } else {
+            self.enter_waiting(false, false);
+        }
+    }
+
+    fn enter_waiting(&self, initial: bool, from_trigger_notify: bool) {
+        // only check the paths if the triggered unit is not active
+        if !from_trigger_notify {
+            if let Some(unit) = self.config.unit() {
+                let um = self.comm.um();
+                if !um.current_active_state(&unit).is_inactive_or_failed() {
+                    self.unwatch();
+                    self.set_state(PathState::Running);
+                    return;
+                }
+            }
+        }
+
+        if self.check_good(initial, from_trigger_notify) {
+            self.enter_running();
+            return;
+        }
+
+        if let Err(e) = self.watch() {
+            log::error!("Failed to watch the paths of {}: {}", self.id(), e);
+            self.enter_dead(PathResult::FailureResources);
+            return;
+        }
+
+        // the path may be changed before it is watched, so check it again
+        if self.check_good(false, from_trigger_notify) {
+            self.enter_running();
+            return;
+        }
+
+        self.set_state(PathState::Waiting);
+    }
+
+    fn enter_running(&self) {
+        if !self.trigger_limit.borrow_mut().ratelimit_below() {
+            log::warn!(
+                "{} trigger limit hit, refusing further activation.",
+                self.id()
+            );
+            self.enter_dead(PathResult::FailureTriggerLimitHit);
+            return;
+        }
+
+        let unit = match self.config.unit() {
+            None => return,
+            Some(unit) => unit,
+        };
+
+        let um = self.comm.um();
+        // don't start the unit which is being stopped, wait for the next state change of it
+        if um.has_stop_job(&unit) {
+            log::debug!("{} is being stopped, skip triggering it.", unit);
+            return;
+        }
+
+        if let Err(e) = um.unit_start_by_job(&unit) {
+            log::warn!("Failed to trigger unit {}: {:?}", unit, e);
+            self.enter_dead(PathResult::FailureResources);
+            return;
+        }
+
+        self.unwatch();
+        self.set_state(PathState::Running);
+    }
+
+    fn enter_dead(&self, res: PathResult) {
+        if self.result() == PathResult::Success {
+            *self.result.borrow_mut() = res;
+        }
+
+        if self.result() == PathResult::Success {
+            self.set_state(PathState::Dead);
+        } else {
+            self.set_state(PathState::Failed);
+        }
+    }
+
+    fn check_good(&self, initial: bool, from_trigger_notify: bool) -> bool {
+        // check every path to update the previous state of them
+        let mut good = false;
+        for spec in self.specs.borrow().iter() {
+            if spec.check_good(initial, from_trigger_notify) {
+                log::debug!("{} got triggered by {:?}.", self.id(), spec.path);
+                good = true;
+            }
+        }
+        good
+    }
+
+    fn watch(&self) -> Result<()> {
+        let events = self.comm.um().events();
+        for spec in self.specs.borrow().iter() {
+            spec.watch(&events)?;
+        }
+        Ok(())
+    }
+
+    fn unwatch(&self) {
+        let events = self.comm.um().events();
+        for spec in self.specs.borrow().iter() {
+            spec.unwatch(&events);
+        }
+    }
+
+    fn make_directory(&self) {
+        for spec in self.specs.borrow().iter() {
+            // the path to be existed is not created, only the watched directory
+            if IN_SET!(spec.p_type, PathType::Exists, PathType::ExistsGlob) {
+                continue;
+            }
+
+            if let Err(e) = fs::create_dir_all(&spec.path) {
+                log::warn!("Failed to create directory {:?}: {}", spec.path, e);
+            }
+        }
+    }
+
+    pub fn get_state(&self) -> String {
+        let state = *self.state.borrow();
+        state.to_string()
+    }
+
+    fn set_state(&self, new_state: PathState) {
+        let old_state = self.state();
+        self.state.replace(new_state);
+
+        if new_state != PathState::Waiting {
+            self.unwatch();
+        }
+
+        if new_state != old_state {
+            log::debug!(
+                "{} original state[{:?}] ->new state[{:?}]",
+                self.id(),
+                old_state,
+                new_state,
+            );
+        }
+
+        if let Some(unit) = self.comm.owner() {
+            unit.notify(
+                old_state.to_unit_state(),
+                new_state.to_unit_state(),
+                UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE,
+            );
+        }
+
+        self.db_update();
+    }
+
+    fn state(&self) -> PathState {
+        *self.state.borrow()
+    }
+
+    fn result(&self) -> PathResult {
+        *self.result.borrow()
+    }
+
+    fn id(&self) -> String {
+        self.comm
+            .owner()
+            .map_or(String::new(), |u| u.id().to_string())
+    }
+
+    pub fn to_unit_state(&self) -> UnitActiveState {
+        self.state().to_unit_state()
+    }
+}
+
+/// limit the times of triggering the unit in the interval
+struct TriggerLimit {
+    interval: u64,
+    burst: u32,
+    begin: Option<Instant>,
+    nums: u32,
+}
+
+impl TriggerLimit {
+    fn new(interval: u64, burst: u32) -> Self {
+        TriggerLimit {
+            interval,
+            burst,
+            begin: None,
+            nums: 0,
+        }
+    }
+
+    fn ratelimit_below(&mut self) -> bool {
+        if self.interval == 0 || self.burst == 0 {
+            return true;
+        }
+
+        let now = Instant::now();
+        if self.begin.is_none()
+            || now.duration_since(self.begin.unwrap()).as_secs() >= self.interval
+        {
+            self.begin = Some(now);
+            self.nums = 1;
+            return true;
+        }
+
+        if self.nums < self.burst {
+            self.nums += 1;
+            return true;
+        }
+
+        false
+    }
+}
+
+/// one watched path of the path unit
+pub(super) struct PathSpec {
+    path: PathBuf,
+    p_type: PathType,
+    inotify: RefCell<RawFd>,
+    primary_wd: RefCell<Option<WatchDescriptor>>,
+    previous_exists: RefCell<bool>,
+    added: RefCell<bool>,
+    mng: Weak<PathMng>,
+}
+
+impl PathSpec {
+    fn new(path: PathBuf, p_type: PathType, mng: Weak<PathMng>) -> Self {
+        PathSpec {
+            path,
+            p_type,
+            inotify: RefCell::new(-1),
+            primary_wd: RefCell::new(None),
+            previous_exists: RefCell::new(false),
+            added: RefCell::new(false),
+            mng,
+        }
+    }
+
+    fn flags(&self) -> AddWatchFlags {
+        let exists = AddWatchFlags::IN_DELETE_SELF
+            | AddWatchFlags::IN_MOVE_SELF
+            | AddWatchFlags::IN_ATTRIB
+            | AddWatchFlags::IN_CREATE
+            | AddWatchFlags::IN_MOVED_TO;
+        let changed = exists
+            | AddWatchFlags::IN_CLOSE_WRITE
+            | AddWatchFlags::IN_DELETE
+            | AddWatchFlags::IN_MOVED_FROM;
+        match self.p_type {
+            PathType::Exists | PathType::ExistsGlob | PathType::DirectoryNotEmpty => exists,
+            PathType::Changed => changed,
+            PathType::Modified => changed | AddWatchFlags::IN_MODIFY,
+        }
+    }
+
+    /// watch the path, or the closest existing ancestor of it, the path is checked again when the ancestor changes
+    fn watch(self: &Rc<Self>, events: &Events) -> Result<()> {
+        self.unwatch(events);
+
+        let inotify = Inotify::init(InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC)?;
+        *self.inotify.borrow_mut() = inotify.as_raw_fd();
+
+        let mut watched = false;
+        for (i, ancestor) in self.path.ancestors().enumerate() {
+            let flags = if i == 0 {
+                self.flags()
+            } else {
+                AddWatchFlags::IN_DELETE_SELF
+                    | AddWatchFlags::IN_MOVE_SELF
+                    | AddWatchFlags::IN_ATTRIB
+                    | AddWatchFlags::IN_CREATE
+                    | AddWatchFlags::IN_MOVED_TO
+            };
+
+            match inotify.add_watch(ancestor, flags) {
+                Ok(wd) => {
+                    if i == 0 {
+                        *self.primary_wd.borrow_mut() = Some(wd);
+                    }
+                    watched = true;
+                    break;
+                }
+                Err(e) => {
+                    log::debug!("Failed to watch {:?}: {}", ancestor, e);
+                }
+            }
+        }
+
+        if !watched {
+            return Err(format!("failed to watch {:?} or any of its ancestors", self.path).into());
+        }
+
+        let source: Rc<dyn Source> = self.clone();
+        if !*self.added.borrow() {
+            events.add_source(source.clone())?;
+            *self.added.borrow_mut() = true;
+        }
+        events.set_enabled(source, EventState::On)?;
+        Ok(())
+    }
+
+    // the source may be pending in the events when another path is dispatched,
+    // so only disable it here, and delete it when the unit is cleared.
+    fn unwatch(self: &Rc<Self>, events: &Events) {
+        if *self.added.borrow() {
+            let source: Rc<dyn Source> = self.clone();
+            if let Err(e) = events.set_enabled(source, EventState::Off) {
+                log::warn!("Failed to disable the watch of {:?}: {}", self.path, e);
+            }
+        }
+
+        if *self.inotify.borrow() >= 0 {
+            fd_util::close(*self.inotify.borrow());
+            *self.inotify.borrow_mut() = -1;
+        }
+        *self.primary_wd.borrow_mut() = None;
+    }
+
+    fn delete_source(self: &Rc<Self>, events: &Events) {
+        self.unwatch(events);
+        if *self.added.borrow() {
+            let source: Rc<dyn Source> = self.clone();
+            if let Err(e) = events.del_source(source) {
+                log::warn!("Failed to delete the watch of {:?}: {}", self.path, e);
+            }
+            *self.added.borrow_mut() = false;
+        }
+    }
+
+    /// return true if the watched path itself is changed or modified
+    fn read_fd_event(&self) -> Result<bool> {
+        let fd = *self.inotify.borrow();
+        if fd < 0 {
+            return Ok(false);
+        }
+
+        let inotify = unsafe { Inotify::from_raw_fd(fd) };
+        let events = match inotify.read_events() {
+            Ok(events) => events,
+            Err(Errno::EAGAIN) | Err(Errno::EINTR) => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+
+        if IN_SET!(self.p_type, PathType::Changed, PathType::Modified) {
+            if let Some(wd) = *self.primary_wd.borrow() {
+                return Ok(events.iter().any(|e| e.wd == wd));
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn check_good(&self, initial: bool, from_trigger_notify: bool) -> bool {
+        match self.p_type {
+            PathType::Exists => self.path.exists(),
+            PathType::ExistsGlob => glob_exists(&self.path),
+            PathType::DirectoryNotEmpty => fs::read_dir(&self.path)
+                .map(|mut d| d.next().is_some())
+                .unwrap_or(false),
+            PathType::Changed | PathType::Modified => {
+                // trigger when the path is created or removed
+                let exists = self.path.exists();
+                let good =
+                    !initial && !from_trigger_notify && exists != *self.previous_exists.borrow();
+                *self.previous_exists.borrow_mut() = exists;
+                good
+            }
+        }
+    }
+
+    fn mng(&self) -> Rc<PathMng> {
+        self.mng.clone().upgrade().unwrap()
+    }
+}
+
+impl Source for PathSpec {
+    fn fd(&self) -> RawFd {
+        *self.inotify.borrow()
+    }
+
+    fn event_type(&self) -> EventType {
+        EventType::Io
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn dispatch(&self, _: &Events) -> i32 {
+        log::debug!("dispatch inotify event of path: {:?}", self.path);
+        self.mng().dispatch_io(self);
+        0
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+/// return true if any file matches the glob pattern
+fn glob_exists(pattern: &Path) -> bool {
+    let pattern = match CString::new(pattern.to_string_lossy().as_bytes()) {
+        Ok(p) => p,
+        Err(_) => return false,
+    };
+
+    unsafe {
+        let mut g: libc::glob_t = std::mem::zeroed();
+        let ret = libc::glob(pattern.as_ptr(), libc::GLOB_NOSORT, None, &mut g);
+        let found = ret == 0 && g.gl_pathc > 0;
+        libc::globfree(&mut g);
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_exists, PathSpec, PathType, TriggerLimit};
+    use std::path::{Path, PathBuf};
+    use std::rc::Weak;
+
+    #[test]
+    fn test_trigger_limit() {
+        let mut limit = TriggerLimit::new(0, 0);
+        assert!(limit.ratelimit_below());
+
+        let mut limit = TriggerLimit::new(10, 2);
+        assert!(limit.ratelimit_below());
+        assert!(limit.ratelimit_below());
+        assert!(!limit.ratelimit_below());
+    }
+
+    #[test]
+    fn test_path_check_good() {
+        assert!(glob_exists(Path::new("/etc/*")));
+        assert!(!glob_exists(Path::new("/nonexistent-dir-*/foo")));
+
+        let spec = PathSpec::new(PathBuf::from("/"), PathType::Exists, Weak::new());
+        assert!(spec.check_good(true, false));
+        let spec = PathSpec::new(PathBuf::from("/"), PathType::DirectoryNotEmpty, Weak::new());
+        assert!(spec.check_good(true, false));
+
+        let spec = PathSpec::new(PathBuf::from("/"), PathType::Changed, Weak::new());
+        assert!(!spec.check_good(true, false));
+        assert!(!spec.check_good(false, false));
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+#![allow(non_snake_case)]
+use confique::Config;
+use macros::EnumDisplay;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use sysmaster::rel::{ReDb, ReDbRoTxn, ReDbRwTxn, ReDbTable, Reliability};
+use sysmaster::serialize::{deserialize_str_or_value, DeserializeWith};
+
+const RELI_DB_HPATH_CONF: &str = "pathconf";
+const RELI_DB_HPATH_MNG: &str = "pathmng";
+
+#[derive(Config, Default, Clone, Debug, Serialize, Deserialize)]
+pub(super) struct SectionPath {
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub PathExists: Option<Vec<String>>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub PathExistsGlob: Option<Vec<String>>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub PathChanged: Option<Vec<String>>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub PathModified: Option<Vec<String>>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub DirectoryNotEmpty: Option<Vec<String>>,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    #[config(default = false)]
+    pub MakeDirectory: bool,
+    pub Unit: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    #[config(default = 2)]
+    pub TriggerLimitIntervalSec: u64,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    #[config(default = 200)]
+    pub TriggerLimitBurst: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PathReConf {
+    path: SectionPath,
+    unit: Option<String>,
+}
+
+impl PathReConf {
+    fn new(pathr: &SectionPath, unit: Option<String>) -> PathReConf {
+        PathReConf {
+            path: pathr.clone(),
+            unit,
+        }
+    }
+}
+
+// Waiting: watching the paths, Running: the triggered unit is running,
+// both of them are the active states of the unit
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Serialize, Deserialize, EnumDisplay)]
+pub(super) enum PathState {
+    Dead,
+    Waiting,
+    Running,
+    Failed,
+    StateMax,
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Serialize, Deserialize, EnumDisplay)]
+pub(super) enum PathResult {
+    Success,
+    FailureResources,
+    FailureTriggerLimitHit,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PathReMng {
+    state: PathState,
+    result: PathResult,
+}
+
+impl PathReMng {
+    fn new(state: PathState, result: PathResult) -> PathReMng {
+        PathReMng { state, result }
+    }
+}
+
+struct PathReDb<K, V>(ReDb<K, V>);
+
+pub(super) struct PathRe {
+    // database: multi-instance(N)
+    conf: Rc<PathReDb<String, PathReConf>>, // RELI_DB_HPATH_CONF; key: unit_id, data: config+unit;
+    mng: Rc<PathReDb<String, PathReMng>>,   // RELI_DB_HPATH_MNG; key: unit_id, data: state+result;
+}
+
+impl PathRe {
+    pub(super) fn new(relir: &Reliability) -> PathRe {
+        let conf = Rc::new(PathReDb(ReDb::new(relir, RELI_DB_HPATH_CONF)));
+        let mng = Rc::new(PathReDb(ReDb::new(relir, RELI_DB_HPATH_MNG)));
+        let rentry = PathRe { conf, mng };
+        rentry.register(relir);
+        rentry
+    }
+
+    pub(super) fn conf_insert(&self, unit_id: &str, path: &SectionPath, unit: Option<String>) {
+        let conf = PathReConf::new(path, unit);
+        self.conf.0.insert(unit_id.to_string(), conf);
+    }
+
+    pub(super) fn conf_get(&self, unit_id: &String) -> Option<(SectionPath, Option<String>)> {
+        let conf = self.conf.0.get(unit_id);
+        conf.map(|c| (c.path, c.unit))
+    }
+
+    pub(super) fn mng_insert(&self, unit_id: &str, state: PathState, result: PathResult) {
+        let mng = PathReMng::new(state, result);
+        self.mng.0.insert(unit_id.to_string(), mng);
+    }
+
+    pub(super) fn mng_get(&self, unit_id: &String) -> Option<(PathState, PathResult)> {
+        let mng = self.mng.0.get(unit_id);
+        mng.map(|m| (m.state, m.result))
+    }
+
+    fn register(&self, relir: &Reliability) {
+        // rel-db: RELI_DB_HPATH_CONF
+        let db = Rc::clone(&self.conf);
+        relir.history_db_register(RELI_DB_HPATH_CONF, db);
+
+        // rel-db: RELI_DB_HPATH_MNG
+        let db = Rc::clone(&self.mng);
+        relir.history_db_register(RELI_DB_HPATH_MNG, db);
+    }
+}
+
+impl ReDbTable for PathReDb<String, PathReConf> {
+    fn clear(&self, wtxn: &mut ReDbRwTxn) {
+        self.0.do_clear(wtxn);
+    }
+
+    fn export(&self, db_wtxn: &mut ReDbRwTxn) {
+        self.0.cache_2_db(db_wtxn);
+    }
+
+    fn import<'a>(&self, db_rtxn: &ReDbRoTxn) {
+        self.0.db_2_cache(db_rtxn);
+    }
+
+    fn ignore_set(&self, ignore: bool) {
+        self.0.set_ignore(ignore);
+    }
+}
+
+impl ReDbTable for PathReDb<String, PathReMng> {
+    fn clear(&self, wtxn: &mut ReDbRwTxn) {
+        self.0.do_clear(wtxn);
+    }
+
+    fn export(&self, db_wtxn: &mut ReDbRwTxn) {
+        self.0.cache_2_db(db_wtxn);
+    }
+
+    fn import<'a>(&self, db_rtxn: &ReDbRoTxn) {
+        self.0.db_2_cache(db_rtxn);
+    }
+
+    fn ignore_set(&self, ignore: bool) {
+        self.0.set_ignore(ignore);
+    }
+}
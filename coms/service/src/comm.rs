@@ -139,8 +139,27 @@ impl ServiceUnitComm {
         self.owner().map(|u| self.rentry().mng_get(u.id()))?
     }
 
-    pub(super) fn _reli(&self) -> Rc<Reliability> {
-        self.umcomm._reli()
+    pub(super) fn rentry_fdstore_insert(&self, fds: Vec<(String, i32)>) {
+        if let Some(u) = self.owner() {
+            self.rentry().fdstore_insert(u.id(), fds)
+        }
+    }
+
+    pub(super) fn rentry_fdstore_get(&self) -> Option<Vec<(String, i32)>> {
+        self.owner().map(|u| self.rentry().fdstore_get(u.id()))?
+    }
+
+    pub(super) fn rentry_remove(&self) {
+        if let Some(u) = self.owner() {
+            let rentry = self.rentry();
+            rentry.conf_remove(u.id());
+            rentry.mng_remove(u.id());
+            rentry.fdstore_remove(u.id());
+        }
+    }
+
+    pub(super) fn reli(&self) -> Rc<Reliability> {
+        self.umcomm.reli()
     }
 
     pub(super) fn log(&self, level: Level, msg: &str) {
@@ -200,9 +219,9 @@ impl ServiceUmComm {
         SERVICE_UM_COMM.clone()
     }
 
-    pub(super) fn _reli(&self) -> Rc<Reliability> {
+    pub(super) fn reli(&self) -> Rc<Reliability> {
         let rdata = self.data.read().unwrap();
-        rdata._reli()
+        rdata.reli()
     }
 
     pub(super) fn um(&self) -> Rc<dyn UmIf> {
@@ -219,7 +238,7 @@ impl ServiceUmComm {
 struct ServiceUmCommData {
     // associated objects
     um: Option<Rc<dyn UmIf>>,
-    _reli: Weak<Reliability>,
+    reli: Weak<Reliability>,
     rentry: Option<Rc<ServiceRe>>,
 }
 
@@ -228,7 +247,7 @@ impl ServiceUmCommData {
     pub(self) fn new() -> ServiceUmCommData {
         ServiceUmCommData {
             um: None,
-            _reli: Weak::new(),
+            reli: Weak::new(),
             rentry: None,
         }
     }
@@ -241,10 +260,10 @@ impl ServiceUmCommData {
     }
 
     pub(self) fn attach_reli(&mut self, reli: Rc<Reliability>) {
-        let old = self._reli.clone().upgrade();
+        let old = self.reli.clone().upgrade();
         if old.is_none() {
             log::debug!("ServiceUmComm attach_reli action.");
-            self._reli = Rc::downgrade(&reli);
+            self.reli = Rc::downgrade(&reli);
             self.rentry.replace(Rc::new(ServiceRe::new(&reli)));
         }
     }
@@ -257,8 +276,8 @@ impl ServiceUmCommData {
         }
     }
 
-    pub(self) fn _reli(&self) -> Rc<Reliability> {
-        self._reli.clone().upgrade().unwrap()
+    pub(self) fn reli(&self) -> Rc<Reliability> {
+        self.reli.clone().upgrade().unwrap()
     }
 
     pub(self) fn rentry(&self) -> Rc<ServiceRe> {
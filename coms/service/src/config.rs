@@ -12,8 +12,10 @@
 //
 #![allow(non_snake_case)]
 use super::comm::ServiceUnitComm;
-use super::rentry::{NotifyAccess, SectionService, ServiceCommand, ServiceType};
+use super::rentry::{NotifyAccess, SectionService, ServiceCommand, ServiceRestart, ServiceType};
 use confique::Config;
+use serde::de::{self, IntoDeserializer};
+use serde::Deserialize;
 use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
@@ -21,7 +23,7 @@ use std::rc::Rc;
 use sysmaster::error::*;
 use sysmaster::exec::ExecCommand;
 use sysmaster::rel::ReStation;
-use sysmaster::unit::KillContext;
+use sysmaster::unit::{CgroupContext, KillContext};
 
 pub(super) struct ServiceConfig {
     // associated objects
@@ -32,6 +34,7 @@ pub(super) struct ServiceConfig {
 
     // resolved from ServiceConfigData
     kill_context: Rc<KillContext>,
+    cgroup_context: Rc<CgroupContext>,
 }
 
 impl ReStation for ServiceConfig {
@@ -60,6 +63,7 @@ impl ServiceConfig {
             comm: Rc::clone(commr),
             data: Rc::new(RefCell::new(ServiceConfigData::default())),
             kill_context: Rc::new(KillContext::default()),
+            cgroup_context: Rc::new(CgroupContext::default()),
         }
     }
 
@@ -98,6 +102,14 @@ impl ServiceConfig {
         self.db_update();
     }
 
+    pub(super) fn notify_access(&self) -> NotifyAccess {
+        self.data
+            .borrow()
+            .Service
+            .NotifyAccess
+            .unwrap_or(NotifyAccess::None)
+    }
+
     pub(super) fn environments(&self) -> Option<HashMap<String, String>> {
         self.data.borrow().Service.Environment.clone()
     }
@@ -115,6 +127,43 @@ impl ServiceConfig {
         self.kill_context.clone()
     }
 
+    pub(super) fn cgroup_context(&self) -> Rc<CgroupContext> {
+        self.cgroup_context.clone()
+    }
+
+    /// change the restart policy or the resource control at runtime, the resource control
+    /// is applied to the cgroup by the unit
+    pub(super) fn set_property(&self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "Restart" => {
+                let restart = ServiceRestart::deserialize(value.into_deserializer()).map_err(
+                    |_: de::value::Error| Error::ConfigureError {
+                        msg: format!("invalid Restart: {value}"),
+                    },
+                )?;
+                self.data.borrow_mut().Service.Restart = restart;
+            }
+            "RestartSec" => self.data.borrow_mut().Service.RestartSec = value.parse::<u64>()?,
+            _ => {
+                self.cgroup_context.set_property(key, value)?;
+                let ctx = &self.cgroup_context;
+                let mut data = self.data.borrow_mut();
+                let service = &mut data.Service;
+                match key {
+                    "MemoryMax" => service.MemoryMax = Some(value.to_string()),
+                    "MemoryHigh" => service.MemoryHigh = Some(value.to_string()),
+                    "CPUWeight" => service.CPUWeight = ctx.cpu_weight(),
+                    "CPUQuota" => service.CPUQuota = Some(value.to_string()),
+                    "TasksMax" => service.TasksMax = Some(value.to_string()),
+                    "IOWeight" => service.IOWeight = ctx.io_weight(),
+                    _ => {}
+                }
+            }
+        }
+        self.db_update();
+        Ok(())
+    }
+
     pub(super) fn flush_timeout(&self) {
         let time_out = self.data.borrow().Service.TimeoutSec;
         if time_out == 0 {
@@ -0,0 +1,151 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! The file descriptor store of the service, the fds are sent by the service with FDSTORE=1,
+//! kept across service restarts and sysmaster reexec, and passed back on the next start.
+use super::comm::ServiceUnitComm;
+use basic::fd_util;
+use log::Level;
+use nix::sys::stat;
+use std::cell::RefCell;
+use std::os::unix::prelude::RawFd;
+use std::rc::Rc;
+
+/// the name of the stored fd if FDNAME= is not specified
+pub(super) const FDSTORE_DEFAULT_NAME: &str = "stored";
+
+/// the max length of the name of the stored fd
+const FDNAME_MAX: usize = 255;
+
+/// the name is passed in LISTEN_FDNAMES separated by ':', so it must be printable without ':'
+pub(super) fn fdname_is_valid(name: &str) -> bool {
+    name.len() <= FDNAME_MAX
+        && name.chars().all(|c| c.is_ascii_graphic() || c == ' ')
+        && !name.contains(':')
+}
+
+pub(super) struct ServiceFdStore {
+    // associated objects
+    comm: Rc<ServiceUnitComm>,
+
+    // owned objects
+    fds: RefCell<Vec<(String, RawFd)>>,
+}
+
+impl ServiceFdStore {
+    pub(super) fn new(commr: &Rc<ServiceUnitComm>) -> ServiceFdStore {
+        ServiceFdStore {
+            comm: Rc::clone(commr),
+            fds: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub(super) fn db_map(&self) {
+        if let Some(fds) = self.comm.rentry_fdstore_get() {
+            let reli = self.comm.reli();
+            *self.fds.borrow_mut() = fds
+                .into_iter()
+                .map(|(name, fd)| (name, reli.fd_take(fd)))
+                .collect();
+        }
+    }
+
+    pub(super) fn db_insert(&self) {
+        self.comm.rentry_fdstore_insert(self.fds.borrow().clone());
+    }
+
+    /// take over the fd, the fd is closed if it can't be stored
+    pub(super) fn add(&self, fd: RawFd, name: &str, max: usize) -> bool {
+        if self.contains(fd) {
+            // the same file is stored already, the new one is useless
+            fd_util::close(fd);
+            return true;
+        }
+
+        if self.fds.borrow().len() >= max {
+            self.comm.log(
+                Level::Warn,
+                &format!(
+                    "the file descriptor store is full (FileDescriptorStoreMax={}), dropping the fd",
+                    max
+                ),
+            );
+            fd_util::close(fd);
+            return false;
+        }
+
+        // keep the fd across sysmaster reexec
+        if let Err(e) = self.comm.reli().fd_cloexec(fd, false) {
+            self.comm.log(
+                Level::Warn,
+                &format!("failed to retain the stored fd {}: {}", fd, e),
+            );
+            fd_util::close(fd);
+            return false;
+        }
+
+        self.comm.log(
+            Level::Debug,
+            &format!("add fd {} to the file descriptor store as {}", fd, name),
+        );
+        self.fds.borrow_mut().push((name.to_string(), fd));
+        true
+    }
+
+    pub(super) fn remove_by_name(&self, name: &str) {
+        let removed = {
+            let mut fds = self.fds.borrow_mut();
+            let (removed, kept): (Vec<_>, Vec<_>) = fds.drain(..).partition(|(n, _)| n == name);
+            *fds = kept;
+            removed
+        };
+
+        for (_, fd) in removed {
+            self.close(fd);
+        }
+    }
+
+    /// close all the stored fds
+    pub(super) fn release(&self) {
+        let fds = std::mem::take(&mut *self.fds.borrow_mut());
+        if !fds.is_empty() {
+            self.comm
+                .log(Level::Debug, "release the file descriptor store");
+        }
+        for (_, fd) in fds {
+            self.close(fd);
+        }
+    }
+
+    /// return the stored fds along with their names, in the order they are stored
+    pub(super) fn fds(&self) -> Vec<(String, RawFd)> {
+        self.fds.borrow().clone()
+    }
+
+    fn contains(&self, fd: RawFd) -> bool {
+        let st = match stat::fstat(fd) {
+            Ok(st) => st,
+            Err(_) => return false,
+        };
+
+        self.fds.borrow().iter().any(|(_, stored)| {
+            stat::fstat(*stored).map_or(false, |s| s.st_dev == st.st_dev && s.st_ino == st.st_ino)
+        })
+    }
+
+    fn close(&self, fd: RawFd) {
+        if let Err(e) = self.comm.reli().fd_cloexec(fd, true) {
+            log::error!("close the stored fd, remark fd[{}] failed: {:?}", fd, e);
+        }
+        fd_util::close(fd);
+    }
+}
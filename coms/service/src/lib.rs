@@ -34,13 +34,16 @@
 //!
 //! Type
 //!
-//! The service type configuration field currently supports simple, forking, oneshot, and notify The default value is simple when not configured.
+//! The service type configuration field currently supports simple, forking, oneshot, notify and notify-reload The default value is simple when not configured.
 //!
 //! The simple mode indicates that the service startup is completed when the fork sub process succeeds.
 //! The forking mode indicates that when the fork sub process exits, the service startup is completed. The pid of the sub process needs to be obtained through PIDFile.
 //! The oneshot mode exits after the service is executed.
 //! Notify the status message to the sysmaster after the notify mode service is started.
-//! Supported notification messages MAINPID=$val, READY=$val, STOPPING=$val, ERRNO=$val
+//! Supported notification messages MAINPID=$val, READY=$val, RELOADING=$val, MONOTONIC_USEC=$val, STOPPING=$val, STATUS=$val,
+//! ERRNO=$val, BUSERROR=$val, EXTEND_TIMEOUT_USEC=$val, WATCHDOG=$val, WATCHDOG_USEC=$val, FDSTORE=$val, FDSTOREREMOVE=$val, FDNAME=$val
+//! The notify-reload mode is the same as notify, except that ReloadSignal is sent to the main process on reloading,
+//! and the reloading is finished when READY=1 is received after RELOADING=1.
 //!
 //!
 //! ExecCondition、ExecStartPre、ExecStart、ExecStop、ExecStartPost
@@ -57,7 +60,13 @@
 //!
 //! NotifyAccess
 //!
-//! The support configuration is main, which means that the notification sent by the MAINPID process is supported.
+//! The support configuration is none, main, exec and all, which means that the notification sent by no process,
+//! the main process, the main and control processes, or any process in the cgroup of the service is accepted.
+//!
+//! FileDescriptorStoreMax
+//!
+//! The max number of the fds kept by sysmaster for the service, the fds are sent with FDSTORE=1, kept across restarts and reexec,
+//! and passed back with LISTEN_FDS and LISTEN_FDNAMES on the next start. The default value is 0, which disables the store.
 //!
 //! Environment
 //!
@@ -70,12 +79,13 @@
 
 // dependency:
 // service_base -> service_rentry -> {service_comm | service_config}
-// {service_pid | service_spawn} ->
+// {service_pid | service_fdstore | service_spawn} ->
 // {service_mng} ->
 // {service_monitor} -> service_unit -> service_manager
 mod base;
 mod comm;
 mod config;
+mod fdstore;
 mod manager;
 mod mng;
 mod monitor;
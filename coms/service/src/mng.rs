@@ -14,14 +14,16 @@ use crate::monitor::ServiceMonitor;
 
 use super::comm::ServiceUnitComm;
 use super::config::ServiceConfig;
+use super::fdstore::{fdname_is_valid, ServiceFdStore, FDSTORE_DEFAULT_NAME};
 use super::pid::ServicePid;
 use super::rentry::{
-    NotifyState, ServiceCommand, ServiceRestart, ServiceResult, ServiceState, ServiceType,
+    NotifyAccess, NotifyState, ServiceCommand, ServiceRestart, ServiceResult, ServiceState,
+    ServiceType,
 };
 use super::spawn::ServiceSpawn;
 use crate::rentry::ExitStatus;
 use basic::{fd_util, IN_SET};
-use basic::{file_util, process_util};
+use basic::{file_util, process_util, time_util};
 use event::{EventState, EventType, Events, Source};
 use log::Level;
 use nix::errno::Errno;
@@ -30,19 +32,21 @@ use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify, WatchDescriptor};
 use nix::sys::signal::Signal;
 use nix::sys::socket::UnixCredentials;
 use nix::sys::wait::WaitStatus;
+use nix::time::ClockId;
 use nix::unistd::Pid;
 use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
 use std::fmt;
 use std::os::unix::prelude::AsRawFd;
 use std::rc::Rc;
+use std::str::FromStr;
 use std::{
     os::unix::prelude::{FromRawFd, RawFd},
     path::PathBuf,
     rc::Weak,
 };
 use sysmaster::error::*;
-use sysmaster::exec::{ExecCommand, ExecContext, ExecFlag, ExecFlags};
+use sysmaster::exec::{self, ExecCommand, ExecContext, ExecFlag, ExecFlags};
 use sysmaster::rel::ReStation;
 use sysmaster::unit::{KillOperation, UnitActiveState, UnitNotifyFlags};
 
@@ -53,6 +57,7 @@ pub(super) struct ServiceMng {
 
     // owned objects
     pid: Rc<ServicePid>,
+    fdstore: Rc<ServiceFdStore>,
     spawn: ServiceSpawn,
     state: RefCell<ServiceState>,
     result: RefCell<ServiceResult>,
@@ -102,6 +107,7 @@ impl ReStation for ServiceMng {
             self.rd.set_wait_status(WaitStatus::from(exit_status));
             *self.monitor.borrow_mut() = monitor;
         }
+        self.fdstore.db_map();
     }
 
     fn db_insert(&self) {
@@ -121,6 +127,7 @@ impl ReStation for ServiceMng {
             exit_status,
             *self.monitor.borrow(),
         );
+        self.fdstore.db_insert();
     }
 
     // reload: no external connections
@@ -147,12 +154,14 @@ impl ServiceMng {
         exec_ctx: &Rc<ExecContext>,
     ) -> ServiceMng {
         let _pid = Rc::new(ServicePid::new(commr));
+        let _fdstore = Rc::new(ServiceFdStore::new(commr));
 
         ServiceMng {
             comm: Rc::clone(commr),
             config: Rc::clone(configr),
             pid: Rc::clone(&_pid),
-            spawn: ServiceSpawn::new(commr, &_pid, configr, exec_ctx, rd),
+            fdstore: Rc::clone(&_fdstore),
+            spawn: ServiceSpawn::new(commr, &_pid, &_fdstore, configr, exec_ctx, rd),
             state: RefCell::new(ServiceState::Dead),
             result: RefCell::new(ServiceResult::Success),
             reload_result: RefCell::new(ServiceResult::Success),
@@ -207,10 +216,50 @@ impl ServiceMng {
             self.rd.set_reset_restart(false);
         }
         self.set_result(ServiceResult::Success);
+        if let Err(e) = self.private_tmp_create() {
+            self.log(
+                Level::Error,
+                &format!("Failed to create the private /tmp: {}", e),
+            );
+            self.enter_dead(ServiceResult::FailureResources, false);
+            self.db_update();
+            return;
+        }
         self.enter_contion();
         self.db_update();
     }
 
+    // the private /tmp is shared by all the processes of the service until it stops
+    fn private_tmp_create(&self) -> Result<()> {
+        let dir = match self.private_tmp_dir() {
+            None => return Ok(()),
+            Some(v) => v,
+        };
+        exec::private_tmp_create(&dir)
+    }
+
+    fn private_tmp_remove(&self) {
+        let dir = match self.private_tmp_dir() {
+            None => return,
+            Some(v) => v,
+        };
+        if let Err(e) = exec::private_tmp_remove(&dir) {
+            self.log(
+                Level::Warn,
+                &format!("Failed to remove the private /tmp {:?}: {}", dir, e),
+            );
+        }
+    }
+
+    fn private_tmp_dir(&self) -> Option<PathBuf> {
+        let unit = self.comm.owner()?;
+        self.config
+            .config_data()
+            .borrow()
+            .Service
+            .private_tmp_dir(unit.id())
+    }
+
     pub(super) fn stop_check(&self) -> Result<()> {
         if IN_SET!(
             self.state(),
@@ -252,6 +301,22 @@ impl ServiceMng {
         self.db_update();
     }
 
+    pub(super) fn reset_failed(&self) {
+        if self.state() == ServiceState::Failed {
+            self.set_state(ServiceState::Dead);
+        }
+        self.set_result(ServiceResult::Success);
+        self.set_reload_result(ServiceResult::Success);
+        self.rd.clear_restarts();
+        self.db_update();
+    }
+
+    /// the unit is unloaded, release what is left
+    pub(super) fn done(&self) {
+        self.fdstore.release();
+        self.private_tmp_remove();
+    }
+
     pub(super) fn current_active_state(&self) -> UnitActiveState {
         service_state_to_unit_state(self.config.service_type(), self.state())
     }
@@ -373,7 +438,7 @@ impl ServiceMng {
                 self.pid.set_control(pid);
                 self.set_state(ServiceState::Start);
             }
-            ServiceType::Oneshot | ServiceType::Notify => {
+            ServiceType::Oneshot | ServiceType::Notify | ServiceType::NotifyReload => {
                 let _ = self.pid.set_main(pid);
                 self.set_state(ServiceState::Start);
             }
@@ -572,6 +637,15 @@ impl ServiceMng {
             self.set_state(ServiceState::AutoRestart);
         } else {
             self.rd.set_reset_restart(true);
+            // the stored fds are kept only if the service will be started again
+            if !self
+                .comm
+                .um()
+                .has_start_like_job(self.comm.owner().unwrap().id())
+            {
+                self.fdstore.release();
+            }
+            self.private_tmp_remove();
         }
 
         self.rd.set_forbid_restart(false);
@@ -590,9 +664,16 @@ impl ServiceMng {
         log::debug!("running service reload command");
         self.control_command.borrow_mut().clear();
         self.pid.unwatch_control();
-        self.control_command_fill(ServiceCommand::Reload);
         self.set_reload_result(ServiceResult::Success);
 
+        if self.config.service_type() == ServiceType::NotifyReload {
+            if let Some(pid) = self.pid.main() {
+                self.enter_reload_by_signal(pid);
+                return;
+            }
+        }
+
+        self.control_command_fill(ServiceCommand::Reload);
         match self.control_command_pop() {
             Some(cmd) => {
                 match self.spawn.start_service(
@@ -614,6 +695,40 @@ impl ServiceMng {
         }
     }
 
+    // the service acknowledges the reload signal with RELOADING=1 and finishes it with READY=1
+    fn enter_reload_by_signal(&self, pid: Pid) {
+        let signal = Signal::from_str(&self.config.config_data().borrow().Service.ReloadSignal)
+            .unwrap_or(Signal::SIGHUP);
+
+        self.rd
+            .set_reload_begin(time_util::now_clockid(ClockId::CLOCK_MONOTONIC));
+        if let Err(e) = process_util::kill_and_cont(pid, signal) {
+            self.log(
+                Level::Warn,
+                &format!("failed to send {} to main process {}: {}", signal, pid, e),
+            );
+            self.set_reload_result(ServiceResult::FailureResources);
+            self.enter_running(ServiceResult::Success);
+            return;
+        }
+
+        self.enter_reload_by_notify();
+    }
+
+    fn enter_reload_by_notify(&self) {
+        if let Err(e) = self
+            .rd
+            .enable_timer(self.config.config_data().borrow().Service.TimeoutStartSec)
+        {
+            self.log(
+                Level::Warn,
+                &format!("action notify reload enable timer error: {}", e),
+            );
+        }
+
+        self.set_state(ServiceState::Reload);
+    }
+
     fn enter_restart(&self) {
         if self.comm.um().has_stop_job(self.comm.owner().unwrap().id()) {
             log::info!("there is stop in pending, not restart");
@@ -1360,7 +1475,12 @@ impl ServiceMng {
                             self.enter_signal(ServiceState::StopSigterm, res);
                         }
                     }
-                    ServiceState::Start if self.config.service_type() == ServiceType::Notify => {
+                    ServiceState::Start
+                        if matches!(
+                            self.config.service_type(),
+                            ServiceType::Notify | ServiceType::NotifyReload
+                        ) =>
+                    {
                         if res != ServiceResult::Success {
                             self.enter_signal(ServiceState::StopSigterm, res);
                         } else {
@@ -1567,19 +1687,59 @@ impl ServiceMng {
         &self,
         ucred: &UnixCredentials,
         messages: &HashMap<&str, &str>,
-        _fds: Vec<i32>,
+        fds: Vec<i32>,
     ) -> Result<()> {
-        let ret = self.do_notify_message(ucred, messages, _fds);
+        let ret = self.do_notify_message(ucred, messages, fds);
         self.db_update();
         ret
     }
 
+    pub(super) fn notify_access_allowed(&self, pid: Pid) -> bool {
+        let allowed = match self.config.notify_access() {
+            NotifyAccess::None => false,
+            NotifyAccess::Main => self.pid.main() == Some(pid),
+            NotifyAccess::Exec => self.pid.main() == Some(pid) || self.pid.control() == Some(pid),
+            // any process in the cgroup of the service
+            NotifyAccess::All => self
+                .comm
+                .um()
+                .same_unit_with_pid(&self.comm.get_owner_id(), pid),
+        };
+
+        if !allowed {
+            self.log(
+                Level::Warn,
+                &format!(
+                    "reject notify message from pid {}, NotifyAccess is {:?}",
+                    pid,
+                    self.config.notify_access()
+                ),
+            );
+        }
+
+        allowed
+    }
+
+    pub(super) fn status_text(&self) -> Option<String> {
+        self.rd.status_text()
+    }
+
+    pub(super) fn main_pid(&self) -> Option<Pid> {
+        self.pid.main()
+    }
+
+    pub(super) fn control_pid(&self) -> Option<Pid> {
+        self.pid.control()
+    }
+
     fn do_notify_message(
         &self,
         ucred: &UnixCredentials,
         messages: &HashMap<&str, &str>,
-        _fds: Vec<i32>,
+        fds: Vec<i32>,
     ) -> Result<()> {
+        self.notify_fds(messages, fds);
+
         if let Some(&pidr) = messages.get("MAINPID") {
             if IN_SET!(
                 self.state(),
@@ -1602,57 +1762,171 @@ impl ServiceMng {
             }
         };
 
-        for (&key, &value) in messages {
-            if key == "READY" && value == "1" {
-                log::debug!("service plugin get READY=1");
-                self.rd.set_notify_state(NotifyState::Ready);
-                if self.config.service_type() == ServiceType::Notify
-                    && self.state() == ServiceState::Start
-                {
-                    self.enter_start_post();
-                }
+        // the fields are handled in a fixed order, RELOADING=1 must be seen before READY=1
+        if messages.get("RELOADING") == Some(&"1") {
+            log::debug!("service plugin get RELOADING=1");
+            let monotonic_usec = messages
+                .get("MONOTONIC_USEC")
+                .and_then(|v| v.parse::<u64>().ok());
+            self.notify_reloading(monotonic_usec);
+        }
+
+        if messages.get("READY") == Some(&"1") {
+            log::debug!("service plugin get READY=1");
+            let reloading = self.rd.notify_state() == NotifyState::Reloading;
+            self.rd.set_notify_state(NotifyState::Ready);
+            if matches!(
+                self.config.service_type(),
+                ServiceType::Notify | ServiceType::NotifyReload
+            ) && self.state() == ServiceState::Start
+            {
+                self.enter_start_post();
             }
 
-            if key == "STOPPING" && value == "1" {
-                self.rd.set_notify_state(NotifyState::Stopping);
-                if self.state() == ServiceState::Running {
-                    self.enter_stop_by_notify();
-                }
+            // READY=1 after RELOADING=1 means the reloading is finished
+            if reloading && self.state() == ServiceState::Reload && !self.pid.control_pid_avail() {
+                self.enter_running(ServiceResult::Success);
             }
+        }
 
-            if key == "ERRNO" {
-                let err = value.parse::<i32>();
-                if err.is_err() {
-                    log::warn!("parse ERRNO failed in received messages");
-                    continue;
-                }
+        if messages.get("STOPPING") == Some(&"1") {
+            self.rd.set_notify_state(NotifyState::Stopping);
+            if self.state() == ServiceState::Running {
+                self.enter_stop_by_notify();
+            }
+        }
+
+        if let Some(&status) = messages.get("STATUS") {
+            self.rd.set_status_text(status);
+        }
 
-                self.rd.set_errno(err.unwrap());
+        if let Some(&errno) = messages.get("ERRNO") {
+            match errno.parse::<i32>() {
+                Ok(v) => self.rd.set_errno(v),
+                Err(_) => log::warn!("parse ERRNO failed in received messages"),
             }
+        }
 
-            if key == "WATCHDOG" {
-                if value == "1" {
-                    self.restart_watchdog();
-                } else if value == "trigger" {
-                    self.force_watchdog();
-                } else {
-                    log::warn!(
-                        "{} send WATCHDOG= field is invalid, ignoring.",
-                        self.comm.owner().unwrap().id()
-                    );
+        if let Some(&bus_error) = messages.get("BUSERROR") {
+            log::debug!(
+                "{} reports bus error: {}",
+                self.comm.get_owner_id(),
+                bus_error
+            );
+        }
+
+        if let Some(&extend) = messages.get("EXTEND_TIMEOUT_USEC") {
+            match extend.parse::<u64>() {
+                Ok(v) => self.extend_timeout(v),
+                Err(_) => log::warn!("failed to parse notify message of EXTEND_TIMEOUT_USEC item"),
+            }
+        }
+
+        if let Some(&watchdog) = messages.get("WATCHDOG") {
+            if watchdog == "1" {
+                self.restart_watchdog();
+            } else if watchdog == "trigger" {
+                self.force_watchdog();
+            } else {
+                log::warn!(
+                    "{} send WATCHDOG= field is invalid, ignoring.",
+                    self.comm.owner().unwrap().id()
+                );
+            }
+        }
+
+        if let Some(&watchdog_usec) = messages.get("WATCHDOG_USEC") {
+            match watchdog_usec.parse::<u64>() {
+                Ok(v) => self.override_watchdog_usec(v),
+                Err(_) => log::warn!("failed to parse notify message of WATCGDOG_USEC item"),
+            }
+        }
+
+        Ok(())
+    }
+
+    // the fds are taken over before the other fields are handled, they are stored or
+    // closed here, so that they are not leaked if the other fields are invalid
+    fn notify_fds(&self, messages: &HashMap<&str, &str>, fds: Vec<i32>) {
+        let name = messages.get("FDNAME").copied();
+        if let Some(name) = name {
+            if !fdname_is_valid(name) {
+                self.log(
+                    Level::Warn,
+                    &format!("FDNAME={} is invalid, ignoring the file descriptors", name),
+                );
+                for fd in fds {
+                    fd_util::close(fd);
                 }
+                return;
+            }
+        }
+
+        if messages.get("FDSTOREREMOVE") == Some(&"1") {
+            match name {
+                Some(name) => self.fdstore.remove_by_name(name),
+                None => log::warn!("FDSTOREREMOVE=1 is sent without FDNAME=, ignoring"),
             }
-            if key == "WATCHDOG_USEC" {
-                let watchdog_override_usec = value.parse::<u64>();
-                if let Ok(v) = watchdog_override_usec {
-                    self.override_watchdog_usec(v);
-                    continue;
+        }
+
+        if messages.get("FDSTORE") == Some(&"1") {
+            self.store_fds(fds, name.unwrap_or(FDSTORE_DEFAULT_NAME));
+        } else {
+            for fd in fds {
+                fd_util::close(fd);
+            }
+        }
+    }
+
+    fn store_fds(&self, fds: Vec<i32>, name: &str) {
+        let max = self
+            .config
+            .config_data()
+            .borrow()
+            .Service
+            .FileDescriptorStoreMax;
+        if max == 0 && !fds.is_empty() {
+            self.log(
+                Level::Warn,
+                "FDSTORE=1 is sent, but the file descriptor store is disabled by FileDescriptorStoreMax=0",
+            );
+        }
+
+        for fd in fds {
+            if max == 0 {
+                fd_util::close(fd);
+                continue;
+            }
+            self.fdstore.add(fd, name, max);
+        }
+    }
+
+    fn notify_reloading(&self, monotonic_usec: Option<u64>) {
+        match self.state() {
+            ServiceState::Reload => {
+                // RELOADING=1 sent before the reload signal is received is stale
+                if let Some(usec) = monotonic_usec {
+                    if usec < self.rd.reload_begin() {
+                        log::debug!("ignore the stale RELOADING=1 message");
+                        return;
+                    }
                 }
-                log::warn!("failed to parse notify message of WATCGDOG_USEC item");
+                self.rd.set_notify_state(NotifyState::Reloading);
+            }
+            ServiceState::Running => {
+                // the reloading is initiated by the service itself
+                self.rd.set_notify_state(NotifyState::Reloading);
+                self.set_reload_result(ServiceResult::Success);
+                self.enter_reload_by_notify();
             }
+            _ => self.rd.set_notify_state(NotifyState::Reloading),
         }
+    }
 
-        Ok(())
+    fn extend_timeout(&self, usec: u64) {
+        if let Err(e) = self.rd.extend_timer(usec) {
+            self.log(Level::Warn, &format!("failed to extend the timeout: {}", e));
+        }
     }
 }
 
@@ -1762,6 +2036,22 @@ impl RunningData {
         self.data.borrow().notify_state()
     }
 
+    pub(self) fn set_status_text(&self, status_text: &str) {
+        self.data.borrow_mut().set_status_text(status_text);
+    }
+
+    pub(self) fn status_text(&self) -> Option<String> {
+        self.data.borrow().status_text()
+    }
+
+    pub(self) fn set_reload_begin(&self, usec: u64) {
+        self.data.borrow_mut().set_reload_begin(usec);
+    }
+
+    pub(self) fn reload_begin(&self) -> u64 {
+        self.data.borrow().reload_begin()
+    }
+
     pub(self) fn set_forbid_restart(&self, forbid_restart: bool) {
         self.data.borrow_mut().set_forbid_restart(forbid_restart);
     }
@@ -1806,6 +2096,7 @@ impl RunningData {
 
         if usec == 0 || usec == u64::MAX {
             // which means not enable the service timer, so delete the previous timer
+            self.data.borrow_mut().set_timer_deadline(None);
             if self.armd_timer() {
                 let timer = self.timer();
                 events.del_source(timer)?;
@@ -1813,6 +2104,10 @@ impl RunningData {
             return Ok(0);
         }
 
+        let now = time_util::now_clockid(ClockId::CLOCK_MONOTONIC);
+        self.data
+            .borrow_mut()
+            .set_timer_deadline(Some(now.saturating_add(usec)));
         if self.armd_timer() {
             let timer = self.timer();
             events.set_enabled(timer.clone(), EventState::Off)?;
@@ -1831,7 +2126,23 @@ impl RunningData {
         Ok(0)
     }
 
+    // push out the pending timer to fire no earlier than usec later, it is never shortened.
+    pub(self) fn extend_timer(&self, usec: u64) -> Result<i32> {
+        let deadline = match self.data.borrow().timer_deadline() {
+            None => return Ok(0),
+            Some(v) => v,
+        };
+
+        let now = time_util::now_clockid(ClockId::CLOCK_MONOTONIC);
+        if deadline <= now || now.saturating_add(usec) <= deadline {
+            return Ok(0);
+        }
+
+        self.enable_timer(usec)
+    }
+
     pub(self) fn delete_timer(&self) {
+        self.data.borrow_mut().set_timer_deadline(None);
         if !self.armd_timer() {
             return;
         }
@@ -1883,6 +2194,8 @@ impl RunningData {
 struct Rtdata {
     errno: i32,
     notify_state: NotifyState,
+    status_text: Option<String>,
+    reload_begin: u64,
     path_inotify: Option<Rc<PathIntofy>>,
 
     forbid_restart: bool,
@@ -1890,6 +2203,7 @@ struct Rtdata {
     will_auto_restart: bool,
     restarts: u32,
     timer: Option<Rc<ServiceTimer>>,
+    timer_deadline: Option<u64>,
 
     exec_status: WaitStatus,
 
@@ -1901,6 +2215,8 @@ impl Rtdata {
         Rtdata {
             errno: 0,
             notify_state: NotifyState::Unknown,
+            status_text: None,
+            reload_begin: 0,
             path_inotify: None,
 
             forbid_restart: false,
@@ -1908,6 +2224,7 @@ impl Rtdata {
             will_auto_restart: false,
             restarts: 0,
             timer: None,
+            timer_deadline: None,
             exec_status: WaitStatus::StillAlive,
             watchdog: None,
         }
@@ -1921,6 +2238,27 @@ impl Rtdata {
         self.notify_state
     }
 
+    pub(self) fn set_status_text(&mut self, status_text: &str) {
+        // an empty STATUS= clears the status text
+        self.status_text = if status_text.is_empty() {
+            None
+        } else {
+            Some(status_text.to_string())
+        };
+    }
+
+    pub(self) fn status_text(&self) -> Option<String> {
+        self.status_text.clone()
+    }
+
+    pub(self) fn set_reload_begin(&mut self, usec: u64) {
+        self.reload_begin = usec;
+    }
+
+    pub(self) fn reload_begin(&self) -> u64 {
+        self.reload_begin
+    }
+
     pub(self) fn set_errno(&mut self, errno: i32) {
         self.errno = errno;
     }
@@ -1974,6 +2312,14 @@ impl Rtdata {
         self.timer.is_some()
     }
 
+    pub(self) fn set_timer_deadline(&mut self, deadline: Option<u64>) {
+        self.timer_deadline = deadline;
+    }
+
+    pub(self) fn timer_deadline(&self) -> Option<u64> {
+        self.timer_deadline
+    }
+
     pub(self) fn add_restarts(&mut self) {
         self.restarts += 1;
     }
@@ -2437,7 +2783,9 @@ impl Source for ServiceMonitorData {
 #[cfg(test)]
 mod tests {
     use super::{RunningData, ServiceMng};
+    use crate::rentry::{NotifyAccess, NotifyState, ServiceState};
     use crate::{comm::ServiceUnitComm, config::ServiceConfig};
+    use nix::unistd::Pid;
     use std::{collections::HashMap, rc::Rc};
     use sysmaster::{exec::ExecContext, UmIf};
 
@@ -2512,4 +2860,186 @@ mod tests {
         assert!(rt.armd_watchdog());
         assert_eq!(rt.watchdog().time(), 15);
     }
+
+    #[test]
+    fn test_notify_status() {
+        use nix::sys::socket::UnixCredentials;
+
+        let (mng, _rt, _config) = create_mng();
+
+        let ucred = UnixCredentials::new();
+        let mut messages = HashMap::new();
+        messages.insert("STATUS", "Processing requests: a=b");
+
+        assert!(mng.notify_message(&ucred, &messages, vec![]).is_ok());
+        assert_eq!(
+            mng.status_text(),
+            Some("Processing requests: a=b".to_string())
+        );
+
+        messages.insert("STATUS", "");
+        assert!(mng.notify_message(&ucred, &messages, vec![]).is_ok());
+        assert_eq!(mng.status_text(), None);
+    }
+
+    #[test]
+    fn test_notify_fdstore_disabled() {
+        use nix::sys::socket::UnixCredentials;
+
+        let (mng, _rt, _config) = create_mng();
+
+        let ucred = UnixCredentials::new();
+        let mut messages = HashMap::new();
+        messages.insert("FDSTORE", "1");
+        messages.insert("FDNAME", "pipe");
+
+        // the fd store is disabled by default, the received fds are closed
+        let (r, w) = nix::unistd::pipe().unwrap();
+        assert!(mng.notify_message(&ucred, &messages, vec![r, w]).is_ok());
+        assert!(mng.fdstore.fds().is_empty());
+    }
+
+    #[test]
+    fn test_notify_reloading_ready() {
+        use nix::sys::socket::UnixCredentials;
+
+        let (mng, rt, _config) = create_mng();
+        // the test process is the alive main process
+        mng.pid.set_main(nix::unistd::getpid()).unwrap();
+        mng.set_state(ServiceState::Running);
+
+        let ucred = UnixCredentials::new();
+        let mut messages = HashMap::new();
+
+        // RELOADING=1 from the running service starts the reloading
+        messages.insert("RELOADING", "1");
+        assert!(mng.notify_message(&ucred, &messages, vec![]).is_ok());
+        assert_eq!(mng.state(), ServiceState::Reload);
+        assert_eq!(rt.notify_state(), NotifyState::Reloading);
+
+        // READY=1 finishes the reloading
+        messages.clear();
+        messages.insert("READY", "1");
+        assert!(mng.notify_message(&ucred, &messages, vec![]).is_ok());
+        assert_eq!(mng.state(), ServiceState::Running);
+        assert_eq!(rt.notify_state(), NotifyState::Ready);
+
+        // READY=1 without RELOADING=1 doesn't finish the reloading
+        mng.set_state(ServiceState::Reload);
+        assert!(mng.notify_message(&ucred, &messages, vec![]).is_ok());
+        assert_eq!(mng.state(), ServiceState::Reload);
+    }
+
+    #[test]
+    fn test_notify_extend_timeout() {
+        use basic::time_util;
+        use nix::sys::socket::UnixCredentials;
+        use nix::time::ClockId;
+
+        let (mng, rt, _config) = create_mng();
+        let ucred = UnixCredentials::new();
+        let mut messages = HashMap::new();
+
+        // nothing is extended without the timer
+        messages.insert("EXTEND_TIMEOUT_USEC", "10000000");
+        assert!(mng.notify_message(&ucred, &messages, vec![]).is_ok());
+        assert_eq!(rt.data.borrow().timer_deadline(), None);
+
+        assert!(rt.enable_timer(1000000).is_ok());
+        let deadline = rt.data.borrow().timer_deadline().unwrap();
+
+        // the shorter timeout doesn't bring the deadline forward
+        messages.insert("EXTEND_TIMEOUT_USEC", "1");
+        assert!(mng.notify_message(&ucred, &messages, vec![]).is_ok());
+        assert_eq!(rt.data.borrow().timer_deadline(), Some(deadline));
+
+        // the longer timeout pushes the deadline back
+        let now = time_util::now_clockid(ClockId::CLOCK_MONOTONIC);
+        messages.insert("EXTEND_TIMEOUT_USEC", "10000000");
+        assert!(mng.notify_message(&ucred, &messages, vec![]).is_ok());
+        assert!(rt.data.borrow().timer_deadline().unwrap() >= now + 10000000);
+
+        // the invalid value is ignored
+        messages.insert("EXTEND_TIMEOUT_USEC", "foo");
+        assert!(mng.notify_message(&ucred, &messages, vec![]).is_ok());
+    }
+
+    #[test]
+    fn test_notify_access() {
+        let (mng, _rt, config) = create_mng();
+        let main = Pid::from_raw(100);
+        let control = Pid::from_raw(101);
+        let other = Pid::from_raw(102);
+        mng.pid.set_main(main).unwrap();
+        mng.pid.set_control(control);
+
+        let set_access = |access: NotifyAccess| {
+            config
+                .config_data()
+                .borrow_mut()
+                .Service
+                .set_notify_access(access)
+        };
+
+        // all the messages are rejected by default
+        for pid in [main, control, other] {
+            assert!(!mng.notify_access_allowed(pid));
+        }
+
+        set_access(NotifyAccess::None);
+        for pid in [main, control, other] {
+            assert!(!mng.notify_access_allowed(pid));
+        }
+
+        set_access(NotifyAccess::Main);
+        assert!(mng.notify_access_allowed(main));
+        assert!(!mng.notify_access_allowed(control));
+        assert!(!mng.notify_access_allowed(other));
+
+        set_access(NotifyAccess::Exec);
+        assert!(mng.notify_access_allowed(main));
+        assert!(mng.notify_access_allowed(control));
+        assert!(!mng.notify_access_allowed(other));
+    }
+
+    #[test]
+    fn test_notify_fds_not_leaked() {
+        use nix::fcntl::OFlag;
+        use nix::sys::socket::UnixCredentials;
+        use nix::unistd::{close, dup, pipe2, read};
+
+        let (mng, _rt, _config) = create_mng();
+        let ucred = UnixCredentials::new();
+        // the pipe is sent to the service manager with a copy of the read end kept,
+        // which reads EOF once the write end is closed by the service manager
+        let send = |messages: &HashMap<&str, &str>| {
+            let (r, w) = pipe2(OFlag::O_NONBLOCK).unwrap();
+            let kept = dup(r).unwrap();
+            let ret = mng.notify_message(&ucred, messages, vec![r, w]);
+            let mut buf = [0u8; 1];
+            let closed = read(kept, &mut buf) == Ok(0);
+            close(kept).unwrap();
+            (ret, closed)
+        };
+
+        // the fds are closed even if the other fields are invalid
+        mng.set_state(ServiceState::Start);
+        let mut messages = HashMap::new();
+        messages.insert("MAINPID", "foo");
+        let (ret, closed) = send(&messages);
+        assert!(ret.is_err());
+        assert!(closed);
+
+        // the fds with the invalid name are not stored
+        let long_name = "a".repeat(256);
+        for name in ["a:b", "a\nb", long_name.as_str()] {
+            let mut messages = HashMap::new();
+            messages.insert("FDSTORE", "1");
+            messages.insert("FDNAME", name);
+            let (ret, closed) = send(&messages);
+            assert!(ret.is_ok());
+            assert!(closed);
+            assert!(mng.fdstore.fds().is_empty());
+        }
+    }
 }
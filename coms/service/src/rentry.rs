@@ -15,6 +15,7 @@ use crate::monitor::ServiceMonitor;
 
 use confique::Config;
 use macros::EnumDisplay;
+use nix::sys::resource::Resource;
 use nix::sys::signal::Signal;
 use nix::sys::wait::WaitStatus;
 use nix::unistd::Pid;
@@ -26,11 +27,15 @@ use std::rc::Rc;
 use std::str::FromStr;
 
 use sysmaster::error::*;
-use sysmaster::exec::ExecCommand;
+use sysmaster::exec::{
+    deserialize_rlimit_sec, deserialize_rlimit_usec, private_tmp_dir, rlimits_from, ExecCommand,
+    ExecInput, ExecOutput, ExecSandbox, ProtectHome, ProtectSystem, Rlimit,
+};
 use sysmaster::rel::{ReDb, ReDbRoTxn, ReDbRwTxn, ReDbTable, Reliability};
-use sysmaster::serialize::DeserializeWith;
+use sysmaster::serialize::{deserialize_str_or_num, deserialize_str_or_value, DeserializeWith};
 use sysmaster::unit::KillMode;
 
+use basic::path_lookup::RUN_SYSTEM_PATH;
 use basic::special::EXEC_RUNTIME_PREFIX;
 use basic::time_util::USEC_PER_SEC;
 
@@ -38,6 +43,7 @@ struct ServiceReDb<K, V>(ReDb<K, V>);
 
 const RELI_DB_HSERVICE_CONF: &str = "svcconf";
 const RELI_DB_HSERVICE_MNG: &str = "svcmng";
+const RELI_DB_HSERVICE_FDSTORE: &str = "svcfdstore";
 
 #[derive(PartialEq, Eq, Serialize, Deserialize, Debug, Clone, Copy, Default)]
 pub(super) enum ServiceType {
@@ -50,6 +56,8 @@ pub(super) enum ServiceType {
     Oneshot,
     #[serde(alias = "notify")]
     Notify,
+    #[serde(alias = "notify-reload")]
+    NotifyReload,
     Idle,
     Exec,
     TypeMax,
@@ -69,6 +77,7 @@ impl DeserializeWith for ServiceType {
             "forking" => Ok(ServiceType::Forking),
             "oneshot" => Ok(ServiceType::Oneshot),
             "notify" => Ok(ServiceType::Notify),
+            "notify-reload" => Ok(ServiceType::NotifyReload),
             &_ => Ok(ServiceType::Simple),
         }
     }
@@ -80,6 +89,10 @@ pub(super) enum NotifyAccess {
     None,
     #[serde(alias = "main")]
     Main,
+    #[serde(alias = "exec")]
+    Exec,
+    #[serde(alias = "all")]
+    All,
 }
 
 #[derive(PartialEq, Eq, Serialize, Deserialize, Debug, Clone, Copy, Default)]
@@ -183,7 +196,7 @@ fn deserialize_time<'de, D>(de: D) -> Result<u64, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let timeout = u64::deserialize(de)?;
+    let timeout: u64 = deserialize_str_or_value(de)?;
     if timeout >= u64::MAX / USEC_PER_SEC {
         return Ok(u64::MAX);
     }
@@ -212,15 +225,21 @@ pub(super) struct SectionService {
     pub ExecCondition: Option<VecDeque<ExecCommand>>,
     #[config(deserialize_with = Vec::<String>::deserialize_with)]
     pub Sockets: Option<Vec<String>>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     #[config(default = 0)]
     pub WatchdogSec: u64,
     #[config(deserialize_with = deserialize_pidfile)]
     pub PIDFile: Option<PathBuf>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     #[config(default = false)]
     pub RemainAfterExit: bool,
     pub NotifyAccess: Option<NotifyAccess>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     #[config(default = false)]
     pub NonBlocking: bool,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    #[config(default = 0)]
+    pub FileDescriptorStoreMax: usize,
     pub Environment: Option<HashMap<String, String>>,
     #[config(deserialize_with = KillMode::deserialize_with)]
     #[config(default = "none")]
@@ -233,11 +252,23 @@ pub(super) struct SectionService {
     pub Group: String,
     #[config(default = "0022")]
     pub UMask: String,
+    #[config(deserialize_with = ExecInput::deserialize_with)]
+    #[config(default = "null")]
+    pub StandardInput: ExecInput,
+    #[config(deserialize_with = ExecOutput::deserialize_with)]
+    #[config(default = "inherit")]
+    pub StandardOutput: ExecOutput,
+    #[config(deserialize_with = ExecOutput::deserialize_with)]
+    #[config(default = "inherit")]
+    pub StandardError: ExecOutput,
+    #[config(default = "/dev/console")]
+    pub TTYPath: String,
     #[config(default = "no")]
     pub Restart: ServiceRestart,
     #[config(deserialize_with = ExitStatusSet::deserialize_with)]
     #[config(default = "")]
     pub RestartPreventExitStatus: ExitStatusSet,
+    #[config(deserialize_with = deserialize_str_or_value)]
     #[config(default = 0)]
     pub RestartSec: u64,
     #[config(deserialize_with = Vec::<String>::deserialize_with)]
@@ -245,6 +276,8 @@ pub(super) struct SectionService {
     pub EnvironmentFile: Vec<String>,
     #[config(default = "SIGTERM")]
     pub KillSignal: String,
+    #[config(default = "SIGHUP")]
+    pub ReloadSignal: String,
     #[config(deserialize_with = deserialize_time)]
     #[config(default = 0)]
     pub TimeoutSec: u64,
@@ -254,9 +287,120 @@ pub(super) struct SectionService {
     #[config(deserialize_with = deserialize_time)]
     #[config(default = 0)]
     pub TimeoutStopSec: u64,
+
+    #[config(deserialize_with = deserialize_rlimit_sec)]
+    pub LimitCPU: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitFSIZE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitDATA: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitSTACK: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitCORE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitRSS: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitNOFILE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitAS: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitNPROC: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitMEMLOCK: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitLOCKS: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitSIGPENDING: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitMSGQUEUE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitNICE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitRTPRIO: Option<Rlimit>,
+    #[config(deserialize_with = deserialize_rlimit_usec)]
+    pub LimitRTTIME: Option<Rlimit>,
+
+    #[config(deserialize_with = ProtectSystem::deserialize_with)]
+    #[config(default = "no")]
+    pub ProtectSystem: ProtectSystem,
+    #[config(deserialize_with = ProtectHome::deserialize_with)]
+    #[config(default = "no")]
+    pub ProtectHome: ProtectHome,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    #[config(default = false)]
+    pub PrivateTmp: bool,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    #[config(default = false)]
+    pub PrivateDevices: bool,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ReadOnlyPaths: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ReadWritePaths: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub InaccessiblePaths: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub BindPaths: Vec<String>,
+
+    pub Slice: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    pub MemoryMax: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    pub MemoryHigh: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    pub CPUWeight: Option<u64>,
+    pub CPUQuota: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    pub TasksMax: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    pub IOWeight: Option<u64>,
 }
 
 impl SectionService {
+    pub(super) fn rlimits(&self) -> Vec<(Resource, Rlimit)> {
+        rlimits_from([
+            self.LimitCPU,
+            self.LimitFSIZE,
+            self.LimitDATA,
+            self.LimitSTACK,
+            self.LimitCORE,
+            self.LimitRSS,
+            self.LimitNOFILE,
+            self.LimitAS,
+            self.LimitNPROC,
+            self.LimitMEMLOCK,
+            self.LimitLOCKS,
+            self.LimitSIGPENDING,
+            self.LimitMSGQUEUE,
+            self.LimitNICE,
+            self.LimitRTPRIO,
+            self.LimitRTTIME,
+        ])
+    }
+
+    pub(super) fn sandbox(&self, unit: &str) -> Result<ExecSandbox> {
+        let mut sandbox = ExecSandbox::new();
+        sandbox.set_protect_system(self.ProtectSystem);
+        sandbox.set_protect_home(self.ProtectHome);
+        sandbox.set_private_tmp(self.private_tmp_dir(unit));
+        sandbox.set_private_devices(self.PrivateDevices);
+        sandbox.add_read_only_paths(&self.ReadOnlyPaths)?;
+        sandbox.add_read_write_paths(&self.ReadWritePaths)?;
+        sandbox.add_inaccessible_paths(&self.InaccessiblePaths)?;
+        sandbox.add_bind_paths(&self.BindPaths)?;
+        Ok(sandbox)
+    }
+
+    /// the per-unit directory of PrivateTmp=, None if /tmp is not private
+    pub(super) fn private_tmp_dir(&self, unit: &str) -> Option<PathBuf> {
+        self.PrivateTmp
+            .then(|| private_tmp_dir(Path::new(RUN_SYSTEM_PATH), unit))
+    }
+
     pub(super) fn set_notify_access(&mut self, v: NotifyAccess) {
         self.NotifyAccess = Some(v);
     }
@@ -342,6 +486,7 @@ pub(super) enum ServiceCommand {
 pub(super) enum NotifyState {
     Unknown,
     Ready,
+    Reloading,
     Stopping,
 }
 
@@ -425,17 +570,24 @@ impl ServiceReMng {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ServiceReFdStore {
+    fds: Vec<(String, i32)>,
+}
+
 pub(super) struct ServiceRe {
     // database: multi-instance(N)
     conf: Rc<ServiceReDb<String, ServiceReConf>>, // RELI_DB_ESERVICE_CONF; key: unit_id, data: config;
     mng: Rc<ServiceReDb<String, ServiceReMng>>, // RELI_DB_HSERVICE_MNG; key: unit_id, data: state+result+main(pid+cmd)+control(pid+cmd)+notify_state;
+    fdstore: Rc<ServiceReDb<String, ServiceReFdStore>>, // RELI_DB_HSERVICE_FDSTORE; key: unit_id, data: (name+fd)s;
 }
 
 impl ServiceRe {
     pub(super) fn new(relir: &Reliability) -> ServiceRe {
         let conf = Rc::new(ServiceReDb(ReDb::new(relir, RELI_DB_HSERVICE_CONF)));
         let mng = Rc::new(ServiceReDb(ReDb::new(relir, RELI_DB_HSERVICE_MNG)));
-        let rentry = ServiceRe { conf, mng };
+        let fdstore = Rc::new(ServiceReDb(ReDb::new(relir, RELI_DB_HSERVICE_FDSTORE)));
+        let rentry = ServiceRe { conf, mng, fdstore };
         rentry.register(relir);
         rentry
     }
@@ -445,7 +597,7 @@ impl ServiceRe {
         self.conf.0.insert(unit_id.to_string(), conf);
     }
 
-    pub(super) fn _conf_remove(&self, unit_id: &String) {
+    pub(super) fn conf_remove(&self, unit_id: &String) {
         self.conf.0.remove(unit_id);
     }
 
@@ -492,7 +644,7 @@ impl ServiceRe {
         self.mng.0.insert(unit_id.to_string(), mng);
     }
 
-    pub(super) fn _mng_remove(&self, unit_id: &String) {
+    pub(super) fn mng_remove(&self, unit_id: &String) {
         self.mng.0.remove(unit_id);
     }
 
@@ -535,6 +687,19 @@ impl ServiceRe {
         })
     }
 
+    pub(super) fn fdstore_insert(&self, unit_id: &str, fds: Vec<(String, i32)>) {
+        let fdstore = ServiceReFdStore { fds };
+        self.fdstore.0.insert(unit_id.to_string(), fdstore);
+    }
+
+    pub(super) fn fdstore_get(&self, unit_id: &String) -> Option<Vec<(String, i32)>> {
+        self.fdstore.0.get(unit_id).map(|f| f.fds)
+    }
+
+    pub(super) fn fdstore_remove(&self, unit_id: &String) {
+        self.fdstore.0.remove(unit_id);
+    }
+
     fn register(&self, relir: &Reliability) {
         // rel-db: RELI_DB_HSERVICE_CONF
         let db = Rc::clone(&self.conf);
@@ -543,6 +708,10 @@ impl ServiceRe {
         // rel-db: RELI_DB_HSERVICE_MNG
         let db = Rc::clone(&self.mng);
         relir.history_db_register(RELI_DB_HSERVICE_MNG, db);
+
+        // rel-db: RELI_DB_HSERVICE_FDSTORE
+        let db = Rc::clone(&self.fdstore);
+        relir.history_db_register(RELI_DB_HSERVICE_FDSTORE, db);
     }
 }
 
@@ -581,3 +750,21 @@ impl ReDbTable for ServiceReDb<String, ServiceReMng> {
         self.0.set_ignore(ignore);
     }
 }
+
+impl ReDbTable for ServiceReDb<String, ServiceReFdStore> {
+    fn clear(&self, wtxn: &mut ReDbRwTxn) {
+        self.0.do_clear(wtxn);
+    }
+
+    fn export(&self, db_wtxn: &mut ReDbRwTxn) {
+        self.0.cache_2_db(db_wtxn);
+    }
+
+    fn import<'a>(&self, db_rtxn: &ReDbRoTxn) {
+        self.0.db_2_cache(db_rtxn);
+    }
+
+    fn ignore_set(&self, ignore: bool) {
+        self.0.set_ignore(ignore);
+    }
+}
@@ -14,17 +14,20 @@ use crate::mng::RunningData;
 
 use super::comm::ServiceUnitComm;
 use super::config::ServiceConfig;
+use super::fdstore::ServiceFdStore;
 use super::pid::ServicePid;
-use super::rentry::ServiceType;
-use nix::unistd::Pid;
+use super::rentry::NotifyAccess;
+use nix::unistd::{self, Pid};
 use std::env;
+use std::path::PathBuf;
 use std::rc::Rc;
 use sysmaster::error::*;
-use sysmaster::exec::{ExecCommand, ExecContext, ExecFlags, ExecParameters};
+use sysmaster::exec::{ExecCommand, ExecContext, ExecFlags, ExecInput, ExecOutput, ExecParameters};
 
 pub(super) struct ServiceSpawn {
     comm: Rc<ServiceUnitComm>,
     pid: Rc<ServicePid>,
+    fdstore: Rc<ServiceFdStore>,
     config: Rc<ServiceConfig>,
     exec_ctx: Rc<ExecContext>,
     rd: Rc<RunningData>,
@@ -34,6 +37,7 @@ impl ServiceSpawn {
     pub(super) fn new(
         commr: &Rc<ServiceUnitComm>,
         pidr: &Rc<ServicePid>,
+        fdstore: &Rc<ServiceFdStore>,
         configr: &Rc<ServiceConfig>,
         exec_ctx: &Rc<ExecContext>,
         rd: &Rc<RunningData>,
@@ -41,6 +45,7 @@ impl ServiceSpawn {
         ServiceSpawn {
             comm: Rc::clone(commr),
             pid: Rc::clone(pidr),
+            fdstore: Rc::clone(fdstore),
             config: configr.clone(),
             exec_ctx: exec_ctx.clone(),
             rd: rd.clone(),
@@ -79,12 +84,12 @@ impl ServiceSpawn {
         self.rd.enable_timer(time_out)?;
 
         if ec_flags.contains(ExecFlags::PASS_FDS) {
-            params.insert_fds(self.collect_socket_fds());
+            let (fd_names, fds): (Vec<String>, Vec<i32>) = self.collect_fds().into_iter().unzip();
+            params.insert_fds(fds);
+            params.insert_fd_names(fd_names);
         }
 
-        if self.config.service_type() == ServiceType::Notify
-            || self.config.config_data().borrow().Service.WatchdogSec > 0
-        {
+        if self.config.notify_access() != NotifyAccess::None {
             let notify_sock = um.notify_socket().unwrap();
             log::debug!("add NOTIFY_SOCKET env: {}", notify_sock.to_str().unwrap());
             params.add_env("NOTIFY_SOCKET", notify_sock.to_str().unwrap().to_string());
@@ -115,6 +120,24 @@ impl ServiceSpawn {
             return Err(e);
         }
 
+        for (resource, rlimit) in self.config.config_data().borrow().Service.rlimits() {
+            params.add_rlimit(resource, rlimit);
+        }
+
+        let sandbox = self
+            .config
+            .config_data()
+            .borrow()
+            .Service
+            .sandbox(unit.id());
+        match sandbox {
+            Ok(sandbox) => params.set_sandbox(sandbox),
+            Err(e) => {
+                log::error!("Failed to add sandbox to execute parameters: {}", e);
+                return Err(e);
+            }
+        }
+
         if let Err(e) = params.add_working_directory(
             self.config
                 .config_data()
@@ -129,8 +152,14 @@ impl ServiceSpawn {
 
         params.set_watchdog_usec(self.watchdog_timer());
 
+        if let Err(e) = self.setup_stdio(&mut params) {
+            log::error!("Failed to set up standard input/output: {}", e);
+            return Err(e);
+        }
+
         log::debug!("begin to exec spawn");
-        match um.exec_spawn(unit.id(), cmdline, &params, self.exec_ctx.clone()) {
+        let ret = um.exec_spawn(unit.id(), cmdline, &params, self.exec_ctx.clone());
+        match ret {
             Ok(pid) => {
                 um.child_watch_pid(unit.id(), pid);
                 Ok(pid)
@@ -142,6 +171,109 @@ impl ServiceSpawn {
         }
     }
 
+    /// decide where the stdio of the child is connected to, only the socket fds owned by
+    /// sysmaster are passed here, the files are opened in the child, so that opening a fifo
+    /// or a tty which blocks can't block sysmaster.
+    fn setup_stdio(&self, params: &mut ExecParameters) -> Result<()> {
+        let data = self.config.config_data();
+        let data = data.borrow();
+        let service = &data.Service;
+
+        let stdin_fd = match &service.StandardInput {
+            ExecInput::Socket => Some(self.socket_fd()?),
+            ExecInput::Fd(name) => Some(self.named_socket_fd(name)?),
+            _ => None,
+        };
+
+        // the output is sent to the log collector if it's enabled, otherwise
+        // StandardOutput=journal and StandardError=journal behave like inherit
+        let collector = self.comm.um().log_collector_enabled();
+        let stdout_collect = match &service.StandardOutput {
+            ExecOutput::Journal => collector,
+            ExecOutput::Inherit => collector && service.StandardInput == ExecInput::Null,
+            _ => false,
+        };
+        let stdout = match &service.StandardOutput {
+            _ if stdout_collect => ExecOutput::Journal,
+            ExecOutput::Inherit | ExecOutput::Journal => {
+                if service.StandardInput != ExecInput::Null {
+                    ExecOutput::Inherit
+                } else if unistd::getpid().as_raw() != 1 {
+                    // not running as the init process, the stdout of sysmaster is what the user expects
+                    ExecOutput::Journal
+                } else {
+                    ExecOutput::Null
+                }
+            }
+            output => output.clone(),
+        };
+
+        let stderr_collect = match &service.StandardError {
+            ExecOutput::Journal => collector,
+            ExecOutput::Inherit => stdout_collect,
+            _ => false,
+        };
+        let stderr = match &service.StandardError {
+            _ if stderr_collect => ExecOutput::Journal,
+            ExecOutput::Inherit | ExecOutput::Journal => ExecOutput::Inherit,
+            output => output.clone(),
+        };
+
+        let stdout_fd = self.output_fd(&stdout)?;
+        let stderr_fd = self.output_fd(&stderr)?;
+
+        params.set_log_collect(stdout_collect, stderr_collect);
+        params.set_stdio(
+            service.StandardInput.clone(),
+            stdout,
+            stderr,
+            PathBuf::from(&service.TTYPath),
+        );
+        params.set_stdio_fds(stdin_fd, stdout_fd, stderr_fd);
+        Ok(())
+    }
+
+    fn output_fd(&self, output: &ExecOutput) -> Result<Option<i32>> {
+        match output {
+            ExecOutput::Socket => Ok(Some(self.socket_fd()?)),
+            ExecOutput::Fd(name) => Ok(Some(self.named_socket_fd(name)?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn socket_fd(&self) -> Result<i32> {
+        let fds = self.collect_socket_fds();
+        if fds.len() != 1 {
+            return Err(format!(
+                "the socket stdio needs exactly one socket fd, but {} found",
+                fds.len()
+            )
+            .into());
+        }
+        Ok(fds[0])
+    }
+
+    fn named_socket_fd(&self, name: &str) -> Result<i32> {
+        // the name of the fd is the name of the socket unit, with or without the suffix
+        self.comm
+            .um()
+            .collect_named_socket_fds(&self.comm.get_owner_id())
+            .into_iter()
+            .find(|(socket, _)| socket == name || socket.strip_suffix(".socket") == Some(name))
+            .map(|(_, fd)| fd)
+            .ok_or_else(|| format!("no socket fd is named {name}").into())
+    }
+
+    /// the socket fds are named with the socket unit, followed by the fds in the fd store
+    fn collect_fds(&self) -> Vec<(String, i32)> {
+        let mut fds = self
+            .comm
+            .um()
+            .collect_named_socket_fds(&self.comm.get_owner_id());
+        fds.extend(self.fdstore.fds());
+        fds
+    }
+
     fn collect_socket_fds(&self) -> Vec<i32> {
         self.comm.um().collect_socket_fds(&self.comm.get_owner_id())
     }
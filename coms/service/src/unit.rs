@@ -18,19 +18,22 @@ use super::config::ServiceConfig;
 use super::mng::RunningData;
 use super::mng::ServiceMng;
 use super::rentry::{NotifyAccess, ServiceCommand, ServiceType};
-use basic::logger;
 use basic::special::{BASIC_TARGET, SHUTDOWN_TARGET, SYSINIT_TARGET};
+use basic::{fd_util, logger};
 use nix::sys::signal::Signal;
 use nix::sys::socket::UnixCredentials;
 use nix::sys::wait::WaitStatus;
+use nix::unistd::Pid;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::str::FromStr;
 use sysmaster::error::*;
 use sysmaster::rel::{ReStation, Reliability};
+use sysmaster::serialize::section_properties;
 use sysmaster::unit::{
-    SubUnit, UmIf, UnitActiveState, UnitBase, UnitDependencyMask, UnitMngUtil, UnitRelations,
+    CgroupContext, SubUnit, UmIf, UnitActiveState, UnitBase, UnitDependencyMask, UnitMngUtil,
+    UnitRelations,
 };
 
 use sysmaster::exec::ExecContext;
@@ -76,7 +79,8 @@ impl SubUnit for ServiceUnit {
     }
 
     fn done(&self) {
-        todo!()
+        self.mng.done();
+        self.comm.rentry_remove();
     }
 
     fn load(&self, paths: Vec<PathBuf>) -> Result<()> {
@@ -139,7 +143,7 @@ impl SubUnit for ServiceUnit {
     }
 
     fn reset_failed(&self) {
-        todo!()
+        self.mng.reset_failed()
     }
 
     fn current_active_state(&self) -> UnitActiveState {
@@ -150,11 +154,35 @@ impl SubUnit for ServiceUnit {
         self.mng.get_state()
     }
 
+    fn properties(&self) -> Vec<(String, String)> {
+        section_properties(&self.config.config_data().borrow().Service)
+    }
+
+    fn set_property(&self, key: &str, value: &str) -> Result<()> {
+        self.config.set_property(key, value)
+    }
+
+    fn get_status_text(&self) -> Option<String> {
+        self.mng.status_text()
+    }
+
+    fn main_pid(&self) -> Option<Pid> {
+        self.mng.main_pid()
+    }
+
+    fn control_pid(&self) -> Option<Pid> {
+        self.mng.control_pid()
+    }
+
     fn attach_unit(&self, unit: Rc<dyn UnitBase>) {
         self.comm.attach_unit(unit);
         self.db_insert();
     }
 
+    fn cgroup_context(&self) -> Option<Rc<CgroupContext>> {
+        Some(self.config.cgroup_context())
+    }
+
     fn notify_message(
         &self,
         ucred: &UnixCredentials,
@@ -167,6 +195,13 @@ impl SubUnit for ServiceUnit {
             fds,
             messages
         );
+        if !self.mng.notify_access_allowed(Pid::from_raw(ucred.pid())) {
+            for fd in fds {
+                fd_util::close(fd);
+            }
+            return Ok(());
+        }
+
         self.mng.notify_message(ucred, messages, fds)
     }
 }
@@ -209,6 +244,22 @@ impl ServiceUnit {
         Ok(())
     }
 
+    fn parse_cgroup_context(&self) -> Result<()> {
+        let ctx = self.config.cgroup_context();
+        let data = self.config.config_data();
+        let data = data.borrow();
+        let service = &data.Service;
+
+        ctx.set_slice(service.Slice.as_deref())?;
+        ctx.set_memory_max(service.MemoryMax.as_deref())?;
+        ctx.set_memory_high(service.MemoryHigh.as_deref())?;
+        ctx.set_cpu_weight(service.CPUWeight)?;
+        ctx.set_cpu_quota(service.CPUQuota.as_deref())?;
+        ctx.set_tasks_max(service.TasksMax.as_deref())?;
+        ctx.set_io_weight(service.IOWeight)?;
+        Ok(())
+    }
+
     fn parse(&self) -> Result<()> {
         // if TimeoutSec is set, flush it's value to TimeoutStartSec and TimeoutStopSec
         self.config.flush_timeout();
@@ -270,11 +321,32 @@ impl ServiceUnit {
 
         self.parse_kill_context()?;
 
+        self.parse_cgroup_context()?;
+
         Ok(())
     }
 
     fn service_add_extras(&self) -> Result<()> {
-        if self.config.service_type() == ServiceType::Notify {
+        // the notify socket is needed by the notify services, the watchdog and the fd store
+        if self
+            .config
+            .config_data()
+            .borrow()
+            .Service
+            .NotifyAccess
+            .is_none()
+            && (matches!(
+                self.config.service_type(),
+                ServiceType::Notify | ServiceType::NotifyReload
+            ) || self.config.config_data().borrow().Service.WatchdogSec > 0
+                || self
+                    .config
+                    .config_data()
+                    .borrow()
+                    .Service
+                    .FileDescriptorStoreMax
+                    > 0)
+        {
             self.config.set_notify_access(NotifyAccess::Main);
         }
 
@@ -321,6 +393,19 @@ impl ServiceUnit {
             });
         }
 
+        let reload_signal = self
+            .config
+            .config_data()
+            .borrow()
+            .Service
+            .ReloadSignal
+            .clone();
+        if Signal::from_str(&reload_signal).is_err() {
+            return Err(Error::ConfigureError {
+                msg: format!("ReloadSignal {} is not a valid signal", reload_signal),
+            });
+        }
+
         if self.config.service_type() == ServiceType::Oneshot
             && !matches!(
                 self.config.config_data().borrow().Service.Restart,
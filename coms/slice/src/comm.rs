@@ -0,0 +1,172 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+/*Associate the unit object
+*You need to notify the Unit object and change the method
+*Get the attributes of the unit object
+*Call relation
+*slice_ unit->slice_ mng->slice_ comm
+*/
+use super::rentry::{SectionSlice, SliceRe, SliceState};
+use once_cell::sync::Lazy;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::sync::{Arc, RwLock};
+use sysmaster::rel::Reliability;
+use sysmaster::unit::{UmIf, UnitBase};
+
+pub(super) struct SliceUnitComm {
+    owner: RefCell<Option<Rc<dyn UnitBase>>>,
+    umcomm: Arc<SliceUmComm>,
+}
+
+impl SliceUnitComm {
+    pub(super) fn new() -> Self {
+        SliceUnitComm {
+            owner: RefCell::new(None),
+            umcomm: SliceUmComm::get_instance(),
+        }
+    }
+
+    pub(super) fn attach_um(&self, um: Rc<dyn UmIf>) {
+        self.umcomm.attach_um(um)
+    }
+
+    pub(super) fn attach_unit(&self, unit: Rc<dyn UnitBase>) {
+        self.owner.replace(Some(unit));
+    }
+
+    pub(super) fn owner(&self) -> Option<Rc<dyn UnitBase>> {
+        if let Some(ref unit) = *self.owner.borrow() {
+            Some(Rc::clone(unit))
+        } else {
+            None
+        }
+    }
+
+    pub(super) fn attach_reli(&self, reli: Rc<Reliability>) {
+        self.umcomm.attach_reli(reli);
+    }
+
+    pub(super) fn rentry_conf_insert(&self, slice: &SectionSlice) {
+        if let Some(u) = self.owner() {
+            self.rentry().conf_insert(u.id(), slice)
+        }
+    }
+
+    pub(super) fn rentry_conf_get(&self) -> Option<SectionSlice> {
+        let ret = self.owner().map(|u| self.rentry().conf_get(u.id()));
+        ret.unwrap_or(None)
+    }
+
+    pub(super) fn rentry_mng_insert(&self, state: SliceState) {
+        if let Some(u) = self.owner() {
+            self.rentry().mng_insert(u.id(), state)
+        }
+    }
+
+    pub(super) fn rentry_mng_get(&self) -> Option<SliceState> {
+        let ret = self.owner().map(|u| self.rentry().mng_get(u.id()));
+        ret.unwrap_or(None)
+    }
+
+    pub(super) fn rentry(&self) -> Rc<SliceRe> {
+        self.umcomm.rentry()
+    }
+}
+
+static SLICE_UM_COMM: Lazy<Arc<SliceUmComm>> = Lazy::new(|| {
+    let comm = SliceUmComm::new();
+    Arc::new(comm)
+});
+
+pub(super) struct SliceUmComm {
+    data: RwLock<SliceUmCommData>,
+}
+
+unsafe impl Send for SliceUmComm {}
+
+unsafe impl Sync for SliceUmComm {}
+
+impl SliceUmComm {
+    pub(super) fn new() -> Self {
+        SliceUmComm {
+            data: RwLock::new(SliceUmCommData::new()),
+        }
+    }
+
+    pub(super) fn attach_reli(&self, reli: Rc<Reliability>) {
+        let mut wdata = self.data.write().unwrap();
+        wdata.attach_reli(reli);
+    }
+
+    pub(super) fn get_instance() -> Arc<SliceUmComm> {
+        SLICE_UM_COMM.clone()
+    }
+
+    pub(super) fn _reli(&self) -> Rc<Reliability> {
+        let rdata = self.data.read().unwrap();
+        rdata._reli()
+    }
+
+    pub(super) fn rentry(&self) -> Rc<SliceRe> {
+        let rdata = self.data.read().unwrap();
+        rdata.rentry()
+    }
+
+    pub(super) fn attach_um(&self, um: Rc<dyn UmIf>) {
+        let mut wdata = self.data.write().unwrap();
+        wdata.attach_um(um);
+    }
+}
+
+struct SliceUmCommData {
+    // associated objects
+    um: Option<Rc<dyn UmIf>>,
+    _reli: Weak<Reliability>,
+    rentry: Option<Rc<SliceRe>>,
+}
+
+// the declaration "pub(self)" is for identification only.
+impl SliceUmCommData {
+    pub(self) fn new() -> SliceUmCommData {
+        SliceUmCommData {
+            um: None,
+            _reli: Weak::new(),
+            rentry: None,
+        }
+    }
+
+    pub(self) fn attach_reli(&mut self, reli: Rc<Reliability>) {
+        let old = self._reli.clone().upgrade();
+        if old.is_none() {
+            log::debug!("SliceUmComm attach_reli action.");
+            self._reli = Rc::downgrade(&reli);
+            self.rentry.replace(Rc::new(SliceRe::new(&reli)));
+        }
+    }
+
+    pub(self) fn attach_um(&mut self, um: Rc<dyn UmIf>) {
+        if self.um.is_none() {
+            log::debug!("SliceUmComm attach_um action.");
+            self.um = Some(um)
+        }
+    }
+
+    pub(self) fn _reli(&self) -> Rc<Reliability> {
+        self._reli.clone().upgrade().unwrap()
+    }
+
+    pub(self) fn rentry(&self) -> Rc<SliceRe> {
+        self.rentry.as_ref().cloned().unwrap()
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! slice_config mod load the conf file list and convert it to structure which is defined in this mod.
+//!
+#![allow(non_snake_case)]
+use super::comm::SliceUnitComm;
+use super::rentry::SectionSlice;
+use confique::Config;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use sysmaster::error::*;
+use sysmaster::rel::ReStation;
+use sysmaster::unit::CgroupContext;
+
+#[derive(Config, Default, Debug)]
+pub(super) struct SliceConfigData {
+    #[config(nested)]
+    pub Slice: SectionSlice,
+}
+
+impl SliceConfigData {
+    pub(self) fn new(Slice: SectionSlice) -> SliceConfigData {
+        SliceConfigData { Slice }
+    }
+}
+
+pub(super) struct SliceConfig {
+    // associated objects
+    comm: Rc<SliceUnitComm>,
+
+    // owned objects
+    data: Rc<RefCell<SliceConfigData>>,
+    cgroup_context: Rc<CgroupContext>,
+}
+
+impl ReStation for SliceConfig {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, reload: bool) {
+        if reload {
+            return;
+        }
+        if let Some(conf) = self.comm.rentry_conf_get() {
+            self.data.replace(SliceConfigData::new(conf));
+            if let Err(e) = self.parse_cgroup_context() {
+                log::error!("Failed to restore the cgroup context of slice: {:?}", e);
+            }
+        }
+    }
+
+    fn db_insert(&self) {
+        self.comm.rentry_conf_insert(&self.data.borrow().Slice);
+    }
+
+    // reload: no external connections, no entry
+}
+
+impl SliceConfig {
+    pub(super) fn new(commr: &Rc<SliceUnitComm>) -> Self {
+        SliceConfig {
+            comm: Rc::clone(commr),
+            data: Rc::new(RefCell::new(SliceConfigData::default())),
+            cgroup_context: Rc::new(CgroupContext::default()),
+        }
+    }
+
+    pub(super) fn load(&self, paths: Vec<PathBuf>, update: bool) -> Result<()> {
+        // the slice like system.slice can be used without configuration file
+        let data = if paths.is_empty() {
+            SliceConfigData::default()
+        } else {
+            let mut builder = SliceConfigData::builder().env();
+            for v in paths {
+                builder = builder.file(v);
+            }
+            builder.load().context(ConfiqueSnafu)?
+        };
+
+        *self.data.borrow_mut() = data;
+        self.parse_cgroup_context()?;
+
+        if update {
+            self.db_update();
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<SliceConfigData>> {
+        self.data.clone()
+    }
+
+    pub(super) fn cgroup_context(&self) -> Rc<CgroupContext> {
+        self.cgroup_context.clone()
+    }
+
+    /// change the resource control at runtime, it's applied to the cgroup by the unit
+    pub(super) fn set_property(&self, key: &str, value: &str) -> Result<()> {
+        self.cgroup_context.set_property(key, value)?;
+        {
+            let ctx = &self.cgroup_context;
+            let mut data = self.data.borrow_mut();
+            let slice = &mut data.Slice;
+            match key {
+                "MemoryMax" => slice.MemoryMax = Some(value.to_string()),
+                "MemoryHigh" => slice.MemoryHigh = Some(value.to_string()),
+                "CPUWeight" => slice.CPUWeight = ctx.cpu_weight(),
+                "CPUQuota" => slice.CPUQuota = Some(value.to_string()),
+                "TasksMax" => slice.TasksMax = Some(value.to_string()),
+                "IOWeight" => slice.IOWeight = ctx.io_weight(),
+                _ => {}
+            }
+        }
+        self.db_update();
+        Ok(())
+    }
+
+    fn parse_cgroup_context(&self) -> Result<()> {
+        let ctx = &self.cgroup_context;
+        let data = self.data.borrow();
+        let slice = &data.Slice;
+
+        ctx.set_memory_max(slice.MemoryMax.as_deref())?;
+        ctx.set_memory_high(slice.MemoryHigh.as_deref())?;
+        ctx.set_cpu_weight(slice.CPUWeight)?;
+        ctx.set_cpu_quota(slice.CPUQuota.as_deref())?;
+        ctx.set_tasks_max(slice.TasksMax.as_deref())?;
+        ctx.set_io_weight(slice.IOWeight)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! # Slice is used to group the units hierarchically for resource management.
+//!  A slice is mapped to a node in the cgroup tree, the name of the slice encodes its position:
+//!  "-" separates the levels, so foo-bar.slice is under foo.slice, and foo.slice is under the root slice "-.slice".
+//!  The units with cgroup, like service and socket, are placed in the slice configured by Slice=, or system.slice by default.
+//!  The slice units can be used without configuration file, like system.slice and user.slice.
+//! #  Example:
+//! ``` toml
+//!  [Unit]
+//!  Description="foo"
+//!
+//!  [Slice]
+//!  MemoryMax="1G"
+//!  CPUWeight=100
+//! ```
+//! ##  Automatic dependency
+//!
+//! ###  Implicit dependency
+//!  The slice has Requires= and After= dependencies on its parent slice, except the top level slices.
+//!  The unit placed in a slice has Requires= and After= dependencies on the slice.
+//!
+//! ###  Default Dependency
+//!  If DefaultDependencies=true is set, the following dependencies will be added by default:
+//!  Conflicts="shutdown.target", Before="shutdown.target"
+
+// dependency: slice_base -> slice_rentry -> slice_comm -> {slice_mng, slice_config} -> slice_unit -> slice_manager
+mod base;
+mod comm;
+mod config;
+mod manager;
+mod mng;
+mod rentry;
+mod unit;
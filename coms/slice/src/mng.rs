@@ -0,0 +1,152 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! is the core of the slice unit
+//!
+use super::comm::SliceUnitComm;
+use super::rentry::SliceState;
+use basic::IN_SET;
+use std::{cell::RefCell, rc::Rc};
+use sysmaster::error::*;
+use sysmaster::rel::ReStation;
+use sysmaster::unit::{UnitActiveState, UnitNotifyFlags};
+
+impl SliceState {
+    fn to_unit_state(self) -> UnitActiveState {
+        match self {
+            SliceState::Dead | SliceState::StateMax => UnitActiveState::UnitInActive,
+            SliceState::Active => UnitActiveState::UnitActive,
+        }
+    }
+}
+
+pub(super) struct SliceMng {
+    comm: Rc<SliceUnitComm>,
+    state: RefCell<SliceState>,
+}
+
+impl ReStation for SliceMng {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, _reload: bool) {
+        if let Some(state) = self.comm.rentry_mng_get() {
+            *self.state.borrow_mut() = state;
+        }
+    }
+
+    fn db_insert(&self) {
+        self.comm.rentry_mng_insert(self.state());
+    }
+
+    // reload: no external connections, no entry
+}
+
+impl SliceMng {
+    pub(super) fn new(_comm: &Rc<SliceUnitComm>) -> Self {
+        SliceMng {
+            comm: Rc::clone(_comm),
+            state: RefCell::new(SliceState::StateMax),
+        }
+    }
+
+    pub(super) fn start_check(&self) -> Result<()> {
+        if IN_SET!(self.state(), SliceState::Active) {
+            return Err(Error::UnitActionEAgain);
+        }
+        Ok(())
+    }
+
+    pub(super) fn start_action(&self, notify: bool) {
+        //todo notify dbus is not implemented
+        self.set_state(SliceState::Active, notify);
+    }
+
+    pub(super) fn stop_check(&self) -> Result<()> {
+        if IN_SET!(self.state(), SliceState::Dead) {
+            return Err(Error::UnitActionEAgain);
+        }
+        Ok(())
+    }
+
+    pub(super) fn stop_action(&self, notify: bool) {
+        self.set_state(SliceState::Dead, notify);
+    }
+
+    pub fn get_state(&self) -> String {
+        let state = *self.state.borrow();
+        state.to_string()
+    }
+
+    fn set_state(&self, new_state: SliceState, notify: bool) {
+        let old_state = self.state();
+        self.state.replace(new_state);
+
+        if notify {
+            self.state_notify(new_state, old_state);
+        }
+    }
+
+    fn state_notify(&self, new_state: SliceState, old_state: SliceState) {
+        let unit = self.comm.owner().unwrap();
+        if new_state != old_state {
+            log::debug!(
+                "{} original state[{:?}] ->new state[{:?}]",
+                unit.id(),
+                old_state,
+                new_state,
+            );
+        }
+
+        let old_unit_state = old_state.to_unit_state();
+        let new_unit_state = new_state.to_unit_state();
+        unit.notify(
+            old_unit_state,
+            new_unit_state,
+            UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE,
+        );
+
+        self.db_update();
+    }
+
+    fn state(&self) -> SliceState {
+        *self.state.borrow()
+    }
+
+    pub fn to_unit_state(&self) -> UnitActiveState {
+        self.state().to_unit_state()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SliceMng;
+    use super::SliceState;
+    use super::SliceUnitComm;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_slice_set_state() {
+        let _comm = Rc::new(SliceUnitComm::new());
+        let tm = SliceMng::new(&_comm);
+        tm.set_state(SliceState::Active, false);
+        assert_eq!(tm.state(), SliceState::Active)
+    }
+
+    #[test]
+    fn test_slice_stop_action() {
+        let _comm = Rc::new(SliceUnitComm::new());
+        let tm = SliceMng::new(&_comm);
+        tm.stop_action(false);
+        assert_eq!(tm.state(), SliceState::Dead)
+    }
+}
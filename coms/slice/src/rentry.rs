@@ -0,0 +1,153 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+#![allow(non_snake_case)]
+use confique::Config;
+use macros::EnumDisplay;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use sysmaster::rel::{ReDb, ReDbRoTxn, ReDbRwTxn, ReDbTable, Reliability};
+use sysmaster::serialize::{deserialize_str_or_num, deserialize_str_or_value};
+
+const RELI_DB_HSLICE_CONF: &str = "sliceconf";
+const RELI_DB_HSLICE_MNG: &str = "slicemng";
+
+#[derive(Config, Default, Clone, Debug, Serialize, Deserialize)]
+pub(super) struct SectionSlice {
+    #[config(deserialize_with = deserialize_str_or_num)]
+    pub MemoryMax: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    pub MemoryHigh: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    pub CPUWeight: Option<u64>,
+    pub CPUQuota: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    pub TasksMax: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    pub IOWeight: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SliceReConf {
+    slice: SectionSlice,
+}
+
+impl SliceReConf {
+    fn new(slicer: &SectionSlice) -> SliceReConf {
+        SliceReConf {
+            slice: slicer.clone(),
+        }
+    }
+}
+
+// slice contain Dead and Active state，correspond the inactive and active of the unit
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Serialize, Deserialize, EnumDisplay)]
+pub(super) enum SliceState {
+    Dead,
+    Active,
+    StateMax,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SliceReMng {
+    state: SliceState,
+}
+
+impl SliceReMng {
+    fn new(state: SliceState) -> SliceReMng {
+        SliceReMng { state }
+    }
+}
+
+struct SliceReDb<K, V>(ReDb<K, V>);
+
+pub(super) struct SliceRe {
+    // database: multi-instance(N)
+    conf: Rc<SliceReDb<String, SliceReConf>>, // RELI_DB_HSLICE_CONF; key: unit_id, data: config;
+    mng: Rc<SliceReDb<String, SliceReMng>>,   // RELI_DB_HSLICE_MNG; key: unit_id, data: state;
+}
+
+impl SliceRe {
+    pub(super) fn new(relir: &Reliability) -> SliceRe {
+        let conf = Rc::new(SliceReDb(ReDb::new(relir, RELI_DB_HSLICE_CONF)));
+        let mng = Rc::new(SliceReDb(ReDb::new(relir, RELI_DB_HSLICE_MNG)));
+        let rentry = SliceRe { conf, mng };
+        rentry.register(relir);
+        rentry
+    }
+
+    pub(super) fn conf_insert(&self, unit_id: &str, slice: &SectionSlice) {
+        let conf = SliceReConf::new(slice);
+        self.conf.0.insert(unit_id.to_string(), conf);
+    }
+
+    pub(super) fn conf_get(&self, unit_id: &String) -> Option<SectionSlice> {
+        let conf = self.conf.0.get(unit_id);
+        conf.map(|c| c.slice)
+    }
+
+    pub(super) fn mng_insert(&self, unit_id: &str, state: SliceState) {
+        let mng = SliceReMng::new(state);
+        self.mng.0.insert(unit_id.to_string(), mng);
+    }
+
+    pub(super) fn mng_get(&self, unit_id: &String) -> Option<SliceState> {
+        let mng = self.mng.0.get(unit_id);
+        mng.map(|m| m.state)
+    }
+
+    fn register(&self, relir: &Reliability) {
+        // rel-db: RELI_DB_HSLICE_CONF
+        let db = Rc::clone(&self.conf);
+        relir.history_db_register(RELI_DB_HSLICE_CONF, db);
+
+        // rel-db: RELI_DB_HSLICE_MNG
+        let db = Rc::clone(&self.mng);
+        relir.history_db_register(RELI_DB_HSLICE_MNG, db);
+    }
+}
+
+impl ReDbTable for SliceReDb<String, SliceReConf> {
+    fn clear(&self, wtxn: &mut ReDbRwTxn) {
+        self.0.do_clear(wtxn);
+    }
+
+    fn export(&self, db_wtxn: &mut ReDbRwTxn) {
+        self.0.cache_2_db(db_wtxn);
+    }
+
+    fn import<'a>(&self, db_rtxn: &ReDbRoTxn) {
+        self.0.db_2_cache(db_rtxn);
+    }
+
+    fn ignore_set(&self, ignore: bool) {
+        self.0.set_ignore(ignore);
+    }
+}
+
+impl ReDbTable for SliceReDb<String, SliceReMng> {
+    fn clear(&self, wtxn: &mut ReDbRwTxn) {
+        self.0.do_clear(wtxn);
+    }
+
+    fn export(&self, db_wtxn: &mut ReDbRwTxn) {
+        self.0.cache_2_db(db_wtxn);
+    }
+
+    fn import<'a>(&self, db_rtxn: &ReDbRoTxn) {
+        self.0.db_2_cache(db_rtxn);
+    }
+
+    fn ignore_set(&self, ignore: bool) {
+        self.0.set_ignore(ignore);
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! SliceUnit is used to group the units hierarchically in the cgroup tree,
+//! the resource control of the slice applies to all the units in it.
+//! SliceUnit is the entrance of the sub unit，implement the trait UnitObj,UnitMngUtil and UnitSubClass.
+//! Trait UnitObj defines the behavior of the sub unit.
+//! Trait UnitMngUtil is used to attach the Unitmanager to the sub unit.
+//! Trait UnitSubClass implement the convert from sub unit to UnitObj.
+use super::base::PLUGIN_NAME;
+use super::comm::SliceUnitComm;
+use super::config::SliceConfig;
+use super::mng::SliceMng;
+use basic::logger;
+use basic::special::SHUTDOWN_TARGET;
+use nix::sys::wait::WaitStatus;
+use std::cell::RefCell;
+use std::{path::PathBuf, rc::Rc};
+use sysmaster::error::*;
+use sysmaster::rel::{ReStation, Reliability};
+use sysmaster::serialize::section_properties;
+use sysmaster::unit::{
+    CgroupContext, SubUnit, UmIf, UnitActiveState, UnitBase, UnitDependencyMask, UnitMngUtil,
+    UnitRelations,
+};
+
+struct Slice {
+    owner: RefCell<Option<Rc<dyn UnitBase>>>,
+    um: Rc<dyn UmIf>,
+    comm: Rc<SliceUnitComm>,
+    config: Rc<SliceConfig>,
+    mng: Rc<SliceMng>,
+}
+
+impl ReStation for Slice {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, reload: bool) {
+        self.config.db_map(reload);
+        self.mng.db_map(reload);
+    }
+
+    fn db_insert(&self) {
+        self.config.db_insert();
+        self.mng.db_insert();
+    }
+
+    // reload: entry-only
+    fn entry_coldplug(&self) {
+        // the cgroup context is applied again by the unit entry
+    }
+
+    fn entry_clear(&self) {
+        // release external connection, like: timer, ...
+        // do nothing now
+    }
+}
+
+impl Slice {
+    fn new(um_if: Rc<dyn UmIf>) -> Slice {
+        let _comm = Rc::new(SliceUnitComm::new());
+        Slice {
+            owner: RefCell::new(None),
+            um: Rc::clone(&um_if),
+            comm: Rc::clone(&_comm),
+            config: Rc::new(SliceConfig::new(&_comm)),
+            mng: Rc::new(SliceMng::new(&_comm)),
+        }
+    }
+
+    pub(self) fn owner(&self) -> Option<Rc<dyn UnitBase>> {
+        if let Some(ref unit) = *self.owner.borrow() {
+            Some(Rc::clone(unit))
+        } else {
+            None
+        }
+    }
+
+    pub(self) fn add_default_dependencies(&self) -> Result<()> {
+        let u = match self.owner() {
+            None => return Ok(()),
+            Some(u) => u,
+        };
+
+        if !u.default_dependencies() {
+            return Ok(());
+        }
+
+        log::debug!("Adding default dependencies for slice: {}", u.id());
+        self.um.unit_add_two_dependency(
+            u.id(),
+            UnitRelations::UnitBefore,
+            UnitRelations::UnitConflicts,
+            SHUTDOWN_TARGET,
+            true,
+            UnitDependencyMask::Default,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl SubUnit for Slice {
+    fn load(&self, paths: Vec<PathBuf>) -> Result<()> {
+        log::debug!("load for slice");
+        self.config.load(paths, true)?;
+
+        // stopping the slice on isolate would stop all the units in it
+        if let Some(u) = self.owner() {
+            u.set_ignore_on_isolate(true);
+        }
+
+        self.add_default_dependencies()
+    }
+
+    fn current_active_state(&self) -> UnitActiveState {
+        self.mng.to_unit_state()
+    }
+
+    fn get_subunit_state(&self) -> String {
+        self.mng.get_state()
+    }
+
+    fn properties(&self) -> Vec<(String, String)> {
+        section_properties(&self.config.config_data().borrow().Slice)
+    }
+
+    fn set_property(&self, key: &str, value: &str) -> Result<()> {
+        self.config.set_property(key, value)
+    }
+
+    fn attach_unit(&self, unit: Rc<dyn UnitBase>) {
+        self.comm.attach_unit(Rc::clone(&unit));
+        self.owner.replace(Some(unit));
+        self.db_insert();
+    }
+
+    fn init(&self) {}
+
+    fn done(&self) {}
+
+    fn dump(&self) {}
+
+    fn start(&self) -> Result<()> {
+        //if current state is not valid, just return.
+        self.mng.start_check()?;
+
+        // create the cgroup of the slice and apply the resource control to it
+        if let Some(u) = self.owner() {
+            u.prepare_exec()?;
+        }
+
+        self.mng.start_action(true);
+        Ok(())
+    }
+
+    fn stop(&self, force: bool) -> Result<()> {
+        if !force {
+            self.mng.stop_check()?;
+        }
+
+        self.mng.stop_action(true);
+
+        // the units in the slice are stopped before it, so the cgroup is empty now
+        if let Some(u) = self.owner() {
+            if let Err(e) = u.cg_remove() {
+                log::warn!("Failed to remove the cgroup of {}: {}", u.id(), e);
+            }
+        }
+        Ok(())
+    }
+
+    fn kill(&self) {}
+
+    fn release_resources(&self) {}
+
+    fn sigchld_events(&self, _wait_status: WaitStatus) {}
+
+    fn reset_failed(&self) {}
+
+    fn cgroup_context(&self) -> Option<Rc<CgroupContext>> {
+        Some(self.config.cgroup_context())
+    }
+}
+
+impl UnitMngUtil for Slice {
+    fn attach_um(&self, _um: Rc<dyn UmIf>) {
+        self.comm.attach_um(_um);
+    }
+
+    fn attach_reli(&self, reli: Rc<Reliability>) {
+        self.comm.attach_reli(reli);
+    }
+}
+
+use sysmaster::declure_unitobj_plugin_with_param;
+declure_unitobj_plugin_with_param!(Slice, Slice::new, PLUGIN_NAME);
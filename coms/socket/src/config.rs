@@ -38,7 +38,7 @@ use std::str::FromStr;
 use sysmaster::error::*;
 use sysmaster::exec::ExecCommand;
 use sysmaster::rel::ReStation;
-use sysmaster::unit::KillContext;
+use sysmaster::unit::{CgroupContext, KillContext};
 
 ///
 #[derive(Default)]
@@ -81,6 +81,7 @@ pub struct SocketConfig {
 
     // resolved from ServiceConfigData
     kill_context: Rc<KillContext>,
+    cgroup_context: Rc<CgroupContext>,
 }
 
 impl ReStation for SocketConfig {
@@ -121,6 +122,7 @@ impl SocketConfig {
             service: RefCell::new(UnitRef::new()),
             ports: RefCell::new(Vec::new()),
             kill_context: Rc::new(KillContext::default()),
+            cgroup_context: Rc::new(CgroupContext::default()),
         }
     }
 
@@ -143,6 +145,7 @@ impl SocketConfig {
         *self.data.borrow_mut() = data;
 
         self.parse_kill_context()?;
+        self.parse_cgroup_context()?;
 
         // parse and record processed configuration
         let ret1 = self.parse_service();
@@ -288,6 +291,25 @@ impl SocketConfig {
         self.kill_context.clone()
     }
 
+    pub(super) fn cgroup_context(&self) -> Rc<CgroupContext> {
+        self.cgroup_context.clone()
+    }
+
+    fn parse_cgroup_context(&self) -> Result<()> {
+        let ctx = &self.cgroup_context;
+        let data = self.data.borrow();
+        let socket = &data.Socket;
+
+        ctx.set_slice(socket.Slice.as_deref())?;
+        ctx.set_memory_max(socket.MemoryMax.as_deref())?;
+        ctx.set_memory_high(socket.MemoryHigh.as_deref())?;
+        ctx.set_cpu_weight(socket.CPUWeight)?;
+        ctx.set_cpu_quota(socket.CPUQuota.as_deref())?;
+        ctx.set_tasks_max(socket.TasksMax.as_deref())?;
+        ctx.set_io_weight(socket.IOWeight)?;
+        Ok(())
+    }
+
     fn parse_kill_context(&self) -> Result<()> {
         self.kill_context
             .set_kill_mode(self.config_data().borrow().Socket.KillMode);
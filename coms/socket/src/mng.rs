@@ -26,7 +26,7 @@ use event::{EventType, Events, Source};
 use nix::sys::wait::WaitStatus;
 use nix::{
     libc::{self},
-    unistd::unlink,
+    unistd::{unlink, Pid},
 };
 use std::os::unix::prelude::RawFd;
 use std::rc::{Rc, Weak};
@@ -143,7 +143,7 @@ impl SocketMng {
             config: Rc::clone(configr),
 
             pid: SocketPid::new(commr),
-            spawn: SocketSpawn::new(commr, exec_ctx),
+            spawn: SocketSpawn::new(commr, configr, exec_ctx),
             ports: RefCell::new(Vec::new()),
             state: RefCell::new(SocketState::StateMax),
             result: RefCell::new(SocketResult::Success),
@@ -232,6 +232,10 @@ impl SocketMng {
         self.state().to_unit_active_state()
     }
 
+    pub(crate) fn control_pid(&self) -> Option<Pid> {
+        self.pid.control()
+    }
+
     #[allow(dead_code)]
     pub(self) fn clear_ports(&self) {
         self.ports.borrow_mut().clear();
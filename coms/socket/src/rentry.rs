@@ -13,14 +13,17 @@
 #![allow(non_snake_case)]
 use confique::Config;
 use macros::EnumDisplay;
+use nix::sys::resource::Resource;
 use nix::unistd::Pid;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::os::unix::prelude::RawFd;
 use std::rc::Rc;
-use sysmaster::exec::ExecCommand;
+use sysmaster::exec::{
+    deserialize_rlimit_sec, deserialize_rlimit_usec, rlimits_from, ExecCommand, Rlimit,
+};
 use sysmaster::rel::{ReDb, ReDbRoTxn, ReDbRwTxn, ReDbTable, Reliability};
-use sysmaster::serialize::DeserializeWith;
+use sysmaster::serialize::{deserialize_str_or_num, deserialize_str_or_value, DeserializeWith};
 use sysmaster::unit::KillMode;
 
 struct SocketReDb<K, V>(ReDb<K, V>);
@@ -57,23 +60,36 @@ pub(super) struct SectionSocket {
     #[config(deserialize_with = Vec::<String>::deserialize_with)]
     pub ListenSpecial: Option<Vec<String>>,
 
+    #[config(deserialize_with = deserialize_str_or_value)]
     #[config(default = false)]
     pub Accept: bool,
     pub Service: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     pub ReceiveBuffer: Option<u64>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     pub SendBuffer: Option<u64>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     pub PassCredentials: Option<bool>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     pub PassPacketInfo: Option<bool>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     pub KeepAlive: Option<bool>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     pub KeepAliveTimeSec: Option<u32>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     pub KeepAliveIntervalSec: Option<u32>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     pub KeepAliveProbes: Option<u32>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     pub Broadcast: Option<bool>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     #[config(default = false)]
     pub RemoveOnStop: bool,
     #[config(deserialize_with = Vec::<String>::deserialize_with)]
     pub Symlinks: Option<Vec<String>>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     pub PassSecurity: Option<bool>,
+    #[config(deserialize_with = deserialize_str_or_value)]
     pub SocketMode: Option<u32>,
 
     #[config(deserialize_with = KillMode::deserialize_with)]
@@ -81,6 +97,75 @@ pub(super) struct SectionSocket {
     pub KillMode: KillMode,
     #[config(default = "SIGTERM")]
     pub KillSignal: String,
+
+    #[config(deserialize_with = deserialize_rlimit_sec)]
+    pub LimitCPU: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitFSIZE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitDATA: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitSTACK: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitCORE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitRSS: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitNOFILE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitAS: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitNPROC: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitMEMLOCK: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitLOCKS: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitSIGPENDING: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitMSGQUEUE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitNICE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub LimitRTPRIO: Option<Rlimit>,
+    #[config(deserialize_with = deserialize_rlimit_usec)]
+    pub LimitRTTIME: Option<Rlimit>,
+
+    pub Slice: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    pub MemoryMax: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    pub MemoryHigh: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    pub CPUWeight: Option<u64>,
+    pub CPUQuota: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    pub TasksMax: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    pub IOWeight: Option<u64>,
+}
+
+impl SectionSocket {
+    pub(super) fn rlimits(&self) -> Vec<(Resource, Rlimit)> {
+        rlimits_from([
+            self.LimitCPU,
+            self.LimitFSIZE,
+            self.LimitDATA,
+            self.LimitSTACK,
+            self.LimitCORE,
+            self.LimitRSS,
+            self.LimitNOFILE,
+            self.LimitAS,
+            self.LimitNPROC,
+            self.LimitMEMLOCK,
+            self.LimitLOCKS,
+            self.LimitSIGPENDING,
+            self.LimitMSGQUEUE,
+            self.LimitNICE,
+            self.LimitRTPRIO,
+            self.LimitRTTIME,
+        ])
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -11,6 +11,7 @@
 // See the Mulan PSL v2 for more details.
 
 use crate::comm::SocketUnitComm;
+use crate::config::SocketConfig;
 use nix::unistd::Pid;
 use std::rc::Rc;
 use sysmaster::error::*;
@@ -18,19 +19,28 @@ use sysmaster::exec::{ExecCommand, ExecContext, ExecParameters};
 
 pub(super) struct SocketSpawn {
     comm: Rc<SocketUnitComm>,
+    config: Rc<SocketConfig>,
     exec_ctx: Rc<ExecContext>,
 }
 
 impl SocketSpawn {
-    pub(super) fn new(comm: &Rc<SocketUnitComm>, exec_ctx: &Rc<ExecContext>) -> SocketSpawn {
+    pub(super) fn new(
+        comm: &Rc<SocketUnitComm>,
+        config: &Rc<SocketConfig>,
+        exec_ctx: &Rc<ExecContext>,
+    ) -> SocketSpawn {
         SocketSpawn {
             comm: comm.clone(),
+            config: config.clone(),
             exec_ctx: exec_ctx.clone(),
         }
     }
 
     pub(super) fn start_socket(&self, cmdline: &ExecCommand) -> Result<Pid> {
-        let params = ExecParameters::new();
+        let mut params = ExecParameters::new();
+        for (resource, rlimit) in self.config.config_data().borrow().Socket.rlimits() {
+            params.add_rlimit(resource, rlimit);
+        }
 
         if let Some(unit) = self.comm.owner() {
             let um = self.comm.um();
@@ -22,11 +22,13 @@ use crate::{
 };
 use basic::logger;
 use nix::sys::wait::WaitStatus;
+use nix::unistd::Pid;
 use std::{path::PathBuf, rc::Rc};
 use sysmaster::error::*;
 use sysmaster::exec::ExecContext;
 use sysmaster::rel::{ReStation, Reliability};
-use sysmaster::unit::{SubUnit, UmIf, UnitActiveState, UnitBase, UnitMngUtil};
+use sysmaster::serialize::section_properties;
+use sysmaster::unit::{CgroupContext, SubUnit, UmIf, UnitActiveState, UnitBase, UnitMngUtil};
 
 // the structuer of the socket unit type
 struct SocketUnit {
@@ -114,6 +116,10 @@ impl SubUnit for SocketUnit {
         self.mng.sigchld_event(wait_status)
     }
 
+    fn control_pid(&self) -> Option<Pid> {
+        self.mng.control_pid()
+    }
+
     fn current_active_state(&self) -> UnitActiveState {
         self.mng.current_active_state()
     }
@@ -122,6 +128,10 @@ impl SubUnit for SocketUnit {
         self.mng.state().to_string()
     }
 
+    fn properties(&self) -> Vec<(String, String)> {
+        section_properties(&self.config.config_data().borrow().Socket)
+    }
+
     fn collect_fds(&self) -> Vec<i32> {
         self.mng.collect_fds()
     }
@@ -130,6 +140,10 @@ impl SubUnit for SocketUnit {
         self.comm.attach_unit(unit);
         self.db_insert();
     }
+
+    fn cgroup_context(&self) -> Option<Rc<CgroupContext>> {
+        Some(self.config.cgroup_context())
+    }
 }
 
 // attach the UnitManager for weak reference
@@ -0,0 +1,114 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+use basic::unit_name::{unit_name_from_path, unit_name_prefix, unit_name_unescape};
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+pub(super) const PLUGIN_NAME: &str = "SwapUnit";
+
+pub(super) const SWAPON_BIN: &str = "/usr/sbin/swapon";
+pub(super) const SWAPOFF_BIN: &str = "/usr/sbin/swapoff";
+
+pub(super) const PROC_SWAPS: &str = "/proc/swaps";
+
+pub(super) fn swap_what_to_unit_name(what: &str) -> String {
+    unit_name_from_path(what, ".swap")
+}
+
+/// the device or file of the swap unit which is not configured, like dev-sda2.swap => /dev/sda2
+pub(super) fn swap_unit_name_to_what(unit_name: &str) -> String {
+    "/".to_string() + &unit_name_unescape(unit_name_prefix(unit_name))
+}
+
+/// the swap devices or files may be referred by symlinks, like /dev/mapper/*,
+/// but /proc/swaps shows the resolved path, compare them after resolving.
+pub(super) fn swap_what_canonicalize(what: &str) -> String {
+    match Path::new(what).canonicalize() {
+        Ok(path) => path.to_string_lossy().to_string(),
+        Err(_) => what.to_string(),
+    }
+}
+
+/// unescape the octal escaped characters in /proc/swaps, like "\040" for space
+fn swap_filename_unescape(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 4 <= bytes.len() {
+            let oct = std::str::from_utf8(&bytes[i + 1..i + 4]).unwrap_or_default();
+            if let Ok(c) = u8::from_str_radix(oct, 8) {
+                result.push(c);
+                i += 4;
+                continue;
+            }
+        }
+        result.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&result).to_string()
+}
+
+/// parse the content of /proc/swaps, return the swap devices or files
+pub(super) fn swaps_parse(content: &str) -> Vec<String> {
+    // the first line is the header: Filename Type Size Used Priority
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().next())
+        .map(swap_filename_unescape)
+        .collect()
+}
+
+/// read the swap devices or files which are active now
+pub(super) fn swaps_read(file: Option<&mut File>) -> Vec<String> {
+    let mut content = String::new();
+    let ret = match file {
+        Some(f) => {
+            // reading from the beginning clears the pending event of the file
+            f.rewind().and_then(|_| f.read_to_string(&mut content))
+        }
+        None => File::open(PROC_SWAPS).and_then(|mut f| f.read_to_string(&mut content)),
+    };
+    if let Err(e) = ret {
+        log::error!("Failed to read {}: {}", PROC_SWAPS, e);
+        return Vec::new();
+    }
+    swaps_parse(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swaps_parse() {
+        let content = "Filename\t\t\t\tType\t\tSize\t\tUsed\t\tPriority\n\
+                       /dev/dm-1                               partition\t4194300\t\t0\t\t-2\n\
+                       /var/swap\\040file                       file\t\t1048572\t\t0\t\t-3\n";
+        assert_eq!(
+            swaps_parse(content),
+            vec!["/dev/dm-1".to_string(), "/var/swap file".to_string()]
+        );
+        assert!(swaps_parse("Filename Type Size Used Priority\n").is_empty());
+    }
+
+    #[test]
+    fn test_swap_unit_name() {
+        assert_eq!(swap_what_to_unit_name("/dev/sda2"), "dev-sda2.swap");
+        assert_eq!(swap_what_to_unit_name("/dev/dm-1"), "dev-dm\\x2d1.swap");
+        assert_eq!(swap_unit_name_to_what("dev-dm\\x2d1.swap"), "/dev/dm-1");
+        assert_eq!(swap_what_canonicalize("/no/such/swap"), "/no/such/swap");
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! swap_config mod load the conf file list and convert it to structure which is defined in this mod.
+//!
+#![allow(non_snake_case)]
+use super::base::{swap_unit_name_to_what, swap_what_to_unit_name};
+use super::comm::SwapUnitComm;
+use super::rentry::SectionSwap;
+use confique::Config;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use sysmaster::error::*;
+use sysmaster::rel::ReStation;
+
+#[derive(Config, Default, Debug)]
+pub(super) struct SwapConfigData {
+    #[config(nested)]
+    pub Swap: SectionSwap,
+}
+
+impl SwapConfigData {
+    pub(self) fn new(Swap: SectionSwap) -> SwapConfigData {
+        SwapConfigData { Swap }
+    }
+}
+
+pub(super) struct SwapConfig {
+    // associated objects
+    comm: Rc<SwapUnitComm>,
+
+    // owned objects
+    data: Rc<RefCell<SwapConfigData>>,
+    // the swap unit is generated from /proc/swaps, no configuration file
+    fragment: RefCell<bool>,
+}
+
+impl ReStation for SwapConfig {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, reload: bool) {
+        if reload {
+            return;
+        }
+        if let Some(conf) = self.comm.rentry_conf_get() {
+            if !conf.What.is_empty() {
+                *self.fragment.borrow_mut() = true;
+                self.comm.set_what(&conf.What);
+            }
+            self.data.replace(SwapConfigData::new(conf));
+        }
+    }
+
+    fn db_insert(&self) {
+        self.comm.rentry_conf_insert(&self.data.borrow().Swap);
+    }
+
+    // reload: no external connections, no entry
+}
+
+impl SwapConfig {
+    pub(super) fn new(commr: &Rc<SwapUnitComm>) -> Self {
+        SwapConfig {
+            comm: Rc::clone(commr),
+            data: Rc::new(RefCell::new(SwapConfigData::default())),
+            fragment: RefCell::new(false),
+        }
+    }
+
+    pub(super) fn load(&self, paths: Vec<PathBuf>, update: bool) -> Result<()> {
+        if paths.is_empty() {
+            // only monitored, nothing to load
+            return Ok(());
+        }
+
+        let mut builder = SwapConfigData::builder().env();
+        for v in paths {
+            builder = builder.file(v);
+        }
+        let data = builder.load().context(ConfiqueSnafu)?;
+        self.verify(&data.Swap)?;
+
+        self.comm.set_what(&data.Swap.What);
+        *self.data.borrow_mut() = data;
+        *self.fragment.borrow_mut() = true;
+
+        if update {
+            self.db_update();
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<SwapConfigData>> {
+        self.data.clone()
+    }
+
+    /// whether the unit is loaded from a configuration file
+    pub(super) fn has_fragment(&self) -> bool {
+        *self.fragment.borrow()
+    }
+
+    /// the swap device or file, which is derived from the unit name if not configured
+    pub(super) fn swap_what(&self) -> String {
+        if self.has_fragment() {
+            return self.data.borrow().Swap.What.clone();
+        }
+        swap_unit_name_to_what(&self.comm.get_owner_id())
+    }
+
+    fn verify(&self, swap: &SectionSwap) -> Result<()> {
+        if !swap.What.starts_with('/') {
+            return Err(format!("What={} is not an absolute path", swap.What).into());
+        }
+
+        let unit_name = swap_what_to_unit_name(&swap.What);
+        let owner_id = self.comm.get_owner_id();
+        if unit_name != owner_id {
+            return Err(format!(
+                "What={} doesn't match the unit name {}",
+                swap.What, owner_id
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! Swap is the entry for the swap management of sysmaster. sysmaster refers to systemd, but it is not the same.
+//! The active swap devices and files in /proc/swaps are monitored and mirrored into swap units.
+//! A swap unit can also be configured by a configuration file, and then sysmaster runs swapon/swapoff actively.
+//! The configuration file of swap unit is like this:
+//!
+//! ```toml
+//! [Swap]
+//! What="/dev/sda2"
+//! Priority=10
+//! Options="discard"
+//! TimeoutSec=90
+//! ```
+//!
+//! Swap unit supports the following options:
+//!
+//! | Option | Type | Description |
+//! |---|---|---|
+//! | What | String | the absolute path of the swap device or file, must match the unit name |
+//! | Priority | i32 | the priority of the swap, passed to swapon -p, optional |
+//! | Options | String | the swap options passed to swapon -o, split by ',' |
+//! | TimeoutSec | u64 | the timeout of the swapon/swapoff command, default is 90s |
+//!
+//! ## Automatic dependency
+//! NA
+//! ### Implicit dependency
+//! The configured swap unit on a device under /dev/ depends on the device unit with BindsTo and After.
+//! ### Default Dependency
+//! Conflicts and Before shutdown.target, Before swap.target.
+
+// dependency: swap_base -> swap_rentry -> swap_comm -> swap_config -> swap_spawn -> {swap_mng -> swap_unit} -> swap_manager
+mod base;
+mod comm;
+mod config;
+mod manager;
+mod mng;
+mod rentry;
+mod spawn;
+mod unit;
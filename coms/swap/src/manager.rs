@@ -0,0 +1,412 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+use super::base::{
+    swap_unit_name_to_what, swap_what_canonicalize, swap_what_to_unit_name, swaps_read,
+    PLUGIN_NAME, PROC_SWAPS,
+};
+use super::comm::SwapUmComm;
+use super::rentry::{SwapRe, SwapReFrame};
+use basic::{logger, IN_SET};
+use event::{EventState, EventType, Events, Source};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs::File;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::rc::Rc;
+use std::sync::Arc;
+use sysmaster::error::*;
+use sysmaster::rel::{ReStation, ReliLastFrame, Reliability};
+use sysmaster::unit::{UmIf, UnitActiveState, UnitManagerObj, UnitMngUtil, UnitType};
+
+struct SwapManager {
+    // owned objects
+    // data
+    comm: Arc<SwapUmComm>,
+    monitor: SwapMonitor,
+}
+
+impl ReStation for SwapManager {
+    // input
+    fn input_rebuild(&self) {
+        self.monitor.defer_enable(true);
+    }
+
+    // compensate
+    fn db_compensate_last(&self, _lframe: (u32, Option<u32>, Option<u32>), lunit: Option<&String>) {
+        assert!(lunit.is_some());
+
+        let frame = self.comm.rentry().last_frame();
+        if frame.is_none() {
+            // debug
+            return;
+        }
+
+        let unit_id = lunit.unwrap();
+        match frame.unwrap() {
+            SwapReFrame::Monitor => self.comm.um().rentry_trigger_merge(unit_id, true), // merge to trigger
+        }
+    }
+
+    fn do_compensate_last(&self, _lframe: (u32, Option<u32>, Option<u32>), lunit: Option<&String>) {
+        assert!(lunit.is_some());
+
+        let frame = self.comm.rentry().last_frame();
+        if frame.is_none() {
+            // debug
+            return;
+        }
+
+        let unit_id = lunit.unwrap();
+        match frame.unwrap() {
+            SwapReFrame::Monitor => self.comm.um().trigger_unit(unit_id), // re-run
+        }
+    }
+
+    // no data
+
+    // reload
+    fn register_ex(&self) {
+        self.monitor.register_ex();
+    }
+}
+
+impl UnitManagerObj for SwapManager {}
+
+// the declaration "pub(self)" is for identification only.
+impl SwapManager {
+    pub fn new() -> SwapManager {
+        let _comm = SwapUmComm::get_instance();
+        SwapManager {
+            comm: Arc::clone(&_comm),
+            monitor: SwapMonitor::new(&_comm),
+        }
+    }
+
+    fn register(&self) {
+        self.monitor.register();
+    }
+}
+
+struct SwapMonitor {
+    // owned objects
+    data: Rc<SwapMonitorData>,
+    defer: Rc<SwapMonitorDefer>,
+    io: Rc<SwapMonitorIo>,
+}
+
+// the declaration "pub(self)" is for identification only.
+impl SwapMonitor {
+    pub fn new(commr: &Arc<SwapUmComm>) -> SwapMonitor {
+        let _data = Rc::new(SwapMonitorData::new(commr, PROC_SWAPS.to_string()));
+        let _defer = Rc::new(SwapMonitorDefer::new(&_data));
+        SwapMonitor {
+            data: Rc::clone(&_data),
+            defer: Rc::clone(&_defer),
+            io: Rc::new(SwapMonitorIo::new(&_data, &_defer)),
+        }
+    }
+
+    pub fn register_ex(&self) {
+        let events = self.data.comm.um().events();
+
+        // io
+        let io = Rc::clone(&self.io);
+        events.add_source(io.clone()).unwrap();
+        events.set_enabled(io, EventState::On).unwrap();
+    }
+
+    pub fn defer_enable(&self, enable: bool) -> i32 {
+        self.io.defer_enable(enable)
+    }
+
+    fn register(&self) {
+        let events = self.data.comm.um().events();
+
+        // defer
+        let defer = Rc::clone(&self.defer);
+        events.add_source(defer).unwrap();
+    }
+}
+
+struct SwapMonitorIo {
+    // associated objects
+    data: Rc<SwapMonitorData>,
+    defer: Rc<SwapMonitorDefer>,
+}
+
+// the declaration "pub(self)" is for identification only.
+impl SwapMonitorIo {
+    pub fn new(datar: &Rc<SwapMonitorData>, deferr: &Rc<SwapMonitorDefer>) -> SwapMonitorIo {
+        SwapMonitorIo {
+            data: Rc::clone(datar),
+            defer: Rc::clone(deferr),
+        }
+    }
+
+    pub fn defer_enable(&self, enable: bool) -> i32 {
+        let source = Rc::clone(&self.defer);
+        let state = match enable {
+            true => EventState::OneShot,
+            false => EventState::Off,
+        };
+        let events = self.data.comm.um().events();
+        match events.set_enabled(source, state) {
+            Ok(_) => 0,
+            Err(_) => -1,
+        }
+    }
+
+    fn reli(&self) -> Rc<Reliability> {
+        self.data.comm.reli()
+    }
+
+    fn rentry(&self) -> Rc<SwapRe> {
+        self.data.comm.rentry()
+    }
+}
+
+impl Source for SwapMonitorIo {
+    fn event_type(&self) -> EventType {
+        EventType::Io
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn dispatch(&self, _e: &Events) -> i32 {
+        drain_out(self.data.epfd);
+
+        self.reli()
+            .set_last_frame2(ReliLastFrame::SubManager as u32, UnitType::UnitSwap as u32);
+        self.rentry().set_last_frame(SwapReFrame::Monitor);
+        let ret = self.data.dispatch_swaps();
+        self.rentry().clear_last_frame();
+        self.reli().clear_last_frame();
+
+        if let Err(e) = ret {
+            log::error!("Failed to dispatch {}, ignoring: {:?}", PROC_SWAPS, e);
+        }
+
+        self.defer_enable(false)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+
+    fn fd(&self) -> RawFd {
+        self.data.epfd
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+}
+
+struct SwapMonitorDefer {
+    // associated objects
+    data: Rc<SwapMonitorData>,
+}
+
+impl SwapMonitorDefer {
+    pub fn new(datar: &Rc<SwapMonitorData>) -> SwapMonitorDefer {
+        SwapMonitorDefer {
+            data: Rc::clone(datar),
+        }
+    }
+
+    fn reli(&self) -> Rc<Reliability> {
+        self.data.comm.reli()
+    }
+
+    fn rentry(&self) -> Rc<SwapRe> {
+        self.data.comm.rentry()
+    }
+}
+
+impl Source for SwapMonitorDefer {
+    fn event_type(&self) -> EventType {
+        EventType::Defer
+    }
+
+    fn epoll_event(&self) -> u32 {
+        0
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+
+    fn dispatch(&self, _event: &Events) -> i32 {
+        log::debug!("swap monitor dispatch");
+
+        self.reli()
+            .set_last_frame2(ReliLastFrame::SubManager as u32, UnitType::UnitSwap as u32);
+        self.rentry().set_last_frame(SwapReFrame::Monitor);
+        let ret = self.data.dispatch_swaps();
+        self.rentry().clear_last_frame();
+        self.reli().clear_last_frame();
+
+        match ret {
+            Ok(_) => 0,
+            Err(_) => -1,
+        }
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+}
+
+struct SwapMonitorData {
+    // associated objects
+    comm: Arc<SwapUmComm>,
+
+    // owned objects
+    file: RefCell<File>,
+    epfd: i32,
+}
+
+impl SwapMonitorData {
+    pub fn new(commr: &Arc<SwapUmComm>, filename: String) -> Self {
+        let sw_file = match File::open(&filename) {
+            Ok(sw_file) => sw_file,
+            Err(why) => {
+                panic!("Failed to open {}: {}", &filename, why);
+            }
+        };
+
+        // the kernel notifies the changes of /proc/swaps by POLLPRI
+        let sw_fd = sw_file.as_raw_fd();
+        let sw_epfd = epoll::create(false).unwrap();
+        let event = epoll::Events::EPOLLET | epoll::Events::EPOLLPRI;
+        let sw_epevent = epoll::Event::new(event, sw_fd as u64);
+        let op = epoll::ControlOptions::EPOLL_CTL_ADD;
+        drain_out(sw_epfd);
+        epoll::ctl(sw_epfd, op, sw_fd, sw_epevent).unwrap();
+
+        log::debug!("Monitoring {} by epool fd {}", filename, sw_epfd);
+        SwapMonitorData {
+            comm: Arc::clone(commr),
+            file: RefCell::new(sw_file),
+            epfd: sw_epfd,
+        }
+    }
+
+    pub fn dispatch_swaps(&self) -> Result<()> {
+        let um = self.comm.um();
+        let swaps: HashSet<String> = swaps_read(Some(&mut *self.file.borrow_mut()))
+            .iter()
+            .map(|s| swap_what_canonicalize(s))
+            .collect();
+
+        // First sync the state of the swap units we know.
+        let mut known_swaps: HashSet<String> = HashSet::new();
+        let mut dead_swaps: Vec<String> = Vec::new();
+        let mut new_swaps: Vec<String> = Vec::new();
+        for unit in um.units_get_all(Some(UnitType::UnitSwap)).iter() {
+            let what = self
+                .comm
+                .unit_what(unit)
+                .unwrap_or_else(|| swap_unit_name_to_what(unit));
+            let what = swap_what_canonicalize(&what);
+            let active = swaps.contains(&what);
+            if active {
+                known_swaps.insert(what);
+            }
+
+            // The configured swap unit may be activating or deactivating
+            // by itself, the control process will reconcile its state.
+            match um.current_active_state(unit) {
+                UnitActiveState::UnitActive if !active => dead_swaps.push(unit.to_string()),
+                UnitActiveState::UnitInActive | UnitActiveState::UnitFailed if active => {
+                    new_swaps.push(unit.to_string())
+                }
+                _ => {}
+            }
+        }
+
+        // Then add the swaps we don't know.
+        for what in swaps.iter().filter(|s| !known_swaps.contains(*s)) {
+            let unit_name = swap_what_to_unit_name(what);
+            if !um.load_unit_success(&unit_name) {
+                log::error!("Failed to load the swap unit {}", unit_name);
+                continue;
+            }
+            if IN_SET!(
+                um.current_active_state(&unit_name),
+                UnitActiveState::UnitInActive,
+                UnitActiveState::UnitFailed
+            ) {
+                new_swaps.push(unit_name);
+            }
+        }
+
+        for unit_name in new_swaps.iter() {
+            // record + action
+            self.comm.reli().set_last_unit(unit_name);
+            let ret = um.unit_start_directly(unit_name);
+            self.comm.reli().clear_last_unit();
+
+            if ret.is_ok() {
+                log::debug!("{} change to active.", unit_name);
+            } else {
+                log::error!("Failed to start {}", unit_name);
+            }
+        }
+
+        // Finally stop the swaps that disappeared.
+        for unit_name in dead_swaps.iter() {
+            // record + action
+            self.comm.reli().set_last_unit(unit_name);
+            let ret = um.unit_stop(unit_name, false);
+            self.comm.reli().clear_last_unit();
+
+            if ret.is_ok() {
+                log::debug!("{} change to dead.", unit_name);
+            } else {
+                log::error!("Failed to stop {}.", unit_name);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn drain_out(epfd: i32) {
+    // drain out all events.
+    let mut sw_events: Vec<epoll::Event> = vec![epoll::Event::new(epoll::Events::empty(), 0)];
+    while epoll::wait(epfd, 0, &mut sw_events).unwrap() > 0 {}
+}
+
+impl UnitMngUtil for SwapManager {
+    fn attach_um(&self, um: Rc<dyn UmIf>) {
+        self.comm.attach_um(um);
+        self.register();
+    }
+
+    fn attach_reli(&self, reli: Rc<Reliability>) {
+        self.comm.attach_reli(reli);
+    }
+}
+
+impl Default for SwapManager {
+    fn default() -> Self {
+        SwapManager::new()
+    }
+}
+
+use sysmaster::declure_umobj_plugin;
+declure_umobj_plugin!(SwapManager, SwapManager::default, PLUGIN_NAME);
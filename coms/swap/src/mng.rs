@@ -0,0 +1,553 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//!  The core logic of the swap subclass
+use super::base::{swap_what_canonicalize, swaps_read, SWAPOFF_BIN, SWAPON_BIN};
+use super::comm::SwapUnitComm;
+use super::config::SwapConfig;
+use super::rentry::{SwapResult, SwapState};
+use super::spawn::SwapSpawn;
+use basic::process_util;
+use event::{EventState, EventType, Events, Source};
+use nix::sys::signal::Signal;
+use nix::sys::wait::WaitStatus;
+use nix::unistd::Pid;
+use std::cell::RefCell;
+use std::os::unix::prelude::RawFd;
+use std::rc::{Rc, Weak};
+use sysmaster::error::*;
+use sysmaster::exec::{ExecCommand, ExecContext};
+use sysmaster::rel::ReStation;
+use sysmaster::unit::{UnitActiveState, UnitNotifyFlags};
+
+impl SwapState {
+    fn swap_state_to_unit_state(&self) -> UnitActiveState {
+        match *self {
+            SwapState::Dead => UnitActiveState::UnitInActive,
+            SwapState::Activating => UnitActiveState::UnitActivating,
+            SwapState::Active => UnitActiveState::UnitActive,
+            SwapState::Deactivating => UnitActiveState::UnitDeActivating,
+            SwapState::Failed => UnitActiveState::UnitFailed,
+        }
+    }
+
+    fn with_control_process(&self) -> bool {
+        matches!(*self, SwapState::Activating | SwapState::Deactivating)
+    }
+}
+
+pub(super) struct SwapMng {
+    // associated objects
+    comm: Rc<SwapUnitComm>,
+    config: Rc<SwapConfig>,
+
+    // owned objects
+    spawn: SwapSpawn,
+    state: RefCell<SwapState>,
+    result: RefCell<SwapResult>,
+    control_pid: RefCell<Option<Pid>>,
+    timer: Rc<SwapTimer>,
+    timer_armed: RefCell<bool>,
+}
+
+impl ReStation for SwapMng {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, _reload: bool) {
+        if let Some((state, result, control_pid)) = self.comm.rentry_mng_get() {
+            *self.state.borrow_mut() = state;
+            *self.result.borrow_mut() = result;
+            *self.control_pid.borrow_mut() = control_pid;
+        }
+    }
+
+    fn db_insert(&self) {
+        self.comm
+            .rentry_mng_insert(self.state(), self.result(), self.control_pid());
+    }
+
+    // reload: entry-only
+    fn entry_coldplug(&self) {
+        if self.state().with_control_process() {
+            if let Err(e) = self.enable_timer(self.timeout_usec()) {
+                log::error!("Failed to rearm the timer of {}: {}", self.id(), e);
+            }
+        }
+    }
+
+    fn entry_clear(&self) {
+        self.delete_timer();
+    }
+}
+
+impl SwapMng {
+    pub(super) fn new(
+        commr: &Rc<SwapUnitComm>,
+        configr: &Rc<SwapConfig>,
+        exec_ctx: &Rc<ExecContext>,
+    ) -> Self {
+        SwapMng {
+            comm: Rc::clone(commr),
+            config: Rc::clone(configr),
+            spawn: SwapSpawn::new(commr, exec_ctx),
+            state: RefCell::new(SwapState::Dead),
+            result: RefCell::new(SwapResult::Success),
+            control_pid: RefCell::new(None),
+            timer: Rc::new(SwapTimer::new(0)),
+            timer_armed: RefCell::new(false),
+        }
+    }
+
+    pub(super) fn attach_timer(&self, mng: Weak<SwapMng>) {
+        self.timer.attach_mng(mng);
+    }
+
+    pub(super) fn start_check(&self) -> Result<bool> {
+        if self.state() == SwapState::Deactivating {
+            return Err(Error::UnitActionEAgain);
+        }
+
+        if self.state() == SwapState::Activating {
+            return Ok(true);
+        }
+
+        let ret = self.comm.owner().map_or(false, |u| u.test_start_limit());
+        if !ret {
+            self.enter_dead(SwapResult::FailureStartLimitHit);
+            return Err(Error::UnitActionECanceled);
+        }
+
+        Ok(false)
+    }
+
+    pub(super) fn start_action(&self) {
+        /* make sure the former failure doesn't disturb later action. */
+        self.set_result(SwapResult::Success);
+
+        // The swap unit without configuration is started by the monitor,
+        // which means the swap is already active.
+        if !self.config.has_fragment() || self.is_active() {
+            self.enter_active(SwapResult::Success);
+            return;
+        }
+
+        self.enter_activating();
+    }
+
+    pub(super) fn stop_check(&self) -> Result<bool> {
+        if self.state() == SwapState::Deactivating {
+            return Ok(true);
+        }
+
+        if self.state() == SwapState::Activating {
+            // the result is reconciled when the control process exits
+            self.kill_control_process();
+            return Err(Error::UnitActionEAgain);
+        }
+
+        Ok(false)
+    }
+
+    pub(super) fn stop_action(&self) {
+        // Nothing to swapoff: the swap is gone already.
+        if !self.is_active() {
+            self.enter_dead(SwapResult::Success);
+            return;
+        }
+
+        self.enter_deactivating();
+    }
+
+    fn enter_activating(&self) {
+        let argv = {
+            let config_data = self.config.config_data();
+            let config = config_data.borrow();
+            let swap = &config.Swap;
+
+            let mut argv = Vec::new();
+            if let Some(priority) = swap.Priority {
+                argv.push("-p".to_string());
+                argv.push(priority.to_string());
+            }
+            if !swap.Options.is_empty() {
+                argv.push("-o".to_string());
+                argv.push(swap.Options.clone());
+            }
+            argv.push(swap.What.clone());
+            argv
+        };
+
+        let cmd = ExecCommand::new(SWAPON_BIN.to_string(), argv);
+        match self.spawn_control(&cmd) {
+            Ok(_) => self.set_state(SwapState::Activating),
+            Err(e) => {
+                log::error!("Failed to run 'swapon' task for {}: {}", self.id(), e);
+                self.enter_dead(SwapResult::FailureResources);
+            }
+        }
+    }
+
+    fn enter_deactivating(&self) {
+        let argv = vec![self.config.swap_what()];
+        let cmd = ExecCommand::new(SWAPOFF_BIN.to_string(), argv);
+        match self.spawn_control(&cmd) {
+            Ok(_) => self.set_state(SwapState::Deactivating),
+            Err(e) => {
+                log::error!("Failed to run 'swapoff' task for {}: {}", self.id(), e);
+                self.enter_active(SwapResult::FailureResources);
+            }
+        }
+    }
+
+    pub(super) fn enter_dead(&self, res: SwapResult) {
+        if self.result() == SwapResult::Success {
+            self.set_result(res);
+        }
+
+        let state = if self.result() == SwapResult::Success {
+            SwapState::Dead
+        } else {
+            SwapState::Failed
+        };
+        self.set_state(state);
+    }
+
+    pub(super) fn enter_active(&self, res: SwapResult) {
+        if self.result() == SwapResult::Success {
+            self.set_result(res);
+        }
+
+        self.set_state(SwapState::Active);
+    }
+
+    fn spawn_control(&self, cmd: &ExecCommand) -> Result<()> {
+        self.unwatch_control();
+        let pid = self.spawn.spawn_cmd(cmd)?;
+        *self.control_pid.borrow_mut() = Some(pid);
+        self.enable_timer(self.timeout_usec())?;
+        Ok(())
+    }
+
+    fn sigchld_result(&self, wait_status: WaitStatus) -> SwapResult {
+        match wait_status {
+            WaitStatus::Exited(_, status) => {
+                if status == 0 {
+                    SwapResult::Success
+                } else {
+                    SwapResult::FailureExitCode
+                }
+            }
+            WaitStatus::Signaled(_, _, core_dump) => {
+                if core_dump {
+                    SwapResult::FailureCoreDump
+                } else {
+                    SwapResult::FailureSignal
+                }
+            }
+            _ => SwapResult::Success,
+        }
+    }
+
+    pub(super) fn sigchld_event(&self, wait_status: WaitStatus) {
+        if wait_status.pid() != self.control_pid() {
+            return;
+        }
+        *self.control_pid.borrow_mut() = None;
+
+        let res = self.sigchld_result(wait_status);
+        log::debug!(
+            "Control process of {} exited in state {:?}, result: {:?}",
+            self.id(),
+            self.state(),
+            res
+        );
+
+        // the state in /proc/swaps is the final judgement.
+        match self.state() {
+            SwapState::Activating => {
+                if self.is_active() {
+                    self.enter_active(res);
+                } else if res == SwapResult::Success {
+                    log::error!(
+                        "Swapon process of {} finished, but there is no swap.",
+                        self.id()
+                    );
+                    self.enter_dead(SwapResult::FailureProtocol);
+                } else {
+                    self.enter_dead(res);
+                }
+            }
+            SwapState::Deactivating => {
+                if self.is_active() {
+                    log::error!("Failed to swapoff {}, it is still active.", self.id());
+                    self.enter_active(res);
+                } else {
+                    self.enter_dead(res);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn dispatch_timer(&self) {
+        log::warn!("{} operation of {} timed out.", self.state(), self.id());
+        if self.state().with_control_process() {
+            self.set_result(SwapResult::FailureTimeout);
+            self.kill_control_process();
+        }
+    }
+
+    fn kill_control_process(&self) {
+        if let Some(pid) = self.control_pid() {
+            if let Err(e) = process_util::kill_and_cont(pid, Signal::SIGKILL) {
+                log::warn!("Failed to kill control process {}: {}", pid, e);
+            }
+        }
+    }
+
+    fn unwatch_control(&self) {
+        if let Some(pid) = self.control_pid.take() {
+            if let Some(u) = self.comm.owner() {
+                self.comm.um().child_unwatch_pid(u.id(), pid)
+            }
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        swap_is_active(&self.config.swap_what())
+    }
+
+    fn timeout_usec(&self) -> u64 {
+        self.config.config_data().borrow().Swap.TimeoutSec
+    }
+
+    fn enable_timer(&self, usec: u64) -> Result<()> {
+        let events = self.comm.um().events();
+        if usec == 0 || usec == u64::MAX {
+            self.delete_timer();
+            return Ok(());
+        }
+
+        let timer = Rc::clone(&self.timer);
+        if *self.timer_armed.borrow() {
+            events.set_enabled(timer.clone(), EventState::Off)?;
+        } else {
+            events.add_source(timer.clone())?;
+            *self.timer_armed.borrow_mut() = true;
+        }
+        timer.set_time(usec);
+        events.set_enabled(timer, EventState::OneShot)?;
+        Ok(())
+    }
+
+    fn delete_timer(&self) {
+        if !*self.timer_armed.borrow() {
+            return;
+        }
+
+        let events = self.comm.um().events();
+        let timer = Rc::clone(&self.timer);
+        events.set_enabled(timer.clone(), EventState::Off).unwrap();
+        events.del_source(timer).unwrap();
+        *self.timer_armed.borrow_mut() = false;
+    }
+
+    pub fn get_state(&self) -> String {
+        let state = *self.state.borrow();
+        state.to_string()
+    }
+
+    fn set_state(&self, new_state: SwapState) {
+        let old_state = self.state();
+        self.change_state(new_state);
+
+        if !new_state.with_control_process() {
+            self.delete_timer();
+            self.unwatch_control();
+        }
+
+        if new_state != old_state {
+            log::debug!(
+                "{} original state[{:?}] -> new state[{:?}]",
+                self.comm.get_owner_id(),
+                old_state,
+                new_state,
+            );
+        }
+
+        if let Some(u) = self.comm.owner() {
+            u.notify(
+                old_state.swap_state_to_unit_state(),
+                new_state.swap_state_to_unit_state(),
+                UnitNotifyFlags::UNIT_NOTIFY_SUCCESS,
+            );
+            self.db_update();
+        }
+    }
+
+    fn change_state(&self, new_state: SwapState) {
+        self.state.replace(new_state);
+    }
+
+    fn state(&self) -> SwapState {
+        *self.state.borrow()
+    }
+
+    fn result(&self) -> SwapResult {
+        *self.result.borrow()
+    }
+
+    fn set_result(&self, res: SwapResult) {
+        *self.result.borrow_mut() = res;
+    }
+
+    pub(super) fn control_pid(&self) -> Option<Pid> {
+        *self.control_pid.borrow()
+    }
+
+    fn id(&self) -> String {
+        self.comm.get_owner_id()
+    }
+
+    pub(super) fn swap_state_to_unit_state(&self) -> UnitActiveState {
+        self.state().swap_state_to_unit_state()
+    }
+}
+
+fn swap_is_active(what: &str) -> bool {
+    if what.is_empty() {
+        return false;
+    }
+
+    let what = swap_what_canonicalize(what);
+    swaps_read(None)
+        .iter()
+        .any(|s| swap_what_canonicalize(s) == what)
+}
+
+pub(super) struct SwapTimer {
+    time: RefCell<u64>,
+    mng: RefCell<Weak<SwapMng>>,
+}
+
+impl SwapTimer {
+    pub fn new(usec: u64) -> Self {
+        SwapTimer {
+            time: RefCell::new(usec),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    pub(super) fn attach_mng(&self, mng: Weak<SwapMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    pub(super) fn set_time(&self, usec: u64) {
+        *self.time.borrow_mut() = usec
+    }
+
+    pub(self) fn mng(&self) -> Rc<SwapMng> {
+        self.mng.borrow().clone().upgrade().unwrap()
+    }
+}
+
+impl Source for SwapTimer {
+    fn fd(&self) -> RawFd {
+        0
+    }
+
+    fn event_type(&self) -> EventType {
+        EventType::TimerMonotonic
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn time_relative(&self) -> u64 {
+        *self.time.borrow()
+    }
+
+    fn dispatch(&self, _: &Events) -> i32 {
+        self.mng().dispatch_timer();
+        0
+    }
+
+    fn priority(&self) -> i8 {
+        0
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SwapMng;
+    use super::SwapResult;
+    use super::SwapState;
+    use super::SwapUnitComm;
+    use crate::config::SwapConfig;
+    use std::rc::Rc;
+    use sysmaster::exec::ExecContext;
+    use sysmaster::unit::UnitActiveState;
+
+    fn new_mng() -> SwapMng {
+        let _comm = Rc::new(SwapUnitComm::new());
+        let _config = Rc::new(SwapConfig::new(&_comm));
+        let _ctx = Rc::new(ExecContext::new());
+        SwapMng::new(&_comm, &_config, &_ctx)
+    }
+
+    #[test]
+    fn test_swap_enter_dead() {
+        let tm = new_mng();
+        tm.enter_dead(SwapResult::Success);
+        assert_eq!(tm.state(), SwapState::Dead);
+
+        tm.enter_dead(SwapResult::FailureExitCode);
+        assert_eq!(tm.state(), SwapState::Failed);
+    }
+
+    #[test]
+    fn test_swap_enter_active() {
+        let tm = new_mng();
+        tm.enter_active(SwapResult::Success);
+        assert_eq!(tm.state(), SwapState::Active);
+        assert_eq!(tm.swap_state_to_unit_state(), UnitActiveState::UnitActive);
+    }
+
+    #[test]
+    fn test_swap_active_state() {
+        assert_eq!(
+            SwapState::Activating.swap_state_to_unit_state(),
+            UnitActiveState::UnitActivating
+        );
+        assert_eq!(
+            SwapState::Deactivating.swap_state_to_unit_state(),
+            UnitActiveState::UnitDeActivating
+        );
+        assert_eq!(
+            SwapState::Failed.swap_state_to_unit_state(),
+            UnitActiveState::UnitFailed
+        );
+    }
+
+    #[test]
+    fn test_swap_is_active() {
+        assert!(!super::swap_is_active(""));
+        assert!(!super::swap_is_active("/no/such/swap"));
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+#![allow(non_snake_case)]
+use basic::time_util::USEC_PER_SEC;
+use confique::Config;
+use macros::EnumDisplay;
+use nix::unistd::Pid;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::rc::Rc;
+use sysmaster::rel::{ReDb, ReDbRoTxn, ReDbRwTxn, ReDbTable, Reliability};
+use sysmaster::serialize::deserialize_str_or_value;
+
+const RELI_DB_HSWAP_CONF: &str = "swapconf";
+const RELI_DB_HSWAP_MNG: &str = "swapmng";
+const RELI_DB_HSWAPM_FRAME: &str = "swapm-frame";
+const RELI_LAST_KEY: u32 = 0; // singleton
+
+fn deserialize_time<'de, D>(de: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let timeout: u64 = deserialize_str_or_value(de)?;
+    if timeout >= u64::MAX / USEC_PER_SEC {
+        return Ok(u64::MAX);
+    }
+
+    Ok(timeout * USEC_PER_SEC)
+}
+
+#[derive(Config, Default, Clone, Debug, Serialize, Deserialize)]
+pub(super) struct SectionSwap {
+    #[config(default = "")]
+    pub What: String,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    pub Priority: Option<i32>,
+    #[config(default = "")]
+    pub Options: String,
+    #[config(deserialize_with = deserialize_time)]
+    #[config(default = 90)]
+    pub TimeoutSec: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SwapReConf {
+    swap: SectionSwap,
+}
+
+impl SwapReConf {
+    fn new(swapr: &SectionSwap) -> SwapReConf {
+        SwapReConf {
+            swap: swapr.clone(),
+        }
+    }
+}
+
+// Dead and Active are the stable states, the corresponding unit status is inactive and active.
+// Activating and Deactivating mean that a swapon/swapoff process is running.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Serialize, Deserialize, EnumDisplay)]
+pub(super) enum SwapState {
+    Dead,
+    Activating,
+    Active,
+    Deactivating,
+    Failed,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub(super) enum SwapResult {
+    Success,
+    FailureResources,
+    FailureTimeout,
+    FailureExitCode,
+    FailureSignal,
+    FailureCoreDump,
+    FailureStartLimitHit,
+    FailureProtocol,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SwapReMng {
+    state: SwapState,
+    result: SwapResult,
+    control_pid: Option<i32>, // i32 ==> nix::unistd::Pid ==> libc::pid_t
+}
+
+impl SwapReMng {
+    fn new(state: SwapState, result: SwapResult, control_pid: Option<i32>) -> SwapReMng {
+        SwapReMng {
+            state,
+            result,
+            control_pid,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub(super) enum SwapReFrame {
+    Monitor,
+}
+
+struct SwapReDb<K, V>(ReDb<K, V>);
+
+pub(super) struct SwapRe {
+    // database: multi-instance(N)
+    conf: Rc<SwapReDb<String, SwapReConf>>, // RELI_DB_HSWAP_CONF; key: unit_id, data: config;
+    mng: Rc<SwapReDb<String, SwapReMng>>, // RELI_DB_HSWAP_MNG; key: unit_id, data: state+result+control_pid;
+
+    // database: singleton(1)
+    frame: Rc<SwapReDb<u32, SwapReFrame>>, // RELI_DB_HSWAPM_FRAME; key: RELI_LAST_KEY, data: SwapReFrame;
+}
+
+impl SwapRe {
+    pub(super) fn new(relir: &Reliability) -> SwapRe {
+        let conf = Rc::new(SwapReDb(ReDb::new(relir, RELI_DB_HSWAP_CONF)));
+        let mng = Rc::new(SwapReDb(ReDb::new(relir, RELI_DB_HSWAP_MNG)));
+        let frame = Rc::new(SwapReDb(ReDb::new(relir, RELI_DB_HSWAPM_FRAME)));
+        let rentry = SwapRe { conf, mng, frame };
+        rentry.register(relir);
+        rentry
+    }
+
+    pub(super) fn conf_insert(&self, unit_id: &str, swap: &SectionSwap) {
+        let conf = SwapReConf::new(swap);
+        self.conf.0.insert(unit_id.to_string(), conf);
+    }
+
+    pub(super) fn conf_get(&self, unit_id: &String) -> Option<SectionSwap> {
+        let conf = self.conf.0.get(unit_id);
+        conf.map(|c| c.swap)
+    }
+
+    pub(super) fn mng_insert(
+        &self,
+        unit_id: &str,
+        state: SwapState,
+        result: SwapResult,
+        control_pid: Option<Pid>,
+    ) {
+        let c_pid = control_pid.map(|x| x.as_raw());
+        let mng = SwapReMng::new(state, result, c_pid);
+        self.mng.0.insert(unit_id.to_string(), mng);
+    }
+
+    pub(super) fn mng_get(&self, unit_id: &String) -> Option<(SwapState, SwapResult, Option<Pid>)> {
+        let mng = self.mng.0.get(unit_id);
+        mng.map(|m| (m.state, m.result, m.control_pid.map(Pid::from_raw)))
+    }
+
+    pub(super) fn set_last_frame(&self, frame: SwapReFrame) {
+        self.frame.0.insert(RELI_LAST_KEY, frame);
+    }
+
+    pub(super) fn clear_last_frame(&self) {
+        self.frame.0.remove(&RELI_LAST_KEY);
+    }
+
+    pub(super) fn last_frame(&self) -> Option<SwapReFrame> {
+        self.frame.0.get(&RELI_LAST_KEY)
+    }
+
+    fn register(&self, relir: &Reliability) {
+        // rel-db: RELI_DB_HSWAP_CONF
+        let db = Rc::clone(&self.conf);
+        relir.history_db_register(RELI_DB_HSWAP_CONF, db);
+
+        // rel-db: RELI_DB_HSWAP_MNG
+        let db = Rc::clone(&self.mng);
+        relir.history_db_register(RELI_DB_HSWAP_MNG, db);
+
+        // rel-db: RELI_DB_HSWAPM_FRAME
+        let db = Rc::clone(&self.frame);
+        relir.history_db_register(RELI_DB_HSWAPM_FRAME, db);
+    }
+}
+
+impl ReDbTable for SwapReDb<String, SwapReConf> {
+    fn clear(&self, wtxn: &mut ReDbRwTxn) {
+        self.0.do_clear(wtxn);
+    }
+
+    fn export(&self, db_wtxn: &mut ReDbRwTxn) {
+        self.0.cache_2_db(db_wtxn);
+    }
+
+    fn import<'a>(&self, db_rtxn: &ReDbRoTxn) {
+        self.0.db_2_cache(db_rtxn);
+    }
+
+    fn ignore_set(&self, ignore: bool) {
+        self.0.set_ignore(ignore);
+    }
+}
+
+impl ReDbTable for SwapReDb<String, SwapReMng> {
+    fn clear(&self, wtxn: &mut ReDbRwTxn) {
+        self.0.do_clear(wtxn);
+    }
+
+    fn export(&self, db_wtxn: &mut ReDbRwTxn) {
+        self.0.cache_2_db(db_wtxn);
+    }
+
+    fn import<'a>(&self, db_rtxn: &ReDbRoTxn) {
+        self.0.db_2_cache(db_rtxn);
+    }
+
+    fn ignore_set(&self, ignore: bool) {
+        self.0.set_ignore(ignore);
+    }
+}
+
+impl ReDbTable for SwapReDb<u32, SwapReFrame> {
+    fn clear(&self, wtxn: &mut ReDbRwTxn) {
+        self.0.do_clear(wtxn);
+    }
+
+    fn export(&self, db_wtxn: &mut ReDbRwTxn) {
+        self.0.cache_2_db(db_wtxn);
+    }
+
+    fn import<'a>(&self, db_rtxn: &ReDbRoTxn) {
+        self.0.db_2_cache(db_rtxn);
+    }
+
+    fn ignore_set(&self, ignore: bool) {
+        self.0.set_ignore(ignore);
+    }
+}
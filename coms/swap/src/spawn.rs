@@ -0,0 +1,54 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! swap_spawn runs the swapon/swapoff command in a forked child process.
+//!
+use crate::comm::SwapUnitComm;
+use nix::unistd::Pid;
+use std::rc::Rc;
+use sysmaster::error::*;
+use sysmaster::exec::{ExecCommand, ExecContext, ExecParameters};
+
+pub(super) struct SwapSpawn {
+    comm: Rc<SwapUnitComm>,
+    exec_ctx: Rc<ExecContext>,
+}
+
+impl SwapSpawn {
+    pub(super) fn new(comm: &Rc<SwapUnitComm>, exec_ctx: &Rc<ExecContext>) -> SwapSpawn {
+        SwapSpawn {
+            comm: comm.clone(),
+            exec_ctx: exec_ctx.clone(),
+        }
+    }
+
+    pub(super) fn spawn_cmd(&self, cmdline: &ExecCommand) -> Result<Pid> {
+        let params = ExecParameters::new();
+
+        if let Some(unit) = self.comm.owner() {
+            let um = self.comm.um();
+            unit.prepare_exec()?;
+            match um.exec_spawn(unit.id(), cmdline, &params, self.exec_ctx.clone()) {
+                Ok(pid) => {
+                    um.child_watch_pid(unit.id(), pid);
+                    Ok(pid)
+                }
+                Err(_e) => {
+                    log::error!("failed to spawn {} for {}", cmdline.path(), unit.id());
+                    Err("spawn exec return error".to_string().into())
+                }
+            }
+        } else {
+            Err("spawn exec return error".to_string().into())
+        }
+    }
+}
@@ -0,0 +1,216 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! swap unit is entry of swap type of unit，need impl
+//! UnitObj,UnitMngUtil, UnitSubClass trait
+
+use super::base::PLUGIN_NAME;
+use super::comm::SwapUnitComm;
+use super::config::SwapConfig;
+use super::mng::SwapMng;
+use basic::logger;
+use basic::special::{SHUTDOWN_TARGET, SWAP_TARGET};
+use basic::unit_name::unit_name_from_path;
+use nix::sys::wait::WaitStatus;
+use nix::unistd::Pid;
+use std::path::PathBuf;
+use std::rc::Rc;
+use sysmaster::error::*;
+use sysmaster::exec::ExecContext;
+use sysmaster::rel::{ReStation, Reliability};
+use sysmaster::serialize::section_properties;
+use sysmaster::unit::{
+    SubUnit, UmIf, UnitActiveState, UnitBase, UnitDependencyMask, UnitMngUtil, UnitRelations,
+};
+
+struct SwapUnit {
+    comm: Rc<SwapUnitComm>,
+    config: Rc<SwapConfig>,
+    mng: Rc<SwapMng>,
+}
+
+impl ReStation for SwapUnit {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, reload: bool) {
+        self.config.db_map(reload);
+        self.mng.db_map(reload);
+    }
+
+    fn db_insert(&self) {
+        self.config.db_insert();
+        self.mng.db_insert();
+    }
+
+    // reload: entry-only
+    fn entry_coldplug(&self) {
+        // rebuild external connections, like: timer, ...
+        self.mng.entry_coldplug();
+    }
+
+    fn entry_clear(&self) {
+        // release external connection, like: timer, ...
+        self.mng.entry_clear();
+    }
+}
+
+impl SwapUnit {
+    fn new(_um: Rc<dyn UmIf>) -> SwapUnit {
+        let context = Rc::new(ExecContext::new());
+        let _comm = Rc::new(SwapUnitComm::new());
+        let _config = Rc::new(SwapConfig::new(&_comm));
+        let _mng = Rc::new(SwapMng::new(&_comm, &_config, &context));
+        _mng.attach_timer(Rc::downgrade(&_mng));
+        SwapUnit {
+            comm: Rc::clone(&_comm),
+            config: Rc::clone(&_config),
+            mng: _mng,
+        }
+    }
+
+    fn add_extras(&self) -> Result<()> {
+        let u = match self.comm.owner() {
+            None => return Ok(()),
+            Some(u) => u,
+        };
+
+        // the configured swap on a block device can't be activated before the
+        // device appears, and has to be stopped when the device goes away.
+        let what = self.config.swap_what();
+        if self.config.has_fragment() && what.starts_with("/dev/") {
+            let device = unit_name_from_path(&what, ".device");
+            self.comm.um().unit_add_two_dependency(
+                &u.id(),
+                UnitRelations::UnitBindsTo,
+                UnitRelations::UnitAfter,
+                &device,
+                true,
+                UnitDependencyMask::Implicit,
+            )?;
+        }
+
+        if !u.default_dependencies() {
+            return Ok(());
+        }
+
+        log::debug!("Adding default dependencies for swap: {}", u.id());
+        let um = self.comm.um();
+        um.unit_add_two_dependency(
+            &u.id(),
+            UnitRelations::UnitBefore,
+            UnitRelations::UnitConflicts,
+            SHUTDOWN_TARGET,
+            true,
+            UnitDependencyMask::Default,
+        )?;
+        um.unit_add_dependency(
+            &u.id(),
+            UnitRelations::UnitBefore,
+            SWAP_TARGET,
+            true,
+            UnitDependencyMask::Default,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl SubUnit for SwapUnit {
+    fn load(&self, paths: Vec<PathBuf>) -> Result<()> {
+        self.config.load(paths, true)?;
+
+        // the swaps that only monitored are not affected by isolate
+        if !self.config.has_fragment() {
+            if let Some(u) = self.comm.owner() {
+                u.set_ignore_on_isolate(true)
+            }
+        }
+
+        self.add_extras()
+    }
+
+    fn current_active_state(&self) -> UnitActiveState {
+        self.mng.swap_state_to_unit_state()
+    }
+
+    fn get_subunit_state(&self) -> String {
+        self.mng.get_state()
+    }
+
+    fn properties(&self) -> Vec<(String, String)> {
+        section_properties(&self.config.config_data().borrow().Swap)
+    }
+
+    fn attach_unit(&self, unit: Rc<dyn UnitBase>) {
+        self.comm.attach_unit(unit);
+        self.db_insert();
+    }
+
+    fn init(&self) {}
+
+    fn done(&self) {}
+
+    fn dump(&self) {}
+
+    fn start(&self) -> Result<()> {
+        let started = self.mng.start_check()?;
+        if started {
+            log::debug!("swap already in starting, just return immediately");
+            return Ok(());
+        }
+
+        self.mng.start_action();
+
+        Ok(())
+    }
+
+    fn stop(&self, force: bool) -> Result<()> {
+        if !force {
+            let stopping = self.mng.stop_check()?;
+            if stopping {
+                log::debug!("swap already in stopping, just return immediately");
+                return Ok(());
+            }
+        }
+
+        self.mng.stop_action();
+        Ok(())
+    }
+
+    fn kill(&self) {}
+
+    fn release_resources(&self) {}
+
+    fn sigchld_events(&self, wait_status: WaitStatus) {
+        self.mng.sigchld_event(wait_status)
+    }
+
+    fn control_pid(&self) -> Option<Pid> {
+        self.mng.control_pid()
+    }
+
+    fn reset_failed(&self) {}
+}
+
+impl UnitMngUtil for SwapUnit {
+    fn attach_um(&self, um: Rc<dyn UmIf>) {
+        self.comm.attach_um(um);
+    }
+
+    fn attach_reli(&self, reli: Rc<Reliability>) {
+        self.comm.attach_reli(reli);
+    }
+}
+
+use sysmaster::declure_unitobj_plugin_with_param;
+declure_unitobj_plugin_with_param!(SwapUnit, SwapUnit::new, PLUGIN_NAME);
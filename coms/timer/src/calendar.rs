@@ -0,0 +1,510 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! calendar mod parse the calendar event expression of OnCalendar= and calculate the next elapse time.
+//! The expression is like "DayOfWeek Year-Month-Day Hour:Minute:Second" in local time, for example:
+//! "Mon..Fri *-*-* 08:00:00", "*-*-01 00:00:00", "*:0/15". The shortcuts like "daily" are also supported.
+//!
+use std::mem::MaybeUninit;
+use sysmaster::error::*;
+
+const YEAR_MIN: u32 = 1970;
+const YEAR_MAX: u32 = 2199;
+// limit the loop of finding the next elapse time, in case of the expression never matches
+const FIND_NEXT_MAX_LOOP: u32 = 100000;
+
+const WEEKDAY_NAMES: [(&str, &str); 7] = [
+    ("sun", "sunday"),
+    ("mon", "monday"),
+    ("tue", "tuesday"),
+    ("wed", "wednesday"),
+    ("thu", "thursday"),
+    ("fri", "friday"),
+    ("sat", "saturday"),
+];
+
+const SHORTCUTS: [(&str, &str); 10] = [
+    ("minutely", "*-*-* *:*:00"),
+    ("hourly", "*-*-* *:00:00"),
+    ("daily", "*-*-* 00:00:00"),
+    ("weekly", "Mon *-*-* 00:00:00"),
+    ("monthly", "*-*-01 00:00:00"),
+    ("quarterly", "*-01,04,07,10-01 00:00:00"),
+    ("semiannually", "*-01,07-01 00:00:00"),
+    ("yearly", "*-01-01 00:00:00"),
+    ("annually", "*-01-01 00:00:00"),
+    ("midnight", "*-*-* 00:00:00"),
+];
+
+/// one item of the field: a single value "5", a range "1..5", or a repetition "0/15"
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct CalendarComponent {
+    start: u32,
+    stop: Option<u32>,
+    repeat: u32,
+}
+
+impl CalendarComponent {
+    /// return the smallest matched value which is not less than v
+    fn next(&self, v: u32, max: u32) -> Option<u32> {
+        let stop = match self.stop {
+            Some(stop) => stop.min(max),
+            None if self.repeat == 0 => self.start,
+            None => max,
+        };
+
+        if v > stop {
+            return None;
+        }
+        if v <= self.start {
+            return Some(self.start);
+        }
+        if self.repeat == 0 {
+            return Some(v);
+        }
+
+        let n = self.start + (v - self.start).div_ceil(self.repeat) * self.repeat;
+        if n > stop {
+            None
+        } else {
+            Some(n)
+        }
+    }
+}
+
+/// the field matches any value if it's empty
+type CalendarField = Vec<CalendarComponent>;
+
+fn field_next(field: &CalendarField, v: u32, max: u32) -> Option<u32> {
+    if field.is_empty() {
+        return Some(v);
+    }
+
+    field.iter().filter_map(|c| c.next(v, max)).min()
+}
+
+/// the parsed calendar event expression
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct CalendarSpec {
+    // bit N is set if the day of week N is matched, 0 is Sunday
+    weekdays: u8,
+    year: CalendarField,
+    month: CalendarField,
+    day: CalendarField,
+    hour: CalendarField,
+    minute: CalendarField,
+    second: CalendarField,
+}
+
+impl CalendarSpec {
+    /// parse the calendar event expression
+    pub(super) fn parse(expr: &str) -> Result<CalendarSpec> {
+        let mut expr = expr.trim();
+        if let Some((_, v)) = SHORTCUTS.iter().find(|(name, _)| *name == expr) {
+            expr = v;
+        }
+
+        let mut spec = CalendarSpec {
+            weekdays: 0x7f,
+            year: Vec::new(),
+            month: Vec::new(),
+            day: Vec::new(),
+            hour: Vec::new(),
+            minute: Vec::new(),
+            second: Vec::new(),
+        };
+
+        let mut tokens: Vec<&str> = expr.split_whitespace().collect();
+        if tokens.is_empty() || tokens.len() > 3 {
+            return Err(format!("invalid calendar expression: {expr}").into());
+        }
+
+        if tokens[0].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            spec.weekdays = parse_weekdays(tokens.remove(0))?;
+        }
+
+        let mut date = None;
+        let mut time = None;
+        for token in tokens {
+            let slot = if token.contains(':') {
+                &mut time
+            } else {
+                &mut date
+            };
+            if slot.is_some() {
+                return Err(format!("invalid calendar expression: {expr}").into());
+            }
+            *slot = Some(token);
+        }
+
+        if let Some(date) = date {
+            let parts: Vec<&str> = date.split('-').collect();
+            let (year, month, day) = match parts.len() {
+                3 => (parts[0], parts[1], parts[2]),
+                2 => ("*", parts[0], parts[1]),
+                _ => return Err(format!("invalid date in calendar expression: {date}").into()),
+            };
+            spec.year = parse_field(year, YEAR_MIN, YEAR_MAX)?;
+            spec.month = parse_field(month, 1, 12)?;
+            spec.day = parse_field(day, 1, 31)?;
+        }
+
+        // the time is 00:00:00 if not configured
+        let time = time.unwrap_or("00:00:00");
+        let parts: Vec<&str> = time.split(':').collect();
+        let (hour, minute, second) = match parts.len() {
+            3 => (parts[0], parts[1], parts[2]),
+            2 => (parts[0], parts[1], "00"),
+            _ => return Err(format!("invalid time in calendar expression: {time}").into()),
+        };
+        spec.hour = parse_field(hour, 0, 23)?;
+        spec.minute = parse_field(minute, 0, 59)?;
+        spec.second = parse_field(second, 0, 59)?;
+
+        Ok(spec)
+    }
+
+    /// return the next elapse time after {after}, both are seconds since epoch
+    pub(super) fn next_elapse(&self, after: i64) -> Option<i64> {
+        let mut tm = localtime(after.checked_add(1)?)?;
+
+        for _ in 0..FIND_NEXT_MAX_LOOP {
+            let year = (tm.tm_year + 1900) as u32;
+            match field_next(&self.year, year, YEAR_MAX) {
+                None => return None,
+                Some(y) if y != year => {
+                    tm.tm_year = y as i32 - 1900;
+                    tm.tm_mon = 0;
+                    tm.tm_mday = 1;
+                    reset_time(&mut tm, 0);
+                    normalize(&mut tm)?;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let month = (tm.tm_mon + 1) as u32;
+            match field_next(&self.month, month, 12) {
+                None => {
+                    tm.tm_year += 1;
+                    tm.tm_mon = 0;
+                    tm.tm_mday = 1;
+                    reset_time(&mut tm, 0);
+                    normalize(&mut tm)?;
+                    continue;
+                }
+                Some(m) if m != month => {
+                    tm.tm_mon = m as i32 - 1;
+                    tm.tm_mday = 1;
+                    reset_time(&mut tm, 0);
+                    normalize(&mut tm)?;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let day = tm.tm_mday as u32;
+            match field_next(&self.day, day, 31) {
+                None => {
+                    next_month(&mut tm)?;
+                    continue;
+                }
+                Some(d) if d != day => {
+                    let mut c = tm;
+                    c.tm_mday = d as i32;
+                    reset_time(&mut c, 0);
+                    normalize(&mut c)?;
+                    // the day doesn't exist in the month, like Feb 30th
+                    if c.tm_mon != tm.tm_mon {
+                        next_month(&mut tm)?;
+                    } else {
+                        tm = c;
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            if self.weekdays & (1 << tm.tm_wday) == 0 {
+                tm.tm_mday += 1;
+                reset_time(&mut tm, 0);
+                normalize(&mut tm)?;
+                continue;
+            }
+
+            let hour = tm.tm_hour as u32;
+            match field_next(&self.hour, hour, 23) {
+                None => {
+                    tm.tm_mday += 1;
+                    reset_time(&mut tm, 0);
+                    normalize(&mut tm)?;
+                    continue;
+                }
+                Some(h) if h != hour => {
+                    reset_time(&mut tm, h as i32);
+                    normalize(&mut tm)?;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let minute = tm.tm_min as u32;
+            match field_next(&self.minute, minute, 59) {
+                None => {
+                    tm.tm_hour += 1;
+                    tm.tm_min = 0;
+                    tm.tm_sec = 0;
+                    normalize(&mut tm)?;
+                    continue;
+                }
+                Some(m) if m != minute => {
+                    tm.tm_min = m as i32;
+                    tm.tm_sec = 0;
+                    normalize(&mut tm)?;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let second = tm.tm_sec as u32;
+            match field_next(&self.second, second, 59) {
+                None => {
+                    tm.tm_min += 1;
+                    tm.tm_sec = 0;
+                    normalize(&mut tm)?;
+                    continue;
+                }
+                Some(s) if s != second => {
+                    tm.tm_sec = s as i32;
+                    normalize(&mut tm)?;
+                    continue;
+                }
+                _ => {}
+            }
+
+            return normalize(&mut tm);
+        }
+
+        None
+    }
+}
+
+fn parse_weekdays(s: &str) -> Result<u8> {
+    let weekday = |name: &str| -> Result<u32> {
+        let name = name.to_lowercase();
+        WEEKDAY_NAMES
+            .iter()
+            .position(|(short, long)| *short == name || *long == name)
+            .map(|p| p as u32)
+            .ok_or_else(|| format!("invalid day of week: {name}").into())
+    };
+
+    let mut weekdays = 0u8;
+    for item in s.split(',') {
+        let range = item.split_once("..").or_else(|| item.split_once('-'));
+        match range {
+            Some((start, stop)) => {
+                let (start, stop) = (weekday(start)?, weekday(stop)?);
+                // the range like "Sat..Mon" is wrapped around Sunday
+                let mut d = start;
+                loop {
+                    weekdays |= 1 << d;
+                    if d == stop {
+                        break;
+                    }
+                    d = (d + 1) % 7;
+                }
+            }
+            None => weekdays |= 1 << weekday(item)?,
+        }
+    }
+
+    Ok(weekdays)
+}
+
+fn parse_field(s: &str, min: u32, max: u32) -> Result<CalendarField> {
+    if s == "*" {
+        return Ok(Vec::new());
+    }
+
+    let number = |v: &str| -> Result<u32> {
+        let n = v
+            .parse::<u32>()
+            .map_err(|_| format!("invalid value in calendar expression: {s}"))?;
+        if n < min || n > max {
+            return Err(format!("value {n} is out of range [{min}, {max}]: {s}").into());
+        }
+        Ok(n)
+    };
+
+    let mut field = Vec::new();
+    for item in s.split(',') {
+        let (value, repeat) = match item.split_once('/') {
+            Some((value, repeat)) => {
+                let repeat = repeat
+                    .parse::<u32>()
+                    .map_err(|_| format!("invalid repetition in calendar expression: {s}"))?;
+                if repeat == 0 {
+                    return Err(format!("repetition can't be 0: {s}").into());
+                }
+                (value, repeat)
+            }
+            None => (item, 0),
+        };
+
+        let component = if value == "*" {
+            CalendarComponent {
+                start: min,
+                stop: None,
+                // "*" without repetition matches everything
+                repeat: repeat.max(1),
+            }
+        } else if let Some((start, stop)) = value.split_once("..") {
+            let (start, stop) = (number(start)?, number(stop)?);
+            if start > stop {
+                return Err(format!("invalid range in calendar expression: {s}").into());
+            }
+            CalendarComponent {
+                start,
+                stop: Some(stop),
+                repeat,
+            }
+        } else {
+            CalendarComponent {
+                start: number(value)?,
+                stop: None,
+                repeat,
+            }
+        };
+        field.push(component);
+    }
+
+    Ok(field)
+}
+
+fn reset_time(tm: &mut libc::tm, hour: i32) {
+    tm.tm_hour = hour;
+    tm.tm_min = 0;
+    tm.tm_sec = 0;
+}
+
+fn next_month(tm: &mut libc::tm) -> Option<i64> {
+    tm.tm_mon += 1;
+    tm.tm_mday = 1;
+    reset_time(tm, 0);
+    normalize(tm)
+}
+
+fn localtime(t: i64) -> Option<libc::tm> {
+    let t = t as libc::time_t;
+    let mut tm = MaybeUninit::<libc::tm>::zeroed();
+    let ret = unsafe { libc::localtime_r(&t, tm.as_mut_ptr()) };
+    if ret.is_null() {
+        return None;
+    }
+    Some(unsafe { tm.assume_init() })
+}
+
+/// normalize the broken-down local time, and return the seconds since epoch
+fn normalize(tm: &mut libc::tm) -> Option<i64> {
+    tm.tm_isdst = -1;
+    let t = unsafe { libc::mktime(tm) };
+    if t == -1 {
+        return None;
+    }
+    Some(t as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize, CalendarSpec};
+    use std::mem::MaybeUninit;
+
+    fn local(year: i32, month: i32, day: i32, hour: i32, min: i32, sec: i32) -> i64 {
+        let mut tm = unsafe { MaybeUninit::<libc::tm>::zeroed().assume_init() };
+        tm.tm_year = year - 1900;
+        tm.tm_mon = month - 1;
+        tm.tm_mday = day;
+        tm.tm_hour = hour;
+        tm.tm_min = min;
+        tm.tm_sec = sec;
+        normalize(&mut tm).unwrap()
+    }
+
+    fn next(expr: &str, after: i64) -> Option<i64> {
+        CalendarSpec::parse(expr).unwrap().next_elapse(after)
+    }
+
+    #[test]
+    fn test_calendar_parse() {
+        assert!(CalendarSpec::parse("daily").is_ok());
+        assert!(CalendarSpec::parse("Mon..Fri *-*-* 08:00:00").is_ok());
+        assert!(CalendarSpec::parse("Sat,Sun 10:30").is_ok());
+        assert!(CalendarSpec::parse("*-*-01").is_ok());
+        assert!(CalendarSpec::parse("*:0/15").is_ok());
+        assert!(CalendarSpec::parse("2023-01..06-01 00:00:00").is_ok());
+        assert_eq!(
+            CalendarSpec::parse("hourly").unwrap(),
+            CalendarSpec::parse("*-*-* *:00:00").unwrap()
+        );
+
+        assert!(CalendarSpec::parse("").is_err());
+        assert!(CalendarSpec::parse("Foo 10:00").is_err());
+        assert!(CalendarSpec::parse("*-13-01").is_err());
+        assert!(CalendarSpec::parse("25:00").is_err());
+        assert!(CalendarSpec::parse("*:0/0").is_err());
+        assert!(CalendarSpec::parse("*-*-* 10:00 11:00").is_err());
+        assert!(CalendarSpec::parse("*-05..03-01").is_err());
+    }
+
+    #[test]
+    fn test_calendar_next_elapse() {
+        // 2023-03-15 is Wednesday
+        let now = local(2023, 3, 15, 10, 20, 30);
+
+        assert_eq!(next("daily", now), Some(local(2023, 3, 16, 0, 0, 0)));
+        assert_eq!(next("hourly", now), Some(local(2023, 3, 15, 11, 0, 0)));
+        assert_eq!(next("*:0/15", now), Some(local(2023, 3, 15, 10, 30, 0)));
+        assert_eq!(next("monthly", now), Some(local(2023, 4, 1, 0, 0, 0)));
+        assert_eq!(next("weekly", now), Some(local(2023, 3, 20, 0, 0, 0)));
+        assert_eq!(next("yearly", now), Some(local(2024, 1, 1, 0, 0, 0)));
+        assert_eq!(next("quarterly", now), Some(local(2023, 4, 1, 0, 0, 0)));
+        assert_eq!(
+            next("Sat,Sun 09:00", now),
+            Some(local(2023, 3, 18, 9, 0, 0))
+        );
+        assert_eq!(
+            next("Mon..Fri 08:00", now),
+            Some(local(2023, 3, 16, 8, 0, 0))
+        );
+        assert_eq!(
+            next("*-*-* 10:20:30", now),
+            Some(local(2023, 3, 16, 10, 20, 30))
+        );
+        assert_eq!(
+            next("*-*-31 12:00", now),
+            Some(local(2023, 3, 31, 12, 0, 0))
+        );
+        // skip the months which don't have the 31st
+        assert_eq!(
+            next("*-*-31 12:00", local(2023, 4, 1, 0, 0, 0)),
+            Some(local(2023, 5, 31, 12, 0, 0))
+        );
+        // Feb 29th only exists in leap years
+        assert_eq!(next("*-02-29", now), Some(local(2024, 2, 29, 0, 0, 0)));
+        assert_eq!(
+            next("*-*-01,30", local(2023, 2, 2, 0, 0, 0)),
+            Some(local(2023, 3, 1, 0, 0, 0))
+        );
+        assert_eq!(next("2020-01-01", now), None);
+    }
+}
@@ -0,0 +1,212 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! timer_config mod load the conf file list and convert it to structure which is defined in this mod.
+//!
+#![allow(non_snake_case)]
+use super::calendar::CalendarSpec;
+use super::comm::TimerUnitComm;
+use super::rentry::SectionTimer;
+use basic::time_util::parse_timespan;
+use confique::Config;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use sysmaster::error::*;
+use sysmaster::rel::ReStation;
+
+#[derive(Config, Default, Debug)]
+pub(super) struct TimerConfigData {
+    #[config(nested)]
+    pub Timer: SectionTimer,
+}
+
+impl TimerConfigData {
+    pub(self) fn new(Timer: SectionTimer) -> TimerConfigData {
+        TimerConfigData { Timer }
+    }
+}
+
+/// the base point of the monotonic timer
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub(super) enum TimerBase {
+    Boot,
+    UnitActive,
+    UnitInactive,
+}
+
+/// the parsed elapse condition of the timer
+#[derive(Clone, Debug)]
+pub(super) enum TimerValue {
+    Monotonic(TimerBase, u64),
+    Calendar(CalendarSpec),
+}
+
+pub(super) struct TimerConfig {
+    // associated objects
+    comm: Rc<TimerUnitComm>,
+
+    // owned objects
+    data: Rc<RefCell<TimerConfigData>>,
+    values: RefCell<Vec<TimerValue>>,
+    accuracy: RefCell<u64>,
+    randomized_delay: RefCell<u64>,
+    unit: RefCell<Option<String>>,
+}
+
+impl ReStation for TimerConfig {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, reload: bool) {
+        if reload {
+            return;
+        }
+        if let Some((data, unit)) = self.comm.rentry_conf_get() {
+            self.data.replace(TimerConfigData::new(data));
+            self.unit.replace(unit);
+            if let Err(e) = self.parse_values() {
+                log::error!("Failed to restore the timer values: {:?}", e);
+            }
+        }
+    }
+
+    fn db_insert(&self) {
+        self.comm
+            .rentry_conf_insert(&self.data.borrow().Timer, self.unit());
+    }
+
+    // reload: no external connections, no entry
+}
+
+impl TimerConfig {
+    pub(super) fn new(commr: &Rc<TimerUnitComm>) -> Self {
+        TimerConfig {
+            comm: Rc::clone(commr),
+            data: Rc::new(RefCell::new(TimerConfigData::default())),
+            values: RefCell::new(Vec::new()),
+            accuracy: RefCell::new(0),
+            randomized_delay: RefCell::new(0),
+            unit: RefCell::new(None),
+        }
+    }
+
+    pub(super) fn load(&self, paths: Vec<PathBuf>, update: bool) -> Result<()> {
+        let mut builder = TimerConfigData::builder().env();
+        for v in paths {
+            builder = builder.file(v);
+        }
+
+        *self.data.borrow_mut() = builder.load().context(ConfiqueSnafu)?;
+        self.parse_values()?;
+        if self.values.borrow().is_empty() {
+            return Err("timer has no OnBootSec=, OnUnitActiveSec=, OnUnitInactiveSec= or OnCalendar= configured".to_string().into());
+        }
+        self.parse_unit()?;
+
+        if update {
+            self.db_update();
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<TimerConfigData>> {
+        self.data.clone()
+    }
+
+    pub(super) fn values(&self) -> Vec<TimerValue> {
+        self.values.borrow().clone()
+    }
+
+    pub(super) fn accuracy(&self) -> u64 {
+        *self.accuracy.borrow()
+    }
+
+    pub(super) fn randomized_delay(&self) -> u64 {
+        *self.randomized_delay.borrow()
+    }
+
+    /// the unit triggered by the timer
+    pub(super) fn unit(&self) -> Option<String> {
+        self.unit.borrow().clone()
+    }
+
+    fn parse_values(&self) -> Result<()> {
+        let data = self.data.borrow();
+        let timer = &data.Timer;
+        let mut values = Vec::new();
+
+        for (name, base, v) in [
+            ("OnBootSec", TimerBase::Boot, &timer.OnBootSec),
+            (
+                "OnUnitActiveSec",
+                TimerBase::UnitActive,
+                &timer.OnUnitActiveSec,
+            ),
+            (
+                "OnUnitInactiveSec",
+                TimerBase::UnitInactive,
+                &timer.OnUnitInactiveSec,
+            ),
+        ] {
+            if let Some(v) = v {
+                let usec = parse_timespan(v).map_err(|e| format!("invalid {name} {v}: {e}"))?;
+                values.push(TimerValue::Monotonic(base, usec));
+            }
+        }
+
+        for v in timer.OnCalendar.iter().flatten() {
+            values.push(TimerValue::Calendar(CalendarSpec::parse(v)?));
+        }
+
+        let accuracy = parse_timespan(&timer.AccuracySec)
+            .map_err(|e| format!("invalid AccuracySec {}: {e}", timer.AccuracySec))?;
+        let randomized_delay = parse_timespan(&timer.RandomizedDelaySec).map_err(|e| {
+            format!(
+                "invalid RandomizedDelaySec {}: {e}",
+                timer.RandomizedDelaySec
+            )
+        })?;
+
+        *self.values.borrow_mut() = values;
+        *self.accuracy.borrow_mut() = accuracy;
+        *self.randomized_delay.borrow_mut() = randomized_delay;
+        Ok(())
+    }
+
+    fn parse_unit(&self) -> Result<()> {
+        let owner = match self.comm.owner() {
+            None => return Ok(()),
+            Some(u) => u,
+        };
+
+        // the unit with the same name as the timer is triggered by default
+        let unit = match self.data.borrow().Timer.Unit.clone() {
+            Some(unit) => unit,
+            None => {
+                let stem = Path::new(owner.id()).file_stem().unwrap().to_str().unwrap();
+                format!("{stem}.service")
+            }
+        };
+
+        if unit.ends_with(".timer") {
+            return Err(format!("timer can't trigger another timer unit {unit}").into());
+        }
+        if !self.comm.um().load_unit_success(&unit) {
+            return Err(format!("failed to load unit {unit}").into());
+        }
+
+        self.unit.replace(Some(unit));
+        Ok(())
+    }
+}
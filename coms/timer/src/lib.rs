@@ -0,0 +1,45 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! # Timer is used to activate another unit at the configured time.
+//!  The timer elapses after a time span relative to a starting point, or at the wall clock time described by the calendar expression.
+//!  When the timer elapses, the unit with the same name as the timer is started, like foo.timer starts foo.service.
+//!  The triggered unit can be changed with Unit=.
+//! #  Example:
+//! ``` toml
+//!  [Unit]
+//!  Description="foo"
+//!
+//!  [Timer]
+//!  OnBootSec="15min"
+//!  OnUnitActiveSec="1d"
+//!  OnCalendar="Mon..Fri *-*-* 08:00:00"
+//!  Persistent=true
+//! ```
+//! ##  Automatic dependency
+//!
+//! ###  Implicit dependency
+//!  The timer has Before= and Triggers= dependencies on the unit it triggers.
+//!
+//! ###  Default Dependency
+//!  If DefaultDependencies=true is set, the following dependencies will be added by default:
+//!  Requires="sysinit.target", After="sysinit.target", Conflicts="shutdown.target", Before="shutdown.target", Before="timers.target"
+
+// dependency: timer_base -> timer_rentry -> timer_comm -> {timer_mng, timer_config} -> timer_unit -> timer_manager
+mod base;
+mod calendar;
+mod comm;
+mod config;
+mod manager;
+mod mng;
+mod rentry;
+mod unit;
@@ -0,0 +1,609 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! is the core of the timer unit, calculate the next elapse time and trigger the unit
+//!
+use super::base::TIMER_STAMP_DIR;
+use super::comm::TimerUnitComm;
+use super::config::{TimerBase, TimerConfig, TimerValue};
+use super::rentry::{DualTimestamp, TimerResult, TimerState};
+use basic::time_util::{now_clockid, USEC_PER_SEC};
+use basic::IN_SET;
+use event::{EventState, EventType, Events, Source};
+use nix::time::ClockId;
+use std::cell::RefCell;
+use std::fs;
+use std::os::unix::prelude::RawFd;
+use std::path::PathBuf;
+use std::rc::{Rc, Weak};
+use sysmaster::error::*;
+use sysmaster::rel::ReStation;
+use sysmaster::unit::{UnitActiveState, UnitNotifyFlags};
+
+impl TimerState {
+    fn to_unit_state(self) -> UnitActiveState {
+        match self {
+            TimerState::Dead | TimerState::StateMax => UnitActiveState::UnitInActive,
+            TimerState::Waiting | TimerState::Running | TimerState::Elapsed => {
+                UnitActiveState::UnitActive
+            }
+            TimerState::Failed => UnitActiveState::UnitFailed,
+        }
+    }
+}
+
+pub(super) struct TimerMng {
+    // associated objects
+    comm: Rc<TimerUnitComm>,
+    config: Rc<TimerConfig>,
+
+    // owned objects
+    state: RefCell<TimerState>,
+    result: RefCell<TimerResult>,
+    last_trigger: RefCell<DualTimestamp>,
+    // the realtime when the timer is started
+    activated: RefCell<u64>,
+    // the monotonic time when the triggered unit is activated or deactivated last time
+    target_active: RefCell<u64>,
+    target_inactive: RefCell<u64>,
+    // the randomized delay of the next elapse, rolled once and kept until the timer elapses
+    random_delay: RefCell<Option<u64>>,
+    monotonic: Rc<TimerSource>,
+    realtime: Rc<TimerSource>,
+}
+
+impl ReStation for TimerMng {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, _reload: bool) {
+        if let Some((state, result, last_trigger, activated, target_active, target_inactive)) =
+            self.comm.rentry_mng_get()
+        {
+            *self.state.borrow_mut() = state;
+            *self.result.borrow_mut() = result;
+            *self.last_trigger.borrow_mut() = last_trigger;
+            *self.activated.borrow_mut() = activated;
+            *self.target_active.borrow_mut() = target_active;
+            *self.target_inactive.borrow_mut() = target_inactive;
+        }
+    }
+
+    fn db_insert(&self) {
+        self.comm.rentry_mng_insert(
+            self.state(),
+            *self.result.borrow(),
+            *self.last_trigger.borrow(),
+            *self.activated.borrow(),
+            *self.target_active.borrow(),
+            *self.target_inactive.borrow(),
+        );
+    }
+
+    // reload: entry-only
+    fn entry_coldplug(&self) {
+        if self.state() == TimerState::Waiting {
+            if let Err(e) = self.arm_timers() {
+                log::error!("Failed to rearm the timer of {}: {}", self.id(), e);
+            }
+        }
+    }
+
+    fn entry_clear(&self) {
+        self.delete_source(&self.monotonic);
+        self.delete_source(&self.realtime);
+    }
+}
+
+impl TimerMng {
+    pub(super) fn new(commr: &Rc<TimerUnitComm>, configr: &Rc<TimerConfig>) -> Self {
+        TimerMng {
+            comm: Rc::clone(commr),
+            config: Rc::clone(configr),
+            state: RefCell::new(TimerState::StateMax),
+            result: RefCell::new(TimerResult::Success),
+            last_trigger: RefCell::new(DualTimestamp::default()),
+            activated: RefCell::new(0),
+            target_active: RefCell::new(0),
+            target_inactive: RefCell::new(0),
+            random_delay: RefCell::new(None),
+            monotonic: Rc::new(TimerSource::new(false)),
+            realtime: Rc::new(TimerSource::new(true)),
+        }
+    }
+
+    pub(super) fn attach_timer(&self, mng: Weak<TimerMng>) {
+        self.monotonic.attach_mng(mng.clone());
+        self.realtime.attach_mng(mng);
+    }
+
+    pub(super) fn start_check(&self) -> Result<()> {
+        if IN_SET!(
+            self.state(),
+            TimerState::Waiting,
+            TimerState::Running,
+            TimerState::Elapsed
+        ) {
+            return Err(Error::UnitActionEAgain);
+        }
+        Ok(())
+    }
+
+    pub(super) fn start_action(&self) {
+        // the missed elapse before the timer is started is only caught up for the persistent timer
+        let mut last_trigger = DualTimestamp::default();
+        if self.config.config_data().borrow().Timer.Persistent {
+            if let Some(realtime) = self.load_stamp() {
+                last_trigger.realtime = realtime;
+            }
+        }
+        *self.last_trigger.borrow_mut() = last_trigger;
+        *self.activated.borrow_mut() = now_clockid(ClockId::CLOCK_REALTIME);
+        *self.result.borrow_mut() = TimerResult::Success;
+        *self.random_delay.borrow_mut() = None;
+        self.enter_waiting();
+    }
+
+    pub(super) fn stop_check(&self) -> Result<()> {
+        if IN_SET!(self.state(), TimerState::Dead) {
+            return Err(Error::UnitActionEAgain);
+        }
+        Ok(())
+    }
+
+    pub(super) fn stop_action(&self) {
+        self.enter_dead(TimerResult::Success);
+    }
+
+    pub(super) fn reset_failed(&self) {
+        if self.state() == TimerState::Failed {
+            self.set_state(TimerState::Dead);
+        }
+        *self.result.borrow_mut() = TimerResult::Success;
+    }
+
+    /// the state of the triggered unit is changed
+    pub(super) fn trigger_notify(&self, other: &str) {
+        if self.config.unit().as_deref() != Some(other) {
+            return;
+        }
+
+        let um = self.comm.um();
+        let inactive = um.current_active_state(other).is_inactive_or_failed();
+        let was_active = *self.target_active.borrow() > *self.target_inactive.borrow();
+        if !inactive && !was_active {
+            *self.target_active.borrow_mut() = now_clockid(ClockId::CLOCK_MONOTONIC);
+        } else if inactive && was_active {
+            *self.target_inactive.borrow_mut() = now_clockid(ClockId::CLOCK_MONOTONIC);
+        }
+
+        match self.state() {
+            TimerState::Waiting | TimerState::Elapsed => self.enter_waiting(),
+            TimerState::Running if inactive => self.enter_waiting(),
+            _ => self.db_update(),
+        }
+    }
+
+    pub(super) fn dispatch_timer(&self) {
+        if self.state() != TimerState::Waiting {
+            return;
+        }
+
+        log::debug!("Timer {} elapsed.", self.id());
+        self.enter_running();
+    }
+
+    fn enter_waiting(&self) {
+        match self.arm_timers() {
+            Ok(true) => self.set_state(TimerState::Waiting),
+            Ok(false) => {
+                log::debug!("Timer {} has no more elapse time.", self.id());
+                self.set_state(TimerState::Elapsed);
+            }
+            Err(e) => {
+                log::error!("Failed to arm the timer of {}: {}", self.id(), e);
+                self.enter_dead(TimerResult::FailureResources);
+            }
+        }
+    }
+
+    fn enter_running(&self) {
+        let unit = match self.config.unit() {
+            None => return,
+            Some(unit) => unit,
+        };
+
+        let um = self.comm.um();
+        // don't start the unit which is being stopped, wait for the next state change of it
+        if um.has_stop_job(&unit) {
+            log::debug!("{} is being stopped, skip triggering it.", unit);
+            return;
+        }
+
+        if let Err(e) = um.unit_start_by_job(&unit) {
+            log::warn!("Failed to trigger unit {}: {:?}", unit, e);
+            self.enter_dead(TimerResult::FailureResources);
+            return;
+        }
+
+        let last_trigger = DualTimestamp {
+            realtime: now_clockid(ClockId::CLOCK_REALTIME),
+            monotonic: now_clockid(ClockId::CLOCK_MONOTONIC),
+        };
+        *self.last_trigger.borrow_mut() = last_trigger;
+        *self.random_delay.borrow_mut() = None;
+        if self.config.config_data().borrow().Timer.Persistent {
+            self.save_stamp(last_trigger.realtime);
+        }
+
+        self.set_state(TimerState::Running);
+    }
+
+    fn enter_dead(&self, res: TimerResult) {
+        if *self.result.borrow() == TimerResult::Success {
+            *self.result.borrow_mut() = res;
+        }
+
+        if *self.result.borrow() == TimerResult::Success {
+            self.set_state(TimerState::Dead);
+        } else {
+            self.set_state(TimerState::Failed);
+        }
+    }
+
+    /// calculate the next elapse time, and arm the event sources.
+    /// return false if the timer will never elapse again.
+    fn arm_timers(&self) -> Result<bool> {
+        let (monotonic, realtime) = self.next_elapse();
+        let delay = self.random_delay();
+        let accuracy = self.config.accuracy();
+
+        let mut armed = false;
+        for (source, next) in [(&self.monotonic, monotonic), (&self.realtime, realtime)] {
+            // the timer with the infinite time span never elapses
+            let next = next
+                .map(|usec| round_up(usec.saturating_add(delay), accuracy))
+                .filter(|usec| *usec != u64::MAX);
+            match next {
+                Some(usec) => {
+                    self.arm_source(source, usec)?;
+                    armed = true;
+                }
+                None => self.disarm_source(source),
+            }
+        }
+
+        Ok(armed)
+    }
+
+    fn disarm_timers(&self) {
+        self.disarm_source(&self.monotonic);
+        self.disarm_source(&self.realtime);
+    }
+
+    /// return the next elapse time of the monotonic clock and the realtime clock
+    fn next_elapse(&self) -> (Option<u64>, Option<u64>) {
+        let last_trigger = *self.last_trigger.borrow();
+        let mut monotonic: Option<u64> = None;
+        let mut realtime: Option<u64> = None;
+
+        for value in self.config.values() {
+            match value {
+                TimerValue::Monotonic(base, usec) => {
+                    let base = match base {
+                        // elapse only once after the boot
+                        TimerBase::Boot => {
+                            if last_trigger.monotonic >= usec {
+                                continue;
+                            }
+                            0
+                        }
+                        TimerBase::UnitActive | TimerBase::UnitInactive => {
+                            let stamp = if base == TimerBase::UnitActive {
+                                *self.target_active.borrow()
+                            } else {
+                                *self.target_inactive.borrow()
+                            };
+                            // the unit has never been activated or deactivated
+                            let base = stamp.max(last_trigger.monotonic);
+                            if base == 0 {
+                                continue;
+                            }
+                            base
+                        }
+                    };
+
+                    let next = base.saturating_add(usec);
+                    monotonic = Some(monotonic.map_or(next, |m| m.min(next)));
+                }
+                TimerValue::Calendar(spec) => {
+                    let base = if last_trigger.realtime > 0 {
+                        last_trigger.realtime
+                    } else {
+                        *self.activated.borrow()
+                    };
+                    let next = match spec.next_elapse((base / USEC_PER_SEC) as i64) {
+                        None => continue,
+                        Some(sec) => sec as u64 * USEC_PER_SEC,
+                    };
+                    realtime = Some(realtime.map_or(next, |r| r.min(next)));
+                }
+            }
+        }
+
+        (monotonic, realtime)
+    }
+
+    fn arm_source(&self, source: &Rc<TimerSource>, usec: u64) -> Result<()> {
+        let events = self.comm.um().events();
+        if source.added() {
+            events.set_enabled(source.clone(), EventState::Off)?;
+        } else {
+            events.add_source(source.clone())?;
+            source.set_added(true);
+        }
+        source.set_time(usec);
+        events.set_enabled(source.clone(), EventState::OneShot)?;
+        Ok(())
+    }
+
+    // the source may be pending in the events when the other one is dispatched,
+    // so only disable it here, and delete it when the unit is cleared.
+    fn disarm_source(&self, source: &Rc<TimerSource>) {
+        if !source.added() {
+            return;
+        }
+
+        let events = self.comm.um().events();
+        events.set_enabled(source.clone(), EventState::Off).unwrap();
+    }
+
+    fn delete_source(&self, source: &Rc<TimerSource>) {
+        if !source.added() {
+            return;
+        }
+
+        let events = self.comm.um().events();
+        events.set_enabled(source.clone(), EventState::Off).unwrap();
+        events.del_source(source.clone()).unwrap();
+        source.set_added(false);
+    }
+
+    fn stamp_path(&self) -> PathBuf {
+        PathBuf::from(TIMER_STAMP_DIR).join(format!("stamp-{}", self.id()))
+    }
+
+    fn load_stamp(&self) -> Option<u64> {
+        let content = fs::read_to_string(self.stamp_path()).ok()?;
+        content.trim().parse::<u64>().ok()
+    }
+
+    fn save_stamp(&self, realtime: u64) {
+        let path = self.stamp_path();
+        if let Err(e) = fs::create_dir_all(TIMER_STAMP_DIR)
+            .and_then(|_| fs::write(&path, format!("{realtime}\n")))
+        {
+            log::warn!("Failed to save the stamp file {:?}: {}", path, e);
+        }
+    }
+
+    pub fn get_state(&self) -> String {
+        let state = *self.state.borrow();
+        state.to_string()
+    }
+
+    fn set_state(&self, new_state: TimerState) {
+        let old_state = self.state();
+        self.state.replace(new_state);
+
+        if new_state != TimerState::Waiting {
+            self.disarm_timers();
+        }
+
+        if new_state != old_state {
+            log::debug!(
+                "{} original state[{:?}] ->new state[{:?}]",
+                self.id(),
+                old_state,
+                new_state,
+            );
+        }
+
+        if let Some(unit) = self.comm.owner() {
+            unit.notify(
+                old_state.to_unit_state(),
+                new_state.to_unit_state(),
+                UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE,
+            );
+        }
+
+        self.db_update();
+    }
+
+    fn state(&self) -> TimerState {
+        *self.state.borrow()
+    }
+
+    fn id(&self) -> String {
+        self.comm
+            .owner()
+            .map_or(String::new(), |u| u.id().to_string())
+    }
+
+    pub fn to_unit_state(&self) -> UnitActiveState {
+        self.state().to_unit_state()
+    }
+
+    fn random_delay(&self) -> u64 {
+        if let Some(delay) = *self.random_delay.borrow() {
+            return delay;
+        }
+
+        let delay = match random_usec(self.config.randomized_delay()) {
+            Ok(delay) => delay,
+            Err(e) => {
+                log::warn!(
+                    "Failed to get the randomized delay, elapse without it: {}",
+                    e
+                );
+                0
+            }
+        };
+        *self.random_delay.borrow_mut() = Some(delay);
+        delay
+    }
+}
+
+fn random_usec(max: u64) -> Result<u64> {
+    if max == 0 {
+        return Ok(0);
+    }
+
+    let mut buf = [0u8; 8];
+    let ret = unsafe { libc::getrandom(buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+    if ret < 0 {
+        return Err(Error::Nix {
+            source: nix::Error::last(),
+        });
+    }
+    if ret as usize != buf.len() {
+        return Err(Error::Nix {
+            source: nix::Error::EIO,
+        });
+    }
+    Ok(u64::from_ne_bytes(buf) % max)
+}
+
+/// round up the elapse time to the multiple of the accuracy, so that the timers can be coalesced
+fn round_up(usec: u64, accuracy: u64) -> u64 {
+    if accuracy <= 1 || usec == u64::MAX {
+        return usec;
+    }
+
+    usec.div_ceil(accuracy).saturating_mul(accuracy)
+}
+
+pub(super) struct TimerSource {
+    // the source is on the realtime clock, or the monotonic clock
+    realtime: bool,
+    time: RefCell<u64>,
+    added: RefCell<bool>,
+    mng: RefCell<Weak<TimerMng>>,
+}
+
+impl TimerSource {
+    pub(super) fn new(realtime: bool) -> Self {
+        TimerSource {
+            realtime,
+            time: RefCell::new(0),
+            added: RefCell::new(false),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    pub(super) fn attach_mng(&self, mng: Weak<TimerMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    pub(super) fn set_time(&self, usec: u64) {
+        *self.time.borrow_mut() = usec
+    }
+
+    fn added(&self) -> bool {
+        *self.added.borrow()
+    }
+
+    fn set_added(&self, added: bool) {
+        *self.added.borrow_mut() = added
+    }
+
+    pub(self) fn mng(&self) -> Rc<TimerMng> {
+        self.mng.borrow().clone().upgrade().unwrap()
+    }
+}
+
+impl Source for TimerSource {
+    fn fd(&self) -> RawFd {
+        0
+    }
+
+    fn event_type(&self) -> EventType {
+        if self.realtime {
+            EventType::TimerRealtime
+        } else {
+            EventType::TimerMonotonic
+        }
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    // the elapse time is absolute on the clock
+    fn time(&self) -> u64 {
+        *self.time.borrow()
+    }
+
+    fn dispatch(&self, _: &Events) -> i32 {
+        self.mng().dispatch_timer();
+        0
+    }
+
+    fn priority(&self) -> i8 {
+        0
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{random_usec, round_up, TimerMng, TimerState};
+    use crate::comm::TimerUnitComm;
+    use crate::config::TimerConfig;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_timer_set_state() {
+        let _comm = Rc::new(TimerUnitComm::new());
+        let _config = Rc::new(TimerConfig::new(&_comm));
+        let tm = TimerMng::new(&_comm, &_config);
+        tm.set_state(TimerState::Elapsed);
+        assert_eq!(tm.state(), TimerState::Elapsed);
+        tm.stop_action();
+        assert_eq!(tm.state(), TimerState::Dead);
+    }
+
+    #[test]
+    fn test_timer_round_up() {
+        assert_eq!(round_up(100, 0), 100);
+        assert_eq!(round_up(100, 60), 120);
+        assert_eq!(round_up(120, 60), 120);
+        assert_eq!(round_up(u64::MAX, 60), u64::MAX);
+        assert_eq!(random_usec(0).unwrap(), 0);
+        assert!(random_usec(10).unwrap() < 10);
+    }
+
+    #[test]
+    fn test_timer_random_delay() {
+        let _comm = Rc::new(TimerUnitComm::new());
+        let _config = Rc::new(TimerConfig::new(&_comm));
+        let tm = TimerMng::new(&_comm, &_config);
+        let delay = tm.random_delay();
+        assert_eq!(tm.random_delay(), delay);
+        *tm.random_delay.borrow_mut() = Some(5);
+        assert_eq!(tm.random_delay(), 5);
+    }
+}
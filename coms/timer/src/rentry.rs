@@ -0,0 +1,229 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+#![allow(non_snake_case)]
+use confique::Config;
+use macros::EnumDisplay;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use sysmaster::rel::{ReDb, ReDbRoTxn, ReDbRwTxn, ReDbTable, Reliability};
+use sysmaster::serialize::{deserialize_str_or_num, deserialize_str_or_value, DeserializeWith};
+
+const RELI_DB_HTIMER_CONF: &str = "timerconf";
+const RELI_DB_HTIMER_MNG: &str = "timermng";
+
+#[derive(Config, Default, Clone, Debug, Serialize, Deserialize)]
+pub(super) struct SectionTimer {
+    #[config(deserialize_with = deserialize_str_or_num)]
+    pub OnBootSec: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    pub OnUnitActiveSec: Option<String>,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    pub OnUnitInactiveSec: Option<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub OnCalendar: Option<Vec<String>>,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    #[config(default = "1min")]
+    pub AccuracySec: String,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    #[config(default = "0")]
+    pub RandomizedDelaySec: String,
+    #[config(deserialize_with = deserialize_str_or_value)]
+    #[config(default = false)]
+    pub Persistent: bool,
+    pub Unit: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct TimerReConf {
+    timer: SectionTimer,
+    unit: Option<String>,
+}
+
+impl TimerReConf {
+    fn new(timerr: &SectionTimer, unit: Option<String>) -> TimerReConf {
+        TimerReConf {
+            timer: timerr.clone(),
+            unit,
+        }
+    }
+}
+
+// Waiting, Running and Elapsed are all active states of the unit:
+// Waiting: waiting for the next elapse, Running: the triggered unit is running,
+// Elapsed: no more elapse will happen until the triggered unit changes
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Serialize, Deserialize, EnumDisplay)]
+pub(super) enum TimerState {
+    Dead,
+    Waiting,
+    Running,
+    Elapsed,
+    Failed,
+    StateMax,
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Serialize, Deserialize, EnumDisplay)]
+pub(super) enum TimerResult {
+    Success,
+    FailureResources,
+}
+
+/// the time point in both the realtime clock and the monotonic clock, in usec
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone, Serialize, Deserialize)]
+pub(super) struct DualTimestamp {
+    pub realtime: u64,
+    pub monotonic: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct TimerReMng {
+    state: TimerState,
+    result: TimerResult,
+    last_trigger: DualTimestamp,
+    activated: u64,
+    target_active: u64,
+    target_inactive: u64,
+}
+
+impl TimerReMng {
+    fn new(
+        state: TimerState,
+        result: TimerResult,
+        last_trigger: DualTimestamp,
+        activated: u64,
+        target_active: u64,
+        target_inactive: u64,
+    ) -> TimerReMng {
+        TimerReMng {
+            state,
+            result,
+            last_trigger,
+            activated,
+            target_active,
+            target_inactive,
+        }
+    }
+}
+
+struct TimerReDb<K, V>(ReDb<K, V>);
+
+pub(super) struct TimerRe {
+    // database: multi-instance(N)
+    conf: Rc<TimerReDb<String, TimerReConf>>, // RELI_DB_HTIMER_CONF; key: unit_id, data: config+unit;
+    mng: Rc<TimerReDb<String, TimerReMng>>, // RELI_DB_HTIMER_MNG; key: unit_id, data: state+result+timestamps;
+}
+
+impl TimerRe {
+    pub(super) fn new(relir: &Reliability) -> TimerRe {
+        let conf = Rc::new(TimerReDb(ReDb::new(relir, RELI_DB_HTIMER_CONF)));
+        let mng = Rc::new(TimerReDb(ReDb::new(relir, RELI_DB_HTIMER_MNG)));
+        let rentry = TimerRe { conf, mng };
+        rentry.register(relir);
+        rentry
+    }
+
+    pub(super) fn conf_insert(&self, unit_id: &str, timer: &SectionTimer, unit: Option<String>) {
+        let conf = TimerReConf::new(timer, unit);
+        self.conf.0.insert(unit_id.to_string(), conf);
+    }
+
+    pub(super) fn conf_get(&self, unit_id: &String) -> Option<(SectionTimer, Option<String>)> {
+        let conf = self.conf.0.get(unit_id);
+        conf.map(|c| (c.timer, c.unit))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn mng_insert(
+        &self,
+        unit_id: &str,
+        state: TimerState,
+        result: TimerResult,
+        last_trigger: DualTimestamp,
+        activated: u64,
+        target_active: u64,
+        target_inactive: u64,
+    ) {
+        let mng = TimerReMng::new(
+            state,
+            result,
+            last_trigger,
+            activated,
+            target_active,
+            target_inactive,
+        );
+        self.mng.0.insert(unit_id.to_string(), mng);
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub(super) fn mng_get(
+        &self,
+        unit_id: &String,
+    ) -> Option<(TimerState, TimerResult, DualTimestamp, u64, u64, u64)> {
+        let mng = self.mng.0.get(unit_id);
+        mng.map(|m| {
+            (
+                m.state,
+                m.result,
+                m.last_trigger,
+                m.activated,
+                m.target_active,
+                m.target_inactive,
+            )
+        })
+    }
+
+    fn register(&self, relir: &Reliability) {
+        // rel-db: RELI_DB_HTIMER_CONF
+        let db = Rc::clone(&self.conf);
+        relir.history_db_register(RELI_DB_HTIMER_CONF, db);
+
+        // rel-db: RELI_DB_HTIMER_MNG
+        let db = Rc::clone(&self.mng);
+        relir.history_db_register(RELI_DB_HTIMER_MNG, db);
+    }
+}
+
+impl ReDbTable for TimerReDb<String, TimerReConf> {
+    fn clear(&self, wtxn: &mut ReDbRwTxn) {
+        self.0.do_clear(wtxn);
+    }
+
+    fn export(&self, db_wtxn: &mut ReDbRwTxn) {
+        self.0.cache_2_db(db_wtxn);
+    }
+
+    fn import<'a>(&self, db_rtxn: &ReDbRoTxn) {
+        self.0.db_2_cache(db_rtxn);
+    }
+
+    fn ignore_set(&self, ignore: bool) {
+        self.0.set_ignore(ignore);
+    }
+}
+
+impl ReDbTable for TimerReDb<String, TimerReMng> {
+    fn clear(&self, wtxn: &mut ReDbRwTxn) {
+        self.0.do_clear(wtxn);
+    }
+
+    fn export(&self, db_wtxn: &mut ReDbRwTxn) {
+        self.0.cache_2_db(db_wtxn);
+    }
+
+    fn import<'a>(&self, db_rtxn: &ReDbRoTxn) {
+        self.0.db_2_cache(db_rtxn);
+    }
+
+    fn ignore_set(&self, ignore: bool) {
+        self.0.set_ignore(ignore);
+    }
+}
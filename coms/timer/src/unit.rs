@@ -0,0 +1,218 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! TimerUnit is the entrance of the sub unit，implement the trait UnitObj,UnitMngUtil and UnitSubClass.
+//! Trait UnitObj defines the behavior of the sub unit.
+//! Trait UnitMngUtil is used to attach the Unitmanager to the sub unit.
+//! Trait UnitSubClass implement the convert from sub unit to UnitObj.
+use super::base::PLUGIN_NAME;
+use super::comm::TimerUnitComm;
+use super::config::TimerConfig;
+use super::mng::TimerMng;
+use basic::logger;
+use basic::special::{SHUTDOWN_TARGET, SYSINIT_TARGET, TIMERS_TARGET};
+use nix::sys::wait::WaitStatus;
+use std::cell::RefCell;
+use std::{path::PathBuf, rc::Rc};
+use sysmaster::error::*;
+use sysmaster::rel::{ReStation, Reliability};
+use sysmaster::serialize::section_properties;
+use sysmaster::unit::{
+    SubUnit, UmIf, UnitActiveState, UnitBase, UnitDependencyMask, UnitMngUtil, UnitRelations,
+};
+
+struct Timer {
+    owner: RefCell<Option<Rc<dyn UnitBase>>>,
+    um: Rc<dyn UmIf>,
+    comm: Rc<TimerUnitComm>,
+    config: Rc<TimerConfig>,
+    mng: Rc<TimerMng>,
+}
+
+impl ReStation for Timer {
+    // no input, no compensate
+
+    // data
+    fn db_map(&self, reload: bool) {
+        self.config.db_map(reload);
+        self.mng.db_map(reload);
+    }
+
+    fn db_insert(&self) {
+        self.config.db_insert();
+        self.mng.db_insert();
+    }
+
+    // reload: entry-only
+    fn entry_coldplug(&self) {
+        self.mng.entry_coldplug();
+    }
+
+    fn entry_clear(&self) {
+        self.mng.entry_clear();
+    }
+}
+
+impl Timer {
+    fn new(um_if: Rc<dyn UmIf>) -> Timer {
+        let _comm = Rc::new(TimerUnitComm::new());
+        let _config = Rc::new(TimerConfig::new(&_comm));
+        let _mng = Rc::new(TimerMng::new(&_comm, &_config));
+        _mng.attach_timer(Rc::downgrade(&_mng));
+        Timer {
+            owner: RefCell::new(None),
+            um: Rc::clone(&um_if),
+            comm: Rc::clone(&_comm),
+            config: _config,
+            mng: _mng,
+        }
+    }
+
+    pub(self) fn owner(&self) -> Option<Rc<dyn UnitBase>> {
+        if let Some(ref unit) = *self.owner.borrow() {
+            Some(Rc::clone(unit))
+        } else {
+            None
+        }
+    }
+
+    pub(self) fn add_trigger_dependencies(&self) -> Result<()> {
+        let (u, unit) = match (self.owner(), self.config.unit()) {
+            (Some(u), Some(unit)) => (u, unit),
+            _ => return Ok(()),
+        };
+
+        self.um.unit_add_two_dependency(
+            u.id(),
+            UnitRelations::UnitBefore,
+            UnitRelations::UnitTriggers,
+            &unit,
+            true,
+            UnitDependencyMask::Implicit,
+        )?;
+        Ok(())
+    }
+
+    pub(self) fn add_default_dependencies(&self) -> Result<()> {
+        let u = match self.owner() {
+            None => return Ok(()),
+            Some(u) => u,
+        };
+
+        if !u.default_dependencies() {
+            return Ok(());
+        }
+
+        log::debug!("Adding default dependencies for timer: {}", u.id());
+        self.um.unit_add_two_dependency(
+            u.id(),
+            UnitRelations::UnitAfter,
+            UnitRelations::UnitRequires,
+            SYSINIT_TARGET,
+            true,
+            UnitDependencyMask::Default,
+        )?;
+        self.um.unit_add_two_dependency(
+            u.id(),
+            UnitRelations::UnitBefore,
+            UnitRelations::UnitConflicts,
+            SHUTDOWN_TARGET,
+            true,
+            UnitDependencyMask::Default,
+        )?;
+        self.um.unit_add_dependency(
+            u.id(),
+            UnitRelations::UnitBefore,
+            TIMERS_TARGET,
+            true,
+            UnitDependencyMask::Default,
+        )?;
+        Ok(())
+    }
+}
+
+impl SubUnit for Timer {
+    fn load(&self, paths: Vec<PathBuf>) -> Result<()> {
+        log::debug!("load for timer");
+        self.config.load(paths, true)?;
+        self.add_trigger_dependencies()?;
+        self.add_default_dependencies()
+    }
+
+    fn current_active_state(&self) -> UnitActiveState {
+        self.mng.to_unit_state()
+    }
+
+    fn get_subunit_state(&self) -> String {
+        self.mng.get_state()
+    }
+
+    fn properties(&self) -> Vec<(String, String)> {
+        section_properties(&self.config.config_data().borrow().Timer)
+    }
+
+    fn attach_unit(&self, unit: Rc<dyn UnitBase>) {
+        self.comm.attach_unit(Rc::clone(&unit));
+        self.owner.replace(Some(unit));
+        self.db_insert();
+    }
+
+    fn init(&self) {}
+
+    fn done(&self) {}
+
+    fn dump(&self) {}
+
+    fn start(&self) -> Result<()> {
+        //if current state is not valid, just return.
+        self.mng.start_check()?;
+
+        self.mng.start_action();
+        Ok(())
+    }
+
+    fn stop(&self, force: bool) -> Result<()> {
+        if !force {
+            self.mng.stop_check()?;
+        }
+
+        self.mng.stop_action();
+        Ok(())
+    }
+
+    fn kill(&self) {}
+
+    fn release_resources(&self) {}
+
+    fn sigchld_events(&self, _wait_status: WaitStatus) {}
+
+    fn reset_failed(&self) {
+        self.mng.reset_failed()
+    }
+
+    fn trigger_notify(&self, other: &str) {
+        self.mng.trigger_notify(other)
+    }
+}
+
+impl UnitMngUtil for Timer {
+    fn attach_um(&self, _um: Rc<dyn UmIf>) {
+        self.comm.attach_um(_um);
+    }
+
+    fn attach_reli(&self, reli: Rc<Reliability>) {
+        self.comm.attach_reli(reli);
+    }
+}
+
+use sysmaster::declure_unitobj_plugin_with_param;
+declure_unitobj_plugin_with_param!(Timer, Timer::new, PLUGIN_NAME);
@@ -20,6 +20,7 @@ use std::cell::RefCell;
 use std::fmt;
 use std::os::unix::prelude::RawFd;
 use std::rc::{Rc, Weak};
+use std::time::Instant;
 use sysmaster::error::*;
 use sysmaster::rel::Reliability;
 use sysmaster::unit::{UnitActiveState, UnitNotifyFlags};
@@ -160,6 +161,7 @@ pub(crate) struct JobInfo {
     pub(crate) attr: JobAttr,
     pub(crate) run_kind: JobKind,
     pub(crate) stage: JobStage,
+    pub(crate) since: Instant,
 }
 
 impl fmt::Debug for JobInfo {
@@ -184,6 +186,7 @@ impl JobInfo {
             attr: job.attr(),
             run_kind: job.run_kind(),
             stage: job.get_stage(),
+            since: job.since,
         }
     }
 }
@@ -209,6 +212,7 @@ pub(super) struct Job {
     /* status: self-generated */
     run_kind: RefCell<JobKind>,
     stage: RefCell<JobStage>,
+    since: Instant,
 }
 
 impl PartialEq for Job {
@@ -256,6 +260,7 @@ impl Job {
             attr: RefCell::new(JobAttr::new(false, false, false, false)),
             run_kind: RefCell::new(job_rkind_new(kind)),
             stage: RefCell::new(JobStage::Init),
+            since: Instant::now(),
         }
     }
 
@@ -187,7 +187,6 @@ impl JobManager {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub(crate) fn remove(&self, id: u32) -> Result<()> {
         self.data.remove(id)?;
         self.try_enable();
@@ -199,6 +198,10 @@ impl JobManager {
         self.data.get_jobinfo(id)
     }
 
+    pub(crate) fn get_all_jobinfo(&self) -> Vec<JobInfo> {
+        self.data.jobs.get_all()
+    }
+
     pub(crate) fn has_stop_job(&self, unit: &Rc<UnitX>) -> bool {
         self.data.get_suspends(unit).is_some()
     }
@@ -286,6 +289,7 @@ struct JobManagerData {
     // associated objects
     reli: Rc<Reliability>,
     db: Rc<UnitDb>,
+    dm: Rc<DataManager>,
 
     // owned objects
     // control
@@ -318,6 +322,7 @@ impl JobManagerData {
         JobManagerData {
             reli: Rc::clone(relir),
             db: Rc::clone(dbr),
+            dm: Rc::clone(dmr),
 
             rentry: Rc::clone(&_rentry),
             ja: JobAlloc::new(relir, &_rentry, eventsr, dmr),
@@ -375,10 +380,8 @@ impl JobManagerData {
         self.jobs.reshuffle();
 
         // stat
-        self.stat
-            .update_changes(&(&triggers, &Vec::new(), &Vec::new()));
-        self.stat
-            .update_changes(&(&suspends, &Vec::new(), &Vec::new()));
+        self.update_changes(&(&triggers, &Vec::new(), &Vec::new()));
+        self.update_changes(&(&suspends, &Vec::new(), &Vec::new()));
         self.stat.clear_cnt(); // no history
     }
 
@@ -412,8 +415,7 @@ impl JobManagerData {
         self.stage.clear();
 
         // update statistics
-        self.stat
-            .update_changes(&(&add_jobs, &del_jobs, &update_jobs));
+        self.update_changes(&(&add_jobs, &del_jobs, &update_jobs));
 
         // output
         affect.record(&(add_jobs, del_jobs, update_jobs));
@@ -447,7 +449,7 @@ impl JobManagerData {
                 (cnt, _) = cnt.overflowing_add(1); // ++
 
                 // update statistics
-                self.stat.update_change(&(&None, &merge_trigger, &None));
+                self.update_change(&(&None, &merge_trigger, &None));
 
                 // try to finish it now in two case, and the case coming from unit has higher priority
                 // case 1. the job has been finished synchronously in context, which is derived from outside('unit') directly.
@@ -500,7 +502,6 @@ impl JobManagerData {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub(self) fn remove(&self, id: u32) -> Result<()> {
         assert!(!*self.running.borrow());
 
@@ -510,15 +511,12 @@ impl JobManagerData {
         }
         let job_info = jinfo.unwrap();
 
-        if self.jobs.is_trigger(id) {
-            return Err(Error::NotSupported);
-        }
-
-        if !self.jobs.is_suspend(id) {
+        if !self.jobs.is_trigger(id) && !self.jobs.is_suspend(id) {
             return Err(Error::Internal);
         }
 
-        // remove it from outside(command) directly
+        // remove it from outside(command) directly, the running(trigger) one is finished
+        // as cancelled, and the jobs depending on it are removed with 'Dependency'.
         self.do_remove(&job_info, JobResult::Cancelled, false);
         // mandatory removement is not considered a failure
 
@@ -555,9 +553,33 @@ impl JobManagerData {
         let (del_trigger, del_suspends) = self.jobs.remove_unit(unit);
 
         // update statistics
-        self.stat.update_change(&(&None, &del_trigger, &None));
-        self.stat
-            .update_changes(&(&Vec::new(), &del_suspends, &Vec::new()));
+        self.update_change(&(&None, &del_trigger, &None));
+        self.update_changes(&(&Vec::new(), &del_suspends, &Vec::new()));
+    }
+
+    // the jobs added or deleted are published to the data manager, besides the statistics.
+    #[allow(clippy::type_complexity)]
+    fn update_change(&self, change: &(&Option<Rc<Job>>, &Option<Rc<Job>>, &Option<Rc<Job>>)) {
+        let (add, del, _) = change;
+        if let Some(job) = add {
+            self.dm.insert_job_info(job.get_id(), JobInfo::map(job));
+        }
+        if let Some(job) = del {
+            self.dm.remove_job_info(job.get_id());
+        }
+        self.stat.update_change(change);
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn update_changes(&self, changes: &(&Vec<Rc<Job>>, &Vec<Rc<Job>>, &Vec<Rc<Job>>)) {
+        let (adds, dels, _) = changes;
+        for job in adds.iter() {
+            self.dm.insert_job_info(job.get_id(), JobInfo::map(job));
+        }
+        for job in dels.iter() {
+            self.dm.remove_job_info(job.get_id());
+        }
+        self.stat.update_changes(changes);
     }
 
     fn do_try_finish(
@@ -641,8 +663,8 @@ impl JobManagerData {
         }
 
         // update statistics
-        self.stat.update_change(&(&None, &del_trigger, &None));
-        self.stat.update_change(&(&None, &del_suspend, &None));
+        self.update_change(&(&None, &del_trigger, &None));
+        self.update_change(&(&None, &del_suspend, &None));
         del_one
     }
 
@@ -673,8 +695,7 @@ impl JobManagerData {
         }
 
         // update statistics
-        self.stat
-            .update_changes(&(&Vec::new(), &del_rel, &Vec::new()));
+        self.update_changes(&(&Vec::new(), &del_rel, &Vec::new()));
     }
 
     fn simulate_job_notify(&self, unit: &Rc<UnitX>, os: UnitActiveState, ns: UnitActiveState) {
@@ -1051,6 +1072,34 @@ mod tests {
         assert!(ret.is_ok());
     }
 
+    #[test]
+    fn job_remove_running() {
+        let relation = Some(UnitRelations::UnitRequires);
+        let (event, reli, db, unit_test1, unit_test2) = prepare_unit_multi(relation);
+        let jm = JobManager::new(&event, &reli, &db, &Rc::new(DataManager::new()));
+
+        // the start job of test2 is running, and the one of test1 requiring it is waiting
+        let attr = JobAttr::new(false, false, false, false);
+        jm.data
+            .rentry
+            .trigger_insert(unit_test2.id(), JobKind::Start, &attr);
+        jm.data
+            .rentry
+            .suspends_insert(unit_test1.id(), JobKind::Start, &attr);
+        let conf = JobConf::new(&unit_test2, JobKind::Start);
+        let trigger = jm.data.jobs.rentry_map_trigger(&jm.data.ja, &conf);
+        let conf = JobConf::new(&unit_test1, JobKind::Start);
+        let suspend = jm.data.jobs.rentry_map_suspend(&jm.data.ja, &conf);
+        jm.data.jobs.reshuffle();
+        assert_eq!(jm.data.jobs.len(), 2);
+
+        let ret = jm.remove(trigger.get_id());
+        assert!(ret.is_ok());
+        assert_eq!(jm.data.jobs.len(), 0);
+        assert_eq!(trigger.get_stage(), JobStage::End(JobResult::Cancelled));
+        assert_eq!(suspend.get_stage(), JobStage::End(JobResult::Dependency));
+    }
+
     #[test]
     fn job_get_jobinfo() {
         let (event, reli, db, unit_test1, _unit_test2) = prepare_unit_multi(None);
@@ -1078,6 +1127,29 @@ mod tests {
         assert_eq!(lkup_info.stage, job_info.stage);
     }
 
+    #[test]
+    fn job_get_all_jobinfo() {
+        let (event, reli, db, unit_test1, unit_test2) = prepare_unit_multi(None);
+        let jm = JobManager::new(&event, &reli, &db, &Rc::new(DataManager::new()));
+        let mut affect = JobAffect::new(false);
+
+        // nothing exists
+        assert!(jm.get_all_jobinfo().is_empty());
+
+        // something exists
+        let conf = JobConf::new(&unit_test2, JobKind::Stop);
+        let ret = jm.exec(&conf, JobMode::Replace, &mut affect);
+        assert!(ret.is_ok());
+        let conf = JobConf::new(&unit_test1, JobKind::Start);
+        let ret = jm.exec(&conf, JobMode::Replace, &mut affect);
+        assert!(ret.is_ok());
+        let jobs = jm.get_all_jobinfo();
+        assert_eq!(jobs.len(), 2);
+        assert!(jobs[0].id < jobs[1].id);
+        assert_eq!(jobs[0].unit.id(), unit_test2.id());
+        assert_eq!(jobs[1].kind, JobKind::Start);
+    }
+
     #[test]
     fn job_has_stop_job() {
         let (event, reli, db, unit_test1, unit_test2) = prepare_unit_multi(None);
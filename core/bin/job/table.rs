@@ -255,6 +255,17 @@ impl JobTable {
         self.t_id.borrow().get(&id).map(|job| JobInfo::map(job))
     }
 
+    pub(super) fn get_all(&self) -> Vec<JobInfo> {
+        let mut jobs: Vec<JobInfo> = self
+            .t_id
+            .borrow()
+            .values()
+            .map(|job| JobInfo::map(job))
+            .collect();
+        jobs.sort_by_key(|job| job.id);
+        jobs
+    }
+
     pub(super) fn get_suspend(&self, unit: &UnitX, kind: JobKind) -> Option<JobInfo> {
         self.t_unit
             .borrow()
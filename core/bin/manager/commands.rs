@@ -11,41 +11,61 @@
 // See the Mulan PSL v2 for more details.
 
 use cmdproto::proto::execute::ExecuterAction;
-use cmdproto::proto::ProstServerStream;
-use event::{EventType, Events, Source};
-use std::net::{SocketAddr, TcpListener};
+use cmdproto::proto::frame::{handle_request, take_frame};
+use cmdproto::proto::SCTL_SOCKET;
+use event::{EventState, EventType, Events, Source};
+use nix::sys::socket::{self, sockopt, UnixCredentials};
+use std::cell::{Cell, RefCell};
+use std::fs::{self, Permissions};
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::fs::PermissionsExt;
 use std::os::unix::io::RawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::rc::Weak;
 use std::{os::unix::prelude::AsRawFd, rc::Rc};
 use sysmaster::rel::{ReliLastFrame, Reliability};
 
+/// the max number of the connections being served at the same time
+const MAX_CONNECTIONS: usize = 256;
+/// reading buffer size of the connection
+const READ_BUF_SIZE: usize = 4096;
+
 pub(super) struct Commands<T> {
     // associated objects
     reli: Rc<Reliability>,
     command_action: Rc<T>,
 
     // owned objects
-    fd: TcpListener,
+    fd: UnixListener,
+    connections: Rc<Cell<usize>>,
 }
 
 impl<T> Commands<T> {
     pub(super) fn new(relir: &Rc<Reliability>, comm_action: T) -> Self {
-        let addrs = [
-            SocketAddr::from(([127, 0, 0, 1], 9526)),
-            SocketAddr::from(([127, 0, 0, 1], 9527)),
-        ];
-        let fd = TcpListener::bind(&addrs[..]).unwrap();
+        let sock_path = Path::new(SCTL_SOCKET);
+        if let Some(parent) = sock_path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        if let Err(e) = nix::unistd::unlink(sock_path) {
+            log::debug!("unlink path failed: {:?}, error: {}", sock_path, e);
+        }
+        let fd = UnixListener::bind(sock_path).unwrap();
+        /* everyone can connect, the requests are authorized by the peer credentials. */
+        fs::set_permissions(sock_path, Permissions::from_mode(0o666)).unwrap();
         fd.set_nonblocking(true).expect("set non-blocking");
         Commands {
             reli: Rc::clone(relir),
             command_action: Rc::new(comm_action),
             fd,
+            connections: Rc::new(Cell::new(0)),
         }
     }
 }
 
 impl<T> Source for Commands<T>
 where
-    T: ExecuterAction,
+    T: ExecuterAction + 'static,
 {
     fn event_type(&self) -> EventType {
         EventType::Io
@@ -55,21 +75,52 @@ where
         (libc::EPOLLIN) as u32
     }
 
-    fn dispatch(&self, _e: &Events) -> i32 {
-        println!("Dispatching Command!");
+    fn dispatch(&self, e: &Events) -> i32 {
+        log::debug!("Dispatching Command!");
 
-        self.reli.set_last_frame1(ReliLastFrame::CmdOp as u32);
-        match self.fd.incoming().next() {
-            None => println!("None CommandRequest!"),
-            Some(stream) => {
-                println!("{stream:?}");
-                if let Ok(s) = stream {
-                    let dispatch = ProstServerStream::new(s, self.command_action.clone());
-                    dispatch.process().unwrap();
+        let stream = match self.fd.accept() {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                if err.kind() != ErrorKind::WouldBlock {
+                    log::error!("Failed to accept the command connection: {}", err);
                 }
+                return 0;
             }
+        };
+        log::debug!("Accepted the command connection: {:?}", stream);
+
+        if self.connections.get() >= MAX_CONNECTIONS {
+            log::warn!("Too many command connections, refusing the new one.");
+            return 0;
         }
-        self.reli.clear_last_frame();
+        if let Err(err) = stream.set_nonblocking(true) {
+            log::error!("Failed to set the command connection non-blocking: {}", err);
+            return 0;
+        }
+        let cred = match socket::getsockopt(stream.as_raw_fd(), sockopt::PeerCredentials) {
+            Ok(v) => Some(v),
+            Err(err) => {
+                log::error!("Failed to get the credentials of the peer: {}", err);
+                None
+            }
+        };
+
+        let conn = CommandConnection::new(
+            &self.reli,
+            &self.command_action,
+            &self.connections,
+            stream,
+            cred,
+        );
+        let source: Rc<dyn Source> = conn.clone();
+        if let Err(err) = e
+            .add_source(source.clone())
+            .and_then(|_| e.set_enabled(source, EventState::On))
+        {
+            log::error!("Failed to watch the command connection: {}", err);
+            return 0;
+        }
+        *conn.me.borrow_mut() = Rc::downgrade(&conn);
 
         0
     }
@@ -87,3 +138,195 @@ where
         10i8
     }
 }
+
+/// one accepted connection of the command socket, the request is read and the response is
+/// written without blocking, so a client that stops sending or receiving can't stall sysmaster
+struct CommandConnection<T> {
+    // associated objects
+    reli: Rc<Reliability>,
+    command_action: Rc<T>,
+    connections: Rc<Cell<usize>>,
+    me: RefCell<Weak<CommandConnection<T>>>,
+
+    // owned objects
+    stream: UnixStream,
+    cred: Option<UnixCredentials>,
+    rbuf: RefCell<Vec<u8>>,
+    wbuf: RefCell<Vec<u8>>,
+    subscribed: Cell<bool>,
+}
+
+impl<T> CommandConnection<T>
+where
+    T: ExecuterAction + 'static,
+{
+    fn new(
+        relir: &Rc<Reliability>,
+        comm_action: &Rc<T>,
+        connections: &Rc<Cell<usize>>,
+        stream: UnixStream,
+        cred: Option<UnixCredentials>,
+    ) -> Rc<Self> {
+        connections.set(connections.get() + 1);
+        Rc::new(CommandConnection {
+            reli: Rc::clone(relir),
+            command_action: Rc::clone(comm_action),
+            connections: Rc::clone(connections),
+            me: RefCell::new(Weak::new()),
+            stream,
+            cred,
+            rbuf: RefCell::new(Vec::new()),
+            wbuf: RefCell::new(Vec::new()),
+            subscribed: Cell::new(false),
+        })
+    }
+
+    /// read what is available, return false if the connection is over
+    fn read(&self) -> bool {
+        let mut buf = [0_u8; READ_BUF_SIZE];
+        loop {
+            match (&self.stream).read(&mut buf) {
+                Ok(0) => return false,
+                Ok(n) => self.rbuf.borrow_mut().extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return true,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    log::error!("Failed to read the command connection: {}", e);
+                    return false;
+                }
+            }
+        }
+    }
+
+    /// handle the request once it is received completely, return false if it's invalid
+    fn process(&self) -> bool {
+        let frame = match take_frame(&mut self.rbuf.borrow_mut()) {
+            Ok(Some(v)) => v,
+            Ok(None) => return true,
+            Err(e) => {
+                log::error!("Failed to receive the command: {}", e);
+                return false;
+            }
+        };
+
+        self.reli.set_last_frame1(ReliLastFrame::CmdOp as u32);
+        let ret = handle_request(&frame, Rc::clone(&self.command_action), self.cred);
+        self.reli.clear_last_frame();
+        match ret {
+            Ok((res, subscribed)) => {
+                self.wbuf.borrow_mut().extend_from_slice(&res);
+                self.subscribed.set(subscribed);
+                true
+            }
+            Err(e) => {
+                log::error!("Failed to process the command: {}", e);
+                false
+            }
+        }
+    }
+
+    /// write what is pending, return false if the connection is over
+    fn write(&self) -> bool {
+        let mut wbuf = self.wbuf.borrow_mut();
+        while !wbuf.is_empty() {
+            match (&self.stream).write(&wbuf) {
+                Ok(n) => {
+                    wbuf.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return true,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    log::error!("Failed to write the command connection: {}", e);
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn close(&self, e: &Events) {
+        if let Some(me) = self.me.borrow().upgrade() {
+            if let Err(err) = e.del_source(me) {
+                log::error!("Failed to remove the command connection: {}", err);
+            }
+        }
+    }
+}
+
+impl<T> Drop for CommandConnection<T> {
+    fn drop(&mut self) {
+        self.connections.set(self.connections.get() - 1);
+    }
+}
+
+impl<T> Source for CommandConnection<T>
+where
+    T: ExecuterAction + 'static,
+{
+    fn event_type(&self) -> EventType {
+        EventType::Io
+    }
+
+    fn epoll_event(&self) -> u32 {
+        if self.wbuf.borrow().is_empty() {
+            libc::EPOLLIN as u32
+        } else {
+            libc::EPOLLOUT as u32
+        }
+    }
+
+    fn dispatch(&self, e: &Events) -> i32 {
+        let responding = !self.wbuf.borrow().is_empty();
+        if !responding {
+            let open = self.read();
+            if !self.process() || (!open && self.wbuf.borrow().is_empty()) {
+                self.close(e);
+                return 0;
+            }
+            if self.wbuf.borrow().is_empty() {
+                /* the request is not received completely yet */
+                return 0;
+            }
+        }
+
+        if !self.write() {
+            self.close(e);
+            return 0;
+        }
+
+        if !self.wbuf.borrow().is_empty() {
+            /* wait until the response can be written */
+            if !responding {
+                if let Some(me) = self.me.borrow().upgrade() {
+                    let source: Rc<dyn Source> = me;
+                    let _ = e.set_enabled(source.clone(), EventState::Off);
+                    let _ = e.set_enabled(source, EventState::On);
+                }
+            }
+            return 0;
+        }
+
+        /* the response is sent, the connection is done unless the client subscribes */
+        if self.subscribed.get() {
+            match self.stream.try_clone() {
+                Ok(stream) => self.command_action.subscribe(stream),
+                Err(err) => log::error!("Failed to keep the subscriber: {}", err),
+            }
+        }
+        self.close(e);
+        0
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+
+    fn fd(&self) -> RawFd {
+        self.stream.as_raw_fd()
+    }
+
+    fn priority(&self) -> i8 {
+        10i8
+    }
+}
@@ -12,7 +12,12 @@
 //
 #![allow(non_snake_case)]
 
+use basic::conf_parser::{parse_size, Base};
+use basic::time_util::{parse_timespan, USEC_PER_MINUTE};
 use confique::Config;
+use nix::sys::resource::Resource;
+use sysmaster::exec::{deserialize_rlimit_sec, deserialize_rlimit_usec, rlimits_from, Rlimit};
+use sysmaster::serialize::{deserialize_str_or_num, DeserializeWith};
 
 pub const SYSTEM_CONFIG: &str = "/etc/sysmaster/system.toml";
 
@@ -29,6 +34,58 @@ pub struct ManagerConfig {
     pub LogTarget: String,
     #[config(default = "")]
     pub LogFile: String,
+    #[config(default = "")]
+    pub ControlGroup: String,
+
+    #[config(default = false)]
+    pub LogCollector: bool,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    #[config(default = "1M")]
+    pub LogCollectorMaxSize: String,
+    #[config(default = 4)]
+    pub LogCollectorMaxFiles: u32,
+
+    #[config(deserialize_with = deserialize_str_or_num)]
+    #[config(default = "0")]
+    pub RuntimeWatchdogSec: String,
+    #[config(deserialize_with = deserialize_str_or_num)]
+    #[config(default = "10min")]
+    pub RebootWatchdogSec: String,
+    #[config(default = "")]
+    pub WatchdogDevice: String,
+
+    #[config(deserialize_with = deserialize_rlimit_sec)]
+    pub DefaultLimitCPU: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitFSIZE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitDATA: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitSTACK: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitCORE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitRSS: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitNOFILE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitAS: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitNPROC: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitMEMLOCK: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitLOCKS: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitSIGPENDING: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitMSGQUEUE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitNICE: Option<Rlimit>,
+    #[config(deserialize_with = Rlimit::deserialize_with)]
+    pub DefaultLimitRTPRIO: Option<Rlimit>,
+    #[config(deserialize_with = deserialize_rlimit_usec)]
+    pub DefaultLimitRTTIME: Option<Rlimit>,
 }
 
 impl ManagerConfig {
@@ -41,6 +98,75 @@ impl ManagerConfig {
             Err(_) => ManagerConfig::default(),
         }
     }
+
+    /// the size limit of each log file of the log collector
+    pub fn log_collector_max_size(&self) -> u64 {
+        match parse_size(&self.LogCollectorMaxSize, Base::Binary) {
+            Ok(v) if v > 0 => v,
+            _ => {
+                log::warn!(
+                    "Invalid LogCollectorMaxSize {}, using the default 1M.",
+                    self.LogCollectorMaxSize
+                );
+                1024 * 1024
+            }
+        }
+    }
+
+    /// the timeout of the hardware watchdog while running, 0 means disabled, in usec
+    pub fn runtime_watchdog_usec(&self) -> u64 {
+        parse_watchdog_sec("RuntimeWatchdogSec", &self.RuntimeWatchdogSec, 0)
+    }
+
+    /// the timeout of the hardware watchdog during reboot, 0 means disabled, in usec
+    pub fn reboot_watchdog_usec(&self) -> u64 {
+        parse_watchdog_sec(
+            "RebootWatchdogSec",
+            &self.RebootWatchdogSec,
+            10 * USEC_PER_MINUTE,
+        )
+    }
+
+    /// the resource limits applied to all spawned processes, unless overridden by the unit
+    pub fn default_rlimits(&self) -> Vec<(Resource, Rlimit)> {
+        rlimits_from([
+            self.DefaultLimitCPU,
+            self.DefaultLimitFSIZE,
+            self.DefaultLimitDATA,
+            self.DefaultLimitSTACK,
+            self.DefaultLimitCORE,
+            self.DefaultLimitRSS,
+            self.DefaultLimitNOFILE,
+            self.DefaultLimitAS,
+            self.DefaultLimitNPROC,
+            self.DefaultLimitMEMLOCK,
+            self.DefaultLimitLOCKS,
+            self.DefaultLimitSIGPENDING,
+            self.DefaultLimitMSGQUEUE,
+            self.DefaultLimitNICE,
+            self.DefaultLimitRTPRIO,
+            self.DefaultLimitRTTIME,
+        ])
+    }
+}
+
+fn parse_watchdog_sec(key: &str, value: &str, default: u64) -> u64 {
+    if value == "off" {
+        return 0;
+    }
+
+    match parse_timespan(value) {
+        Ok(v) => v,
+        Err(_) => {
+            log::warn!(
+                "Invalid {} {}, using the default {}us.",
+                key,
+                value,
+                default
+            );
+            default
+        }
+    }
 }
 
 impl Default for ManagerConfig {
@@ -51,6 +177,29 @@ impl Default for ManagerConfig {
             LogLevel: log::LevelFilter::Debug,
             LogTarget: "console".to_string(),
             LogFile: String::new(),
+            ControlGroup: String::new(),
+            LogCollector: false,
+            LogCollectorMaxSize: "1M".to_string(),
+            LogCollectorMaxFiles: 4,
+            RuntimeWatchdogSec: "0".to_string(),
+            RebootWatchdogSec: "10min".to_string(),
+            WatchdogDevice: String::new(),
+            DefaultLimitCPU: None,
+            DefaultLimitFSIZE: None,
+            DefaultLimitDATA: None,
+            DefaultLimitSTACK: None,
+            DefaultLimitCORE: None,
+            DefaultLimitRSS: None,
+            DefaultLimitNOFILE: None,
+            DefaultLimitAS: None,
+            DefaultLimitNPROC: None,
+            DefaultLimitMEMLOCK: None,
+            DefaultLimitLOCKS: None,
+            DefaultLimitSIGPENDING: None,
+            DefaultLimitMSGQUEUE: None,
+            DefaultLimitNICE: None,
+            DefaultLimitRTPRIO: None,
+            DefaultLimitRTTIME: None,
         }
     }
 }
@@ -69,4 +218,21 @@ mod test {
         println!("{config:?}");
         assert_eq!(config.DefaultRestartSec, 100);
     }
+
+    #[test]
+    fn watchdog_sec() {
+        use basic::time_util::USEC_PER_SEC;
+
+        let mut config = ManagerConfig::default();
+        assert_eq!(config.runtime_watchdog_usec(), 0);
+        assert_eq!(config.reboot_watchdog_usec(), 10 * USEC_PER_MINUTE);
+
+        config.RuntimeWatchdogSec = "30s".to_string();
+        config.RebootWatchdogSec = "off".to_string();
+        assert_eq!(config.runtime_watchdog_usec(), 30 * USEC_PER_SEC);
+        assert_eq!(config.reboot_watchdog_usec(), 0);
+
+        config.RebootWatchdogSec = "invalid".to_string();
+        assert_eq!(config.reboot_watchdog_usec(), 10 * USEC_PER_MINUTE);
+    }
 }
@@ -0,0 +1,267 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! The generators are run before loading the units, at startup and on daemon-reload.
+//! Each generator is called with the normal, early and late output directories as its arguments,
+//! and the units it writes there are found through the unit search path.
+use basic::path_lookup::{LookupPaths, SYSTEM_GENERATOR_PATH};
+use nix::errno::Errno;
+use nix::poll::{poll, PollFd, PollFlags};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{ErrorKind, Read};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// run all the generators in parallel, they share the timeout, so that the stuck generators
+/// can't block sysmaster longer than it, the failure of a generator doesn't stop the others
+pub(super) fn run_generators(lookup_path: &LookupPaths, timeout_usec: u64) {
+    let dirs = [
+        &lookup_path.generator,
+        &lookup_path.generator_early,
+        &lookup_path.generator_late,
+    ];
+    if dirs.iter().any(|d| d.is_empty()) {
+        return;
+    }
+
+    // the units generated last time are removed, they may be gone in the configuration,
+    // even if all the generators are removed
+    for dir in dirs {
+        if let Err(e) = fs::remove_dir_all(dir) {
+            if e.kind() != ErrorKind::NotFound {
+                log::warn!("Failed to remove generator directory {}: {}", dir, e);
+            }
+        }
+        if let Err(e) = fs::create_dir_all(dir) {
+            log::error!("Failed to create generator directory {}: {}", dir, e);
+            return;
+        }
+    }
+
+    let generators = find_generators(&SYSTEM_GENERATOR_PATH);
+    let deadline = Instant::now() + Duration::from_micros(timeout_usec);
+    run_all(&generators, &dirs, deadline);
+}
+
+fn run_all(generators: &[PathBuf], dirs: &[&String], deadline: Instant) {
+    let mut children = Vec::new();
+    for generator in generators {
+        log::debug!("Running generator {:?}", generator);
+        match spawn_generator(generator, dirs) {
+            Ok(child) => children.push((generator, child)),
+            Err(e) => log::error!("Generator {:?} failed: {}", generator, e),
+        }
+    }
+
+    for (generator, child) in children {
+        if let Err(e) = wait_generator(generator, child, deadline) {
+            log::error!("Generator {:?} failed: {}", generator, e);
+        }
+    }
+}
+
+/// the executables in the directories, the former directory overrides the latter with the same name
+fn find_generators(dirs: &[&str]) -> Vec<PathBuf> {
+    let mut names = HashSet::new();
+    let mut generators = Vec::new();
+    for dir in dirs {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            if !names.insert(entry.file_name()) {
+                continue;
+            }
+            // follow the symlink, a link to /dev/null masks the generator
+            let path = entry.path();
+            match fs::metadata(&path) {
+                Ok(m) if m.is_file() && m.permissions().mode() & 0o111 != 0 => {
+                    generators.push(path)
+                }
+                _ => log::debug!("Skipping non-executable generator {:?}", path),
+            }
+        }
+    }
+
+    generators.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+    generators
+}
+
+fn spawn_generator(generator: &Path, dirs: &[&String]) -> Result<Child, String> {
+    Command::new(generator)
+        .args(dirs)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to spawn: {e}"))
+}
+
+/// wait for the generator until the deadline, it's killed if the deadline passed
+fn wait_generator(generator: &Path, mut child: Child, deadline: Instant) -> Result<(), String> {
+    let name = generator
+        .file_name()
+        .map_or(String::new(), |n| n.to_string_lossy().to_string());
+    let finished = log_stderr(&mut child, &name, deadline);
+
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => return Err(format!("exited with {status}")),
+            Ok(None) if finished && Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(10))
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err("timed out".to_string());
+            }
+            Err(e) => return Err(format!("failed to wait: {e}")),
+        }
+    }
+}
+
+/// log the stderr of the generator line by line until it's closed, what is written before
+/// the deadline is logged even if the deadline passed, return false if it's not closed
+fn log_stderr(child: &mut Child, name: &str, deadline: Instant) -> bool {
+    let mut stderr = match child.stderr.take() {
+        Some(stderr) => stderr,
+        None => return true,
+    };
+
+    let mut buf = [0u8; 4096];
+    let mut line = Vec::new();
+    let log_line = |line: &mut Vec<u8>| {
+        if !line.is_empty() {
+            log::info!("{}: {}", name, String::from_utf8_lossy(line).trim_end());
+            line.clear();
+        }
+    };
+
+    loop {
+        let remain = deadline.saturating_duration_since(Instant::now());
+        let mut fds = [PollFd::new(stderr.as_raw_fd(), PollFlags::POLLIN)];
+        match poll(&mut fds, remain.as_millis().min(i32::MAX as u128) as i32) {
+            Ok(0) if remain.is_zero() => {
+                log_line(&mut line);
+                return false;
+            }
+            Ok(0) => continue,
+            Ok(_) => {}
+            Err(Errno::EINTR) => continue,
+            Err(e) => {
+                log::warn!("Failed to poll the stderr of {}: {}", name, e);
+                return true;
+            }
+        }
+
+        let n = match stderr.read(&mut buf) {
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(_) => 0,
+        };
+        if n == 0 {
+            log_line(&mut line);
+            return true;
+        }
+        for &c in &buf[..n] {
+            if c == b'\n' {
+                log_line(&mut line);
+            } else {
+                line.push(c);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_script(dir: &Path, name: &str, content: &str) {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_run_generators() {
+        let root = std::env::temp_dir().join(format!("generator-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let (high, low) = (root.join("etc"), root.join("lib"));
+        fs::create_dir_all(&high).unwrap();
+        fs::create_dir_all(&low).unwrap();
+
+        write_script(
+            &high,
+            "a",
+            "#!/bin/sh\necho a >&2\ntouch $1/a.service $2/a.target\n",
+        );
+        write_script(&low, "a", "#!/bin/sh\ntouch $1/overridden.service\n");
+        write_script(&low, "b", "#!/bin/sh\ntouch $3/b.service\nexit 1\n");
+        for name in ["c", "d", "e"] {
+            write_script(
+                &low,
+                name,
+                &format!("#!/bin/sh\nsleep 10\ntouch $1/{name}.service\n"),
+            );
+        }
+        fs::write(low.join("f"), "#!/bin/sh\ntouch $1/f.service\n").unwrap();
+
+        let generators = find_generators(&[high.to_str().unwrap(), low.to_str().unwrap()]);
+        assert_eq!(
+            generators,
+            vec![
+                high.join("a"),
+                low.join("b"),
+                low.join("c"),
+                low.join("d"),
+                low.join("e")
+            ]
+        );
+
+        let dirs: Vec<String> = ["normal", "early", "late"]
+            .iter()
+            .map(|d| root.join(d).to_string_lossy().to_string())
+            .collect();
+        for dir in &dirs {
+            fs::create_dir_all(dir).unwrap();
+        }
+        let dirs: Vec<&String> = dirs.iter().collect();
+
+        let deadline = Instant::now() + Duration::from_secs(1);
+        let child = spawn_generator(&generators[0], &dirs).unwrap();
+        assert!(wait_generator(&generators[0], child, deadline).is_ok());
+        let child = spawn_generator(&generators[1], &dirs).unwrap();
+        assert!(wait_generator(&generators[1], child, deadline).is_err());
+
+        // the stuck generators share the deadline, rather than waiting one by one
+        let start = Instant::now();
+        run_all(&generators, &dirs, start + Duration::from_secs(1));
+        assert!(start.elapsed() < Duration::from_millis(2500));
+
+        assert!(root.join("normal/a.service").exists());
+        assert!(root.join("early/a.target").exists());
+        assert!(root.join("late/b.service").exists());
+        assert!(!root.join("normal/overridden.service").exists());
+        for name in ["c", "d", "e"] {
+            assert!(!root.join(format!("normal/{name}.service")).exists());
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
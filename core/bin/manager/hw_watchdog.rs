@@ -0,0 +1,304 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! The hardware watchdog of the manager, the watchdog is fed from a timer in the main loop while running,
+//! and switched to the reboot timeout during the shutdown, so that a hung shutdown still resets the system.
+use basic::time_util::USEC_PER_SEC;
+use event::{EventState, EventType, Events, Source};
+use std::cell::RefCell;
+use std::os::unix::prelude::RawFd;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+use watchdog::{HardwareWatchdog, Watchdog};
+
+pub(super) struct RuntimeWatchdog {
+    sub: Rc<RuntimeWatchdogSub>,
+}
+
+impl RuntimeWatchdog {
+    pub(super) fn new(
+        eventr: &Rc<Events>,
+        device: &str,
+        runtime_usec: u64,
+        reboot_usec: u64,
+    ) -> RuntimeWatchdog {
+        RuntimeWatchdog {
+            sub: RuntimeWatchdogSub::new(eventr, device, runtime_usec, reboot_usec),
+        }
+    }
+
+    /// open the device and start feeding it in the main loop
+    pub(super) fn enable(&self) {
+        self.sub.enable()
+    }
+
+    /// switch to the reboot timeout, the watchdog is not fed anymore
+    pub(super) fn arm_reboot(&self) {
+        self.sub.data.arm_reboot()
+    }
+
+    #[cfg(test)]
+    fn armed(&self) -> bool {
+        self.sub.data.armed()
+    }
+}
+
+struct RuntimeWatchdogSub {
+    // associated objects
+    event: Rc<Events>,
+
+    // owned objects
+    data: Rc<RuntimeWatchdogData>,
+}
+
+impl RuntimeWatchdogSub {
+    pub(self) fn new(
+        eventr: &Rc<Events>,
+        device: &str,
+        runtime_usec: u64,
+        reboot_usec: u64,
+    ) -> Rc<RuntimeWatchdogSub> {
+        let sub = Rc::new(RuntimeWatchdogSub {
+            event: Rc::clone(eventr),
+            data: Rc::new(RuntimeWatchdogData::new(device, runtime_usec, reboot_usec)),
+        });
+        sub.data.set_sub(&sub);
+        sub
+    }
+
+    pub(self) fn enable(&self) {
+        if !self.data.arm_runtime() {
+            return;
+        }
+
+        let source = Rc::clone(&self.data);
+        if let Err(e) = self.event.add_source(source) {
+            log::error!("Failed to add the watchdog timer: {}", e);
+            return;
+        }
+        self.rearm();
+    }
+
+    pub(self) fn rearm(&self) -> i32 {
+        let source = Rc::clone(&self.data);
+        self.event
+            .set_enabled(source, EventState::OneShot)
+            .unwrap_or(-1)
+    }
+}
+
+struct RuntimeWatchdogData {
+    // associated objects
+    sub: RefCell<Weak<RuntimeWatchdogSub>>,
+
+    // owned objects
+    wdog: RefCell<HardwareWatchdog>,
+    runtime_usec: u64,
+    reboot_usec: u64,
+    armed: RefCell<bool>,
+}
+
+impl RuntimeWatchdogData {
+    pub(self) fn new(device: &str, runtime_usec: u64, reboot_usec: u64) -> RuntimeWatchdogData {
+        let mut wdog = HardwareWatchdog::default();
+        if !device.is_empty() {
+            wdog.set_device(device.to_string());
+        }
+
+        RuntimeWatchdogData {
+            sub: RefCell::new(Weak::new()),
+            wdog: RefCell::new(wdog),
+            runtime_usec,
+            reboot_usec,
+            armed: RefCell::new(false),
+        }
+    }
+
+    pub(self) fn set_sub(&self, sub: &Rc<RuntimeWatchdogSub>) {
+        self.sub.replace(Rc::downgrade(sub));
+    }
+
+    pub(self) fn sub(&self) -> Rc<RuntimeWatchdogSub> {
+        self.sub.clone().into_inner().upgrade().unwrap()
+    }
+
+    fn arm_runtime(&self) -> bool {
+        if self.runtime_usec == 0 {
+            return false;
+        }
+
+        if let Err(e) = self
+            .wdog
+            .borrow_mut()
+            .config(Some(usec_to_timeout(self.runtime_usec)))
+        {
+            log::warn!("Failed to set up the hardware watchdog: {}", e);
+            return false;
+        }
+
+        log::info!(
+            "Hardware watchdog is armed, timeout: {}s",
+            usec_to_timeout(self.runtime_usec).as_secs()
+        );
+        *self.armed.borrow_mut() = true;
+        true
+    }
+
+    fn arm_reboot(&self) {
+        let mut wdog = self.wdog.borrow_mut();
+        if self.reboot_usec == 0 {
+            if *self.armed.borrow() {
+                log::info!("Disabling the hardware watchdog for the reboot.");
+                if let Err(e) = wdog.close() {
+                    log::warn!("Failed to disable the hardware watchdog: {}", e);
+                }
+            }
+            *self.armed.borrow_mut() = false;
+            return;
+        }
+
+        let ret = wdog
+            .config(Some(usec_to_timeout(self.reboot_usec)))
+            .and_then(|_| wdog.feed());
+        match ret {
+            Ok(_) => {
+                log::info!(
+                    "Hardware watchdog is armed for the reboot, timeout: {}s",
+                    usec_to_timeout(self.reboot_usec).as_secs()
+                );
+                *self.armed.borrow_mut() = true;
+            }
+            Err(e) => log::warn!("Failed to arm the hardware watchdog for the reboot: {}", e),
+        }
+    }
+
+    fn feed(&self) {
+        if let Err(e) = self.wdog.borrow_mut().feed() {
+            log::warn!("Failed to feed the hardware watchdog: {}", e);
+        }
+    }
+
+    fn armed(&self) -> bool {
+        *self.armed.borrow()
+    }
+}
+
+impl Source for RuntimeWatchdogData {
+    fn fd(&self) -> RawFd {
+        0
+    }
+
+    fn event_type(&self) -> EventType {
+        EventType::TimerMonotonic
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    /// feed the watchdog at the half of the timeout
+    fn time_relative(&self) -> u64 {
+        usec_to_timeout(self.runtime_usec).as_micros() as u64 / 2
+    }
+
+    fn dispatch(&self, _: &Events) -> i32 {
+        if !self.armed() {
+            return 0;
+        }
+
+        self.feed();
+        self.sub().rearm()
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+/// the watchdog timeout is in seconds, at least 1 second
+fn usec_to_timeout(usec: u64) -> Duration {
+    Duration::from_secs((usec / USEC_PER_SEC).clamp(1, i32::MAX as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usec_to_timeout() {
+        assert_eq!(usec_to_timeout(500000), Duration::from_secs(1));
+        assert_eq!(usec_to_timeout(30 * USEC_PER_SEC), Duration::from_secs(30));
+        assert_eq!(
+            usec_to_timeout(u64::MAX),
+            Duration::from_secs(i32::MAX as u64)
+        );
+    }
+
+    #[test]
+    fn test_fake_device() {
+        let events = Rc::new(Events::new().unwrap());
+
+        // the runtime watchdog is disabled
+        let wdog = RuntimeWatchdog::new(&events, "/dev/null", 0, 0);
+        wdog.enable();
+        assert!(!wdog.armed());
+
+        // the device doesn't exist
+        let wdog = RuntimeWatchdog::new(&events, "/nonexistent/watchdog", 10 * USEC_PER_SEC, 0);
+        wdog.enable();
+        assert!(!wdog.armed());
+        wdog.arm_reboot();
+        assert!(!wdog.armed());
+
+        // the device is not a watchdog
+        let path = std::env::temp_dir().join(format!("fake-watchdog-{}", std::process::id()));
+        std::fs::write(&path, "").unwrap();
+        let wdog = RuntimeWatchdog::new(
+            &events,
+            path.to_str().unwrap(),
+            10 * USEC_PER_SEC,
+            10 * USEC_PER_SEC,
+        );
+        wdog.enable();
+        assert!(!wdog.armed());
+        wdog.arm_reboot();
+        assert!(!wdog.armed());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// arms a real watchdog device, which resets the system if it's not disarmed in time,
+    /// run it manually with the device in SYSMASTER_TEST_WATCHDOG, such as a softdog device
+    #[ignore]
+    #[test]
+    fn test_softdog() {
+        let device = match std::env::var("SYSMASTER_TEST_WATCHDOG") {
+            Ok(device) => device,
+            Err(_) => {
+                println!("SYSMASTER_TEST_WATCHDOG is not set, skipping.");
+                return;
+            }
+        };
+
+        let events = Rc::new(Events::new().unwrap());
+        let wdog = RuntimeWatchdog::new(&events, &device, 10 * USEC_PER_SEC, 0);
+        wdog.enable();
+        assert!(wdog.armed());
+        // disable the watchdog, otherwise the system is reset
+        wdog.arm_reboot();
+        assert!(!wdog.armed());
+    }
+}
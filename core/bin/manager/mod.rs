@@ -12,11 +12,12 @@
 
 #[cfg(test)]
 pub(crate) use rentry::RELI_HISTORY_MAX_DBS;
-use sysmaster::unit::UnitStatus;
 
 pub(crate) mod alive_timer;
 pub(crate) mod commands;
 pub(crate) mod config;
+pub(crate) mod generator;
+pub(crate) mod hw_watchdog;
 pub(crate) mod pre_install;
 pub(crate) mod rentry;
 pub(crate) mod signals;
@@ -25,24 +26,30 @@ use crate::unit::UnitManagerX;
 use basic::path_lookup::LookupPaths;
 use basic::process_util::{self};
 use basic::special::{BASIC_TARGET, CGROUP_SYSMASTER};
+use basic::time_util::USEC_PER_SEC;
 use cgroup::CgController;
 use cgroup::{cg_create_and_attach, CgFlags};
+use cmdproto::proto::abi::{kill_param, JobList, UnitList, UnitProperties, UnitStatus};
 use cmdproto::proto::execute::ExecuterAction;
 use commands::Commands;
 use event::{EventState, Events};
 use nix::sys::reboot::{self, RebootMode};
 use nix::sys::signal::Signal;
-use nix::unistd::Pid;
+use nix::sys::socket::UnixCredentials;
+use nix::unistd::{Group, Pid, Uid, User};
 use pre_install::{Install, PresetMode};
 use signals::{SignalDispatcher, Signals};
 use std::cell::RefCell;
 use std::collections::HashSet;
+use std::os::unix::net::UnixStream;
 use std::path::PathBuf;
 use std::rc::Rc;
 use sysmaster::error::*;
 use sysmaster::rel::{ReliLastFrame, Reliability};
+use sysmaster::unit::KillWhom;
 
 use alive_timer::AliveTimer;
+use hw_watchdog::RuntimeWatchdog;
 
 use self::config::ManagerConfig;
 
@@ -75,11 +82,12 @@ impl SignalDispatcher for SignalMgr {
 struct CommandActionMgr {
     um: Rc<UnitManagerX>,
     state: Rc<RefCell<State>>,
+    config: Rc<ManagerConfig>,
 }
 
 impl CommandActionMgr {
-    fn new(um: Rc<UnitManagerX>, state: Rc<RefCell<State>>) -> Self {
-        CommandActionMgr { um, state }
+    fn new(um: Rc<UnitManagerX>, state: Rc<RefCell<State>>, config: Rc<ManagerConfig>) -> Self {
+        CommandActionMgr { um, state, config }
     }
 
     fn set_state(&self, state: State) {
@@ -91,7 +99,6 @@ impl CommandActionMgr {
 
 impl ExecuterAction for CommandActionMgr {
     type Error = sysmaster::error::Error;
-    type Status = sysmaster::unit::UnitStatus;
     // type Result<T, Error> = Result<T, E>;
     fn start(&self, unit_name: &str) -> Result<(), Self::Error> {
         self.um.start_unit(unit_name, true)
@@ -109,14 +116,57 @@ impl ExecuterAction for CommandActionMgr {
         self.um.reload(unit_name)
     }
 
+    fn reset_failed(&self, unit_name: &str) -> Result<(), Self::Error> {
+        self.um.reset_failed(unit_name)
+    }
+
+    fn kill(
+        &self,
+        unit_name: &str,
+        whom: kill_param::Whom,
+        signal: i32,
+        value: Option<i32>,
+    ) -> Result<(), Self::Error> {
+        let whom = match whom {
+            kill_param::Whom::Main => KillWhom::Main,
+            kill_param::Whom::Control => KillWhom::Control,
+            kill_param::Whom::All => KillWhom::All,
+        };
+        self.um.kill_unit(unit_name, whom, signal, value)
+    }
+
     fn status(&self, unit_name: &str) -> Result<UnitStatus, Self::Error> {
         self.um.get_unit_status(unit_name)
     }
 
-    fn list_units(&self) -> Result<String, Self::Error> {
+    fn show(&self, unit_name: &str) -> Result<UnitProperties, Self::Error> {
+        self.um.get_unit_properties(unit_name)
+    }
+
+    fn set_property(
+        &self,
+        unit_name: &str,
+        properties: &[(String, String)],
+        runtime: bool,
+    ) -> Result<(), Self::Error> {
+        self.um.set_unit_properties(unit_name, properties, runtime)
+    }
+
+    fn list_units(&self) -> Result<UnitList, Self::Error> {
         self.um.get_all_units()
     }
 
+    fn list_jobs(&self) -> Result<JobList, Self::Error> {
+        self.um.get_all_jobs()
+    }
+
+    fn cancel_job(&self, job_id: Option<u32>) -> Result<(), Self::Error> {
+        match job_id {
+            Some(id) => self.um.cancel_job(id),
+            None => self.um.cancel_all_jobs(),
+        }
+    }
+
     fn suspend(&self) -> Result<i32, Self::Error> {
         self.set_state(State::Suspend);
         Ok(0)
@@ -153,6 +203,18 @@ impl ExecuterAction for CommandActionMgr {
         self.um.unmask_unit(unit_file)
     }
 
+    fn start_transient(
+        &self,
+        unit_name: &str,
+        properties: &[(String, String)],
+    ) -> Result<(), Self::Error> {
+        self.um.start_transient_unit(unit_name, properties)
+    }
+
+    fn subscribe(&self, stream: UnixStream) {
+        self.um.subscribe(stream);
+    }
+
     fn daemon_reload(&self) {
         self.set_state(State::ReLoad);
     }
@@ -160,6 +222,34 @@ impl ExecuterAction for CommandActionMgr {
     fn daemon_reexec(&self) {
         self.set_state(State::ReExecute);
     }
+
+    fn is_privileged(&self, cred: &UnixCredentials) -> bool {
+        if cred.uid() == 0 {
+            return true;
+        }
+
+        let group_name = &self.config.ControlGroup;
+        if group_name.is_empty() {
+            return false;
+        }
+
+        let group = match Group::from_name(group_name) {
+            Ok(Some(g)) => g,
+            _ => {
+                log::warn!("ControlGroup {} is not a valid group.", group_name);
+                return false;
+            }
+        };
+        if cred.gid() == group.gid.as_raw() {
+            return true;
+        }
+
+        // the peer credentials only carry the primary group, check the members too
+        match User::from_uid(Uid::from_raw(cred.uid())) {
+            Ok(Some(user)) => group.mem.contains(&user.name),
+            _ => false,
+        }
+    }
 }
 
 /// Encapsulate manager and expose api to the outside
@@ -174,7 +264,7 @@ pub struct Manager {
     um: Rc<UnitManagerX>,
     lookup_path: Rc<LookupPaths>,
     alive_timer: Rc<AliveTimer>,
-    #[allow(dead_code)]
+    watchdog: Rc<RuntimeWatchdog>,
     config: Rc<ManagerConfig>,
 }
 
@@ -206,12 +296,22 @@ impl Manager {
 
         let kp = KeepAlive::get_instance();
         let alive_timer = Rc::new(AliveTimer::new(&event, kp.get_fd()));
+        let watchdog = Rc::new(RuntimeWatchdog::new(
+            &event,
+            &manager_config.WatchdogDevice,
+            manager_config.runtime_watchdog_usec(),
+            manager_config.reboot_watchdog_usec(),
+        ));
 
         Manager {
             event,
             commands: Rc::new(Commands::new(
                 &reli,
-                CommandActionMgr::new(Rc::clone(&um), Rc::clone(&state)),
+                CommandActionMgr::new(
+                    Rc::clone(&um),
+                    Rc::clone(&state),
+                    manager_config.clone(),
+                ),
             )),
             signal: Rc::new(Signals::new(&reli, SignalMgr::new(Rc::clone(&um)))),
             reli,
@@ -221,6 +321,7 @@ impl Manager {
             um,
             lookup_path,
             alive_timer,
+            watchdog,
             config: manager_config,
         }
     }
@@ -260,6 +361,9 @@ impl Manager {
         let restore = self.reli.enable();
         log::info!("startup with restore[{}] reload[{}]", restore, reload);
 
+        // the generated units are loaded with the others
+        self.run_generators();
+
         // recover
         if reload {
             // sctl daemon-reexec needs to reload the configuration.
@@ -340,6 +444,9 @@ impl Manager {
     }
 
     fn reload(&self) {
+        // the configuration of the generators may be changed too
+        self.run_generators();
+
         // clear data
         self.um.entry_clear();
 
@@ -355,6 +462,15 @@ impl Manager {
         self.reli.clear_last_frame();
     }
 
+    fn run_generators(&self) {
+        if self.mode != Mode::System {
+            return;
+        }
+
+        let timeout_usec = self.config.DefaultTimeoutSec.saturating_mul(USEC_PER_SEC);
+        generator::run_generators(&self.lookup_path, timeout_usec);
+    }
+
     fn set_restore(&self, enable: bool) {
         match enable {
             true => self.reli.set_enable(true),
@@ -379,6 +495,8 @@ impl Manager {
 
     fn reboot(&self, reboot_mode: RebootMode) {
         log::debug!("RebootMode: {:?}", reboot_mode);
+        // the main loop doesn't feed the watchdog anymore, a hung shutdown resets the system
+        self.watchdog.arm_reboot();
         // self.start_unit("shutdown.target");
         if let Ok(mut cg_ctrl) = CgController::new("sysmaster", Pid::from_raw(0)) {
             if let Err(e) = cg_ctrl.trim(false) {
@@ -416,6 +534,9 @@ impl Manager {
         // time
         let timer = Rc::clone(&self.alive_timer);
         timer.enable(true);
+
+        // hardware watchdog
+        self.watchdog.enable();
     }
 }
 
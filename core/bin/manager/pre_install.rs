@@ -14,6 +14,8 @@
 use crate::unit::{unit_name_to_type, UeConfigInstall, UnitType};
 use basic::fs_util;
 use basic::path_lookup::LookupPaths;
+use basic::specifier::specifier_expand;
+use basic::unit_name::{unit_name_is_template, unit_name_template};
 use bitflags::bitflags;
 use confique::Config;
 use nix::unistd::UnlinkatFlags;
@@ -155,20 +157,22 @@ impl UnitInstall {
     }
 
     fn fill_struct(&self, config: &UeConfigData) {
+        // the specifiers are expanded for the instance
+        let expand = |v: &String| specifier_expand(v, &self.name);
         for v in &config.Install.Alias {
-            self.aliases.borrow_mut().push(v.to_string());
+            self.aliases.borrow_mut().push(expand(v));
         }
 
         for v in &config.Install.WantedBy {
-            self.wanted_by.borrow_mut().push(v.to_string());
+            self.wanted_by.borrow_mut().push(expand(v));
         }
 
         for v in &config.Install.RequiredBy {
-            self.required_by.borrow_mut().push(v.to_string());
+            self.required_by.borrow_mut().push(expand(v));
         }
 
         for v in &config.Install.Also {
-            self.also.borrow_mut().push(v.to_string());
+            self.also.borrow_mut().push(expand(v));
         }
     }
 
@@ -418,7 +422,8 @@ impl Install {
                 let file_name = String::from(u_path.file_name().unwrap().to_str().unwrap());
                 let unit_type = unit_name_to_type(&file_name);
 
-                if unit_type == UnitType::UnitTypeInvalid {
+                // the template can only be enabled with an instance
+                if unit_type == UnitType::UnitTypeInvalid || unit_name_is_template(&file_name) {
                     continue;
                 }
 
@@ -433,6 +438,11 @@ impl Install {
     /// enable one unit file
     pub fn unit_enable_files(&self, file: &str) -> Result<()> {
         log::debug!("unit enable file: {}", file);
+        if unit_name_is_template(file) {
+            return Err(Error::Other {
+                msg: format!("{file} is a template, please enable it with an instance name"),
+            });
+        }
         let target_path = &self.lookup_path.persistent_path;
 
         self.unit_install_discover(file, self.enable_ctx.clone())?;
@@ -511,8 +521,16 @@ impl Install {
             self.unit_file_load(&unit_install.path(), unit_install.clone(), ctx.clone())?;
         }
 
+        let template = unit_name_template(&unit_install.name());
         for v in &self.lookup_path.search_path {
-            let unit = Path::new(v).join(unit_install.clone().name());
+            let mut unit = Path::new(v).join(unit_install.clone().name());
+            // the instance is installed with the unit file of its template
+            if !unit.exists() {
+                match &template {
+                    Some(template) => unit = Path::new(v).join(template),
+                    None => continue,
+                }
+            }
             if !unit.exists() {
                 continue;
             }
@@ -562,29 +580,33 @@ impl Install {
         let mut builder = UeConfigData::builder().env();
         builder = builder.file(&tmp);
 
-        let dropin_dir_name = format!("{}.d", unit_install.name());
+        let mut dropin_dir_names = vec![format!("{}.d", unit_install.name())];
+        if let Some(template) = unit_name_template(&unit_install.name()) {
+            dropin_dir_names.push(format!("{template}.d"));
+        }
 
         for v in &self.lookup_path.search_path {
-            let base_dir = Path::new(v);
-            #[allow(clippy::needless_borrow)]
-            let dropin_dir = base_dir.join(&dropin_dir_name);
+            for dropin_dir_name in &dropin_dir_names {
+                let dropin_dir = Path::new(v).join(dropin_dir_name);
 
-            if !dropin_dir.exists() {
-                log::debug!("dropin path is not exist, ignore it: {:?}", &dropin_dir);
-                continue;
-            }
+                if !dropin_dir.exists() {
+                    log::debug!("dropin path is not exist, ignore it: {:?}", &dropin_dir);
+                    continue;
+                }
 
-            let dirs = dropin_dir.read_dir()?;
-            for entry in dirs {
-                let dir_entry = entry?;
-                let fragment = dir_entry.path();
-                if fragment.is_file() {
-                    let file_name = String::from(fragment.file_name().unwrap().to_str().unwrap());
-                    if file_name.starts_with('.') || !file_name.ends_with(".toml") {
-                        continue;
+                let dirs = dropin_dir.read_dir()?;
+                for entry in dirs {
+                    let dir_entry = entry?;
+                    let fragment = dir_entry.path();
+                    if fragment.is_file() {
+                        let file_name =
+                            String::from(fragment.file_name().unwrap().to_str().unwrap());
+                        if file_name.starts_with('.') || !file_name.ends_with(".toml") {
+                            continue;
+                        }
+
+                        builder = builder.file(fragment);
                     }
-
-                    builder = builder.file(fragment);
                 }
             }
         }
@@ -61,11 +61,14 @@ pub(crate) const RELI_DB_HUNIT_CHILD: &str = "uchild";
 pub(crate) const RELI_DB_HUNIT_PPS: &str = "upps";
 pub(crate) const RELI_DB_HUNIT_DEP: &str = "udep";
 pub(crate) const RELI_DB_HUM_NOTIFY: &str = "um-notify";
+pub(crate) const RELI_DB_HUM_LOGSTREAM: &str = "um-logstream";
 /* service */
 #[allow(dead_code)]
 const RELI_DB_HSERVICE_CONF: &str = "svcconf";
 #[allow(dead_code)]
 const RELI_DB_HSERVICE_MNG: &str = "svcmng";
+#[allow(dead_code)]
+const RELI_DB_HSERVICE_FDSTORE: &str = "svcfdstore";
 /* socket */
 #[allow(dead_code)]
 const RELI_DB_HSOCKET_CONF: &str = "sockconf";
@@ -75,14 +78,48 @@ const RELI_DB_HSOCKET_MNG: &str = "sockmng";
 const RELI_DB_HSOCKETM_FRAME: &str = "sockm-frame";
 /* mount */
 #[allow(dead_code)]
+const RELI_DB_HMOUNT_CONF: &str = "mntconf";
+#[allow(dead_code)]
 const RELI_DB_HMOUNT_MNG: &str = "mntmng";
 #[allow(dead_code)]
 const RELI_DB_HMOUNTM_FRAME: &str = "mntm-frame";
 #[allow(dead_code)]
 /* target */
 const RELI_DB_HTARGET_MNG: &str = "tarmng";
+/* slice */
+#[allow(dead_code)]
+const RELI_DB_HSLICE_CONF: &str = "sliceconf";
+#[allow(dead_code)]
+const RELI_DB_HSLICE_MNG: &str = "slicemng";
+/* timer */
+#[allow(dead_code)]
+const RELI_DB_HTIMER_CONF: &str = "timerconf";
+#[allow(dead_code)]
+const RELI_DB_HTIMER_MNG: &str = "timermng";
+/* path */
+#[allow(dead_code)]
+const RELI_DB_HPATH_CONF: &str = "pathconf";
+#[allow(dead_code)]
+const RELI_DB_HPATH_MNG: &str = "pathmng";
+/* device */
+#[allow(dead_code)]
+const RELI_DB_HDEVICE_MNG: &str = "devmng";
+#[allow(dead_code)]
+const RELI_DB_HDEVICEM_FRAME: &str = "devm-frame";
+/* swap */
+#[allow(dead_code)]
+const RELI_DB_HSWAP_CONF: &str = "swapconf";
+#[allow(dead_code)]
+const RELI_DB_HSWAP_MNG: &str = "swapmng";
+#[allow(dead_code)]
+const RELI_DB_HSWAPM_FRAME: &str = "swapm-frame";
+/* automount */
+#[allow(dead_code)]
+const RELI_DB_HAUTOMOUNT_CONF: &str = "amntconf";
+#[allow(dead_code)]
+const RELI_DB_HAUTOMOUNT_MNG: &str = "amntmng";
 
-pub const RELI_HISTORY_MAX_DBS: u32 = 18;
+pub const RELI_HISTORY_MAX_DBS: u32 = 34;
 #[allow(dead_code)]
 static RELI_HISTORY_DB_NAME: [&str; RELI_HISTORY_MAX_DBS as usize] = [
     RELI_DB_HJOB_TRIGGER,
@@ -95,12 +132,28 @@ static RELI_HISTORY_DB_NAME: [&str; RELI_HISTORY_MAX_DBS as usize] = [
     RELI_DB_HUNIT_PPS,
     RELI_DB_HUNIT_DEP,
     RELI_DB_HUM_NOTIFY,
+    RELI_DB_HUM_LOGSTREAM,
     RELI_DB_HSERVICE_CONF,
     RELI_DB_HSERVICE_MNG,
+    RELI_DB_HSERVICE_FDSTORE,
     RELI_DB_HSOCKET_CONF,
     RELI_DB_HSOCKET_MNG,
     RELI_DB_HSOCKETM_FRAME,
+    RELI_DB_HMOUNT_CONF,
     RELI_DB_HMOUNT_MNG,
     RELI_DB_HMOUNTM_FRAME,
     RELI_DB_HTARGET_MNG,
+    RELI_DB_HSLICE_CONF,
+    RELI_DB_HSLICE_MNG,
+    RELI_DB_HTIMER_CONF,
+    RELI_DB_HTIMER_MNG,
+    RELI_DB_HPATH_CONF,
+    RELI_DB_HPATH_MNG,
+    RELI_DB_HDEVICE_MNG,
+    RELI_DB_HDEVICEM_FRAME,
+    RELI_DB_HSWAP_CONF,
+    RELI_DB_HSWAP_MNG,
+    RELI_DB_HSWAPM_FRAME,
+    RELI_DB_HAUTOMOUNT_CONF,
+    RELI_DB_HAUTOMOUNT_MNG,
 ];
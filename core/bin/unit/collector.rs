@@ -0,0 +1,372 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! The log collector captures the stdout and stderr of the services through the pipes
+//! it holds, tags each line with the unit name, PID, priority and timestamp, and writes
+//! the lines into the per-unit log store.
+use super::rentry::UnitRe;
+use crate::manager::config::ManagerConfig;
+use basic::fd_util;
+use basic::log_store::{self, LogStore, LOG_STORE_DIR};
+use basic::time_util;
+use event::{EventState, EventType, Events, Source};
+use nix::errno::Errno;
+use nix::fcntl::OFlag;
+use nix::time::ClockId;
+use nix::unistd::{self, Pid};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::os::unix::prelude::RawFd;
+use std::path::Path;
+use std::rc::{Rc, Weak};
+use sysmaster::error::*;
+use sysmaster::rel::Reliability;
+
+/// the default priority of the lines from stdout: LOG_INFO
+pub(super) const LOG_PRIORITY_STDOUT: u8 = 6;
+/// the default priority of the lines from stderr: LOG_ERR
+pub(super) const LOG_PRIORITY_STDERR: u8 = 3;
+
+// the longer line is split into several lines
+const LOG_LINE_MAX: usize = 2048;
+// read at most this many times in one dispatch, so that a chatty service can't block the others
+const LOG_READ_MAX: usize = 16;
+
+type LogStreams = RefCell<HashMap<RawFd, Rc<LogStream>>>;
+
+pub(super) struct LogCollector {
+    // associated objects
+    events: Rc<Events>,
+    reli: Rc<Reliability>,
+    rentry: Rc<UnitRe>,
+
+    // owned objects
+    enable: bool,
+    store: Rc<LogStore>,
+    streams: Rc<LogStreams>,
+}
+
+impl LogCollector {
+    pub(super) fn new(
+        eventr: &Rc<Events>,
+        relir: &Rc<Reliability>,
+        rentryr: &Rc<UnitRe>,
+        config: &ManagerConfig,
+    ) -> LogCollector {
+        LogCollector {
+            events: Rc::clone(eventr),
+            reli: Rc::clone(relir),
+            rentry: Rc::clone(rentryr),
+            enable: config.LogCollector,
+            store: Rc::new(LogStore::new(
+                Path::new(LOG_STORE_DIR),
+                config.log_collector_max_size(),
+                config.LogCollectorMaxFiles,
+            )),
+            streams: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    pub(super) fn enabled(&self) -> bool {
+        self.enable
+    }
+
+    pub(super) fn db_map(&self, reload: bool) {
+        // the streams are kept in memory during reloading
+        if reload {
+            return;
+        }
+
+        for (fd, unit, pid, priority) in self.rentry.logstream_entries() {
+            let fd = self.reli.fd_take(fd);
+            let stream = self.new_stream(fd, &unit, priority);
+            *stream.pid.borrow_mut() = pid;
+            self.streams.borrow_mut().insert(fd, stream);
+        }
+    }
+
+    pub(super) fn register_ex(&self) {
+        for stream in self.streams.borrow().values() {
+            if let Err(e) = self.add_source(stream) {
+                log::error!("Failed to watch the log stream of {}: {}", stream.unit, e);
+            }
+        }
+    }
+
+    /// open a pipe for the output of the unit, return the read end which identifies
+    /// the stream, and the write end which should be passed to the child
+    pub(super) fn open_stream(&self, unit: &str, priority: u8) -> Result<(RawFd, RawFd)> {
+        let (rfd, wfd) = unistd::pipe2(OFlag::O_CLOEXEC).context(NixSnafu)?;
+        let stream = self.new_stream(rfd, unit, priority);
+        if let Err(e) = fd_util::fd_nonblock(rfd, true) {
+            fd_util::close(wfd);
+            stream.release();
+            return Err(e.into());
+        }
+
+        // the read end is kept across re-executing, so that the services won't get EPIPE
+        if let Err(e) = self.reli.fd_cloexec(rfd, false) {
+            fd_util::close(wfd);
+            stream.release();
+            return Err(e);
+        }
+
+        self.streams.borrow_mut().insert(rfd, Rc::clone(&stream));
+        self.rentry.logstream_insert(rfd, unit, 0, priority);
+        if let Err(e) = self.add_source(&stream) {
+            fd_util::close(wfd);
+            stream.release();
+            return Err(e);
+        }
+
+        Ok((rfd, wfd))
+    }
+
+    /// tag the lines of the stream with the pid of the child
+    pub(super) fn attach_pid(&self, rfd: RawFd, pid: Pid) {
+        if let Some(stream) = self.streams.borrow().get(&rfd) {
+            *stream.pid.borrow_mut() = pid.as_raw();
+            self.rentry
+                .logstream_insert(rfd, &stream.unit, pid.as_raw(), stream.priority);
+        }
+    }
+
+    /// close the stream that is not used by any child
+    pub(super) fn close_stream(&self, rfd: RawFd) {
+        let stream = self.streams.borrow().get(&rfd).cloned();
+        if let Some(stream) = stream {
+            stream.release();
+        }
+    }
+
+    /// return the last n lines of the logs of the unit
+    pub(super) fn tail(&self, unit: &str, n: usize) -> Vec<String> {
+        if !self.enable {
+            return Vec::new();
+        }
+
+        log_store::tail(Path::new(LOG_STORE_DIR), unit, Some(n)).unwrap_or_else(|e| {
+            log::debug!("Failed to read the logs of {}: {}", unit, e);
+            Vec::new()
+        })
+    }
+
+    fn new_stream(&self, fd: RawFd, unit: &str, priority: u8) -> Rc<LogStream> {
+        Rc::new(LogStream {
+            events: Rc::clone(&self.events),
+            reli: Rc::clone(&self.reli),
+            rentry: Rc::clone(&self.rentry),
+            store: Rc::clone(&self.store),
+            streams: Rc::downgrade(&self.streams),
+            fd,
+            unit: unit.to_string(),
+            priority,
+            pid: RefCell::new(0),
+            buffer: RefCell::new(Vec::new()),
+        })
+    }
+
+    fn add_source(&self, stream: &Rc<LogStream>) -> Result<()> {
+        let source = Rc::clone(stream);
+        self.events.add_source(source)?;
+        let source = Rc::clone(stream);
+        self.events.set_enabled(source, EventState::On)?;
+        Ok(())
+    }
+}
+
+struct LogStream {
+    // associated objects
+    events: Rc<Events>,
+    reli: Rc<Reliability>,
+    rentry: Rc<UnitRe>,
+    store: Rc<LogStore>,
+    streams: Weak<LogStreams>,
+
+    // owned objects
+    fd: RawFd,
+    unit: String,
+    priority: u8,
+    pid: RefCell<i32>,
+    buffer: RefCell<Vec<u8>>,
+}
+
+impl LogStream {
+    fn read(&self) -> bool {
+        let mut buf = [0u8; 4096];
+        for _ in 0..LOG_READ_MAX {
+            match unistd::read(self.fd, &mut buf) {
+                Ok(0) => return true,
+                Ok(n) => self.buffer.borrow_mut().extend_from_slice(&buf[..n]),
+                Err(Errno::EINTR) => continue,
+                Err(Errno::EAGAIN) => return false,
+                Err(e) => {
+                    log::error!("Failed to read the log stream of {}: {}", self.unit, e);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn flush(&self, eof: bool) {
+        let lines = split_lines(&mut self.buffer.borrow_mut(), eof);
+        if lines.is_empty() {
+            return;
+        }
+
+        let realtime = time_util::now_clockid(ClockId::CLOCK_REALTIME);
+        let lines = lines
+            .iter()
+            .map(|line| self.format_line(line, realtime))
+            .collect::<Vec<_>>();
+        if let Err(e) = self.store.write(&self.unit, &lines) {
+            log::error!("Failed to write the logs of {}: {}", self.unit, e);
+        }
+    }
+
+    fn format_line(&self, line: &str, realtime: u64) -> String {
+        let (priority, message) = parse_priority(line, self.priority);
+        format!(
+            "<{}>{} {}[{}]: {}",
+            priority,
+            time_util::format_timestamp(realtime),
+            self.unit,
+            self.pid.borrow(),
+            message
+        )
+    }
+
+    fn release(&self) {
+        let stream = match self.streams.upgrade() {
+            Some(streams) => streams.borrow_mut().remove(&self.fd),
+            None => None,
+        };
+        if let Some(stream) = stream {
+            let source: Rc<dyn Source> = stream;
+            if let Err(e) = self.events.del_source(source) {
+                log::debug!("Failed to remove the log stream of {}: {}", self.unit, e);
+            }
+        }
+
+        self.rentry.logstream_remove(self.fd);
+        if let Err(e) = self.reli.fd_cloexec(self.fd, true) {
+            log::debug!("Failed to remark the log stream fd {}: {}", self.fd, e);
+        }
+        fd_util::close(self.fd);
+    }
+}
+
+/// take the complete lines out of the buffer, the line longer than LOG_LINE_MAX is split,
+/// and the unfinished line is kept in the buffer unless eof is set
+fn split_lines(buffer: &mut Vec<u8>, eof: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    while start < buffer.len() {
+        let rest = &buffer[start..];
+        let len = match rest.iter().position(|b| *b == b'\n') {
+            Some(pos) if pos <= LOG_LINE_MAX => pos,
+            _ if rest.len() >= LOG_LINE_MAX => LOG_LINE_MAX,
+            // the last line is not finished yet
+            _ if !eof => break,
+            _ => rest.len(),
+        };
+        lines.push(String::from_utf8_lossy(&rest[..len]).into_owned());
+        start += len;
+        if buffer.get(start) == Some(&b'\n') {
+            start += 1;
+        }
+    }
+    buffer.drain(..start);
+    lines
+}
+
+/// parse the priority prefix like "<3>" of the line, return the priority and the message
+fn parse_priority(line: &str, default: u8) -> (u8, &str) {
+    let bytes = line.as_bytes();
+    if bytes.len() >= 3 && bytes[0] == b'<' && (b'0'..=b'7').contains(&bytes[1]) && bytes[2] == b'>'
+    {
+        return (bytes[1] - b'0', &line[3..]);
+    }
+    (default, line)
+}
+
+impl Source for LogStream {
+    fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    fn event_type(&self) -> EventType {
+        EventType::Io
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn dispatch(&self, _e: &Events) -> i32 {
+        let eof = self.read();
+        self.flush(eof);
+        if eof {
+            // all the writers are gone
+            self.release();
+        }
+        0
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_priority, split_lines, LOG_LINE_MAX};
+
+    #[test]
+    fn test_split_lines() {
+        let mut buffer = b"foo\n\nbar\nba".to_vec();
+        assert_eq!(split_lines(&mut buffer, false), vec!["foo", "", "bar"]);
+        assert_eq!(buffer, b"ba");
+
+        buffer.extend_from_slice(b"z");
+        assert!(split_lines(&mut buffer, false).is_empty());
+        assert_eq!(split_lines(&mut buffer, true), vec!["baz"]);
+        assert!(buffer.is_empty());
+
+        let mut buffer = vec![b'a'; LOG_LINE_MAX + 10];
+        buffer.push(b'\n');
+        let lines = split_lines(&mut buffer, false);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), LOG_LINE_MAX);
+        assert_eq!(lines[1].len(), 10);
+        assert!(buffer.is_empty());
+
+        let mut buffer = vec![b'f', 0xff, b'o', b'\n'];
+        assert_eq!(split_lines(&mut buffer, false), vec!["f\u{fffd}o"]);
+    }
+
+    #[test]
+    fn test_parse_priority() {
+        assert_eq!(parse_priority("<3>failed", 6), (3, "failed"));
+        assert_eq!(parse_priority("<7>", 6), (7, ""));
+        assert_eq!(parse_priority("<8>foo", 6), (6, "<8>foo"));
+        assert_eq!(parse_priority("<3 foo", 6), (6, "<3 foo"));
+        assert_eq!(parse_priority("foo", 3), (3, "foo"));
+        assert_eq!(parse_priority("", 6), (6, ""));
+    }
+}
@@ -12,7 +12,7 @@
 
 use super::dep_conf::UnitDepConf;
 use super::state::UnitState;
-use crate::job::JobResult;
+use crate::job::{JobInfo, JobResult};
 use crate::unit::entry::StartLimitResult;
 use crate::utils::table::{Table, TableSubscribe};
 use std::cell::RefCell;
@@ -26,6 +26,7 @@ pub struct DataManager {
         RefCell<Table<String, UnitState>>,        // [1]unit-state
         RefCell<Table<String, StartLimitResult>>, // [2]unit-start-limit-hit
         RefCell<Table<String, JobResult>>,        // [3] unit-job-timeout
+        RefCell<Table<u32, JobInfo>>,             // [4] job-info
     ),
 }
 
@@ -37,6 +38,7 @@ impl ReStation for DataManager {
     fn entry_clear(&self) {
         self.tables.0.borrow_mut().data_clear();
         self.tables.1.borrow_mut().data_clear();
+        self.tables.4.borrow_mut().data_clear();
     }
 }
 
@@ -56,6 +58,7 @@ impl DataManager {
                 RefCell::new(Table::new()),
                 RefCell::new(Table::new()),
                 RefCell::new(Table::new()),
+                RefCell::new(Table::new()),
             ),
         }
     }
@@ -134,10 +137,30 @@ impl DataManager {
         table.subscribe(name.to_string(), subscriber)
     }
 
+    pub(crate) fn insert_job_info(&self, id: u32, job_info: JobInfo) -> Option<JobInfo> {
+        let mut table = self.tables.4.borrow_mut();
+        table.insert(id, job_info)
+    }
+
+    pub(crate) fn remove_job_info(&self, id: u32) -> Option<JobInfo> {
+        let mut table = self.tables.4.borrow_mut();
+        table.remove(&id)
+    }
+
+    pub(crate) fn register_job_info(
+        &self,
+        name: &str,
+        subscriber: Rc<dyn TableSubscribe<u32, JobInfo>>,
+    ) -> Option<Rc<dyn TableSubscribe<u32, JobInfo>>> {
+        let mut table = self.tables.4.borrow_mut();
+        table.subscribe(name.to_string(), subscriber)
+    }
+
     // repeating protection
     pub(crate) fn clear(&self) {
         self.tables.0.borrow_mut().clear();
         self.tables.1.borrow_mut().clear();
+        self.tables.4.borrow_mut().clear();
     }
 }
 
@@ -139,7 +139,9 @@ impl UnitChildData {
         self.watch_pids.borrow().get(&pid).cloned()
     }
 
-    fn remove_unit(&self, _unit: &Rc<UnitX>) {}
+    fn remove_unit(&self, unit: &Rc<UnitX>) {
+        self.watch_pids.borrow_mut().retain(|_, u| u != unit);
+    }
 }
 
 #[cfg(test)]
@@ -74,7 +74,6 @@ impl UnitDb {
         self.units.insert(name, unit)
     }
 
-    #[allow(dead_code)]
     pub fn unit_remove(&self, name: &str) {
         self.units.remove(name);
     }
@@ -164,6 +163,23 @@ impl UnitDb {
         self.child.get_unit_by_pid(pid)
     }
 
+    /// return the unit which the cgroup of the pid belongs to, the nearest ancestor cgroup wins.
+    pub fn get_unit_by_cgroup(&self, pid: Pid) -> Option<Rc<UnitX>> {
+        let cg_path = cgroup::cg_pid_get_path(pid).ok()?;
+        let units = self.units.get_all();
+        for path in cg_path.ancestors() {
+            if path.is_empty() {
+                break;
+            }
+
+            if let Some(unit) = units.iter().find(|u| u.cg_path() == path) {
+                return Some(Rc::clone(unit));
+            }
+        }
+
+        None
+    }
+
     // repeating protection
     pub fn clear(&self) {
         self.child.entry_clear();
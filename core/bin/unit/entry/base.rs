@@ -81,11 +81,11 @@ impl UeBase {
         self.rentry.conf_get(&self.id)
     }
 
-    pub(super) fn rentry_cgroup_insert(&self, cg_path: &Path) {
-        self.rentry.cgroup_insert(&self.id, cg_path);
+    pub(super) fn rentry_cgroup_insert(&self, cg_path: &Path, applied_attrs: &[(String, String)]) {
+        self.rentry.cgroup_insert(&self.id, cg_path, applied_attrs);
     }
 
-    pub(super) fn rentry_cgroup_get(&self) -> Option<PathBuf> {
+    pub(super) fn rentry_cgroup_get(&self) -> Option<(PathBuf, Vec<(String, String)>)> {
         self.rentry.cgroup_get(&self.id)
     }
 
@@ -11,11 +11,23 @@
 // See the Mulan PSL v2 for more details.
 
 use super::base::UeBase;
+use basic::time_util::USEC_PER_SEC;
+use cgroup::CgType;
+use nix::unistd::Pid;
 use nix::NixPath;
 use std::rc::Rc;
 use std::{cell::RefCell, path::PathBuf};
 use sysmaster::error::*;
 use sysmaster::rel::ReStation;
+use sysmaster::unit::{slice_cg_path, CgroupContext, UnitType, CGROUP_LIMIT_MAX};
+
+const CPU_QUOTA_PERIOD_USEC: u64 = 100000;
+const CPU_SHARES_MIN: u64 = 2;
+const CPU_SHARES_MAX: u64 = 262144;
+const BLKIO_WEIGHT_MIN: u64 = 10;
+const BLKIO_WEIGHT_MAX: u64 = 1000;
+/// the controllers whose hierarchies may be used for the resource control on cgroup v1
+const V1_CONTROLLERS: [&str; 4] = ["memory", "cpu", "pids", "blkio"];
 
 pub(super) struct UeCgroup {
     data: RefCell<UnitCgroupData>,
@@ -45,6 +57,10 @@ impl UeCgroup {
         cgroup
     }
 
+    pub(super) fn set_slice(&self, slice: Option<String>) {
+        self.data.borrow_mut().set_slice(slice);
+    }
+
     pub(super) fn setup_cg_path(&self) {
         self.data.borrow_mut().setup_cg_path();
         self.db_update();
@@ -59,6 +75,21 @@ impl UeCgroup {
 
         cg_path
     }
+
+    pub(super) fn apply_cgroup_context(&self, ctx: &CgroupContext) {
+        self.data.borrow_mut().apply_cgroup_context(ctx);
+        self.db_update();
+    }
+
+    pub(super) fn cg_attach(&self, pid: Pid) -> Result<()> {
+        self.data.borrow().cg_attach(pid)
+    }
+
+    pub(super) fn cg_remove(&self) -> Result<()> {
+        let ret = self.data.borrow_mut().cg_remove();
+        self.db_update();
+        ret
+    }
 }
 
 struct UnitCgroupData {
@@ -67,6 +98,10 @@ struct UnitCgroupData {
 
     // owned objects
     cg_path: PathBuf,
+    // the slice which the unit belongs to, the cgroup of the unit is created under it
+    slice: Option<String>,
+    // (controller, attribute) written to the cgroup
+    applied_attrs: Vec<(String, String)>,
 }
 
 impl UnitCgroupData {
@@ -74,12 +109,15 @@ impl UnitCgroupData {
         UnitCgroupData {
             base: Rc::clone(baser),
             cg_path: PathBuf::from(""),
+            slice: None,
+            applied_attrs: Vec::new(),
         }
     }
 
     pub(self) fn db_map(&mut self) {
-        if let Some(cg_path) = self.base.rentry_cgroup_get() {
+        if let Some((cg_path, applied_attrs)) = self.base.rentry_cgroup_get() {
             self.cg_path = cg_path;
+            self.applied_attrs = applied_attrs;
         }
     }
 
@@ -91,10 +129,22 @@ impl UnitCgroupData {
         self.set_default_cg_path();
     }
 
+    pub(self) fn set_slice(&mut self, slice: Option<String>) {
+        self.slice = slice;
+    }
+
     fn set_default_cg_path(&mut self) {
-        let cg_tree_name = PathBuf::from(cgroup::cg_escape(self.base.id()));
+        // the cgroup of the slice is decided by its name, the others are under their slice
+        if self.base.unit_type() == UnitType::UnitSlice {
+            self.cg_path = slice_cg_path(self.base.id());
+            return;
+        }
 
-        self.cg_path = cg_tree_name;
+        let cg_tree_name = PathBuf::from(cgroup::cg_escape(self.base.id()));
+        self.cg_path = match &self.slice {
+            Some(slice) => slice_cg_path(slice).join(cg_tree_name),
+            None => cg_tree_name,
+        };
     }
 
     pub(self) fn prepare_cg_exec(&mut self) -> Result<()> {
@@ -108,7 +158,232 @@ impl UnitCgroupData {
         self.cg_path.clone()
     }
 
+    pub(self) fn apply_cgroup_context(&mut self, ctx: &CgroupContext) {
+        if self.cg_path.is_empty() {
+            return;
+        }
+
+        let unified = match cgroup::cg_type() {
+            Ok(t) => t == CgType::UnifiedV2,
+            Err(_) => {
+                log::debug!("cgroup is not supported, skip resource control");
+                return;
+            }
+        };
+
+        let attrs = cgroup_context_attrs(ctx, unified);
+
+        if unified {
+            let mut controllers: Vec<&str> = attrs.iter().map(|(c, _, _)| *c).collect();
+            controllers.dedup();
+            if let Err(e) = cgroup::cg_enable_controllers(&self.cg_path, &controllers) {
+                log::warn!(
+                    "Failed to enable cgroup controllers {:?}: {}",
+                    controllers,
+                    e
+                );
+            }
+        }
+
+        // reset the attributes which are not configured any more
+        for (controller, attr) in self.applied_attrs.iter() {
+            if attrs.iter().any(|(_, a, _)| *a == attr.as_str()) {
+                continue;
+            }
+            if let Some(value) = default_attr_value(attr) {
+                if let Err(e) = cgroup::cg_set_attr(controller, &self.cg_path, attr, value) {
+                    log::warn!("Failed to reset cgroup attribute {}: {}", attr, e);
+                }
+            }
+        }
+
+        self.applied_attrs.clear();
+        for (controller, attr, value) in attrs {
+            match cgroup::cg_set_attr(controller, &self.cg_path, attr, &value) {
+                Ok(_) => self
+                    .applied_attrs
+                    .push((controller.to_string(), attr.to_string())),
+                Err(e) => log::warn!(
+                    "Failed to set cgroup attribute {} of {:?} to {}: {}",
+                    attr,
+                    self.cg_path,
+                    value,
+                    e
+                ),
+            }
+        }
+    }
+
+    pub(self) fn cg_attach(&self, pid: Pid) -> Result<()> {
+        cgroup::cg_attach(pid, &self.cg_path).context(CgroupSnafu)?;
+
+        // the controllers are in their own hierarchies on cgroup v1
+        if let Ok(CgType::UnifiedV2) = cgroup::cg_type() {
+            return Ok(());
+        }
+        let mut controllers: Vec<&str> =
+            self.applied_attrs.iter().map(|(c, _)| c.as_str()).collect();
+        controllers.dedup();
+        for controller in controllers {
+            if let Err(e) = cgroup::cg_attach_controller(controller, pid, &self.cg_path) {
+                log::warn!(
+                    "Failed to attach {} to {} controller: {}",
+                    pid,
+                    controller,
+                    e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub(self) fn cg_remove(&mut self) -> Result<()> {
+        if self.cg_path.is_empty() {
+            return Ok(());
+        }
+
+        cgroup::cg_remove(&self.cg_path).context(CgroupSnafu)?;
+
+        // the cgroups created in the hierarchies of the controllers are left on cgroup v1
+        if !matches!(cgroup::cg_type(), Ok(CgType::UnifiedV2)) {
+            for controller in V1_CONTROLLERS {
+                if let Err(e) = cgroup::cg_remove_controller(controller, &self.cg_path) {
+                    log::warn!(
+                        "Failed to remove the cgroup {:?} of {} controller: {}",
+                        self.cg_path,
+                        controller,
+                        e
+                    );
+                }
+            }
+        }
+
+        // the attributes are gone with the cgroup, they are applied again after it's created
+        self.applied_attrs.clear();
+        Ok(())
+    }
+
     pub(self) fn db_insert(&self) {
-        self.base.rentry_cgroup_insert(&self.cg_path);
+        self.base
+            .rentry_cgroup_insert(&self.cg_path, &self.applied_attrs);
+    }
+}
+
+fn limit_to_string(v: u64, max: &str) -> String {
+    if v == CGROUP_LIMIT_MAX {
+        max.to_string()
+    } else {
+        v.to_string()
+    }
+}
+
+/// convert the resource control context to the (controller, attribute, value) list
+fn cgroup_context_attrs(
+    ctx: &CgroupContext,
+    unified: bool,
+) -> Vec<(&'static str, &'static str, String)> {
+    let mut attrs = Vec::new();
+
+    if let Some(v) = ctx.memory_max() {
+        match unified {
+            true => attrs.push(("memory", "memory.max", limit_to_string(v, "max"))),
+            false => attrs.push(("memory", "memory.limit_in_bytes", limit_to_string(v, "-1"))),
+        }
+    }
+    if let Some(v) = ctx.memory_high() {
+        match unified {
+            true => attrs.push(("memory", "memory.high", limit_to_string(v, "max"))),
+            false => attrs.push((
+                "memory",
+                "memory.soft_limit_in_bytes",
+                limit_to_string(v, "-1"),
+            )),
+        }
+    }
+
+    if let Some(v) = ctx.cpu_weight() {
+        match unified {
+            true => attrs.push(("cpu", "cpu.weight", v.to_string())),
+            false => {
+                let shares = (v * 1024 / 100).clamp(CPU_SHARES_MIN, CPU_SHARES_MAX);
+                attrs.push(("cpu", "cpu.shares", shares.to_string()));
+            }
+        }
+    }
+    if let Some(v) = ctx.cpu_quota_per_sec_usec() {
+        let quota = (v * CPU_QUOTA_PERIOD_USEC / USEC_PER_SEC).max(1000);
+        match unified {
+            true => attrs.push(("cpu", "cpu.max", format!("{quota} {CPU_QUOTA_PERIOD_USEC}"))),
+            false => {
+                attrs.push((
+                    "cpu",
+                    "cpu.cfs_period_us",
+                    CPU_QUOTA_PERIOD_USEC.to_string(),
+                ));
+                attrs.push(("cpu", "cpu.cfs_quota_us", quota.to_string()));
+            }
+        }
+    }
+
+    if let Some(v) = ctx.tasks_max() {
+        attrs.push(("pids", "pids.max", limit_to_string(v, "max")));
+    }
+
+    if let Some(v) = ctx.io_weight() {
+        match unified {
+            true => attrs.push(("io", "io.weight", format!("default {v}"))),
+            false => {
+                let weight = (v * 5).clamp(BLKIO_WEIGHT_MIN, BLKIO_WEIGHT_MAX);
+                attrs.push(("blkio", "blkio.weight", weight.to_string()));
+            }
+        }
+    }
+
+    attrs
+}
+
+/// the value of the attribute when the resource control is not configured
+fn default_attr_value(attr: &str) -> Option<&'static str> {
+    match attr {
+        "memory.max" | "memory.high" | "pids.max" => Some("max"),
+        "memory.limit_in_bytes" | "memory.soft_limit_in_bytes" | "cpu.cfs_quota_us" => Some("-1"),
+        "cpu.weight" => Some("100"),
+        "cpu.max" => Some("max"),
+        "cpu.shares" => Some("1024"),
+        "io.weight" => Some("default 100"),
+        "blkio.weight" => Some("500"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::cgroup_context_attrs;
+    use sysmaster::unit::CgroupContext;
+
+    #[test]
+    fn test_cgroup_context_attrs() {
+        let ctx = CgroupContext::default();
+        assert!(cgroup_context_attrs(&ctx, true).is_empty());
+
+        ctx.set_memory_max(Some("1M")).unwrap();
+        ctx.set_cpu_weight(Some(200)).unwrap();
+        ctx.set_cpu_quota(Some("20%")).unwrap();
+        ctx.set_tasks_max(Some("infinity")).unwrap();
+        ctx.set_io_weight(Some(50)).unwrap();
+
+        let attrs = cgroup_context_attrs(&ctx, true);
+        assert!(attrs.contains(&("memory", "memory.max", "1048576".to_string())));
+        assert!(attrs.contains(&("cpu", "cpu.weight", "200".to_string())));
+        assert!(attrs.contains(&("cpu", "cpu.max", "20000 100000".to_string())));
+        assert!(attrs.contains(&("pids", "pids.max", "max".to_string())));
+        assert!(attrs.contains(&("io", "io.weight", "default 50".to_string())));
+
+        let attrs = cgroup_context_attrs(&ctx, false);
+        assert!(attrs.contains(&("memory", "memory.limit_in_bytes", "1048576".to_string())));
+        assert!(attrs.contains(&("cpu", "cpu.shares", "2048".to_string())));
+        assert!(attrs.contains(&("cpu", "cpu.cfs_quota_us", "20000".to_string())));
+        assert!(attrs.contains(&("pids", "pids.max", "max".to_string())));
+        assert!(attrs.contains(&("blkio", "blkio.weight", "250".to_string())));
     }
 }
@@ -123,6 +123,14 @@ impl UeLoad {
         *self.in_load_queue.borrow()
     }
 
+    pub(super) fn has_fragment(&self) -> bool {
+        self.file.build_name_map(
+            self.base.id().clone(),
+            self.load_state() == UnitLoadState::Loaded,
+        );
+        !self.get_unit_id_fragment_pathbuf().is_empty()
+    }
+
     pub(super) fn load_unit_confs(&self) -> Result<()> {
         self.file.build_name_map(
             self.base.id().clone(),
@@ -134,6 +142,20 @@ impl UeLoad {
         Ok(())
     }
 
+    /// the unit requires its slice and is started after it
+    pub(super) fn add_slice_dependency(&self, slice: &str) {
+        let mut ud_conf = UnitDepConf::new();
+        for relation in [
+            UnitRelations::UnitInSlice,
+            UnitRelations::UnitRequires,
+            UnitRelations::UnitAfter,
+        ] {
+            ud_conf.deps.insert(relation, vec![slice.to_string()]);
+        }
+
+        self.dm.insert_ud_config(self.base.id().clone(), ud_conf);
+    }
+
     pub(super) fn set_in_target_dep_queue(&self, t: bool) {
         self.in_target_dep_queue.replace(t);
         if t {
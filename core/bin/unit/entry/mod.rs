@@ -55,5 +55,6 @@ mod condition;
 mod config;
 mod load;
 mod ratelimit;
+mod timestamp;
 mod uentry;
 mod unitx;
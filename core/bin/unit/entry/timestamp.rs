@@ -0,0 +1,119 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! the realtime of the unit entering and leaving the active states, which is shown as the
+//! properties of the unit
+
+use basic::time_util;
+use nix::time::ClockId;
+use std::cell::RefCell;
+use sysmaster::unit::UnitActiveState;
+
+/// 0 means the state is never entered or left since sysmaster starts
+#[derive(Default, Clone, Copy)]
+struct StateTimestamps {
+    state_change: u64,
+    inactive_exit: u64,
+    active_enter: u64,
+    active_exit: u64,
+    inactive_enter: u64,
+}
+
+pub(super) struct UeTimestamp {
+    data: RefCell<StateTimestamps>,
+}
+
+impl UeTimestamp {
+    pub(super) fn new() -> UeTimestamp {
+        UeTimestamp {
+            data: RefCell::new(StateTimestamps::default()),
+        }
+    }
+
+    pub(super) fn update(&self, os: UnitActiveState, ns: UnitActiveState) {
+        if os == ns {
+            return;
+        }
+
+        let now = time_util::now_clockid(ClockId::CLOCK_REALTIME);
+        let mut data = self.data.borrow_mut();
+        data.state_change = now;
+
+        if os.is_inactive_or_failed() && !ns.is_inactive_or_failed() {
+            data.inactive_exit = now;
+        } else if !os.is_inactive_or_failed() && ns.is_inactive_or_failed() {
+            data.inactive_enter = now;
+        }
+
+        if !os.is_active_or_reloading() && ns.is_active_or_reloading() {
+            data.active_enter = now;
+        } else if os.is_active_or_reloading() && !ns.is_active_or_reloading() {
+            data.active_exit = now;
+        }
+    }
+
+    pub(super) fn properties(&self) -> Vec<(String, String)> {
+        let data = *self.data.borrow();
+        [
+            ("StateChangeTimestamp", data.state_change),
+            ("InactiveExitTimestamp", data.inactive_exit),
+            ("ActiveEnterTimestamp", data.active_enter),
+            ("ActiveExitTimestamp", data.active_exit),
+            ("InactiveEnterTimestamp", data.inactive_enter),
+        ]
+        .iter()
+        .map(|(key, usec)| {
+            let value = match usec {
+                0 => String::new(),
+                _ => time_util::format_timestamp(*usec),
+            };
+            (key.to_string(), value)
+        })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp_set(ts: &UeTimestamp, key: &str) -> bool {
+        ts.properties()
+            .iter()
+            .any(|(k, v)| k == key && !v.is_empty())
+    }
+
+    #[test]
+    fn test_timestamp_update() {
+        let ts = UeTimestamp::new();
+        assert!(!timestamp_set(&ts, "StateChangeTimestamp"));
+
+        ts.update(UnitActiveState::UnitInActive, UnitActiveState::UnitInActive);
+        assert!(!timestamp_set(&ts, "StateChangeTimestamp"));
+
+        ts.update(
+            UnitActiveState::UnitInActive,
+            UnitActiveState::UnitActivating,
+        );
+        assert!(timestamp_set(&ts, "StateChangeTimestamp"));
+        assert!(timestamp_set(&ts, "InactiveExitTimestamp"));
+        assert!(!timestamp_set(&ts, "ActiveEnterTimestamp"));
+
+        ts.update(UnitActiveState::UnitActivating, UnitActiveState::UnitActive);
+        assert!(timestamp_set(&ts, "ActiveEnterTimestamp"));
+        assert!(!timestamp_set(&ts, "ActiveExitTimestamp"));
+
+        ts.update(UnitActiveState::UnitActive, UnitActiveState::UnitFailed);
+        assert!(timestamp_set(&ts, "ActiveExitTimestamp"));
+        assert!(timestamp_set(&ts, "InactiveEnterTimestamp"));
+    }
+}
@@ -17,12 +17,15 @@ use super::condition::{assert_keys::*, condition_keys::*, UeCondition};
 use super::config::UeConfig;
 use super::load::UeLoad;
 use super::ratelimit::StartLimit;
+use super::timestamp::UeTimestamp;
 use super::UnitEmergencyAction;
 use crate::unit::data::{DataManager, UnitState};
 use crate::unit::rentry::{UnitLoadState, UnitRe};
 use crate::unit::util::UnitFile;
 use basic::process_util::{self, my_child};
+use basic::special::{ROOT_SLICE, SYSMASTER_SLICE};
 use cgroup::{self, CgFlags};
+use nix::sys::signal::Signal;
 use nix::sys::socket::UnixCredentials;
 use nix::sys::wait::WaitStatus;
 use nix::unistd::Pid;
@@ -34,8 +37,11 @@ use std::path::PathBuf;
 use std::rc::Rc;
 use sysmaster::error::*;
 use sysmaster::rel::ReStation;
-use sysmaster::unit::{KillContext, KillMode, KillOperation, UnitNotifyFlags};
-use sysmaster::unit::{SubUnit, UnitActiveState, UnitBase, UnitType};
+use sysmaster::serialize::section_properties;
+use sysmaster::unit::{
+    slice_parent, SubUnit, UnitActiveState, UnitBase, UnitType, CGROUP_PROPERTIES,
+};
+use sysmaster::unit::{KillContext, KillMode, KillOperation, KillWhom, UnitNotifyFlags};
 
 ///
 pub struct Unit {
@@ -51,6 +57,7 @@ pub struct Unit {
     cgroup: UeCgroup,
     conditions: Rc<UeCondition>,
     start_limit: StartLimit,
+    timestamp: UeTimestamp,
     sub: Box<dyn SubUnit>,
 }
 
@@ -97,7 +104,15 @@ impl ReStation for Unit {
     // reload: entry-only
     fn entry_coldplug(&self) {
         // rebuild external connections, like: timer, ...
-        // unit-frame: do nothing now
+        // unit-frame: the resource control may be changed by daemon-reload
+        if !matches!(
+            self.current_active_state(),
+            UnitActiveState::UnitInActive | UnitActiveState::UnitFailed
+        ) {
+            if let Some(ctx) = self.sub.cgroup_context() {
+                self.cgroup.apply_cgroup_context(&ctx);
+            }
+        }
 
         // sub
         self.sub.entry_coldplug();
@@ -156,6 +171,10 @@ impl UnitBase for Unit {
         self.cg_path()
     }
 
+    fn cg_remove(&self) -> Result<()> {
+        self.cgroup.cg_remove()
+    }
+
     fn ignore_on_isolate(&self) -> bool {
         self.ignore_on_isolate()
     }
@@ -191,6 +210,7 @@ impl Unit {
             conditions: Rc::new(UeCondition::new()),
             sub,
             start_limit: StartLimit::new(),
+            timestamp: UeTimestamp::new(),
         });
         let owner = Rc::clone(&_u);
         _u.sub.attach_unit(owner);
@@ -293,6 +313,8 @@ impl Unit {
             );
         }
 
+        self.timestamp.update(original_state, new_state);
+
         let u_state = UnitState::new(original_state, new_state, flags);
         self.dm.insert_unit_state(self.id().clone(), u_state);
     }
@@ -317,6 +339,59 @@ impl Unit {
         self.load.get_documentation()
     }
 
+    /// return the configuration and the runtime data of the unit as the KEY=VALUE properties
+    pub fn properties(&self) -> Vec<(String, String)> {
+        let pid = |pid: Option<Pid>| pid.map_or(0, |p| p.as_raw()).to_string();
+        let load_state = format!("{:?}", self.load_state()).to_lowercase();
+        let mut props: Vec<(String, String)> = [
+            ("Id", self.id().to_string()),
+            ("LoadState", load_state),
+            ("ActiveState", self.current_active_state().to_string()),
+            ("SubState", self.get_subunit_state()),
+            ("StatusText", self.get_status_text().unwrap_or_default()),
+            ("MainPID", pid(self.sub.main_pid())),
+            ("ControlPID", pid(self.sub.control_pid())),
+            ("ControlGroup", self.cg_path().to_string_lossy().to_string()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+        props.append(&mut self.timestamp.properties());
+
+        let config = self.config.config_data();
+        props.append(&mut section_properties(&config.borrow().Unit));
+        props.append(&mut section_properties(&config.borrow().Install));
+        props.append(&mut self.sub.properties());
+        props
+    }
+
+    /// change the property at runtime, return UnitActionEOpNotSupp if the property can only
+    /// take effect after the unit is reloaded
+    pub fn set_property(&self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "Description" => {
+                self.config.config_data().borrow_mut().Unit.Description = value.to_string()
+            }
+            "Documentation" => {
+                self.config.config_data().borrow_mut().Unit.Documentation = value.to_string()
+            }
+            _ => {
+                self.sub.set_property(key, value)?;
+                // the resource control is applied to the cgroup of the running unit at once
+                if CGROUP_PROPERTIES.contains(&key)
+                    && !self.current_active_state().is_inactive_or_failed()
+                {
+                    if let Some(ctx) = self.sub.cgroup_context() {
+                        self.cgroup.apply_cgroup_context(&ctx);
+                    }
+                }
+                return Ok(());
+            }
+        }
+        self.config.db_update();
+        Ok(())
+    }
+
     ///
     pub fn prepare_exec(&self) -> Result<()> {
         log::debug!("prepare exec cgroup");
@@ -324,7 +399,17 @@ impl Unit {
 
         self.cgroup
             .prepare_cg_exec()
-            .map_err(|_| sysmaster::error::Error::ConvertToSysmaster)
+            .map_err(|_| sysmaster::error::Error::ConvertToSysmaster)?;
+
+        if let Some(ctx) = self.sub.cgroup_context() {
+            self.cgroup.apply_cgroup_context(&ctx);
+        }
+        Ok(())
+    }
+
+    /// attach the process to the cgroup of the unit
+    pub fn cg_attach(&self, pid: Pid) -> Result<()> {
+        self.cgroup.cg_attach(pid)
     }
 
     /// return the cgroup name of the unit
@@ -396,6 +481,75 @@ impl Unit {
         Ok(wait_exit)
     }
 
+    /// send the signal to the processes of the unit on request, the signal is queued with the
+    /// value if it's given, in which case only the main and control process receive it
+    pub fn kill(&self, whom: KillWhom, signal: i32, value: Option<i32>) -> Result<()> {
+        if signal <= 0 || signal > nix::libc::SIGRTMAX() {
+            return Err(Error::UnitActionEInval);
+        }
+
+        let m_pid = self.sub.main_pid();
+        let c_pid = self.sub.control_pid();
+        log::debug!(
+            "unit: {}, kill whom: {:?}, kill signal: {}, value: {:?}, main_pid: {:?}, control_pid: {:?}",
+            self.id(),
+            whom,
+            signal,
+            value,
+            m_pid,
+            c_pid
+        );
+
+        let pid = match whom {
+            KillWhom::Main => m_pid,
+            KillWhom::Control => c_pid,
+            KillWhom::All => {
+                self.kill_all(signal, value, m_pid, c_pid);
+                return Ok(());
+            }
+        };
+        match pid {
+            Some(pid) => process_util::kill_with_value(pid, signal, value).context(NixSnafu),
+            None => Err(Error::Nix {
+                source: Errno::ESRCH,
+            }),
+        }
+    }
+
+    fn kill_all(&self, signal: i32, value: Option<i32>, m_pid: Option<Pid>, c_pid: Option<Pid>) {
+        let pids = self.pids_set(m_pid, c_pid);
+        for pid in pids.iter() {
+            if let Err(e) = process_util::kill_with_value(*pid, signal, value) {
+                log::warn!("Failed to kill pid {}, errno: {}", pid, e);
+            }
+        }
+
+        if value.is_some() || self.cgroup.cg_path().is_empty() {
+            return;
+        }
+
+        match Signal::try_from(signal) {
+            Ok(sig) => {
+                if let Err(e) =
+                    cgroup::cg_kill_recursive(&self.cg_path(), sig, CgFlags::IGNORE_SELF, pids)
+                {
+                    log::warn!("Failed to kill cgroup {:?}: {}", self.cg_path(), e);
+                }
+            }
+            // the real-time signals are not covered by Signal
+            Err(_) => {
+                for pid in cgroup::cg_get_pids(&self.cg_path()) {
+                    if pids.contains(&pid) || pid == nix::unistd::getpid() {
+                        continue;
+                    }
+                    if let Err(e) = process_util::kill_with_value(pid, signal, None) {
+                        log::warn!("Failed to kill pid {}, errno: {}", pid, e);
+                    }
+                }
+            }
+        }
+    }
+
     ///
     pub fn default_dependencies(&self) -> bool {
         self.get_config()
@@ -495,6 +649,11 @@ impl Unit {
         self.sub.get_subunit_state()
     }
 
+    ///
+    pub fn get_status_text(&self) -> Option<String> {
+        self.sub.get_status_text()
+    }
+
     /// test start rate, if start more than burst times in interval time, return error
     fn test_start_limit(&self) -> bool {
         if self.config.config_data().borrow().Unit.StartLimitInterval > 0
@@ -544,9 +703,17 @@ impl Unit {
 
     pub(super) fn load_unit(&self) -> Result<()> {
         self.set_in_load_queue(false);
-        // Mount unit doesn't have config file, set its loadstate to
-        // UnitLoaded directly.
-        if self.unit_type() == UnitType::UnitMount {
+        // Mount unit generated from mountinfo, swap unit generated from /proc/swaps
+        // and device unit generated from devmaster don't have config file, and slice
+        // unit like system.slice can be used without config file, set its loadstate
+        // to UnitLoaded directly.
+        if matches!(
+            self.unit_type(),
+            UnitType::UnitMount | UnitType::UnitSlice | UnitType::UnitDevice | UnitType::UnitSwap
+        ) && !self.load.has_fragment()
+        {
+            self.sub.load(Vec::new())?;
+            self.setup_slice();
             self.load.set_load_state(UnitLoadState::Loaded);
             return Ok(());
         }
@@ -555,6 +722,7 @@ impl Unit {
                 let paths = self.load.get_unit_id_fragment_pathbuf();
                 log::debug!("Begin exec sub class load");
                 self.sub.load(paths)?;
+                self.setup_slice();
 
                 self.load.set_load_state(UnitLoadState::Loaded);
                 Ok(())
@@ -566,6 +734,25 @@ impl Unit {
         }
     }
 
+    /// place the unit in its slice: the parent slice for the slice unit,
+    /// Slice= or the default system.slice for the unit with cgroup context.
+    fn setup_slice(&self) {
+        let slice = if self.unit_type() == UnitType::UnitSlice {
+            slice_parent(self.id())
+        } else {
+            self.sub
+                .cgroup_context()
+                .map(|ctx| ctx.slice().unwrap_or_else(|| SYSMASTER_SLICE.to_string()))
+        };
+
+        if let Some(s) = &slice {
+            if s != ROOT_SLICE {
+                self.load.add_slice_dependency(s);
+            }
+        }
+        self.cgroup.set_slice(slice);
+    }
+
     /// Stub or Merges is temporarily state which represent not load complete
     pub(super) fn load_complete(&self) -> bool {
         self.load_state() != UnitLoadState::Stub && self.load_state() != UnitLoadState::Merged
@@ -687,6 +874,23 @@ impl Unit {
         }
     }
 
+    /// reset the failed state of the unit
+    pub(super) fn reset_failed(&self) {
+        self.sub.reset_failed()
+    }
+
+    /// unload the unit, the sub unit releases what it holds and the cgroup is removed
+    pub(super) fn done(&self) {
+        self.sub.done();
+        if let Err(e) = self.cgroup.cg_remove() {
+            log::warn!("Failed to remove the cgroup of {}: {}", self.id(), e);
+        }
+    }
+
+    pub(super) fn trigger(&self, other: &str) {
+        self.sub.trigger_notify(other)
+    }
+
     pub(super) fn sigchld_events(&self, wait_status: WaitStatus) {
         self.sub.sigchld_events(wait_status)
     }
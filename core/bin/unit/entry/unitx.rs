@@ -25,7 +25,7 @@ use std::path::PathBuf;
 use std::rc::Rc;
 use sysmaster::error::*;
 use sysmaster::rel::ReStation;
-use sysmaster::unit::{SubUnit, UnitActiveState, UnitRelations, UnitType};
+use sysmaster::unit::{KillWhom, SubUnit, UnitActiveState, UnitRelations, UnitType};
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) struct UnitX(Rc<Unit>);
@@ -70,8 +70,9 @@ impl UnitX {
 
     #[allow(dead_code)]
     pub(crate) fn init(&self) {}
-    #[allow(dead_code)]
-    pub(crate) fn done(&self) {}
+    pub(crate) fn done(&self) {
+        self.0.done()
+    }
     #[allow(dead_code)]
     pub(crate) fn load(&self) -> Result<()> {
         self.0.load_unit()
@@ -94,16 +95,23 @@ impl UnitX {
         self.0.reload()
     }
 
-    #[allow(dead_code)]
-    pub(crate) fn kill(&self) {}
+    pub(crate) fn kill(&self, whom: KillWhom, signal: i32, value: Option<i32>) -> Result<()> {
+        self.0.kill(whom, signal, value)
+    }
+    pub(crate) fn set_property(&self, key: &str, value: &str) -> Result<()> {
+        self.0.set_property(key, value)
+    }
     #[allow(dead_code)]
     pub(crate) fn release_resources(&self) {}
     pub(crate) fn sigchld_events(&self, wait_status: WaitStatus) {
         self.0.sigchld_events(wait_status)
     }
-    #[allow(dead_code)]
-    pub(crate) fn reset_failed(&self) {}
-    pub(crate) fn trigger(&self, _other: &Self) {}
+    pub(crate) fn reset_failed(&self) {
+        self.0.reset_failed()
+    }
+    pub(crate) fn trigger(&self, other: &Self) {
+        self.0.trigger(other.id())
+    }
     pub(crate) fn in_load_queue(&self) -> bool {
         self.0.in_load_queue()
     }
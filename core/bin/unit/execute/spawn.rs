@@ -10,27 +10,53 @@
 // NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
 // See the Mulan PSL v2 for more details.
 
+use super::super::collector::{LogCollector, LOG_PRIORITY_STDERR, LOG_PRIORITY_STDOUT};
 use super::super::entry::Unit;
 use basic::fd_util;
 use nix::fcntl::FcntlArg;
+use nix::sys::resource::{setrlimit, Resource};
 use nix::sys::signal::{pthread_sigmask, SigmaskHow};
 use nix::sys::signalfd::SigSet;
 use nix::sys::stat::Mode;
 use nix::unistd::{self, setresgid, setresuid, ForkResult, Gid, Group, Pid, Uid, User};
 use regex::Regex;
+use std::collections::HashMap;
+use std::os::unix::prelude::RawFd;
 use std::path::PathBuf;
 use std::process;
 use std::rc::Rc;
 use sysmaster::error::*;
-use sysmaster::exec::{ExecCommand, ExecContext, ExecFlags, ExecParameters};
+use sysmaster::exec::{ExecCommand, ExecContext, ExecFlags, ExecOutput, ExecParameters, Rlimit};
 use walkdir::DirEntry;
 use walkdir::WalkDir;
 
-pub(in crate::unit) struct ExecSpawn;
+/// the exit status of the child when it fails before executing the command, so that the
+/// failure of setting up the process is not taken as the result of the command
+const EXIT_CHDIR: i32 = 200;
+const EXIT_FDS: i32 = 202;
+const EXIT_EXEC: i32 = 203;
+const EXIT_LIMITS: i32 = 205;
+const EXIT_STDIO: i32 = 208;
+const EXIT_USER: i32 = 217;
+const EXIT_UMASK: i32 = 221;
+const EXIT_NAMESPACE: i32 = 226;
+const EXIT_ENVIRONMENT: i32 = 245;
+
+pub(in crate::unit) struct ExecSpawn {
+    // the DefaultLimit* of the manager, overridden by the Limit* of the unit
+    default_rlimits: Vec<(Resource, Rlimit)>,
+    collector: Rc<LogCollector>,
+}
 
 impl ExecSpawn {
-    pub(in crate::unit) fn new() -> ExecSpawn {
-        ExecSpawn
+    pub(in crate::unit) fn new(
+        default_rlimits: Vec<(Resource, Rlimit)>,
+        collector: &Rc<LogCollector>,
+    ) -> ExecSpawn {
+        ExecSpawn {
+            default_rlimits,
+            collector: Rc::clone(collector),
+        }
     }
 
     pub(in crate::unit) fn spawn(
@@ -40,12 +66,17 @@ impl ExecSpawn {
         params: &ExecParameters,
         ctx: Rc<ExecContext>,
     ) -> Result<Pid> {
+        let log_streams = self.open_log_streams(unit, params)?;
         let ret = unsafe { unistd::fork() };
 
         match ret {
             Ok(ForkResult::Parent { child }) => {
                 log::debug!("child pid is :{}", child);
-                cgroup::cg_attach(child, &unit.cg_path()).context(CgroupSnafu)?;
+                for (rfd, wfd) in log_streams.iter().flatten() {
+                    fd_util::close(*wfd);
+                    self.collector.attach_pid(*rfd, child);
+                }
+                unit.cg_attach(child)?;
                 Ok(child)
             }
             Ok(ForkResult::Child) => {
@@ -53,10 +84,49 @@ impl ExecSpawn {
                 if pthread_sigmask(SigmaskHow::SIG_SETMASK, Some(&set), None).is_err() {
                     log::info!("Failed to reset the sigmask of child process, ignoring.");
                 }
-                exec_child(unit, cmdline, params, ctx);
-                process::exit(0);
+                let log_fds = log_streams.map(|stream| stream.map(|(_, wfd)| wfd));
+                let ret = exec_child(unit, cmdline, params, ctx, &self.default_rlimits, log_fds);
+                process::exit(ret);
+            }
+            Err(_e) => {
+                self.close_log_streams(&log_streams);
+                Err(Error::SpawnError)
             }
-            Err(_e) => Err(Error::SpawnError),
+        }
+    }
+
+    // open the pipes of stdout and stderr which are captured by the log collector
+    fn open_log_streams(
+        &self,
+        unit: &Unit,
+        params: &ExecParameters,
+    ) -> Result<[Option<(RawFd, RawFd)>; 2]> {
+        let mut streams = [None; 2];
+        if !self.collector.enabled() {
+            return Ok(streams);
+        }
+
+        let priorities = [LOG_PRIORITY_STDOUT, LOG_PRIORITY_STDERR];
+        for (i, collect) in params.log_collect().iter().enumerate() {
+            if !collect {
+                continue;
+            }
+            match self.collector.open_stream(unit.id(), priorities[i]) {
+                Ok(stream) => streams[i] = Some(stream),
+                Err(e) => {
+                    log::error!("Failed to open the log stream of {}: {}", unit.id(), e);
+                    self.close_log_streams(&streams);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(streams)
+    }
+
+    fn close_log_streams(&self, streams: &[Option<(RawFd, RawFd)>; 2]) {
+        for (rfd, wfd) in streams.iter().flatten() {
+            fd_util::close(*wfd);
+            self.collector.close_stream(*rfd);
         }
     }
 }
@@ -94,6 +164,26 @@ fn apply_user_and_group(
     setresuid(user.uid, user.uid, user.uid).context(NixSnafu)
 }
 
+fn apply_rlimits(default_rlimits: &[(Resource, Rlimit)], params: &ExecParameters) -> Result<()> {
+    let mut rlimits: HashMap<Resource, Rlimit> = default_rlimits.iter().cloned().collect();
+    rlimits.extend(params.rlimits());
+
+    for (resource, rlimit) in rlimits {
+        log::debug!("Setting {:?} to {:?}", resource, rlimit);
+        setrlimit(resource, rlimit.soft(), rlimit.hard()).context(NixSnafu)?;
+    }
+    Ok(())
+}
+
+fn apply_sandbox(params: &ExecParameters) -> Result<()> {
+    let sandbox = params.sandbox();
+    if !sandbox.enabled() {
+        return Ok(());
+    }
+    log::debug!("Setting up the sandbox: {:?}", sandbox);
+    sandbox.setup()
+}
+
 fn apply_working_directory(working_directory: Option<PathBuf>) -> Result<()> {
     let working_directory = match working_directory {
         None => {
@@ -115,29 +205,90 @@ fn apply_umask(umask: Option<Mode>) -> Result<()> {
     Ok(())
 }
 
-fn exec_child(unit: &Unit, cmdline: &ExecCommand, params: &ExecParameters, ctx: Rc<ExecContext>) {
+fn apply_stdio(params: &ExecParameters, log_fds: [Option<RawFd>; 2]) -> Result<()> {
+    let (stdin, stdout, stderr) = params.stdio();
+    let tty_path = params.tty_path();
+
+    // the fds owned by sysmaster take precedence, the files are opened here in the child,
+    // the output captured by the log collector goes to its pipes
+    let mut stdio_fds = params.stdio_fds();
+    if stdio_fds[0].is_none() {
+        stdio_fds[0] = stdin.open(&tty_path)?;
+    }
+    for (i, output) in [stdout, stderr].into_iter().enumerate() {
+        if log_fds[i].is_some() {
+            stdio_fds[i + 1] = log_fds[i];
+        } else if stdio_fds[i + 1].is_none() {
+            stdio_fds[i + 1] = match output {
+                ExecOutput::Inherit => stdio_fds[i],
+                output => output.open(&tty_path)?,
+            };
+        }
+    }
+
+    // move the fds above stdio first, or they may be overwritten by the dup2 of the others
+    let mut fds = [None; 3];
+    for (i, fd) in stdio_fds.iter().enumerate() {
+        if let Some(fd) = fd {
+            fds[i] = Some(nix::fcntl::fcntl(*fd, FcntlArg::F_DUPFD_CLOEXEC(3)).context(NixSnafu)?);
+        }
+    }
+
+    for (i, fd) in fds.iter().enumerate() {
+        if let Some(fd) = fd {
+            unistd::dup2(*fd, i as i32).context(NixSnafu)?;
+            fd_util::close(*fd);
+        }
+    }
+    Ok(())
+}
+
+fn exec_child(
+    unit: &Unit,
+    cmdline: &ExecCommand,
+    params: &ExecParameters,
+    ctx: Rc<ExecContext>,
+    default_rlimits: &[(Resource, Rlimit)],
+    log_fds: [Option<RawFd>; 2],
+) -> i32 {
     log::debug!("exec context params: {:?}", ctx.envs());
 
+    if let Err(e) = apply_stdio(params, log_fds) {
+        log::error!("Failed to set up standard input/output: {}", e.to_string());
+        return EXIT_STDIO;
+    }
+
+    // Raising the hard limit needs privilege, so set rlimits before changing user
+    if let Err(e) = apply_rlimits(default_rlimits, params) {
+        log::error!("Failed to apply resource limits: {}", e.to_string());
+        return EXIT_LIMITS;
+    }
+
+    // The mount namespace needs privilege, and the working directory must be in it
+    if let Err(e) = apply_sandbox(params) {
+        log::error!("Failed to set up sandbox: {}", e.to_string());
+        return EXIT_NAMESPACE;
+    }
+
     if let Err(e) = apply_user_and_group(params.get_user(), params.get_group(), params) {
         log::error!("Failed to apply user or group: {}", e.to_string());
-        return;
+        return EXIT_USER;
     }
 
     if let Err(e) = apply_working_directory(params.get_working_directory()) {
         log::error!("Failed to apply working directory: {}", e.to_string());
-        return;
+        return EXIT_CHDIR;
     }
 
     if let Err(e) = apply_umask(params.get_umask()) {
         log::error!("Failed to apply umask: {}", e.to_string());
-        return;
+        return EXIT_UMASK;
     }
 
     if let Err(e) = ctx.load_env_from_file() {
         log::error!("{}", e);
-        return;
+        return EXIT_ENVIRONMENT;
     }
-
     for (key, value) in ctx.envs() {
         params.add_env(&key, value.to_string());
     }
@@ -165,27 +316,28 @@ fn exec_child(unit: &Unit, cmdline: &ExecCommand, params: &ExecParameters, ctx:
     let ret = close_all_fds(params.fds());
     if !ret {
         log::error!("close all needless fds failed");
-        return;
+        return EXIT_FDS;
     }
 
     if !shift_fds(&mut keep_fds) {
         log::error!("shift all fds error");
-        return;
+        return EXIT_FDS;
     }
 
     if !flags_fds(&mut keep_fds, params.get_nonblock()) {
         log::error!("flags set all fds error");
-        return;
+        return EXIT_FDS;
     }
 
     log::debug!("exec child envs to execve is: {:?}", envs_cstr);
     match unistd::execve(&cmd, &cstr_args, &envs_cstr) {
         Ok(_) => {
             log::debug!("execv returned Ok()");
+            0
         }
         Err(e) => {
             log::error!("exec child failed: {:?}", e);
-            std::process::exit(1);
+            EXIT_EXEC
         }
     }
 }
@@ -239,6 +391,13 @@ fn build_environment(_unit: &Unit, ep: &ExecParameters) -> Vec<std::ffi::CString
         envs.push(std::ffi::CString::new(format!("LISTEN_PID={}", nix::unistd::getpid())).unwrap());
 
         envs.push(std::ffi::CString::new(format!("LISTEN_FDS={fds}")).unwrap());
+
+        let fd_names = ep.fd_names();
+        if fd_names.len() == fds {
+            envs.push(
+                std::ffi::CString::new(format!("LISTEN_FDNAMES={}", fd_names.join(":"))).unwrap(),
+            );
+        }
     }
 
     if ep.exec_flags().contains(ExecFlags::SOFT_WATCHDOG) && ep.watchdog_usec() > 0 {
@@ -0,0 +1,118 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+use super::manager::UnitManager;
+use event::{EventState, EventType, Events, Source};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use sysmaster::rel::{ReliLastFrame, Reliability};
+
+/// the units which may be garbage collected are queued here, and collected in a defer
+/// event after the jobs are run, rather than in the notify of the unit state
+pub(super) struct UnitGc {
+    // associated objects
+    events: Rc<Events>,
+
+    // owned objects
+    data: Rc<UnitGcData>,
+}
+
+impl UnitGc {
+    pub(super) fn new(eventr: &Rc<Events>, relir: &Rc<Reliability>) -> UnitGc {
+        let gc = UnitGc {
+            events: Rc::clone(eventr),
+            data: Rc::new(UnitGcData::new(relir)),
+        };
+        gc.register();
+        gc
+    }
+
+    pub(super) fn set_um(&self, um: &Rc<UnitManager>) {
+        self.data.um.replace(Rc::downgrade(um));
+    }
+
+    pub(super) fn push(&self, name: &str) {
+        let mut queue = self.data.queue.borrow_mut();
+        if queue.iter().any(|n| n == name) {
+            return;
+        }
+        queue.push(name.to_string());
+
+        let source = Rc::clone(&self.data);
+        if let Err(e) = self.events.set_enabled(source, EventState::OneShot) {
+            log::error!("Failed to enable the unit gc event: {}", e);
+        }
+    }
+
+    fn register(&self) {
+        let source = Rc::clone(&self.data);
+        self.events.add_source(source).unwrap();
+    }
+}
+
+struct UnitGcData {
+    // associated objects
+    reli: Rc<Reliability>,
+    um: RefCell<Weak<UnitManager>>,
+
+    // owned objects
+    queue: RefCell<Vec<String>>,
+}
+
+// the declaration "pub(self)" is for identification only.
+impl UnitGcData {
+    pub(self) fn new(relir: &Rc<Reliability>) -> UnitGcData {
+        UnitGcData {
+            reli: Rc::clone(relir),
+            um: RefCell::new(Weak::new()),
+            queue: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl Source for UnitGcData {
+    fn event_type(&self) -> EventType {
+        EventType::Defer
+    }
+
+    fn epoll_event(&self) -> u32 {
+        0
+    }
+
+    fn priority(&self) -> i8 {
+        // after the job manager, the jobs pending on the units are settled then
+        120
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+
+    fn dispatch(&self, _event: &Events) -> i32 {
+        log::debug!("unit gc dispatch");
+
+        let um = match self.um.borrow().upgrade() {
+            None => return 0,
+            Some(um) => um,
+        };
+        let names = std::mem::take(&mut *self.queue.borrow_mut());
+
+        self.reli.set_last_frame1(ReliLastFrame::OtherEvent as u32);
+        for name in names {
+            um.try_collect_unit(&name);
+        }
+        self.reli.clear_last_frame();
+
+        0
+    }
+}
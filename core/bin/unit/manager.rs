@@ -19,15 +19,19 @@
 /// ManagerX-> Manager | ---->job_manager
 ///                      ---->rentry
 ///
-use super::super::job::{JobAffect, JobConf, JobKind, JobManager};
+use super::super::job::{JobAffect, JobConf, JobKind, JobManager, JobStage};
+use super::collector::LogCollector;
 use super::datastore::UnitDb;
 use super::entry::{StartLimitResult, Unit, UnitEmergencyAction, UnitX};
 use super::execute::ExecSpawn;
+use super::gc::UnitGc;
+use super::monitor::UnitMonitor;
 use super::notify::NotifyManager;
-use super::rentry::{JobMode, UnitLoadState, UnitRe};
+use super::rentry::{unit_name_to_type, JobMode, UnitLoadState, UnitRe};
 use super::runtime::UnitRT;
 use super::sigchld::Sigchld;
 use super::uload::UnitLoad;
+use super::util::transient_unit_content;
 use super::UnitRelationAtom;
 use super::UnitRelations;
 use crate::job::JobResult;
@@ -39,18 +43,23 @@ use crate::utils::table::{TableOp, TableSubscribe};
 use basic::path_lookup::LookupPaths;
 use basic::proc_cmdline::get_process_cmdline;
 use basic::process_util;
-use basic::show_table::{CellColor, ShowTable};
+use basic::unit_name::{unit_name_is_template, unit_name_is_valid};
+use cmdproto::proto::abi::{
+    JobInfo, JobList, UnitInfo, UnitList, UnitProcess, UnitProperties, UnitProperty, UnitStatus,
+};
 use event::Events;
 use nix::unistd::Pid;
 use std::cell::RefCell;
 use std::convert::TryFrom;
-use std::path::PathBuf;
+use std::fs;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use sysmaster::error::*;
 use sysmaster::exec::ExecParameters;
 use sysmaster::exec::{ExecCommand, ExecContext};
 use sysmaster::rel::{ReStation, ReStationKind, ReliLastFrame, Reliability};
-use sysmaster::unit::{UmIf, UnitActiveState, UnitDependencyMask, UnitStatus, UnitType};
+use sysmaster::unit::{KillWhom, UmIf, UnitActiveState, UnitDependencyMask, UnitType};
 use unit_submanager::UnitSubManagers;
 
 //#[derive(Debug)]
@@ -58,6 +67,7 @@ pub(crate) struct UnitManagerX {
     dm: Rc<DataManager>,
     sub_name: String, // key for table-subscriber: UnitState
     data: Rc<UnitManager>,
+    monitor: Rc<UnitMonitor>,
     lookup_path: Rc<LookupPaths>,
     state: Rc<RefCell<State>>,
     #[allow(dead_code)]
@@ -92,6 +102,7 @@ impl UnitManagerX {
                 Rc::clone(&state),
                 manager_config.clone(),
             ),
+            monitor: Rc::new(UnitMonitor::new(eventr)),
             lookup_path: Rc::clone(lookup_path),
             state,
             manager_config,
@@ -131,22 +142,73 @@ impl UnitManagerX {
         self.data.stop_unit(name, is_manual)
     }
 
+    pub(crate) fn start_transient_unit(
+        &self,
+        name: &str,
+        properties: &[(String, String)],
+    ) -> Result<()> {
+        self.data.start_transient_unit(name, properties)
+    }
+
     pub(crate) fn reload(&self, name: &str) -> Result<()> {
         self.data.reload(name)
     }
 
+    pub(crate) fn reset_failed(&self, name: &str) -> Result<()> {
+        self.data.reset_failed(name)
+    }
+
+    pub(crate) fn get_all_jobs(&self) -> Result<JobList> {
+        self.data.get_all_jobs()
+    }
+
+    pub(crate) fn cancel_job(&self, id: u32) -> Result<()> {
+        self.data.cancel_job(id)
+    }
+
+    pub(crate) fn cancel_all_jobs(&self) -> Result<()> {
+        self.data.cancel_all_jobs()
+    }
+
     pub(crate) fn restart_unit(&self, name: &str, is_manual: bool) -> Result<()> {
         self.data.restart_unit(name, is_manual)
     }
 
+    pub(crate) fn kill_unit(
+        &self,
+        name: &str,
+        whom: KillWhom,
+        signal: i32,
+        value: Option<i32>,
+    ) -> Result<()> {
+        self.data.kill_unit(name, whom, signal, value)
+    }
+
     pub(crate) fn get_unit_status(&self, name: &str) -> Result<UnitStatus> {
         self.data.get_unit_status(name)
     }
 
-    pub(crate) fn get_all_units(&self) -> Result<String> {
+    pub(crate) fn get_unit_properties(&self, name: &str) -> Result<UnitProperties> {
+        self.data.get_unit_properties(name)
+    }
+
+    pub(crate) fn set_unit_properties(
+        &self,
+        name: &str,
+        properties: &[(String, String)],
+        runtime: bool,
+    ) -> Result<()> {
+        self.data.set_unit_properties(name, properties, runtime)
+    }
+
+    pub(crate) fn get_all_units(&self) -> Result<UnitList> {
         self.data.get_all_units()
     }
 
+    pub(crate) fn subscribe(&self, stream: UnixStream) {
+        self.monitor.subscribe(stream);
+    }
+
     pub(crate) fn child_sigchld_enable(&self, enable: bool) -> i32 {
         self.data.sigchld.enable(enable)
     }
@@ -168,6 +230,13 @@ impl UnitManagerX {
         let ret = dm.register_job_result(&self.sub_name, subscriber);
         assert!(ret.is_none());
 
+        // dm-unit_state, dm-job_info: sent to the subscribers of the control socket
+        let monitor = Rc::clone(&self.monitor);
+        let ret = dm.register_unit_state("UnitMonitor", monitor.clone());
+        assert!(ret.is_none());
+        let ret = dm.register_job_info("UnitMonitor", monitor);
+        assert!(ret.is_none());
+
         // reliability-station
         let station = Rc::clone(&self.data);
         let kind = ReStationKind::Level2;
@@ -252,10 +321,13 @@ pub struct UnitManager {
     load: UnitLoad,
     jm: Rc<JobManager>,
     exec: ExecSpawn,
+    collector: Rc<LogCollector>,
     sigchld: Sigchld,
     notify: NotifyManager,
+    gc: UnitGc,
     sms: UnitSubManagers,
     manager_config: Rc<ManagerConfig>,
+    lookup_path: Rc<LookupPaths>,
 }
 
 impl UmIf for UnitManager {
@@ -324,6 +396,10 @@ impl UmIf for UnitManager {
     fn has_stop_job(&self, name: &str) -> bool {
         self.has_stop_job(name)
     }
+
+    fn has_start_like_job(&self, name: &str) -> bool {
+        self.has_start_like_job(name)
+    }
     /// check the unit that will be triggered by {name} is in active or activating state
     fn relation_active_or_pending(&self, name: &str) -> bool {
         self.relation_active_or_pending(name)
@@ -333,6 +409,10 @@ impl UmIf for UnitManager {
         self.start_unit(name, false)
     }
 
+    fn unit_stop_by_job(&self, name: &str) -> Result<()> {
+        self.stop_unit(name, false)
+    }
+
     fn events(&self) -> Rc<Events> {
         self.events()
     }
@@ -386,6 +466,14 @@ impl UmIf for UnitManager {
         self.collect_socket_fds(name)
     }
 
+    fn collect_named_socket_fds(&self, name: &str) -> Vec<(String, i32)> {
+        self.collect_named_socket_fds(name)
+    }
+
+    fn log_collector_enabled(&self) -> bool {
+        self.collector.enabled()
+    }
+
     fn get_dependency_list(&self, _unit_name: &str, _atom: UnitRelationAtom) -> Vec<String> {
         self.get_dependency_list(_unit_name, _atom)
     }
@@ -572,6 +660,16 @@ impl UnitManager {
         self.jm.has_stop_job(&u)
     }
 
+    fn has_start_like_job(&self, name: &str) -> bool {
+        let u = if let Some(unit) = self.db.units_get(name) {
+            unit
+        } else {
+            return false;
+        };
+
+        self.jm.has_start_like_job(&u)
+    }
+
     ///
     pub fn unit_emergency_action(&self, action: UnitEmergencyAction, reason: String) {
         if action == UnitEmergencyAction::None {
@@ -661,6 +759,23 @@ impl UnitManager {
         fds
     }
 
+    /// return the fds that trigger the unit {name}, along with the socket unit they belong to;
+    fn collect_named_socket_fds(&self, name: &str) -> Vec<(String, i32)> {
+        let deps = self.db.dep_gets(name, UnitRelations::UnitTriggeredBy);
+        let mut fds = Vec::new();
+        for dep in deps.iter() {
+            if dep.unit_type() != UnitType::UnitSocket {
+                continue;
+            }
+
+            for fd in dep.collect_fds() {
+                fds.push((dep.id().to_string(), fd));
+            }
+        }
+
+        fds
+    }
+
     /// check the unit that will be triggered by {name} is in active or activating state
     fn relation_active_or_pending(&self, name: &str) -> bool {
         let deps = self.db.dep_gets(name, UnitRelations::UnitTriggers);
@@ -681,7 +796,10 @@ impl UnitManager {
             return false;
         }
 
-        let p_unit = self.db.get_unit_by_pid(pid);
+        let p_unit = self
+            .db
+            .get_unit_by_pid(pid)
+            .or_else(|| self.db.get_unit_by_cgroup(pid));
         if p_unit.is_none() {
             return false;
         }
@@ -769,6 +887,13 @@ impl UnitManager {
         {
             return Err(Error::UnitActionERefuseManualStop);
         }
+
+        // the failed transient unit is kept for inspection, stopping it resets and collects it
+        if unit.active_state() == UnitActiveState::UnitFailed && self.unit_is_transient(name) {
+            unit.reset_failed();
+            return Ok(());
+        }
+
         self.jm.exec(
             &JobConf::new(&unit, JobKind::Stop),
             JobMode::Replace,
@@ -777,6 +902,110 @@ impl UnitManager {
         Ok(())
     }
 
+    /// write the unit file of the transient unit and start it through the normal load path
+    pub(self) fn start_transient_unit(
+        &self,
+        name: &str,
+        properties: &[(String, String)],
+    ) -> Result<()> {
+        // the name is a part of the path, so it must be a valid unit name without "/"
+        if !unit_name_is_valid(name)
+            || unit_name_is_template(name)
+            || unit_name_to_type(name) == UnitType::UnitTypeInvalid
+        {
+            return Err(Error::UnitActionEInval);
+        }
+
+        // the transient unit can not take the place of the existing one
+        if let Some(unit) = self.db.units_get(name) {
+            if unit.load_state() != UnitLoadState::NotFound {
+                return Err(Error::UnitActionEAlready);
+            }
+        }
+        for dir in &self.lookup_path.search_path {
+            if dir != &self.lookup_path.transient && Path::new(dir).join(name).exists() {
+                return Err(Error::UnitActionEAlready);
+            }
+        }
+
+        let content = transient_unit_content(name, properties)?;
+        let dir = Path::new(&self.lookup_path.transient);
+        fs::create_dir_all(dir).context(IoSnafu)?;
+        fs::write(dir.join(name), content).context(IoSnafu)?;
+        log::info!("Created the transient unit {}", name);
+
+        if let Err(e) = self.start_unit(name, true) {
+            self.remove_transient_unit(name);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn unit_is_transient(&self, name: &str) -> bool {
+        Path::new(&self.lookup_path.transient).join(name).exists()
+    }
+
+    /// remove the unit file of the transient unit and unload it
+    fn remove_transient_unit(&self, name: &str) {
+        log::info!("Removing the transient unit {}", name);
+        let dir = Path::new(&self.lookup_path.transient);
+        for path in [dir.join(name), dir.join(format!("{name}.toml"))] {
+            if let Err(e) = fs::remove_file(&path) {
+                if e.kind() != ErrorKind::NotFound {
+                    log::warn!("Failed to remove {:?}: {}", path, e);
+                }
+            }
+        }
+
+        if let Some(unit) = self.db.units_get(name) {
+            self.unload_unit(&unit);
+        }
+    }
+
+    /// collect the transient unit queued in the gc, if it's still inactive and no job is
+    /// pending on it, the failed one is kept until it's reset or stopped
+    pub(super) fn try_collect_unit(&self, name: &str) {
+        let unit = match self.db.units_get(name) {
+            None => return,
+            Some(u) => u,
+        };
+        if unit.active_state() != UnitActiveState::UnitInActive
+            || self.jm.has_start_like_job(&unit)
+            || self.jm.has_stop_job(&unit)
+            || !self.unit_is_transient(name)
+        {
+            return;
+        }
+        self.remove_transient_unit(name);
+    }
+
+    /// unload the unit, what it holds are released, and it's removed from the database
+    /// along with its dependencies, child watches and jobs
+    fn unload_unit(&self, unit: &Rc<UnitX>) {
+        let name = unit.id().clone();
+        log::debug!("Unloading the unit {}", name);
+        unit.done();
+        self.db.unit_remove(&name);
+
+        self.rentry.base_remove(&name);
+        self.rentry.load_remove(&name);
+        self.rentry.conf_remove(&name);
+        self.rentry.cgroup_remove(&name);
+        self.rentry.child_remove(&name);
+        self.rentry.pps_remove(&name);
+        self.rentry.dep_remove(&name);
+    }
+
+    /// reset the failed state of the unit, the failed transient unit is collected then
+    pub(self) fn reset_failed(&self, name: &str) -> Result<()> {
+        let unit = match self.db.units_get(name) {
+            None => return Err(Error::UnitActionENoent),
+            Some(u) => u,
+        };
+        unit.reset_failed();
+        Ok(())
+    }
+
     pub(self) fn reload(&self, name: &str) -> Result<()> {
         if let Some(unit) = self.load_unitx(name) {
             self.jm.exec(
@@ -827,32 +1056,106 @@ impl UnitManager {
         Ok(())
     }
 
-    fn get_unit_cgroup_path(&self, unit: Rc<Unit>) -> String {
-        let res = match unit.cg_path().to_str() {
-            Some(res) => res.to_string(),
-            None => String::new(),
-        };
-        if res.is_empty() {
-            return "Empty cgroup path".to_string();
+    fn get_unit_status_pids(&self, unit: Rc<Unit>) -> Vec<UnitProcess> {
+        unit.get_pids()
+            .iter()
+            .map(|pid| UnitProcess {
+                pid: pid.as_raw() as u32,
+                cmdline: get_process_cmdline(pid),
+            })
+            .collect()
+    }
+
+    pub(self) fn kill_unit(
+        &self,
+        name: &str,
+        whom: KillWhom,
+        signal: i32,
+        value: Option<i32>,
+    ) -> Result<()> {
+        match self.units_get(name) {
+            Some(unit) => unit.kill(whom, signal, value),
+            None => Err(Error::NotExisted),
         }
-        res
     }
 
-    fn get_unit_status_pids(&self, unit: Rc<Unit>) -> String {
-        let pids = unit.get_pids();
-        if pids.is_empty() {
-            return "No process".to_string();
+    pub(self) fn get_unit_properties(&self, name: &str) -> Result<UnitProperties> {
+        let unit = match self.units_get(name) {
+            Some(unit) => unit,
+            None => {
+                return Err(Error::NotExisted);
+            }
+        };
+        Ok(UnitProperties {
+            unit: name.to_string(),
+            properties: unit
+                .properties()
+                .into_iter()
+                .map(|(key, value)| UnitProperty { key, value })
+                .collect(),
+        })
+    }
+
+    /// apply the properties to the unit and save each of them in the dropin
+    /// "50-{key}.conf" of the control directory, the properties that can't be
+    /// applied at runtime take effect after the unit is reloaded
+    pub(self) fn set_unit_properties(
+        &self,
+        name: &str,
+        properties: &[(String, String)],
+        runtime: bool,
+    ) -> Result<()> {
+        // the name is a part of the path of the dropins
+        if !unit_name_is_valid(name) {
+            return Err(Error::UnitActionEInval);
+        }
+
+        let unit = match self.load_unitx(name) {
+            None => {
+                return Err(Error::UnitActionENoent);
+            }
+            Some(v) => v,
+        };
+
+        let mut keys: Vec<&str> = Vec::new();
+        for (key, _) in properties {
+            if !keys.contains(&key.as_str()) {
+                keys.push(key);
+            }
         }
-        let mut res = String::new();
-        for pid in pids.iter() {
-            if !res.is_empty() {
-                res += "\n";
+        // check all the properties before any of them is applied
+        let mut dropins = Vec::new();
+        for key in keys {
+            let values: Vec<(String, String)> = properties
+                .iter()
+                .filter(|(k, _)| k == key)
+                .cloned()
+                .collect();
+            dropins.push((key, transient_unit_content(name, &values)?));
+        }
+
+        for (key, value) in properties {
+            match unit.set_property(key, value) {
+                Ok(_) => {}
+                Err(Error::UnitActionEOpNotSupp) => {
+                    log::info!("{} of {} takes effect after reload", key, name);
+                }
+                Err(e) => return Err(e),
             }
-            res += &pid.to_string();
-            res += " ";
-            res += get_process_cmdline(pid).as_str();
         }
-        res
+
+        let control = if runtime {
+            &self.lookup_path.runtime_control
+        } else {
+            &self.lookup_path.persistent_control
+        };
+        let dir = Path::new(control).join(format!("{name}.d"));
+        fs::create_dir_all(&dir).context(IoSnafu)?;
+        for (key, content) in dropins {
+            fs::write(dir.join(format!("50-{key}.conf")), content).context(IoSnafu)?;
+        }
+        log::info!("Set the properties of {} in {:?}", name, dir);
+        Ok(())
     }
 
     pub(self) fn get_unit_status(&self, name: &str) -> Result<UnitStatus> {
@@ -862,29 +1165,24 @@ impl UnitManager {
                 return Err(Error::NotExisted);
             }
         };
-        let error_code = match self.current_active_state(name) {
-            // systemd will return 3 if the unit's state is failed or inactive.
-            UnitActiveState::UnitFailed | UnitActiveState::UnitInActive => 3,
-            _ => 0,
-        };
-        Ok(UnitStatus::new(
-            name.to_string(),
-            unit.get_description(),
-            unit.get_documentation(),
-            self.load_unit_success(name).to_string(),
-            self.get_subunit_state(name),
-            self.current_active_state(name).to_string(),
-            self.get_unit_cgroup_path(unit.clone()),
-            self.get_unit_status_pids(unit.clone()),
-            error_code,
-        ))
-    }
-
-    pub(self) fn get_all_units(&self) -> Result<String> {
-        let mut list_units_table = ShowTable::new();
-        list_units_table.add_line(vec!["UNIT", "LOAD", "ACTIVE", "SUB", "DESCRIPTION"]);
+        Ok(UnitStatus {
+            name: name.to_string(),
+            description: unit.get_description().unwrap_or_default(),
+            documentation: unit.get_documentation().unwrap_or_default(),
+            load_state: self.load_unit_success(name).to_string(),
+            active_state: self.current_active_state(name).to_string(),
+            sub_state: self.get_subunit_state(name),
+            status_text: unit.get_status_text().unwrap_or_default(),
+            cgroup_path: unit.cg_path().to_string_lossy().to_string(),
+            processes: self.get_unit_status_pids(unit.clone()),
+            // the latest logs captured by the log collector
+            logs: self.collector.tail(name, 10),
+        })
+    }
+
+    pub(self) fn get_all_units(&self) -> Result<UnitList> {
+        let mut units = Vec::new();
         for unit_type in UnitType::iterator() {
-            list_units_table.set_current_row_underline(true);
             for unit_name in self.units_get_all(Some(unit_type)) {
                 let unit = match self.units_get(&unit_name) {
                     Some(unit) => unit,
@@ -893,26 +1191,56 @@ impl UnitManager {
                         continue;
                     }
                 };
-                let load_state = self.load_unit_success(&unit_name).to_string();
-                let active_state = self.current_active_state(&unit_name).to_string();
-                let sub_state = self.get_subunit_state(&unit_name);
-                let description = match unit.get_description() {
-                    None => String::from(&unit_name),
-                    Some(str) => str,
+                units.push(UnitInfo {
+                    load_state: self.load_unit_success(&unit_name).to_string(),
+                    active_state: self.current_active_state(&unit_name).to_string(),
+                    sub_state: self.get_subunit_state(&unit_name),
+                    description: unit.get_description().unwrap_or_else(|| unit_name.clone()),
+                    name: unit_name,
+                });
+            }
+        }
+        Ok(UnitList { units })
+    }
+
+    pub(self) fn get_all_jobs(&self) -> Result<JobList> {
+        let jobs = self
+            .jm
+            .get_all_jobinfo()
+            .into_iter()
+            .map(|job| {
+                let stage = match job.stage {
+                    JobStage::Init => "init",
+                    JobStage::Wait => "waiting",
+                    JobStage::Running => "running",
+                    JobStage::End(_) => "end",
                 };
-                list_units_table.add_line(vec![
-                    &unit_name,
-                    &load_state,
-                    &active_state,
-                    &sub_state,
-                    &description,
-                ]);
-                if active_state == "failed" {
-                    list_units_table.set_current_row_color(CellColor::Red);
+                JobInfo {
+                    id: job.id,
+                    unit: job.unit.id().to_string(),
+                    kind: format!("{:?}", job.kind).to_lowercase(),
+                    stage: stage.to_string(),
+                    waiting_usec: job.since.elapsed().as_micros() as u64,
                 }
+            })
+            .collect();
+        Ok(JobList { jobs })
+    }
+
+    /// cancel the waiting or running job, the jobs depending on it are cancelled too
+    pub(self) fn cancel_job(&self, id: u32) -> Result<()> {
+        self.jm.remove(id)
+    }
+
+    pub(self) fn cancel_all_jobs(&self) -> Result<()> {
+        for job in self.jm.get_all_jobinfo() {
+            // the job may have been removed as the dependency of the former one
+            match self.jm.remove(job.id) {
+                Ok(()) | Err(Error::NotExisted) => {}
+                Err(e) => return Err(e),
             }
         }
-        Ok(list_units_table.to_string())
+        Ok(())
     }
 
     pub(self) fn new(
@@ -927,6 +1255,7 @@ impl UnitManager {
         let _db = Rc::new(UnitDb::new(&_rentry));
         let _rt = Rc::new(UnitRT::new(relir, &_rentry, &_db));
         let _jm = Rc::new(JobManager::new(eventr, relir, &_db, dmr));
+        let _collector = Rc::new(LogCollector::new(eventr, relir, &_rentry, &manager_config));
         let um = Rc::new(UnitManager {
             events: Rc::clone(eventr),
             reli: Rc::clone(relir),
@@ -935,15 +1264,19 @@ impl UnitManager {
             db: Rc::clone(&_db),
             rt: Rc::clone(&_rt),
             jm: Rc::clone(&_jm),
-            exec: ExecSpawn::new(),
+            exec: ExecSpawn::new(manager_config.default_rlimits(), &_collector),
+            collector: Rc::clone(&_collector),
             sigchld: Sigchld::new(eventr, relir, &_db, &_jm),
             notify: NotifyManager::new(eventr, relir, &_rentry, &_db, &_jm),
+            gc: UnitGc::new(eventr, relir),
             sms: UnitSubManagers::new(relir),
             state,
             manager_config,
+            lookup_path: Rc::clone(lookup_path),
         });
         um.load.set_um(&um);
         um.sms.set_um(&um);
+        um.gc.set_um(&um);
         um
     }
 
@@ -1011,6 +1344,11 @@ impl UnitManager {
         for other in self.db.dep_gets_atom(&unitx, atom) {
             other.trigger(&unitx);
         }
+
+        // the inactive transient unit is garbage collected out of the notify
+        if state.ns == UnitActiveState::UnitInActive {
+            self.gc.push(source);
+        }
     }
 
     fn remove_states(&self, _source: &str) {
@@ -1149,6 +1487,9 @@ impl ReStation for UnitManager {
         // notify
         self.notify.db_map(reload);
 
+        // log collector
+        self.collector.db_map(reload);
+
         // sub-manager
         self.sms.db_map(reload);
     }
@@ -1158,6 +1499,9 @@ impl ReStation for UnitManager {
         // notify
         self.notify.register_ex();
 
+        // log collector
+        self.collector.register_ex();
+
         // sub-manager
         self.sms.enumerate();
     }
@@ -1355,6 +1699,23 @@ mod tests {
         (dm, event, um)
     }
 
+    #[test]
+    fn test_transient_unit_invalid_name() {
+        let dm = init_dm_for_test();
+        let props = vec![("ExecStart".to_string(), "/bin/true".to_string())];
+        // the invalid names are rejected before the unit file is written
+        for name in ["../../etc/x.service", "a/b.service", "foo@.service", "foo"] {
+            assert!(matches!(
+                dm.2.start_transient_unit(name, &props),
+                Err(Error::UnitActionEInval)
+            ));
+            assert!(matches!(
+                dm.2.set_unit_properties(name, &props, true),
+                Err(Error::UnitActionEInval) | Err(Error::UnitActionENoent)
+            ));
+        }
+    }
+
     #[allow(dead_code)]
     fn setup_mount_point() -> Result<()> {
         setup::mount_setup()
@@ -1481,4 +1842,26 @@ mod tests {
             };
         }
     }
+
+    #[test]
+    fn test_unload_unit() {
+        let dm = init_dm_for_test();
+        let name = String::from("testsunit.target");
+        let unit = match dm.2.load_unitx(&name) {
+            None => return,
+            Some(u) => u,
+        };
+        let deps = dm.2.db.dep_gets(&name, UnitRelations::UnitRequires);
+
+        dm.2.unload_unit(&unit);
+        assert!(dm.2.db.units_get(&name).is_none());
+        assert!(dm.2.rentry.dep_get(&name).is_empty());
+        // the dependencies are removed in both directions
+        for other in deps {
+            let required_by = dm.2.db.dep_gets(other.id(), UnitRelations::UnitRequiresBy);
+            assert!(required_by.iter().all(|u| u.id() != &name));
+            let deps = dm.2.rentry.dep_get(other.id());
+            assert!(deps.iter().all(|(_, n)| n != &name));
+        }
+    }
 }
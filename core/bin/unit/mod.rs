@@ -54,11 +54,14 @@ pub enum UnitErrno {
 // {execute | sigchld | notify} -> unit_manager -> um_interface
 
 mod base;
+mod collector;
 mod data;
 mod datastore;
 mod entry;
 mod execute;
+mod gc;
 mod manager;
+mod monitor;
 mod notify;
 mod rentry;
 mod runtime;
@@ -0,0 +1,299 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! The monitor sends the changes of the unit states and the jobs to the clients subscribing
+//! them on the control socket. Each subscriber is watched in the event loop, so it is dropped
+//! as soon as it disconnects, and it is dropped too when the event can't be sent to it, which
+//! means it doesn't read the events in time.
+use super::data::UnitState;
+use crate::job::JobInfo;
+use crate::utils::table::{TableOp, TableSubscribe};
+use ::event::{EventState, EventType, Events, Source};
+use cmdproto::proto::abi::{
+    command_response::ResponseData, event, CommandResponse, Event, JobEvent, UnitStateChange,
+};
+use cmdproto::proto::frame::write_frame;
+use cmdproto::proto::StatusCode;
+use std::cell::RefCell;
+use std::io::{ErrorKind, Read};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::rc::{Rc, Weak};
+
+/// the max number of the subscribers, each of them holds a connection in sysmaster
+const MAX_SUBSCRIBERS: usize = 64;
+
+type Subscribers = RefCell<Vec<Rc<Subscriber>>>;
+
+pub(super) struct UnitMonitor {
+    // associated objects
+    events: Rc<Events>,
+
+    // owned objects
+    subscribers: Rc<Subscribers>,
+}
+
+impl UnitMonitor {
+    pub(super) fn new(eventr: &Rc<Events>) -> UnitMonitor {
+        UnitMonitor {
+            events: Rc::clone(eventr),
+            subscribers: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    pub(super) fn subscribe(&self, stream: UnixStream) {
+        if self.subscribers.borrow().len() >= MAX_SUBSCRIBERS {
+            log::warn!("Too many subscribers, refusing the new one.");
+            return;
+        }
+        // a slow client must not block the manager
+        if let Err(e) = stream.set_nonblocking(true) {
+            log::error!("Failed to set the subscriber non-blocking: {}", e);
+            return;
+        }
+
+        let subscriber = Rc::new(Subscriber {
+            subscribers: Rc::downgrade(&self.subscribers),
+            stream,
+        });
+        let source: Rc<dyn Source> = subscriber.clone();
+        if let Err(e) = self
+            .events
+            .add_source(source.clone())
+            .and_then(|_| self.events.set_enabled(source, EventState::On))
+        {
+            log::error!("Failed to watch the subscriber: {}", e);
+            return;
+        }
+        self.subscribers.borrow_mut().push(subscriber);
+    }
+
+    fn send(&self, event: event::Event) {
+        if self.subscribers.borrow().is_empty() {
+            return;
+        }
+
+        let msg = CommandResponse {
+            status: StatusCode::OK.as_u16() as _,
+            response_data: Some(ResponseData::Event(Event { event: Some(event) })),
+            ..Default::default()
+        };
+        let subscribers = std::mem::take(&mut *self.subscribers.borrow_mut());
+        let (alive, dropped): (Vec<_>, Vec<_>) = subscribers
+            .into_iter()
+            .partition(|subscriber| subscriber.send(&msg));
+        *self.subscribers.borrow_mut() = alive;
+        for subscriber in dropped {
+            subscriber.unwatch(&self.events);
+        }
+    }
+}
+
+/// the connection of a subscriber, nothing is expected to be read from it, it's watched
+/// to find out the client disconnecting
+struct Subscriber {
+    // associated objects
+    subscribers: Weak<Subscribers>,
+
+    // owned objects
+    stream: UnixStream,
+}
+
+impl Subscriber {
+    fn send(&self, msg: &CommandResponse) -> bool {
+        match write_frame(&mut &self.stream, msg) {
+            Ok(_) => true,
+            Err(e) => {
+                log::debug!("Failed to send the event, drop the subscriber: {}", e);
+                false
+            }
+        }
+    }
+
+    /// read what the client sends, return false if it has disconnected
+    fn read(&self) -> bool {
+        let mut buf = [0_u8; 256];
+        loop {
+            match (&self.stream).read(&mut buf) {
+                Ok(0) => return false,
+                Ok(_) => continue,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return true,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    log::debug!("Failed to read the subscriber: {}", e);
+                    return false;
+                }
+            }
+        }
+    }
+
+    fn close(&self, e: &Events) {
+        let subscribers = match self.subscribers.upgrade() {
+            None => return,
+            Some(v) => v,
+        };
+        let pos = subscribers
+            .borrow()
+            .iter()
+            .position(|s| std::ptr::eq(s.as_ref(), self));
+        if let Some(pos) = pos {
+            let me = subscribers.borrow_mut().remove(pos);
+            me.unwatch(e);
+        }
+    }
+
+    fn unwatch(self: Rc<Self>, e: &Events) {
+        if let Err(err) = e.del_source(self) {
+            log::error!("Failed to remove the subscriber: {}", err);
+        }
+    }
+}
+
+impl Source for Subscriber {
+    fn event_type(&self) -> EventType {
+        EventType::Io
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN | libc::EPOLLRDHUP) as u32
+    }
+
+    fn dispatch(&self, e: &Events) -> i32 {
+        if !self.read() {
+            log::debug!("The subscriber has disconnected, drop it.");
+            self.close(e);
+        }
+        0
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+
+    fn fd(&self) -> RawFd {
+        self.stream.as_raw_fd()
+    }
+
+    fn priority(&self) -> i8 {
+        10i8
+    }
+}
+
+impl TableSubscribe<String, UnitState> for UnitMonitor {
+    fn notify(&self, op: &TableOp<String, UnitState>) {
+        if let TableOp::TableInsert(name, state) = op {
+            if state.os == state.ns {
+                return;
+            }
+            self.send(event::Event::UnitState(UnitStateChange {
+                unit: name.to_string(),
+                old_state: state.os.to_string(),
+                new_state: state.ns.to_string(),
+            }));
+        }
+    }
+}
+
+impl TableSubscribe<u32, JobInfo> for UnitMonitor {
+    fn notify(&self, op: &TableOp<u32, JobInfo>) {
+        let event = match op {
+            TableOp::TableInsert(_, job) => event::Event::JobNew(job_event(job)),
+            TableOp::TableRemove(_, job) => event::Event::JobRemoved(job_event(job)),
+        };
+        self.send(event);
+    }
+}
+
+fn job_event(job: &JobInfo) -> JobEvent {
+    JobEvent {
+        id: job.id,
+        unit: job.unit.id().to_string(),
+        kind: format!("{:?}", job.kind).to_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cmdproto::proto::ProstClientStream;
+    use sysmaster::unit::{UnitActiveState, UnitNotifyFlags};
+
+    fn recv_event(client: &mut ProstClientStream<UnixStream>) -> event::Event {
+        let res = client.next_event().unwrap();
+        match res.response_data {
+            Some(ResponseData::Event(Event { event: Some(e) })) => e,
+            _ => panic!("not an event: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn monitor_send_unit_state() {
+        let events = Rc::new(Events::new().unwrap());
+        let monitor = UnitMonitor::new(&events);
+        let (server, client) = UnixStream::pair().unwrap();
+        let mut client = ProstClientStream::new(client);
+        monitor.subscribe(server);
+
+        let name = String::from("test.service");
+        let flags = UnitNotifyFlags::UNIT_NOTIFY_SUCCESS;
+        let state = UnitState::new(
+            UnitActiveState::UnitActive,
+            UnitActiveState::UnitActive,
+            flags,
+        );
+        monitor.notify(&TableOp::TableInsert(&name, &state));
+        let state = UnitState::new(
+            UnitActiveState::UnitActive,
+            UnitActiveState::UnitFailed,
+            flags,
+        );
+        monitor.notify(&TableOp::TableInsert(&name, &state));
+
+        // the one without change is not sent
+        let expect = event::Event::UnitState(UnitStateChange {
+            unit: name,
+            old_state: UnitActiveState::UnitActive.to_string(),
+            new_state: UnitActiveState::UnitFailed.to_string(),
+        });
+        assert_eq!(recv_event(&mut client), expect);
+    }
+
+    #[test]
+    fn monitor_drop_disconnected() {
+        let events = Rc::new(Events::new().unwrap());
+        let monitor = UnitMonitor::new(&events);
+        let (server, client) = UnixStream::pair().unwrap();
+        monitor.subscribe(server);
+        let (server, _client) = UnixStream::pair().unwrap();
+        monitor.subscribe(server);
+        assert_eq!(monitor.subscribers.borrow().len(), 2);
+
+        // it's dropped once it disconnects, without waiting for the next event
+        drop(client);
+        events.run(100).unwrap();
+        assert_eq!(monitor.subscribers.borrow().len(), 1);
+    }
+
+    #[test]
+    fn monitor_max_subscribers() {
+        let events = Rc::new(Events::new().unwrap());
+        let monitor = UnitMonitor::new(&events);
+        let mut clients = Vec::new();
+        for _ in 0..MAX_SUBSCRIBERS + 1 {
+            let (server, client) = UnixStream::pair().unwrap();
+            monitor.subscribe(server);
+            clients.push(client);
+        }
+        assert_eq!(monitor.subscribers.borrow().len(), MAX_SUBSCRIBERS);
+    }
+}
@@ -30,6 +30,8 @@ use sysmaster::error::*;
 use sysmaster::rel::{ReStation, ReliLastFrame, Reliability};
 
 const NOTIFY_SOCKET: &str = "/run/sysmaster/notify";
+/// the max number of fds that can be sent within one notify message
+const NOTIFY_FD_MAX: usize = 768;
 
 pub(super) struct NotifyManager {
     // associated objects
@@ -186,9 +188,14 @@ impl Notify {
 
         // peek
         let pid = notify_peek_pid(self.rawfd(), flags)?;
+        // the sender may be any process in the cgroup of the unit, not only the watched ones
+        let unit = self
+            .db
+            .get_unit_by_pid(Pid::from_raw(pid))
+            .or_else(|| self.db.get_unit_by_cgroup(Pid::from_raw(pid)));
         //  record + pop + action
         let ret;
-        if let Some(unit) = self.db.get_unit_by_pid(Pid::from_raw(pid)) {
+        if let Some(unit) = unit {
             self.reli.set_last_unit(unit.id());
             ret = self.notify_recv_dispatch(flags, pid, Some(unit));
             self.reli.clear_last_unit();
@@ -207,7 +214,7 @@ impl Notify {
     ) -> Result<i32> {
         let mut buffer = [0u8; 4096];
         let mut iov = [IoSliceMut::new(&mut buffer)];
-        let mut space = cmsg_space!(libc::ucred, RawFd);
+        let mut space = cmsg_space!(libc::ucred, [RawFd; NOTIFY_FD_MAX]);
 
         // pop
         let msgs = socket::recvmsg::<()>(self.rawfd(), &mut iov, Some(&mut space), flags)
@@ -227,15 +234,11 @@ impl Notify {
         let contents = String::from_utf8(buffer.to_vec()).unwrap();
         let mut messages = HashMap::new();
         for line in contents.lines() {
-            let content: Vec<&str> = line
-                .split('=')
-                .map(|s| s.trim_end_matches(char::from(0)))
-                .collect();
-            if content.len() != 2 {
-                continue;
+            // the value may contain '=', like STATUS=a=b
+            let line = line.trim_end_matches(char::from(0));
+            if let Some((key, value)) = line.split_once('=') {
+                messages.insert(key, value.trim_end());
             }
-
-            messages.insert(content[0], content[1].trim_end());
         }
         log::debug!("[notify] ucred: {:?}, messages: {:?}", &ucred, messages,);
 
@@ -243,6 +246,11 @@ impl Notify {
         if let Some(u) = unit {
             log::debug!("[notify] unit: {:?}", u.id());
             let _ = u.notify_message(&ucred, &messages, received_fds);
+        } else {
+            // nobody takes the fds over
+            for fd in received_fds {
+                fd_util::close(fd);
+            }
         }
 
         Ok(0)
@@ -261,7 +269,7 @@ impl Notify {
 fn notify_peek_pid(fd: RawFd, flags: MsgFlags) -> Result<libc::pid_t> {
     let mut buffer = [0u8; 4096];
     let mut iov = [IoSliceMut::new(&mut buffer)];
-    let mut space = cmsg_space!(libc::ucred, RawFd);
+    let mut space = cmsg_space!(libc::ucred, [RawFd; NOTIFY_FD_MAX]);
 
     // peek
     let peek_flags = flags | MsgFlags::MSG_PEEK;
@@ -13,8 +13,9 @@
 #![allow(non_snake_case)]
 
 use crate::manager::rentry::{
-    RELI_DB_HUM_NOTIFY, RELI_DB_HUNIT_BASE, RELI_DB_HUNIT_CGROUP, RELI_DB_HUNIT_CHILD,
-    RELI_DB_HUNIT_CONFIG, RELI_DB_HUNIT_DEP, RELI_DB_HUNIT_LOAD, RELI_DB_HUNIT_PPS,
+    RELI_DB_HUM_LOGSTREAM, RELI_DB_HUM_NOTIFY, RELI_DB_HUNIT_BASE, RELI_DB_HUNIT_CGROUP,
+    RELI_DB_HUNIT_CHILD, RELI_DB_HUNIT_CONFIG, RELI_DB_HUNIT_DEP, RELI_DB_HUNIT_LOAD,
+    RELI_DB_HUNIT_PPS,
 };
 use crate::unit::entry::UnitEmergencyAction;
 use bitflags::bitflags;
@@ -25,7 +26,7 @@ use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::str::FromStr;
 use sysmaster::rel::{ReDb, Reliability};
-use sysmaster::serialize::DeserializeWith;
+use sysmaster::serialize::{deserialize_str_or_value, DeserializeWith};
 use sysmaster::unit::{UnitRelations, UnitType};
 
 pub(crate) fn unit_name_to_type(unit_name: &str) -> UnitType {
@@ -118,12 +119,16 @@ pub(crate) struct UeConfigUnit {
     #[config(default = "")]
     pub Documentation: String,
     //When set to true, the unit will not be stopped when systemctl isolate is executed. For service, target, socket timer and path, the default value is false. For other units, the default value is true
+    #[config(deserialize_with = deserialize_str_or_value)]
     #[config(default = false)]
     pub IgnoreOnIsolate: bool,
+    #[config(deserialize_with = deserialize_str_or_value)]
     #[config(default = true)]
     pub DefaultDependencies: bool,
+    #[config(deserialize_with = deserialize_str_or_value)]
     #[config(default = false)]
     pub RefuseManualStart: bool,
+    #[config(deserialize_with = deserialize_str_or_value)]
     #[config(default = false)]
     pub RefuseManualStop: bool,
     #[config(deserialize_with = JobMode::deserialize_with)]
@@ -164,6 +169,7 @@ pub(crate) struct UeConfigUnit {
     pub Conflicts: Vec<String>,
 
     /* Conditions */
+    #[config(deserialize_with = deserialize_str_or_value)]
     pub ConditionACPower: Option<bool>,
     #[config(default = "")]
     pub ConditionCapability: String,
@@ -173,6 +179,7 @@ pub(crate) struct UeConfigUnit {
     pub ConditionFileIsExecutable: String,
     #[config(default = "")]
     pub ConditionFileNotEmpty: String,
+    #[config(deserialize_with = deserialize_str_or_value)]
     pub ConditionFirstBoot: Option<bool>,
     #[config(default = "")]
     pub ConditionKernelCommandLine: String,
@@ -198,8 +205,10 @@ pub(crate) struct UeConfigUnit {
     /* Asserts */
     #[config(default = "")]
     pub AssertPathExists: String,
+    #[config(deserialize_with = deserialize_str_or_value)]
     #[config(default = 10)]
     pub StartLimitInterval: u64,
+    #[config(deserialize_with = deserialize_str_or_value)]
     #[config(default = 5)]
     pub StartLimitBurst: u32,
     #[config(deserialize_with = UnitEmergencyAction::deserialize_with)]
@@ -211,6 +220,7 @@ pub(crate) struct UeConfigUnit {
     #[config(deserialize_with = UnitEmergencyAction::deserialize_with)]
     #[config(default = "none")]
     pub StartLimitAction: UnitEmergencyAction,
+    #[config(deserialize_with = deserialize_str_or_value)]
     #[config(default = 0)]
     pub JobTimeoutSec: u64,
     #[config(deserialize_with = UnitEmergencyAction::deserialize_with)]
@@ -252,12 +262,16 @@ impl UnitReConfig {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct UnitReCgroup {
     cg_path: PathBuf,
+    // (controller, attribute) written to the cgroup
+    #[serde(default)]
+    applied_attrs: Vec<(String, String)>,
 }
 
 impl UnitReCgroup {
-    fn new(cg_pathr: &Path) -> UnitReCgroup {
+    fn new(cg_pathr: &Path, applied_attrsr: &[(String, String)]) -> UnitReCgroup {
         UnitReCgroup {
             cg_path: cg_pathr.to_path_buf(),
+            applied_attrs: applied_attrsr.to_vec(),
         }
     }
 }
@@ -318,18 +332,36 @@ impl UmReNotify {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct UmReLogStream {
+    unit: String,
+    pid: i32,
+    priority: u8,
+}
+
+impl UmReLogStream {
+    fn new(unit: &str, pid: i32, priority: u8) -> UmReLogStream {
+        UmReLogStream {
+            unit: String::from(unit),
+            pid,
+            priority,
+        }
+    }
+}
+
 pub(crate) struct UnitRe {
     // database: multi-instance(N)
     base: Rc<ReDb<String, UnitReBase>>, // RELI_DB_HUNIT_BASE; key: unit_id, data: unit_type;
     load: Rc<ReDb<String, UnitReLoad>>, // RELI_DB_HUNIT_LOAD; key: unit_id, data: load_state;
     conf: Rc<ReDb<String, UnitReConfig>>, // RELI_DB_HUNIT_CONFIG; key: unit_id, data: unit_conf+install_conf;
-    cgroup: Rc<ReDb<String, UnitReCgroup>>, // RELI_DB_HUNIT_CGROUP; key: unit_id, data: cg_path;
-    child: Rc<ReDb<String, UnitReChild>>, // RELI_DB_HUNIT_CHILD; key: unit_id, data: pid[s];
-    pps: Rc<ReDb<String, UnitRePps>>,     // RELI_DB_HUNIT_PPS; key: unit_id, data: pipeline[s];
+    cgroup: Rc<ReDb<String, UnitReCgroup>>, // RELI_DB_HUNIT_CGROUP; key: unit_id, data: cg_path+applied_attrs;
+    child: Rc<ReDb<String, UnitReChild>>,   // RELI_DB_HUNIT_CHILD; key: unit_id, data: pid[s];
+    pps: Rc<ReDb<String, UnitRePps>>,       // RELI_DB_HUNIT_PPS; key: unit_id, data: pipeline[s];
     dep: Rc<ReDb<String, UnitReDep>>, // RELI_DB_HUNIT_DEP; key: unit_id, data: {UnitRelation+unit_id}[s]
 
     // database: singleton(1)
     notify: Rc<ReDb<u32, UmReNotify>>, // RELI_DB_HUM_NOTIFY; key: RELI_LAST_KEY, data: UmReNotify;
+    logstream: Rc<ReDb<i32, UmReLogStream>>, // RELI_DB_HUM_LOGSTREAM; key: fd, data: UmReLogStream;
 }
 
 impl UnitRe {
@@ -342,6 +374,7 @@ impl UnitRe {
         let pps = Rc::new(ReDb::new(relir, RELI_DB_HUNIT_PPS));
         let dep = Rc::new(ReDb::new(relir, RELI_DB_HUNIT_DEP));
         let notify = Rc::new(ReDb::new(relir, RELI_DB_HUM_NOTIFY));
+        let logstream = Rc::new(ReDb::new(relir, RELI_DB_HUM_LOGSTREAM));
         let rentry = UnitRe {
             base,
             load,
@@ -351,6 +384,7 @@ impl UnitRe {
             pps,
             dep,
             notify,
+            logstream,
         };
         rentry.register(relir);
         rentry
@@ -361,7 +395,6 @@ impl UnitRe {
         self.base.insert(String::from(unit_id), u_base);
     }
 
-    #[allow(dead_code)]
     pub(super) fn base_remove(&self, unit_id: &String) {
         self.base.remove(unit_id);
     }
@@ -382,7 +415,6 @@ impl UnitRe {
         self.load.insert(unit_id.clone(), u_load);
     }
 
-    #[allow(dead_code)]
     pub(super) fn load_remove(&self, unit_id: &String) {
         self.load.remove(unit_id);
     }
@@ -404,7 +436,6 @@ impl UnitRe {
         self.conf.insert(unit_id.clone(), u_conf);
     }
 
-    #[allow(dead_code)]
     pub(super) fn conf_remove(&self, unit_id: &String) {
         self.conf.remove(unit_id);
     }
@@ -414,21 +445,25 @@ impl UnitRe {
         u_conf.map(|c| (c.unit, c.install))
     }
 
-    pub(super) fn cgroup_insert(&self, unit_id: &String, cg_path: &Path) {
+    pub(super) fn cgroup_insert(
+        &self,
+        unit_id: &String,
+        cg_path: &Path,
+        applied_attrs: &[(String, String)],
+    ) {
         assert!(self.base_contains(unit_id));
 
-        let u_cgroup = UnitReCgroup::new(cg_path);
+        let u_cgroup = UnitReCgroup::new(cg_path, applied_attrs);
         self.cgroup.insert(unit_id.clone(), u_cgroup);
     }
 
-    #[allow(dead_code)]
     pub(super) fn cgroup_remove(&self, unit_id: &String) {
         self.cgroup.remove(unit_id);
     }
 
-    pub(super) fn cgroup_get(&self, unit_id: &String) -> Option<PathBuf> {
+    pub(super) fn cgroup_get(&self, unit_id: &String) -> Option<(PathBuf, Vec<(String, String)>)> {
         let u_cgroup = self.cgroup.get(unit_id);
-        u_cgroup.map(|c| c.cg_path)
+        u_cgroup.map(|c| (c.cg_path, c.applied_attrs))
     }
 
     pub(super) fn child_insert(&self, unit_id: &String, pids: &[Pid]) {
@@ -438,7 +473,6 @@ impl UnitRe {
         self.child.insert(unit_id.clone(), u_child);
     }
 
-    #[allow(dead_code)]
     pub(super) fn child_remove(&self, unit_id: &String) {
         self.child.remove(unit_id);
     }
@@ -480,7 +514,6 @@ impl UnitRe {
         self.pps.insert(unit_id.clone(), pps);
     }
 
-    #[allow(dead_code)]
     pub(super) fn pps_remove(&self, unit_id: &String) {
         self.pps.remove(unit_id);
     }
@@ -543,6 +576,23 @@ impl UnitRe {
         um_notify.map(|n| n.fd)
     }
 
+    pub(super) fn logstream_insert(&self, fd: i32, unit: &str, pid: i32, priority: u8) {
+        let um_logstream = UmReLogStream::new(unit, pid, priority);
+        self.logstream.insert(fd, um_logstream);
+    }
+
+    pub(super) fn logstream_remove(&self, fd: i32) {
+        self.logstream.remove(&fd);
+    }
+
+    pub(super) fn logstream_entries(&self) -> Vec<(i32, String, i32, u8)> {
+        self.logstream
+            .entries()
+            .into_iter()
+            .map(|(fd, s)| (fd, s.unit, s.pid, s.priority))
+            .collect()
+    }
+
     fn register(&self, relir: &Reliability) {
         // reliability-db: RELI_DB_HUNIT_BASE
         let db = Rc::clone(&self.base);
@@ -575,5 +625,9 @@ impl UnitRe {
         // reliability-db: RELI_DB_HUM_NOTIFY
         let db = Rc::clone(&self.notify);
         relir.history_db_register(RELI_DB_HUM_NOTIFY, db);
+
+        // reliability-db: RELI_DB_HUM_LOGSTREAM
+        let db = Rc::clone(&self.logstream);
+        relir.history_db_register(RELI_DB_HUM_LOGSTREAM, db);
     }
 }
@@ -10,7 +10,9 @@
 // NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
 // See the Mulan PSL v2 for more details.
 
+pub(crate) use transient::transient_unit_content;
 pub use unit_file::UnitFile;
 
 // dependency: {unit_file | unit_parser_mgr}
+mod transient;
 mod unit_file;
@@ -0,0 +1,157 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! the unit file of the transient unit, which is created over the control API
+//! from the unit properties instead of being installed on the disk
+
+use crate::unit::rentry::UeConfigUnit;
+use serde_json::Value;
+use sysmaster::error::*;
+
+/// the properties that belong to the [Unit] section are the fields of the [Unit]
+/// configuration, the others belong to the section of the unit type
+fn unit_section_keys() -> Vec<String> {
+    match serde_json::to_value(UeConfigUnit::default()) {
+        Ok(Value::Object(fields)) => fields.into_iter().map(|(key, _)| key).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// the values are always written as the strings, which the typed fields accept too,
+/// and '%' is escaped because the unit file goes through the specifier expansion
+fn toml_value(value: &str) -> String {
+    let mut s = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => s += "\\\"",
+            '\\' => s += "\\\\",
+            '\n' => s += "\\n",
+            '\t' => s += "\\t",
+            '\r' => s += "\\r",
+            '%' => s += "%%",
+            _ => s.push(c),
+        }
+    }
+    s.push('"');
+    s
+}
+
+/// generate the content of the unit file from the properties, the values of the
+/// same key are joined with ';' as the list
+pub(crate) fn transient_unit_content(
+    name: &str,
+    properties: &[(String, String)],
+) -> Result<String> {
+    let section = match name.rsplit_once('.') {
+        Some((_, suffix)) if !suffix.is_empty() => {
+            let mut chars = suffix.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            format!("{first}{}", chars.as_str())
+        }
+        _ => return Err(Error::UnitActionEInval),
+    };
+
+    let unit_keys = unit_section_keys();
+    let mut unit: Vec<(&str, Vec<&str>)> = Vec::new();
+    let mut sub: Vec<(&str, Vec<&str>)> = Vec::new();
+    for (key, value) in properties {
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(Error::ConfigureError {
+                msg: format!("invalid property name '{key}'"),
+            });
+        }
+        let keys = if unit_keys.contains(key) {
+            &mut unit
+        } else {
+            &mut sub
+        };
+        match keys.iter_mut().find(|(k, _)| k == key) {
+            Some((_, values)) => values.push(value),
+            None => keys.push((key, vec![value])),
+        }
+    }
+
+    let mut content = String::new();
+    for (section, keys) in [("Unit".to_string(), unit), (section, sub)] {
+        if keys.is_empty() {
+            continue;
+        }
+        content += &format!("[{section}]\n");
+        for (key, values) in keys {
+            content += &format!("{key}={}\n", toml_value(&values.join(";")));
+        }
+    }
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn props(v: &[(&str, &str)]) -> Vec<(String, String)> {
+        v.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_transient_unit_content() {
+        let content = transient_unit_content(
+            "run-1.service",
+            &props(&[
+                ("Description", "echo \"hello\""),
+                ("ExecStart", "/bin/echo hello"),
+                ("After", "a.service"),
+                ("After", "b.service"),
+                ("RemainAfterExit", "true"),
+                ("ConditionPathExists", "/tmp"),
+                ("DefaultDependencies", "false"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(
+            content,
+            "[Unit]\n\
+             Description=\"echo \\\"hello\\\"\"\n\
+             After=\"a.service;b.service\"\n\
+             ConditionPathExists=\"/tmp\"\n\
+             DefaultDependencies=\"false\"\n\
+             [Service]\n\
+             ExecStart=\"/bin/echo hello\"\n\
+             RemainAfterExit=\"true\"\n"
+        );
+
+        let content = transient_unit_content("run-1.service", &props(&[("TimeoutSec", "10")]));
+        assert_eq!(content.unwrap(), "[Service]\nTimeoutSec=\"10\"\n");
+
+        // the specifiers are not expanded in the values
+        let content = transient_unit_content(
+            "run-1.service",
+            &props(&[("ExecStart", "/bin/date +%p"), ("Description", "100%")]),
+        )
+        .unwrap();
+        assert_eq!(
+            content,
+            "[Unit]\n\
+             Description=\"100%%\"\n\
+             [Service]\n\
+             ExecStart=\"/bin/date +%%p\"\n"
+        );
+    }
+
+    #[test]
+    fn test_transient_unit_content_invalid() {
+        assert!(transient_unit_content("run-1", &[]).is_err());
+        assert!(transient_unit_content("run-1.service", &props(&[("Exec Start", "a")])).is_err());
+        assert!(transient_unit_content("run-1.service", &props(&[("", "a")])).is_err());
+    }
+}
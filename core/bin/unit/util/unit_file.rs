@@ -11,7 +11,9 @@
 // See the Mulan PSL v2 for more details.
 
 use basic::path_lookup::LookupPaths;
+use basic::specifier::specifier_expand_toml;
 use basic::time_util;
+use basic::unit_name::{unit_name_instance, unit_name_is_template, unit_name_template};
 use siphasher::sip::SipHasher24;
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -103,43 +105,58 @@ impl UnitFileData {
 
     fn build_id_fragment(&mut self, name: &String) {
         let mut pathbuf_fragment = Vec::new();
+        // the template itself can't be loaded, only its instances can
+        if unit_name_is_template(name) {
+            self.unit_id_fragment
+                .insert(name.to_string(), pathbuf_fragment);
+            return;
+        }
+
+        // the files of the template are copied to the instance directory for the instance,
+        // which is rebuilt each time so that no stale copy is left
+        let template = unit_name_template(name);
+        let instance_dir = Path::new(&self.lookup_path.instance).join(name);
+        if template.is_some() {
+            let _ = fs::remove_dir_all(&instance_dir);
+            if let Err(e) = fs::create_dir_all(&instance_dir) {
+                log::warn!("create instance directory {:?} error: {}", instance_dir, e);
+            }
+        }
+
         for v in &self.lookup_path.search_path {
             if let Err(_e) = fs::metadata(v) {
                 continue;
             }
-            let pathd = format!("{v}/{name}.d");
-            let dir = Path::new(&pathd);
-            if dir.is_dir() {
-                for entry in dir.read_dir().unwrap() {
-                    let fragment = entry.unwrap().path();
-                    if fragment.is_file() {
-                        let file_name =
-                            String::from(fragment.file_name().unwrap().to_str().unwrap());
-                        if file_name.starts_with('.') || file_name.ends_with(".toml") {
-                            continue;
-                        }
-                        let path = format!("{}.toml", fragment.to_string_lossy());
-
-                        if let Err(e) = std::fs::copy(fragment, &path) {
-                            log::warn!("copy file content to toml file error: {}", e);
-                        }
-                        pathbuf_fragment.push(Path::new(&path).to_path_buf());
-                    }
+            // the dropins of the instance override the dropins of the template
+            collect_dropins(v, name, name, None, &mut pathbuf_fragment);
+            if let Some(template) = &template {
+                collect_dropins(
+                    v,
+                    template,
+                    name,
+                    Some(&instance_dir),
+                    &mut pathbuf_fragment,
+                );
+            }
+
+            // the instance falls back to the fragment of the template
+            let fragment = Path::new(v).join(name);
+            if fragment.exists() {
+                if !fragment.is_symlink() {
+                    let path = Path::new(v).join(format!("{name}.toml"));
+                    copy_fragment(&fragment, &path, name);
+                    pathbuf_fragment.push(path);
                 }
+                continue;
             }
-            let path = if v.ends_with('/') {
-                format!("{v}{name}")
-            } else {
-                format!("{v}/{name}")
-            };
-            let tmp = Path::new(&path);
-            if tmp.exists() && !tmp.is_symlink() {
-                let path = format!("{}.toml", tmp.to_string_lossy());
-                if let Err(e) = std::fs::copy(tmp, &path) {
-                    log::warn!("copy file content to toml file error: {}", e);
+            if let Some(template) = &template {
+                let fragment = Path::new(v).join(template);
+                if fragment.exists() && !fragment.is_symlink() {
+                    let path =
+                        instance_dir.join(format!("{}-{template}.toml", pathbuf_fragment.len()));
+                    copy_fragment(&fragment, &path, name);
+                    pathbuf_fragment.push(path);
                 }
-                let to = Path::new(&path);
-                pathbuf_fragment.push(to.to_path_buf());
             }
         }
 
@@ -149,18 +166,30 @@ impl UnitFileData {
 
     fn build_id_dropin(&mut self, name: &String, suffix: String) {
         let mut pathbuf_dropin = Vec::new();
+        let mut names = vec![name.to_string()];
+        if let Some(template) = unit_name_template(name) {
+            names.push(template);
+        }
         for v in &self.lookup_path.search_path {
-            let path = format!("{v}/{name}.{suffix}");
-            let dir = Path::new(&path);
-            if dir.is_dir() {
+            for name in &names {
+                let path = format!("{v}/{name}.{suffix}");
+                let dir = Path::new(&path);
+                if !dir.is_dir() {
+                    continue;
+                }
                 for entry in dir.read_dir().unwrap() {
                     let symlink_unit = entry.unwrap().path();
-                    if symlink_unit.is_symlink() {
-                        if let Ok(abs_path) = symlink_unit.canonicalize() {
-                            let mut file_name = PathBuf::new();
-                            file_name.push(abs_path.file_name().unwrap());
-                            pathbuf_dropin.push(file_name);
-                        }
+                    if !symlink_unit.is_symlink() {
+                        continue;
+                    }
+                    // the instance links to its template, so the name of the link is used
+                    let link_name = symlink_unit.file_name().unwrap().to_string_lossy();
+                    if unit_name_instance(&link_name).is_some() {
+                        pathbuf_dropin.push(PathBuf::from(link_name.to_string()));
+                    } else if let Ok(abs_path) = symlink_unit.canonicalize() {
+                        let mut file_name = PathBuf::new();
+                        file_name.push(abs_path.file_name().unwrap());
+                        pathbuf_dropin.push(file_name);
                     }
                 }
             }
@@ -203,3 +232,166 @@ impl UnitFileData {
         path_updated
     }
 }
+
+// collect the dropins in the "{dir}/{name}.d" directory for the unit, the dropins are
+// copied beside themselves, or to the "to" directory if it's the template's
+fn collect_dropins(
+    dir: &str,
+    name: &str,
+    unit: &str,
+    to: Option<&Path>,
+    fragments: &mut Vec<PathBuf>,
+) {
+    let pathd = format!("{dir}/{name}.d");
+    let dropin_dir = Path::new(&pathd);
+    if !dropin_dir.is_dir() {
+        return;
+    }
+
+    for entry in dropin_dir.read_dir().unwrap() {
+        let fragment = entry.unwrap().path();
+        if !fragment.is_file() {
+            continue;
+        }
+        let file_name = String::from(fragment.file_name().unwrap().to_str().unwrap());
+        if file_name.starts_with('.') || file_name.ends_with(".toml") {
+            continue;
+        }
+        // the index keeps the copies of the same name in different directories apart
+        let path = match to {
+            None => dropin_dir.join(format!("{file_name}.toml")),
+            Some(to) => to.join(format!("{}-{file_name}.toml", fragments.len())),
+        };
+        copy_fragment(&fragment, &path, unit);
+        fragments.push(path);
+    }
+}
+
+// copy the unit file to the toml file, with the specifiers expanded for the unit,
+// the expanded values are escaped in the toml strings
+fn copy_fragment(from: &Path, to: &Path, unit: &str) {
+    let content = match fs::read_to_string(from) {
+        Ok(content) => content,
+        Err(e) => {
+            log::warn!("read unit file {:?} error: {}", from, e);
+            return;
+        }
+    };
+    if let Err(e) = fs::write(to, specifier_expand_toml(&content, unit)) {
+        log::warn!("copy file content to toml file error: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnitFile;
+    use basic::path_lookup::LookupPaths;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+
+    fn unit_file(dir: &Path) -> UnitFile {
+        let mut lookup_path = LookupPaths::new();
+        lookup_path
+            .search_path
+            .push(dir.join("units").to_str().unwrap().to_string());
+        lookup_path.instance = dir.join("instance").to_str().unwrap().to_string();
+        UnitFile::new(&Rc::new(lookup_path))
+    }
+
+    #[test]
+    fn test_template_fallback() {
+        let dir = std::env::temp_dir().join(format!("unit-file-{}", std::process::id()));
+        let units = dir.join("units");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(units.join("foo@.service.d")).unwrap();
+        fs::write(units.join("foo@.service"), "Description=\"%i\"").unwrap();
+        fs::write(units.join("foo@.service.d/a.conf"), "Documentation=\"%n\"").unwrap();
+        fs::write(units.join("foo@baz.service"), "Description=\"baz\"").unwrap();
+
+        let file = unit_file(&dir);
+        let instance = dir.join("instance/foo@bar.service");
+
+        // the instance is loaded from the template, with the specifiers expanded
+        file.build_name_map("foo@bar.service".to_string(), false);
+        let fragments = file.get_unit_id_fragment_pathbuf(&"foo@bar.service".to_string());
+        assert_eq!(
+            fragments,
+            vec![
+                instance.join("0-a.conf.toml"),
+                instance.join("1-foo@.service.toml")
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(&fragments[0]).unwrap(),
+            "Documentation=\"foo@bar.service\""
+        );
+        assert_eq!(
+            fs::read_to_string(&fragments[1]).unwrap(),
+            "Description=\"bar\""
+        );
+
+        // the fragment of the instance overrides the template, while the dropins are kept
+        file.build_name_map("foo@baz.service".to_string(), false);
+        assert_eq!(
+            file.get_unit_id_fragment_pathbuf(&"foo@baz.service".to_string()),
+            vec![
+                dir.join("instance/foo@baz.service/0-a.conf.toml"),
+                units.join("foo@baz.service.toml")
+            ]
+        );
+
+        // the template itself can't be loaded, and nothing is written into its directory
+        file.build_name_map("foo@.service".to_string(), false);
+        assert!(file
+            .get_unit_id_fragment_pathbuf(&"foo@.service".to_string())
+            .is_empty());
+        assert_eq!(
+            fs::read_dir(units.join("foo@.service.d")).unwrap().count(),
+            1
+        );
+
+        // the stale copy is removed when the instance is loaded again
+        fs::remove_file(units.join("foo@.service.d/a.conf")).unwrap();
+        file.build_name_map("foo@bar.service".to_string(), false);
+        assert_eq!(
+            file.get_unit_id_fragment_pathbuf(&"foo@bar.service".to_string()),
+            vec![instance.join("0-foo@.service.toml")]
+        );
+        assert_eq!(
+            fs::read_dir(&instance)
+                .unwrap()
+                .map(|e| e.unwrap().path())
+                .collect::<Vec<PathBuf>>(),
+            vec![instance.join("0-foo@.service.toml")]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_template_escaped_instance() {
+        let dir = std::env::temp_dir().join(format!("unit-file-escaped-{}", std::process::id()));
+        let units = dir.join("units");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&units).unwrap();
+        fs::write(
+            units.join("getty@.service"),
+            "[Service]\nExecStart=\"/sbin/agetty %i %I\"",
+        )
+        .unwrap();
+
+        let file = unit_file(&dir);
+        let name = "getty@tty\\x2d1.service".to_string();
+        file.build_name_map(name.clone(), false);
+        let fragments = file.get_unit_id_fragment_pathbuf(&name);
+        assert_eq!(fragments.len(), 1);
+        // the backslash of the instance is not taken as the escape of toml
+        assert_eq!(
+            fs::read_to_string(&fragments[0]).unwrap(),
+            "[Service]\nExecStart=\"/sbin/agetty tty\\\\x2d1 tty-1\""
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
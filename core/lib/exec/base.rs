@@ -10,8 +10,12 @@
 // NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
 // See the Mulan PSL v2 for more details.
 
+use super::limit::Rlimit;
+use super::sandbox::ExecSandbox;
+use super::stdio::{ExecInput, ExecOutput};
 use crate::error::*;
 use bitflags::bitflags;
+use nix::sys::resource::Resource;
 use nix::sys::stat::Mode;
 use nix::unistd::{Group, Uid, User};
 
@@ -102,11 +106,18 @@ impl ExecContext {
 pub struct ExecParameters {
     environment: Rc<EnvData>,
     fds: Vec<i32>,
+    fd_names: Vec<String>,
+    stdio: (ExecInput, ExecOutput, ExecOutput),
+    tty_path: PathBuf,
+    stdio_fds: [Option<i32>; 3],
+    log_collect: [bool; 2],
     notify_sock: Option<PathBuf>,
     working_directory: Option<PathBuf>,
     user: Option<User>,
     group: Option<Group>,
     umask: Option<Mode>,
+    rlimits: HashMap<Resource, Rlimit>,
+    sandbox: ExecSandbox,
     watchdog_usec: u64,
     flags: ExecFlags,
     nonblock: bool,
@@ -154,11 +165,18 @@ impl ExecParameters {
         ExecParameters {
             environment: Rc::new(EnvData::new()),
             fds: Vec::new(),
+            fd_names: Vec::new(),
+            stdio: (ExecInput::Null, ExecOutput::Inherit, ExecOutput::Inherit),
+            tty_path: PathBuf::new(),
+            stdio_fds: [None; 3],
+            log_collect: [false; 2],
             notify_sock: None,
             working_directory: None,
             user: None,
             group: None,
             umask: None,
+            rlimits: HashMap::new(),
+            sandbox: ExecSandbox::new(),
             watchdog_usec: 0,
             flags: ExecFlags::CONTROL,
             nonblock: false,
@@ -200,6 +218,63 @@ impl ExecParameters {
         self.fds.to_vec()
     }
 
+    /// insert the names of the fds that will be passed to child, in the same order with the fds
+    pub fn insert_fd_names(&mut self, fd_names: Vec<String>) {
+        self.fd_names = fd_names
+    }
+
+    /// return the names of the fds that will be passed to child
+    pub fn fd_names(&self) -> Vec<String> {
+        self.fd_names.to_vec()
+    }
+
+    /// set where the stdin, stdout and stderr of child are connected to, the files are opened
+    /// in the child. the output Inherit means the same as the previous one (stdout as stdin,
+    /// stderr as stdout), Journal means keeping the one inherited from sysmaster when the
+    /// output is not collected.
+    pub fn set_stdio(
+        &mut self,
+        stdin: ExecInput,
+        stdout: ExecOutput,
+        stderr: ExecOutput,
+        tty_path: PathBuf,
+    ) {
+        self.stdio = (stdin, stdout, stderr);
+        self.tty_path = tty_path;
+    }
+
+    /// return where the stdin, stdout and stderr of child are connected to
+    pub fn stdio(&self) -> (&ExecInput, &ExecOutput, &ExecOutput) {
+        (&self.stdio.0, &self.stdio.1, &self.stdio.2)
+    }
+
+    /// return the terminal path used by the tty stdio
+    pub fn tty_path(&self) -> PathBuf {
+        self.tty_path.clone()
+    }
+
+    /// set the fds owned by sysmaster that the stdin, stdout and stderr of child are connected
+    /// to, like the socket fds, which take precedence over the stdio setting
+    pub fn set_stdio_fds(&mut self, stdin: Option<i32>, stdout: Option<i32>, stderr: Option<i32>) {
+        self.stdio_fds = [stdin, stdout, stderr];
+    }
+
+    /// return the fds that the stdin, stdout and stderr of child are connected to
+    pub fn stdio_fds(&self) -> [Option<i32>; 3] {
+        self.stdio_fds
+    }
+
+    /// set whether the stdout and stderr of child are sent to the log collector,
+    /// which takes precedence over the stdio fds
+    pub fn set_log_collect(&mut self, stdout: bool, stderr: bool) {
+        self.log_collect = [stdout, stderr];
+    }
+
+    /// return whether the stdout and stderr of child are sent to the log collector
+    pub fn log_collect(&self) -> [bool; 2] {
+        self.log_collect
+    }
+
     /// set the NOTIFY_SOCKET value
     pub fn set_notify_sock(&mut self, notify_sock: PathBuf) {
         self.notify_sock = Some(notify_sock)
@@ -320,6 +395,26 @@ impl ExecParameters {
         self.umask
     }
 
+    /// add the resource limit of the resource
+    pub fn add_rlimit(&mut self, resource: Resource, rlimit: Rlimit) {
+        self.rlimits.insert(resource, rlimit);
+    }
+
+    /// return all the resource limits that will be set to child
+    pub fn rlimits(&self) -> Vec<(Resource, Rlimit)> {
+        self.rlimits.iter().map(|(k, v)| (*k, *v)).collect()
+    }
+
+    /// set the sandboxing settings of child
+    pub fn set_sandbox(&mut self, sandbox: ExecSandbox) {
+        self.sandbox = sandbox;
+    }
+
+    /// return the sandboxing settings of child
+    pub fn sandbox(&self) -> &ExecSandbox {
+        &self.sandbox
+    }
+
     /// set the software watchdog time
     pub fn set_watchdog_usec(&mut self, usec: u64) {
         self.watchdog_usec = usec;
@@ -361,6 +456,8 @@ mod tests {
     };
 
     use super::ExecParameters;
+    use crate::exec::Rlimit;
+    use nix::sys::resource::Resource;
 
     #[test]
     fn test_add_working_directory() {
@@ -450,4 +547,16 @@ mod tests {
         assert!(params.add_umask("0011".to_string()).is_ok());
         assert_eq!(params.get_umask().unwrap(), Mode::from_bits(9).unwrap());
     }
+
+    #[test]
+    fn test_add_rlimit() {
+        let mut params = ExecParameters::new();
+        assert!(params.rlimits().is_empty());
+        params.add_rlimit(Resource::RLIMIT_NOFILE, Rlimit::new(1024, 4096));
+        params.add_rlimit(Resource::RLIMIT_NOFILE, Rlimit::new(2048, 4096));
+        assert_eq!(
+            params.rlimits(),
+            vec![(Resource::RLIMIT_NOFILE, Rlimit::new(2048, 4096))]
+        );
+    }
 }
@@ -0,0 +1,241 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+use crate::error::*;
+use crate::serialize::{deserialize_str_or_num, DeserializeWith};
+use basic::conf_parser::{parse_size, Base};
+use basic::time_util::{parse_timespan, USEC_PER_SEC};
+use nix::sys::resource::Resource;
+use serde::{
+    de::{self, Unexpected},
+    Deserialize, Deserializer, Serialize,
+};
+
+/// the value that means no limit of the resource
+pub const RLIM_INFINITY: u64 = libc::RLIM_INFINITY;
+
+/// the resources of the Limit*= fields, in the order of the fields
+const RLIMIT_RESOURCES: [Resource; 16] = [
+    Resource::RLIMIT_CPU,
+    Resource::RLIMIT_FSIZE,
+    Resource::RLIMIT_DATA,
+    Resource::RLIMIT_STACK,
+    Resource::RLIMIT_CORE,
+    Resource::RLIMIT_RSS,
+    Resource::RLIMIT_NOFILE,
+    Resource::RLIMIT_AS,
+    Resource::RLIMIT_NPROC,
+    Resource::RLIMIT_MEMLOCK,
+    Resource::RLIMIT_LOCKS,
+    Resource::RLIMIT_SIGPENDING,
+    Resource::RLIMIT_MSGQUEUE,
+    Resource::RLIMIT_NICE,
+    Resource::RLIMIT_RTPRIO,
+    Resource::RLIMIT_RTTIME,
+];
+
+/// pair the configured Limit*= fields with their resources, the limits are given in the
+/// order of LimitCPU, LimitFSIZE, LimitDATA, LimitSTACK, LimitCORE, LimitRSS, LimitNOFILE,
+/// LimitAS, LimitNPROC, LimitMEMLOCK, LimitLOCKS, LimitSIGPENDING, LimitMSGQUEUE,
+/// LimitNICE, LimitRTPRIO and LimitRTTIME
+pub fn rlimits_from(limits: [Option<Rlimit>; 16]) -> Vec<(Resource, Rlimit)> {
+    RLIMIT_RESOURCES
+        .into_iter()
+        .zip(limits)
+        .filter_map(|(resource, rlimit)| rlimit.map(|v| (resource, v)))
+        .collect()
+}
+
+/// the resource limit that was parsed from the Limit*= fields of the unit file.
+/// the value can be "soft:hard", a single value for both, or "infinity".
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Rlimit {
+    soft: u64,
+    hard: u64,
+}
+
+impl Rlimit {
+    /// create a new instance of the resource limit
+    pub fn new(soft: u64, hard: u64) -> Rlimit {
+        Rlimit { soft, hard }
+    }
+
+    /// return the soft limit
+    pub fn soft(&self) -> u64 {
+        self.soft
+    }
+
+    /// return the hard limit
+    pub fn hard(&self) -> u64 {
+        self.hard
+    }
+
+    /// parse the resource limit from string
+    pub fn parse(s: &str) -> Result<Rlimit> {
+        Self::parse_with(s, parse_rlim_value)
+    }
+
+    /// parse the resource limit of time, the number without unit is in unit_usec,
+    /// or a time span like "1h 30min" is given
+    pub fn parse_time(s: &str, unit_usec: u64) -> Result<Rlimit> {
+        Self::parse_with(s, |v| parse_rlim_time(v, unit_usec))
+    }
+
+    fn parse_with<F>(s: &str, parse_value: F) -> Result<Rlimit>
+    where
+        F: Fn(&str) -> Result<u64>,
+    {
+        let (soft, hard) = match s.trim().split_once(':') {
+            Some((soft, hard)) => (parse_value(soft)?, parse_value(hard)?),
+            None => {
+                let v = parse_value(s)?;
+                (v, v)
+            }
+        };
+
+        if soft > hard {
+            return Err(Error::ConfigureError {
+                msg: format!("soft limit {soft} is greater than hard limit {hard}"),
+            });
+        }
+
+        Ok(Rlimit::new(soft, hard))
+    }
+}
+
+fn parse_rlim_value(s: &str) -> Result<u64> {
+    let s = s.trim();
+    if s == "infinity" {
+        return Ok(RLIM_INFINITY);
+    }
+
+    parse_size(s, Base::Binary).context(UtilSnafu)
+}
+
+fn parse_rlim_time(s: &str, unit_usec: u64) -> Result<u64> {
+    let s = s.trim();
+    if s == "infinity" {
+        return Ok(RLIM_INFINITY);
+    }
+    if let Ok(v) = s.parse::<u64>() {
+        return Ok(v);
+    }
+
+    let usec = parse_timespan(s).context(UtilSnafu)?;
+    Ok(usec / unit_usec)
+}
+
+fn deserialize_rlimit<'de, D, F>(de: D, parse: F) -> Result<Rlimit, D::Error>
+where
+    D: Deserializer<'de>,
+    F: Fn(&str) -> Result<Rlimit>,
+{
+    let s = deserialize_str_or_num(de)?;
+
+    parse(&s).map_err(|_| {
+        de::Error::invalid_value(
+            Unexpected::Str(&s),
+            &"a number, \"infinity\" or \"soft:hard\"",
+        )
+    })
+}
+
+/// deserialize LimitCPU=, the number is in seconds, or a time span like "1h" is given
+pub fn deserialize_rlimit_sec<'de, D>(de: D) -> Result<Rlimit, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_rlimit(de, |s| Rlimit::parse_time(s, USEC_PER_SEC))
+}
+
+/// deserialize LimitRTTIME=, the number is in usec, or a time span like "1s" is given
+pub fn deserialize_rlimit_usec<'de, D>(de: D) -> Result<Rlimit, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_rlimit(de, |s| Rlimit::parse_time(s, 1))
+}
+
+impl DeserializeWith for Rlimit {
+    type Item = Self;
+    fn deserialize_with<'de, D>(de: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_rlimit(de, Rlimit::parse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rlimits_from, Rlimit, RLIM_INFINITY};
+    use nix::sys::resource::Resource;
+
+    #[test]
+    fn test_rlimit_parse() {
+        assert_eq!(Rlimit::parse("1024").unwrap(), Rlimit::new(1024, 1024));
+        assert_eq!(Rlimit::parse("1024:4096").unwrap(), Rlimit::new(1024, 4096));
+        assert_eq!(
+            Rlimit::parse("infinity").unwrap(),
+            Rlimit::new(RLIM_INFINITY, RLIM_INFINITY)
+        );
+        assert_eq!(
+            Rlimit::parse("0:infinity").unwrap(),
+            Rlimit::new(0, RLIM_INFINITY)
+        );
+        assert_eq!(
+            Rlimit::parse("8M").unwrap(),
+            Rlimit::new(8 * 1024 * 1024, 8 * 1024 * 1024)
+        );
+        assert!(Rlimit::parse("4096:1024").is_err());
+        assert!(Rlimit::parse("infinity:1024").is_err());
+        assert!(Rlimit::parse("").is_err());
+        assert!(Rlimit::parse("foo").is_err());
+        assert!(Rlimit::parse("-1").is_err());
+    }
+
+    #[test]
+    fn test_rlimit_parse_time() {
+        assert_eq!(
+            Rlimit::parse_time("10", 1_000_000).unwrap(),
+            Rlimit::new(10, 10)
+        );
+        assert_eq!(
+            Rlimit::parse_time("1min:1h", 1_000_000).unwrap(),
+            Rlimit::new(60, 3600)
+        );
+        assert_eq!(
+            Rlimit::parse_time("500ms", 1).unwrap(),
+            Rlimit::new(500_000, 500_000)
+        );
+        assert_eq!(
+            Rlimit::parse_time("1s:infinity", 1).unwrap(),
+            Rlimit::new(1_000_000, RLIM_INFINITY)
+        );
+        assert!(Rlimit::parse_time("1h:1min", 1_000_000).is_err());
+        assert!(Rlimit::parse_time("1x", 1_000_000).is_err());
+    }
+
+    #[test]
+    fn test_rlimits_from() {
+        let mut limits = [None; 16];
+        limits[0] = Some(Rlimit::new(1, 2));
+        limits[15] = Some(Rlimit::new(3, 4));
+        assert_eq!(
+            rlimits_from(limits),
+            vec![
+                (Resource::RLIMIT_CPU, Rlimit::new(1, 2)),
+                (Resource::RLIMIT_RTTIME, Rlimit::new(3, 4)),
+            ]
+        );
+    }
+}
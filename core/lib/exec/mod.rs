@@ -13,6 +13,17 @@
 //! execute module
 mod base;
 mod cmd;
+mod limit;
+mod sandbox;
+mod stdio;
 pub use base::{ExecContext, ExecFlags, ExecParameters};
 pub use cmd::ExecCommand;
 pub use cmd::ExecFlag;
+pub use limit::{
+    deserialize_rlimit_sec, deserialize_rlimit_usec, rlimits_from, Rlimit, RLIM_INFINITY,
+};
+pub use sandbox::{
+    private_tmp_create, private_tmp_dir, private_tmp_remove, ExecSandbox, ProtectHome,
+    ProtectSystem,
+};
+pub use stdio::{ExecInput, ExecOutput};
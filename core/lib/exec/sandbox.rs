@@ -0,0 +1,733 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! the sandboxing of the child, the file system is restricted in a new mount namespace
+//! according to ProtectSystem=, ProtectHome=, PrivateTmp=, PrivateDevices=,
+//! ReadOnlyPaths=, ReadWritePaths=, InaccessiblePaths= and BindPaths=.
+use crate::error::*;
+use crate::serialize::DeserializeWith;
+use basic::mount_util;
+use basic::path_lookup::RUN_SYSTEM_PATH;
+use nix::mount::MsFlags;
+use nix::sched::{unshare, CloneFlags};
+use nix::sys::statvfs::{statvfs, FsFlags};
+use nix::unistd;
+use serde::{
+    de::{self, Unexpected},
+    Deserialize, Deserializer, Serialize,
+};
+use std::fs;
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+const DEV_NULL: &str = "/dev/null";
+
+// the device nodes that are kept by PrivateDevices=
+const PRIVATE_DEVICES: [&str; 6] = ["null", "zero", "full", "random", "urandom", "tty"];
+
+// the directory under the runtime directory where the private /tmp of the units are kept
+const PRIVATE_TMP_DIR: &str = "private-tmp";
+
+/// how the system directories are protected, parsed from the ProtectSystem= field.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum ProtectSystem {
+    /// not protected
+    #[default]
+    No,
+    /// /usr, /boot and /efi are read-only
+    Yes,
+    /// /etc is read-only too
+    Full,
+    /// the whole file system is read-only, except /dev, /proc and /sys
+    Strict,
+}
+
+/// how the home directories are protected, parsed from the ProtectHome= field.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum ProtectHome {
+    /// not protected
+    #[default]
+    No,
+    /// /home, /root and /run/user are inaccessible
+    Yes,
+    /// /home, /root and /run/user are read-only
+    ReadOnly,
+    /// /home, /root and /run/user are replaced by empty read-only tmpfs
+    Tmpfs,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BoolOrStr {
+    Bool(bool),
+    Str(String),
+}
+
+impl BoolOrStr {
+    fn into_string(self) -> String {
+        match self {
+            BoolOrStr::Bool(true) => "yes".to_string(),
+            BoolOrStr::Bool(false) => "no".to_string(),
+            BoolOrStr::Str(s) => s,
+        }
+    }
+}
+
+impl ProtectSystem {
+    /// parse the ProtectSystem= value, like "no", "yes", "full" or "strict"
+    pub fn parse(s: &str) -> Result<ProtectSystem> {
+        match s.trim() {
+            "no" | "false" => Ok(ProtectSystem::No),
+            "yes" | "true" => Ok(ProtectSystem::Yes),
+            "full" => Ok(ProtectSystem::Full),
+            "strict" => Ok(ProtectSystem::Strict),
+            s => Err(Error::ConfigureError {
+                msg: format!("invalid ProtectSystem: \"{s}\""),
+            }),
+        }
+    }
+}
+
+impl ProtectHome {
+    /// parse the ProtectHome= value, like "no", "yes", "read-only" or "tmpfs"
+    pub fn parse(s: &str) -> Result<ProtectHome> {
+        match s.trim() {
+            "no" | "false" => Ok(ProtectHome::No),
+            "yes" | "true" => Ok(ProtectHome::Yes),
+            "read-only" => Ok(ProtectHome::ReadOnly),
+            "tmpfs" => Ok(ProtectHome::Tmpfs),
+            s => Err(Error::ConfigureError {
+                msg: format!("invalid ProtectHome: \"{s}\""),
+            }),
+        }
+    }
+}
+
+impl DeserializeWith for ProtectSystem {
+    type Item = Self;
+    fn deserialize_with<'de, D>(de: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = BoolOrStr::deserialize(de)?.into_string();
+
+        ProtectSystem::parse(&s).map_err(|_| {
+            de::Error::invalid_value(Unexpected::Str(&s), &"a boolean, \"full\" or \"strict\"")
+        })
+    }
+}
+
+impl DeserializeWith for ProtectHome {
+    type Item = Self;
+    fn deserialize_with<'de, D>(de: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = BoolOrStr::deserialize(de)?.into_string();
+
+        ProtectHome::parse(&s).map_err(|_| {
+            de::Error::invalid_value(
+                Unexpected::Str(&s),
+                &"a boolean, \"read-only\" or \"tmpfs\"",
+            )
+        })
+    }
+}
+
+// the path prefixed with "-" is ignored if it doesn't exist
+fn parse_path(s: &str) -> Result<(PathBuf, bool)> {
+    let s = s.trim();
+    let (s, ignore_missing) = match s.strip_prefix('-') {
+        Some(s) => (s, true),
+        None => (s, false),
+    };
+
+    let path = PathBuf::from(s);
+    if !path.is_absolute() {
+        return Err(Error::ConfigureError {
+            msg: format!("sandbox path {path:?} is not absolute"),
+        });
+    }
+    Ok((path, ignore_missing))
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+enum MountMode {
+    ReadOnly,
+    ReadWrite,
+    Tmpfs { mode: u32, readonly: bool },
+    PrivateDev,
+    Bind { source: PathBuf, recursive: bool },
+    Inaccessible,
+}
+
+impl MountMode {
+    // the mode with higher priority wins if several modes are configured for the same path
+    fn priority(&self) -> u8 {
+        match self {
+            MountMode::ReadOnly => 0,
+            MountMode::ReadWrite => 1,
+            MountMode::Tmpfs { .. } => 2,
+            MountMode::PrivateDev => 3,
+            MountMode::Bind { .. } => 4,
+            MountMode::Inaccessible => 5,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+struct MountEntry {
+    path: PathBuf,
+    mode: MountMode,
+    ignore_missing: bool,
+}
+
+impl MountEntry {
+    fn new(path: &str, mode: MountMode, ignore_missing: bool) -> MountEntry {
+        MountEntry {
+            path: PathBuf::from(path),
+            mode,
+            ignore_missing,
+        }
+    }
+}
+
+/// the sandboxing settings of the child
+#[derive(Clone, Debug, Default)]
+pub struct ExecSandbox {
+    protect_system: ProtectSystem,
+    protect_home: ProtectHome,
+    // the per-unit directory bound to /tmp and /var/tmp
+    private_tmp: Option<PathBuf>,
+    private_devices: bool,
+    paths: Vec<MountEntry>,
+    // the directory where the private mounts are staged, RUN_SYSTEM_PATH if not set
+    runtime_dir: Option<PathBuf>,
+}
+
+impl ExecSandbox {
+    /// create the sandbox settings without any restriction
+    pub fn new() -> ExecSandbox {
+        ExecSandbox::default()
+    }
+
+    /// set the ProtectSystem= value
+    pub fn set_protect_system(&mut self, protect_system: ProtectSystem) {
+        self.protect_system = protect_system;
+    }
+
+    /// set the ProtectHome= value
+    pub fn set_protect_home(&mut self, protect_home: ProtectHome) {
+        self.protect_home = protect_home;
+    }
+
+    /// bind the per-unit directory created by private_tmp_create() to /tmp and /var/tmp of
+    /// the child, so that all the processes of the unit share them, None means not private
+    pub fn set_private_tmp(&mut self, private_tmp: Option<PathBuf>) {
+        self.private_tmp = private_tmp;
+    }
+
+    /// mount a private /dev with only the pseudo devices for the child
+    pub fn set_private_devices(&mut self, private_devices: bool) {
+        self.private_devices = private_devices;
+    }
+
+    /// set the directory where the private mounts are staged, RUN_SYSTEM_PATH by default
+    pub fn set_runtime_dir(&mut self, runtime_dir: PathBuf) {
+        self.runtime_dir = Some(runtime_dir);
+    }
+
+    /// add the paths of ReadOnlyPaths=, the path prefixed with "-" is ignored if it doesn't exist
+    pub fn add_read_only_paths(&mut self, paths: &[String]) -> Result<()> {
+        self.add_paths(paths, MountMode::ReadOnly)
+    }
+
+    /// add the paths of ReadWritePaths=, which are writable even under the read-only paths
+    pub fn add_read_write_paths(&mut self, paths: &[String]) -> Result<()> {
+        self.add_paths(paths, MountMode::ReadWrite)
+    }
+
+    /// add the paths of InaccessiblePaths=
+    pub fn add_inaccessible_paths(&mut self, paths: &[String]) -> Result<()> {
+        self.add_paths(paths, MountMode::Inaccessible)
+    }
+
+    /// add the paths of BindPaths=, like "[-]SOURCE[:DEST[:rbind|norbind]]",
+    /// the source is bound to the same path if DEST is not configured
+    pub fn add_bind_paths(&mut self, paths: &[String]) -> Result<()> {
+        for s in paths.iter().filter(|s| !s.trim().is_empty()) {
+            let mut fields = s.trim().splitn(3, ':');
+            let (source, ignore_missing) = parse_path(fields.next().unwrap_or_default())?;
+            let dest = match fields.next() {
+                Some(dest) if dest.starts_with('-') => {
+                    return Err(Error::ConfigureError {
+                        msg: format!("invalid BindPaths: \"{s}\""),
+                    })
+                }
+                Some(dest) => parse_path(dest)?.0,
+                None => source.clone(),
+            };
+            let recursive = match fields.next() {
+                None | Some("rbind") => true,
+                Some("norbind") => false,
+                Some(_) => {
+                    return Err(Error::ConfigureError {
+                        msg: format!("invalid BindPaths: \"{s}\""),
+                    })
+                }
+            };
+
+            self.paths.push(MountEntry {
+                path: dest,
+                mode: MountMode::Bind { source, recursive },
+                ignore_missing,
+            });
+        }
+        Ok(())
+    }
+
+    fn add_paths(&mut self, paths: &[String], mode: MountMode) -> Result<()> {
+        for s in paths.iter().filter(|s| !s.trim().is_empty()) {
+            let (path, ignore_missing) = parse_path(s)?;
+            self.paths.push(MountEntry {
+                path,
+                mode: mode.clone(),
+                ignore_missing,
+            });
+        }
+        Ok(())
+    }
+
+    /// whether the child runs in a new mount namespace
+    pub fn enabled(&self) -> bool {
+        self.protect_system != ProtectSystem::No
+            || self.protect_home != ProtectHome::No
+            || self.private_tmp.is_some()
+            || self.private_devices
+            || !self.paths.is_empty()
+    }
+
+    // collect all the mounts, sorted by the path, so that the parent is mounted before the child
+    fn mount_entries(&self) -> Vec<MountEntry> {
+        let mut entries = Vec::new();
+        match self.protect_system {
+            ProtectSystem::No => {}
+            ProtectSystem::Yes | ProtectSystem::Full => {
+                entries.push(MountEntry::new("/usr", MountMode::ReadOnly, true));
+                entries.push(MountEntry::new("/boot", MountMode::ReadOnly, true));
+                entries.push(MountEntry::new("/efi", MountMode::ReadOnly, true));
+                if self.protect_system == ProtectSystem::Full {
+                    entries.push(MountEntry::new("/etc", MountMode::ReadOnly, false));
+                }
+            }
+            ProtectSystem::Strict => {
+                entries.push(MountEntry::new("/", MountMode::ReadOnly, false));
+                entries.push(MountEntry::new("/dev", MountMode::ReadWrite, true));
+                entries.push(MountEntry::new("/proc", MountMode::ReadWrite, true));
+                entries.push(MountEntry::new("/sys", MountMode::ReadWrite, true));
+            }
+        }
+
+        let home_mode = match self.protect_home {
+            ProtectHome::No => None,
+            ProtectHome::Yes => Some(MountMode::Inaccessible),
+            ProtectHome::ReadOnly => Some(MountMode::ReadOnly),
+            ProtectHome::Tmpfs => Some(MountMode::Tmpfs {
+                mode: 0o755,
+                readonly: true,
+            }),
+        };
+        if let Some(mode) = home_mode {
+            for path in ["/home", "/root", "/run/user"] {
+                entries.push(MountEntry::new(path, mode.clone(), true));
+            }
+        }
+
+        if self.private_devices {
+            entries.push(MountEntry::new("/dev", MountMode::PrivateDev, false));
+        }
+
+        entries.extend(self.paths.iter().cloned());
+
+        // pushed after BindPaths=, which wins for the same path
+        if let Some(dir) = &self.private_tmp {
+            for (path, sub, ignore_missing) in
+                [("/tmp", "tmp", false), ("/var/tmp", "var-tmp", true)]
+            {
+                let mode = MountMode::Bind {
+                    source: dir.join(sub),
+                    recursive: false,
+                };
+                entries.push(MountEntry::new(path, mode, ignore_missing));
+            }
+        }
+
+        // keep the mode with the highest priority for the same path
+        entries.sort_by(|a, b| {
+            a.path
+                .cmp(&b.path)
+                .then(b.mode.priority().cmp(&a.mode.priority()))
+        });
+        entries.dedup_by(|b, a| a.path == b.path);
+
+        // the paths under the inaccessible ones can't be reached any more
+        let mut result: Vec<MountEntry> = Vec::new();
+        for entry in entries {
+            let hidden = result.iter().any(|e| {
+                e.mode == MountMode::Inaccessible
+                    && e.path != entry.path
+                    && entry.path.starts_with(&e.path)
+            });
+            if !hidden {
+                result.push(entry);
+            }
+        }
+        result
+    }
+
+    /// enter a new mount namespace and set up the mounts, must be called in the child
+    pub fn setup(&self) -> Result<()> {
+        if !self.enabled() {
+            return Ok(());
+        }
+
+        unshare(CloneFlags::CLONE_NEWNS).context(NixSnafu)?;
+        // the mounts of the child must not be propagated to the host
+        mount_util::mount_slave_recursive(Path::new("/"))?;
+
+        // create all the mounts first, and then apply the read-only or read-write state in
+        // order, so that the state of the child overrides the state of the parent
+        let runtime_dir = self
+            .runtime_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(RUN_SYSTEM_PATH));
+        let mut mounted = Vec::new();
+        for entry in self.mount_entries() {
+            if let Some(readonly) = apply_mount(&entry, &runtime_dir)? {
+                mounted.push((entry, readonly));
+            }
+        }
+
+        for (entry, readonly) in mounted {
+            let extra = match entry.mode {
+                MountMode::Inaccessible => {
+                    MsFlags::MS_NOSUID | MsFlags::MS_NODEV | MsFlags::MS_NOEXEC
+                }
+                _ => MsFlags::empty(),
+            };
+            mount_util::bind_remount_recursive(&entry.path, readonly, extra)?;
+        }
+        Ok(())
+    }
+}
+
+/// the per-unit directory of PrivateTmp= under the runtime directory
+pub fn private_tmp_dir(runtime_dir: &Path, unit: &str) -> PathBuf {
+    runtime_dir.join(PRIVATE_TMP_DIR).join(unit)
+}
+
+/// create the per-unit directory of PrivateTmp=, which is kept until the unit stops, the
+/// directory is only accessible by root, and /tmp and /var/tmp are created in it
+pub fn private_tmp_create(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).context(IoSnafu)?;
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o700)).context(IoSnafu)?;
+    for sub in ["tmp", "var-tmp"] {
+        let path = dir.join(sub);
+        if !path_exists(&path) {
+            fs::create_dir(&path).context(IoSnafu)?;
+        }
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o1777)).context(IoSnafu)?;
+    }
+    Ok(())
+}
+
+/// remove the per-unit directory of PrivateTmp= with all the files in it
+pub fn private_tmp_remove(dir: &Path) -> Result<()> {
+    match fs::remove_dir_all(dir) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e).context(IoSnafu),
+    }
+}
+
+fn path_exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+fn is_readonly(path: &Path) -> Result<bool> {
+    let flags = statvfs(path).context(NixSnafu)?.flags();
+    Ok(flags.contains(FsFlags::ST_RDONLY))
+}
+
+// create the mount of the entry, return whether it should be read-only,
+// or None if it's skipped because the path doesn't exist
+fn apply_mount(entry: &MountEntry, runtime_dir: &Path) -> Result<Option<bool>> {
+    let source = match &entry.mode {
+        MountMode::Bind { source, .. } => source.as_path(),
+        _ => entry.path.as_path(),
+    };
+    for path in [source, entry.path.as_path()] {
+        if path_exists(path) {
+            continue;
+        }
+        if entry.ignore_missing {
+            return Ok(None);
+        }
+        return Err(Error::NotFound {
+            what: format!("sandbox path {path:?}"),
+        });
+    }
+
+    let readonly = match &entry.mode {
+        MountMode::ReadOnly | MountMode::ReadWrite => {
+            // make the path a mount point so that it can be remounted, the root is already one
+            if entry.path != Path::new("/") {
+                mount_util::bind_mount(&entry.path, &entry.path, true)?;
+            }
+            entry.mode == MountMode::ReadOnly
+        }
+        MountMode::Tmpfs { mode, readonly } => {
+            let flags = MsFlags::MS_NOSUID | MsFlags::MS_NODEV;
+            mount_util::mount_tmpfs(&entry.path, *mode, flags)?;
+            *readonly
+        }
+        MountMode::PrivateDev => {
+            setup_private_dev(&entry.path, runtime_dir)?;
+            false
+        }
+        MountMode::Bind { source, recursive } => {
+            // the bind mount keeps the read-only state of the source
+            let readonly = is_readonly(source)?;
+            mount_util::bind_mount(source, &entry.path, *recursive)?;
+            readonly
+        }
+        MountMode::Inaccessible => {
+            if entry.path.is_dir() {
+                let flags = MsFlags::MS_NOSUID | MsFlags::MS_NODEV | MsFlags::MS_NOEXEC;
+                mount_util::mount_tmpfs(&entry.path, 0o000, flags)?;
+            } else {
+                // the device node can't be opened on the mount with nodev
+                mount_util::bind_mount(Path::new(DEV_NULL), &entry.path, false)?;
+            }
+            true
+        }
+    };
+    Ok(Some(readonly))
+}
+
+// build the private /dev in a temporary tmpfs under the runtime directory, and move it to
+// the dev path
+fn setup_private_dev(dev: &Path, runtime_dir: &Path) -> Result<()> {
+    fs::create_dir_all(runtime_dir).context(IoSnafu)?;
+    // the stale directory left by the crashed child with the same pid is removed first
+    let temp = runtime_dir.join(format!("private-dev-{}", unistd::getpid()));
+    let _ = fs::remove_dir(&temp);
+    fs::create_dir(&temp).context(IoSnafu)?;
+    let ret = build_private_dev(dev, &temp).and_then(|_| {
+        mount_util::move_mount(&temp, dev)?;
+        Ok(())
+    });
+    if ret.is_err() {
+        let _ = nix::mount::umount2(&temp, nix::mount::MntFlags::MNT_DETACH);
+    }
+    let _ = fs::remove_dir(&temp);
+    ret
+}
+
+fn build_private_dev(dev: &Path, temp: &Path) -> Result<()> {
+    let flags = MsFlags::MS_NOSUID | MsFlags::MS_NOEXEC;
+    mount_util::mount_tmpfs(temp, 0o755, flags)?;
+
+    for name in PRIVATE_DEVICES {
+        let source = dev.join(name);
+        if !path_exists(&source) {
+            continue;
+        }
+        let target = temp.join(name);
+        fs::File::create(&target).context(IoSnafu)?;
+        mount_util::bind_mount(&source, &target, false)?;
+    }
+
+    for name in ["pts", "shm"] {
+        fs::create_dir(temp.join(name)).context(IoSnafu)?;
+    }
+    fs::set_permissions(temp.join("shm"), fs::Permissions::from_mode(0o1777)).context(IoSnafu)?;
+
+    for (name, target) in [
+        ("fd", "/proc/self/fd"),
+        ("stdin", "/proc/self/fd/0"),
+        ("stdout", "/proc/self/fd/1"),
+        ("stderr", "/proc/self/fd/2"),
+    ] {
+        symlink(target, temp.join(name)).context(IoSnafu)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        private_tmp_create, private_tmp_dir, private_tmp_remove, ExecSandbox, MountEntry,
+        MountMode, ProtectHome, ProtectSystem,
+    };
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_protect_parse() {
+        assert_eq!(ProtectSystem::parse("no").unwrap(), ProtectSystem::No);
+        assert_eq!(ProtectSystem::parse("true").unwrap(), ProtectSystem::Yes);
+        assert_eq!(ProtectSystem::parse("full").unwrap(), ProtectSystem::Full);
+        assert_eq!(
+            ProtectSystem::parse("strict").unwrap(),
+            ProtectSystem::Strict
+        );
+        assert!(ProtectSystem::parse("read-only").is_err());
+
+        assert_eq!(ProtectHome::parse("false").unwrap(), ProtectHome::No);
+        assert_eq!(ProtectHome::parse("yes").unwrap(), ProtectHome::Yes);
+        assert_eq!(
+            ProtectHome::parse("read-only").unwrap(),
+            ProtectHome::ReadOnly
+        );
+        assert_eq!(ProtectHome::parse("tmpfs").unwrap(), ProtectHome::Tmpfs);
+        assert!(ProtectHome::parse("strict").is_err());
+    }
+
+    #[test]
+    fn test_add_paths() {
+        let mut sandbox = ExecSandbox::new();
+        assert!(!sandbox.enabled());
+        sandbox
+            .add_read_only_paths(&["/var".to_string(), "-/opt/foo".to_string()])
+            .unwrap();
+        assert!(sandbox.enabled());
+        assert!(sandbox.add_read_write_paths(&["var".to_string()]).is_err());
+        assert!(sandbox.add_inaccessible_paths(&["".to_string()]).is_ok());
+
+        sandbox
+            .add_bind_paths(&[
+                "/srv/data".to_string(),
+                "-/srv/a:/mnt/b:norbind".to_string(),
+            ])
+            .unwrap();
+        assert!(sandbox.add_bind_paths(&["/srv:-/mnt".to_string()]).is_err());
+        assert!(sandbox
+            .add_bind_paths(&["/srv:/mnt:bind".to_string()])
+            .is_err());
+
+        let entries = sandbox.mount_entries();
+        assert_eq!(
+            entries,
+            vec![
+                MountEntry {
+                    path: PathBuf::from("/mnt/b"),
+                    mode: MountMode::Bind {
+                        source: PathBuf::from("/srv/a"),
+                        recursive: false
+                    },
+                    ignore_missing: true,
+                },
+                MountEntry::new("/opt/foo", MountMode::ReadOnly, true),
+                MountEntry {
+                    path: PathBuf::from("/srv/data"),
+                    mode: MountMode::Bind {
+                        source: PathBuf::from("/srv/data"),
+                        recursive: true
+                    },
+                    ignore_missing: false,
+                },
+                MountEntry::new("/var", MountMode::ReadOnly, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mount_entries() {
+        let mut sandbox = ExecSandbox::new();
+        sandbox.set_protect_system(ProtectSystem::Strict);
+        sandbox.set_protect_home(ProtectHome::Yes);
+        sandbox.set_private_devices(true);
+        sandbox
+            .add_read_write_paths(&["/home/foo".to_string(), "/var/lib/foo".to_string()])
+            .unwrap();
+
+        let paths = sandbox
+            .mount_entries()
+            .into_iter()
+            .map(|e| (e.path.to_str().unwrap().to_string(), e.mode))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                ("/".to_string(), MountMode::ReadOnly),
+                ("/dev".to_string(), MountMode::PrivateDev),
+                ("/home".to_string(), MountMode::Inaccessible),
+                ("/proc".to_string(), MountMode::ReadWrite),
+                ("/root".to_string(), MountMode::Inaccessible),
+                ("/run/user".to_string(), MountMode::Inaccessible),
+                ("/sys".to_string(), MountMode::ReadWrite),
+                ("/var/lib/foo".to_string(), MountMode::ReadWrite),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_private_tmp() {
+        let runtime_dir = std::env::temp_dir().join(format!("sandbox-{}", std::process::id()));
+        let dir = private_tmp_dir(&runtime_dir, "foo.service");
+        assert_eq!(dir, runtime_dir.join("private-tmp/foo.service"));
+
+        private_tmp_create(&dir).unwrap();
+        let mode = |p: PathBuf| fs::metadata(p).unwrap().permissions().mode() & 0o7777;
+        assert_eq!(mode(dir.clone()), 0o700);
+        assert_eq!(mode(dir.join("tmp")), 0o1777);
+        assert_eq!(mode(dir.join("var-tmp")), 0o1777);
+        // the files are kept when it's created again by the next process of the unit
+        fs::write(dir.join("tmp/foo"), "foo").unwrap();
+        private_tmp_create(&dir).unwrap();
+        assert!(dir.join("tmp/foo").exists());
+
+        let mut sandbox = ExecSandbox::new();
+        sandbox.set_private_tmp(Some(dir.clone()));
+        sandbox
+            .add_bind_paths(&["/srv/tmp:/var/tmp".to_string()])
+            .unwrap();
+        assert_eq!(
+            sandbox.mount_entries(),
+            vec![
+                MountEntry::new(
+                    "/tmp",
+                    MountMode::Bind {
+                        source: dir.join("tmp"),
+                        recursive: false
+                    },
+                    false
+                ),
+                MountEntry {
+                    path: PathBuf::from("/var/tmp"),
+                    mode: MountMode::Bind {
+                        source: PathBuf::from("/srv/tmp"),
+                        recursive: true
+                    },
+                    ignore_missing: false,
+                },
+            ]
+        );
+
+        private_tmp_remove(&dir).unwrap();
+        assert!(!dir.exists());
+        private_tmp_remove(&dir).unwrap();
+        fs::remove_dir_all(&runtime_dir).unwrap();
+    }
+}
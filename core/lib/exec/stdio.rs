@@ -0,0 +1,280 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+use crate::error::*;
+use crate::serialize::DeserializeWith;
+use nix::fcntl::{open, OFlag};
+use nix::sys::stat::Mode;
+use serde::{
+    de::{self, Unexpected},
+    Deserialize, Deserializer, Serialize,
+};
+use std::path::{Path, PathBuf};
+
+const DEV_NULL: &str = "/dev/null";
+
+/// where the stdin of the child is connected to, parsed from the StandardInput= field.
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize, Deserialize)]
+pub enum ExecInput {
+    /// connected to /dev/null
+    #[default]
+    Null,
+    /// connected to the terminal of TTYPath=
+    Tty,
+    /// connected to the socket fd passed by the socket unit
+    Socket,
+    /// connected to the socket fd with the name
+    Fd(String),
+    /// connected to the file with the path
+    File(PathBuf),
+}
+
+/// where the stdout or stderr of the child is connected to, parsed from the
+/// StandardOutput= and StandardError= fields.
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize, Deserialize)]
+pub enum ExecOutput {
+    /// stdout is connected to the same as stdin, stderr is connected to the same as stdout
+    #[default]
+    Inherit,
+    /// connected to /dev/null
+    Null,
+    /// connected to the terminal of TTYPath=
+    Tty,
+    /// connected to the log collector of sysmaster
+    Journal,
+    /// connected to the socket fd passed by the socket unit
+    Socket,
+    /// connected to the socket fd with the name
+    Fd(String),
+    /// connected to the file with the path, written from the beginning
+    File(PathBuf),
+    /// connected to the file with the path, written at the end
+    Append(PathBuf),
+    /// connected to the file with the path, truncated before written
+    Truncate(PathBuf),
+}
+
+fn parse_fd_name(name: &str) -> Result<String> {
+    if name.is_empty() || name.contains(':') {
+        return Err(Error::ConfigureError {
+            msg: format!("invalid fd name: \"{name}\""),
+        });
+    }
+    Ok(name.to_string())
+}
+
+fn parse_file_path(path: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(path);
+    if !path.is_absolute() {
+        return Err(Error::ConfigureError {
+            msg: format!("file path {path:?} is not absolute"),
+        });
+    }
+    Ok(path)
+}
+
+fn open_fd(path: &Path, flags: OFlag) -> Result<Option<i32>> {
+    let fd = open(
+        path,
+        flags | OFlag::O_NOCTTY | OFlag::O_CLOEXEC,
+        Mode::from_bits_truncate(0o644),
+    )
+    .context(NixSnafu)?;
+    Ok(Some(fd))
+}
+
+impl ExecInput {
+    /// parse the StandardInput= value, like "null", "tty", "socket", "fd:NAME" or "file:PATH"
+    pub fn parse(s: &str) -> Result<ExecInput> {
+        let s = s.trim();
+        match s {
+            "null" => return Ok(ExecInput::Null),
+            "tty" => return Ok(ExecInput::Tty),
+            "socket" => return Ok(ExecInput::Socket),
+            _ => {}
+        }
+
+        if let Some(name) = s.strip_prefix("fd:") {
+            return Ok(ExecInput::Fd(parse_fd_name(name)?));
+        }
+        if let Some(path) = s.strip_prefix("file:") {
+            return Ok(ExecInput::File(parse_file_path(path)?));
+        }
+
+        Err(Error::ConfigureError {
+            msg: format!("invalid StandardInput: \"{s}\""),
+        })
+    }
+
+    /// open the file the stdin is connected to, return None if the fd is provided by others.
+    /// the file is opened in read-write mode, so that stdout can inherit it.
+    pub fn open(&self, tty_path: &Path) -> Result<Option<i32>> {
+        match self {
+            ExecInput::Null => open_fd(Path::new(DEV_NULL), OFlag::O_RDONLY),
+            ExecInput::Tty => open_fd(tty_path, OFlag::O_RDWR),
+            ExecInput::File(path) => open_fd(path, OFlag::O_RDWR),
+            ExecInput::Socket | ExecInput::Fd(_) => Ok(None),
+        }
+    }
+}
+
+impl ExecOutput {
+    /// parse the StandardOutput= or StandardError= value, like "inherit", "null", "tty",
+    /// "journal", "socket", "fd:NAME", "file:PATH", "append:PATH" or "truncate:PATH"
+    pub fn parse(s: &str) -> Result<ExecOutput> {
+        let s = s.trim();
+        match s {
+            "inherit" => return Ok(ExecOutput::Inherit),
+            "null" => return Ok(ExecOutput::Null),
+            "tty" => return Ok(ExecOutput::Tty),
+            "journal" => return Ok(ExecOutput::Journal),
+            "socket" => return Ok(ExecOutput::Socket),
+            _ => {}
+        }
+
+        if let Some(name) = s.strip_prefix("fd:") {
+            return Ok(ExecOutput::Fd(parse_fd_name(name)?));
+        }
+        if let Some(path) = s.strip_prefix("file:") {
+            return Ok(ExecOutput::File(parse_file_path(path)?));
+        }
+        if let Some(path) = s.strip_prefix("append:") {
+            return Ok(ExecOutput::Append(parse_file_path(path)?));
+        }
+        if let Some(path) = s.strip_prefix("truncate:") {
+            return Ok(ExecOutput::Truncate(parse_file_path(path)?));
+        }
+
+        Err(Error::ConfigureError {
+            msg: format!("invalid StandardOutput or StandardError: \"{s}\""),
+        })
+    }
+
+    /// open the file the output is connected to, return None if the fd is provided by others,
+    /// inherited or connected to the log collector.
+    pub fn open(&self, tty_path: &Path) -> Result<Option<i32>> {
+        let create = OFlag::O_WRONLY | OFlag::O_CREAT;
+        match self {
+            ExecOutput::Null => open_fd(Path::new(DEV_NULL), OFlag::O_WRONLY),
+            ExecOutput::Tty => open_fd(tty_path, OFlag::O_WRONLY),
+            ExecOutput::File(path) => open_fd(path, create),
+            ExecOutput::Append(path) => open_fd(path, create | OFlag::O_APPEND),
+            ExecOutput::Truncate(path) => open_fd(path, create | OFlag::O_TRUNC),
+            ExecOutput::Inherit | ExecOutput::Journal | ExecOutput::Socket | ExecOutput::Fd(_) => {
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl DeserializeWith for ExecInput {
+    type Item = Self;
+    fn deserialize_with<'de, D>(de: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(de)?;
+
+        ExecInput::parse(&s).map_err(|_| {
+            de::Error::invalid_value(
+                Unexpected::Str(&s),
+                &"\"null\", \"tty\", \"socket\", \"fd:NAME\" or \"file:PATH\"",
+            )
+        })
+    }
+}
+
+impl DeserializeWith for ExecOutput {
+    type Item = Self;
+    fn deserialize_with<'de, D>(de: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(de)?;
+
+        ExecOutput::parse(&s).map_err(|_| {
+            de::Error::invalid_value(
+                Unexpected::Str(&s),
+                &"\"inherit\", \"null\", \"tty\", \"journal\", \"socket\", \"fd:NAME\", \"file:PATH\", \"append:PATH\" or \"truncate:PATH\"",
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExecInput, ExecOutput};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_exec_input_parse() {
+        assert_eq!(ExecInput::parse("null").unwrap(), ExecInput::Null);
+        assert_eq!(ExecInput::parse("tty").unwrap(), ExecInput::Tty);
+        assert_eq!(ExecInput::parse("socket").unwrap(), ExecInput::Socket);
+        assert_eq!(
+            ExecInput::parse("fd:foo.socket").unwrap(),
+            ExecInput::Fd("foo.socket".to_string())
+        );
+        assert_eq!(
+            ExecInput::parse("file:/tmp/input").unwrap(),
+            ExecInput::File(PathBuf::from("/tmp/input"))
+        );
+        assert!(ExecInput::parse("inherit").is_err());
+        assert!(ExecInput::parse("fd:").is_err());
+        assert!(ExecInput::parse("file:tmp/input").is_err());
+        assert!(ExecInput::parse("append:/tmp/input").is_err());
+        assert!(ExecInput::parse("").is_err());
+    }
+
+    #[test]
+    fn test_exec_output_parse() {
+        assert_eq!(ExecOutput::parse("inherit").unwrap(), ExecOutput::Inherit);
+        assert_eq!(ExecOutput::parse("null").unwrap(), ExecOutput::Null);
+        assert_eq!(ExecOutput::parse("tty").unwrap(), ExecOutput::Tty);
+        assert_eq!(ExecOutput::parse("journal").unwrap(), ExecOutput::Journal);
+        assert_eq!(ExecOutput::parse("socket").unwrap(), ExecOutput::Socket);
+        assert_eq!(
+            ExecOutput::parse("fd:log").unwrap(),
+            ExecOutput::Fd("log".to_string())
+        );
+        assert_eq!(
+            ExecOutput::parse("file:/var/log/foo.log").unwrap(),
+            ExecOutput::File(PathBuf::from("/var/log/foo.log"))
+        );
+        assert_eq!(
+            ExecOutput::parse("append:/var/log/foo.log").unwrap(),
+            ExecOutput::Append(PathBuf::from("/var/log/foo.log"))
+        );
+        assert_eq!(
+            ExecOutput::parse("truncate:/var/log/foo.log").unwrap(),
+            ExecOutput::Truncate(PathBuf::from("/var/log/foo.log"))
+        );
+        assert!(ExecOutput::parse("syslog").is_err());
+        assert!(ExecOutput::parse("append:").is_err());
+        assert!(ExecOutput::parse("truncate:foo.log").is_err());
+    }
+
+    #[test]
+    fn test_exec_output_open() {
+        let tty = Path::new("/dev/console");
+        assert_eq!(ExecOutput::Inherit.open(tty).unwrap(), None);
+        assert_eq!(ExecOutput::Socket.open(tty).unwrap(), None);
+
+        let fd = ExecOutput::Null.open(tty).unwrap().unwrap();
+        assert!(fd >= 0);
+        nix::unistd::close(fd).unwrap();
+
+        let fd = ExecInput::Null.open(tty).unwrap().unwrap();
+        assert!(fd >= 0);
+        nix::unistd::close(fd).unwrap();
+    }
+}
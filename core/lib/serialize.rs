@@ -11,7 +11,10 @@
 // See the Mulan PSL v2 for more details.
 
 //!
-use serde::{Deserialize, Deserializer};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::fmt::Display;
+use std::str::FromStr;
 
 ///
 pub trait DeserializeWith: Sized {
@@ -39,3 +42,146 @@ impl DeserializeWith for Vec<String> {
         Ok(vec)
     }
 }
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StrOrNum {
+    Str(String),
+    Num(u64),
+}
+
+/// deserialize the value which can be configured as a string or a number, like "1G" or 1024
+pub fn deserialize_str_or_num<'de, D>(de: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match StrOrNum::deserialize(de)? {
+        StrOrNum::Str(s) => Ok(s),
+        StrOrNum::Num(n) => Ok(n.to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StrOrValue<T> {
+    Str(String),
+    Value(T),
+}
+
+/// deserialize the typed value which can also be configured as a string, like 10 or "10",
+/// true or "true"
+pub fn deserialize_str_or_value<'de, D, T>(de: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: Display,
+{
+    match StrOrValue::<T>::deserialize(de)? {
+        StrOrValue::Str(s) => s.trim().parse().map_err(de::Error::custom),
+        StrOrValue::Value(v) => Ok(v),
+    }
+}
+
+/// flatten the fields of the configuration section into the KEY=VALUE properties, the lists
+/// are joined with spaces and the unset options are skipped
+pub fn section_properties<T: Serialize>(section: &T) -> Vec<(String, String)> {
+    let fields = match serde_json::to_value(section) {
+        Ok(Value::Object(fields)) => fields,
+        _ => return Vec::new(),
+    };
+
+    fields
+        .into_iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(key, value)| (key, property_value(&value)))
+        .collect()
+}
+
+fn property_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.to_string(),
+        Value::Array(items) => items
+            .iter()
+            .map(property_value)
+            .collect::<Vec<String>>()
+            .join(" "),
+        Value::Object(fields) => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(key, value)| format!("{key}={}", property_value(value)))
+                .collect();
+            format!("{{ {} }}", fields.join(" ; "))
+        }
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    #[allow(non_snake_case)]
+    struct Section {
+        Description: String,
+        After: Vec<String>,
+        MemoryMax: Option<String>,
+        TasksMax: Option<String>,
+        RestartSec: u64,
+        Limit: u64,
+        Command: Command,
+    }
+
+    #[derive(Serialize)]
+    struct Command {
+        path: String,
+        argv: Vec<String>,
+    }
+
+    #[test]
+    fn test_section_properties() {
+        let section = Section {
+            Description: "foo".to_string(),
+            After: vec!["a.service".to_string(), "b.service".to_string()],
+            MemoryMax: Some("1G".to_string()),
+            TasksMax: None,
+            RestartSec: 3,
+            Limit: u64::MAX,
+            Command: Command {
+                path: "/bin/sleep".to_string(),
+                argv: vec!["sleep".to_string(), "10".to_string()],
+            },
+        };
+        let props = section_properties(&section);
+        let get = |key: &str| {
+            props
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.to_string())
+        };
+        assert_eq!(get("Description").unwrap(), "foo");
+        assert_eq!(get("After").unwrap(), "a.service b.service");
+        assert_eq!(get("MemoryMax").unwrap(), "1G");
+        assert_eq!(get("TasksMax"), None);
+        assert_eq!(get("RestartSec").unwrap(), "3");
+        assert_eq!(get("Limit").unwrap(), u64::MAX.to_string());
+        assert_eq!(
+            get("Command").unwrap(),
+            "{ argv=sleep 10 ; path=/bin/sleep }"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_str_or_value() {
+        let value = |v: Value| deserialize_str_or_value::<_, u64>(v);
+        assert_eq!(value(Value::from(10)).unwrap(), 10);
+        assert_eq!(value(Value::from("10")).unwrap(), 10);
+        assert!(value(Value::from("ten")).is_err());
+
+        let value = |v: Value| deserialize_str_or_value::<_, bool>(v);
+        assert!(value(Value::from(true)).unwrap());
+        assert!(!value(Value::from("false")).unwrap());
+        assert!(value(Value::from("no")).is_err());
+    }
+}
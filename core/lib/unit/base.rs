@@ -11,6 +11,7 @@
 // See the Mulan PSL v2 for more details.
 
 use super::super::rel::ReStation;
+use super::cgroup::CgroupContext;
 use super::kill::{KillContext, KillOperation};
 use super::state::{UnitActiveState, UnitNotifyFlags};
 use super::umif::UnitMngUtil;
@@ -55,6 +56,9 @@ pub trait UnitBase {
     ///
     fn cg_path(&self) -> PathBuf;
 
+    /// remove the cgroup of the unit, it fails if there are processes or sub cgroups left
+    fn cg_remove(&self) -> Result<()>;
+
     ///
     fn ignore_on_isolate(&self) -> bool;
 
@@ -131,6 +135,32 @@ pub trait SubUnit: ReStation + UnitMngUtil {
     /// Return the state of subunit, i.e. (mounted for .mount, running for .service, listening for .socket)
     fn get_subunit_state(&self) -> String;
 
+    /// Return the status text reported by the unit itself, i.e. STATUS= of the notify message
+    fn get_status_text(&self) -> Option<String> {
+        None
+    }
+
+    /// Return the main process of the unit, if it's tracked
+    fn main_pid(&self) -> Option<Pid> {
+        None
+    }
+
+    /// Return the control process of the unit, if it's running
+    fn control_pid(&self) -> Option<Pid> {
+        None
+    }
+
+    /// Return the configuration of the sub unit as the KEY=VALUE properties
+    fn properties(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// Change the property of the sub unit at runtime, return UnitActionEOpNotSupp if the
+    /// property can only take effect after the unit is reloaded
+    fn set_property(&self, _key: &str, _value: &str) -> Result<()> {
+        Err(Error::UnitActionEOpNotSupp)
+    }
+
     ///
     fn attach_unit(&self, unit: Rc<dyn UnitBase>);
 
@@ -139,8 +169,12 @@ pub trait SubUnit: ReStation + UnitMngUtil {
         &self,
         _ucred: &UnixCredentials,
         _events: &HashMap<&str, &str>,
-        _fds: Vec<i32>,
+        fds: Vec<i32>,
     ) -> Result<()> {
+        // the received fds are not used by default
+        for fd in fds {
+            basic::fd_util::close(fd);
+        }
         Ok(())
     }
 
@@ -148,6 +182,14 @@ pub trait SubUnit: ReStation + UnitMngUtil {
     fn get_perpetual(&self) -> bool {
         false
     }
+
+    /// return the resource control context which is applied to the cgroup of the unit
+    fn cgroup_context(&self) -> Option<Rc<CgroupContext>> {
+        None
+    }
+
+    /// called when the state of the unit {_other} triggered by this unit is changed
+    fn trigger_notify(&self, _other: &str) {}
 }
 
 /// #[macro_use]
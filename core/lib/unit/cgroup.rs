@@ -0,0 +1,315 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+use crate::error::*;
+use basic::conf_parser::{parse_size, Base};
+use basic::special::ROOT_SLICE;
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+/// the value that means no limit of the resource
+pub const CGROUP_LIMIT_MAX: u64 = u64::MAX;
+
+/// the minimum value of CPUWeight and IOWeight
+pub const CGROUP_WEIGHT_MIN: u64 = 1;
+/// the maximum value of CPUWeight and IOWeight
+pub const CGROUP_WEIGHT_MAX: u64 = 10000;
+
+/// the resource control properties, which can be changed at runtime
+pub const CGROUP_PROPERTIES: [&str; 6] = [
+    "MemoryMax",
+    "MemoryHigh",
+    "CPUWeight",
+    "CPUQuota",
+    "TasksMax",
+    "IOWeight",
+];
+
+/// the resource control context of the unit, applied to the cgroup of the unit
+#[derive(Default, Debug)]
+pub struct CgroupContext {
+    slice: RefCell<Option<String>>,
+    memory_max: RefCell<Option<u64>>,
+    memory_high: RefCell<Option<u64>>,
+    cpu_weight: RefCell<Option<u64>>,
+    cpu_quota_per_sec_usec: RefCell<Option<u64>>,
+    tasks_max: RefCell<Option<u64>>,
+    io_weight: RefCell<Option<u64>>,
+}
+
+impl CgroupContext {
+    /// set the Slice, the value is the name of a slice unit like "foo.slice"
+    pub fn set_slice(&self, value: Option<&str>) -> Result<()> {
+        if let Some(slice) = value {
+            if !slice_name_is_valid(slice) {
+                return Err(Error::ConfigureError {
+                    msg: format!("invalid Slice: {slice}"),
+                });
+            }
+        }
+        *self.slice.borrow_mut() = value.map(|v| v.to_string());
+        Ok(())
+    }
+
+    /// get the Slice
+    pub fn slice(&self) -> Option<String> {
+        self.slice.borrow().clone()
+    }
+
+    /// set the MemoryMax, the value is bytes with K, M, G, T suffix, or "infinity"
+    pub fn set_memory_max(&self, value: Option<&str>) -> Result<()> {
+        *self.memory_max.borrow_mut() = value.map(parse_limit).transpose()?;
+        Ok(())
+    }
+
+    /// get the MemoryMax
+    pub fn memory_max(&self) -> Option<u64> {
+        *self.memory_max.borrow()
+    }
+
+    /// set the MemoryHigh, the value is bytes with K, M, G, T suffix, or "infinity"
+    pub fn set_memory_high(&self, value: Option<&str>) -> Result<()> {
+        *self.memory_high.borrow_mut() = value.map(parse_limit).transpose()?;
+        Ok(())
+    }
+
+    /// get the MemoryHigh
+    pub fn memory_high(&self) -> Option<u64> {
+        *self.memory_high.borrow()
+    }
+
+    /// set the CPUWeight, the value is between 1 and 10000
+    pub fn set_cpu_weight(&self, value: Option<u64>) -> Result<()> {
+        *self.cpu_weight.borrow_mut() = value.map(check_weight).transpose()?;
+        Ok(())
+    }
+
+    /// get the CPUWeight
+    pub fn cpu_weight(&self) -> Option<u64> {
+        *self.cpu_weight.borrow()
+    }
+
+    /// set the CPUQuota, the value is a percentage like "20%"
+    pub fn set_cpu_quota(&self, value: Option<&str>) -> Result<()> {
+        *self.cpu_quota_per_sec_usec.borrow_mut() = value.map(parse_cpu_quota).transpose()?;
+        Ok(())
+    }
+
+    /// get the cpu time allowed in one second, in usec
+    pub fn cpu_quota_per_sec_usec(&self) -> Option<u64> {
+        *self.cpu_quota_per_sec_usec.borrow()
+    }
+
+    /// set the TasksMax, the value is a number or "infinity"
+    pub fn set_tasks_max(&self, value: Option<&str>) -> Result<()> {
+        *self.tasks_max.borrow_mut() = value.map(parse_limit).transpose()?;
+        Ok(())
+    }
+
+    /// get the TasksMax
+    pub fn tasks_max(&self) -> Option<u64> {
+        *self.tasks_max.borrow()
+    }
+
+    /// set the IOWeight, the value is between 1 and 10000
+    pub fn set_io_weight(&self, value: Option<u64>) -> Result<()> {
+        *self.io_weight.borrow_mut() = value.map(check_weight).transpose()?;
+        Ok(())
+    }
+
+    /// get the IOWeight
+    pub fn io_weight(&self) -> Option<u64> {
+        *self.io_weight.borrow()
+    }
+
+    /// set the resource control property by its name, the value is checked as in the unit file
+    pub fn set_property(&self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "MemoryMax" => self.set_memory_max(Some(value)),
+            "MemoryHigh" => self.set_memory_high(Some(value)),
+            "CPUWeight" => self.set_cpu_weight(Some(value.parse::<u64>()?)),
+            "CPUQuota" => self.set_cpu_quota(Some(value)),
+            "TasksMax" => self.set_tasks_max(Some(value)),
+            "IOWeight" => self.set_io_weight(Some(value.parse::<u64>()?)),
+            _ => Err(Error::UnitActionEOpNotSupp),
+        }
+    }
+
+    /// whether any resource control is configured
+    pub fn is_empty(&self) -> bool {
+        self.memory_max().is_none()
+            && self.memory_high().is_none()
+            && self.cpu_weight().is_none()
+            && self.cpu_quota_per_sec_usec().is_none()
+            && self.tasks_max().is_none()
+            && self.io_weight().is_none()
+    }
+}
+
+fn slice_name_is_valid(name: &str) -> bool {
+    if name == ROOT_SLICE {
+        return true;
+    }
+
+    let prefix = match name.strip_suffix(".slice") {
+        None => return false,
+        Some(p) => p,
+    };
+
+    // "-" separates the levels, so empty levels like "a--b" are not allowed
+    !prefix.is_empty() && !prefix.contains('/') && !prefix.split('-').any(|level| level.is_empty())
+}
+
+/// return the parent slice of the slice, "a-b.slice" is under "a.slice",
+/// and the top level slices like "a.slice" are under the root slice.
+pub fn slice_parent(slice: &str) -> Option<String> {
+    if slice == ROOT_SLICE || !slice_name_is_valid(slice) {
+        return None;
+    }
+
+    let prefix = slice.strip_suffix(".slice").unwrap();
+    match prefix.rfind('-') {
+        Some(pos) => Some(format!("{}.slice", &prefix[..pos])),
+        None => Some(ROOT_SLICE.to_string()),
+    }
+}
+
+/// return the cgroup path of the slice relative to the cgroup root of sysmaster,
+/// "a-b.slice" is mapped to "a.slice/a-b.slice".
+pub fn slice_cg_path(slice: &str) -> PathBuf {
+    let mut chain = Vec::new();
+    let mut cur = slice.to_string();
+    while cur != ROOT_SLICE && slice_name_is_valid(&cur) {
+        let parent = slice_parent(&cur);
+        chain.push(cur);
+        match parent {
+            Some(p) => cur = p,
+            None => break,
+        }
+    }
+
+    chain.iter().rev().collect()
+}
+
+fn parse_limit(value: &str) -> Result<u64> {
+    let value = value.trim();
+    if value == "infinity" {
+        return Ok(CGROUP_LIMIT_MAX);
+    }
+
+    parse_size(value, Base::Binary).context(UtilSnafu)
+}
+
+fn check_weight(value: u64) -> Result<u64> {
+    if !(CGROUP_WEIGHT_MIN..=CGROUP_WEIGHT_MAX).contains(&value) {
+        return Err(Error::ConfigureError {
+            msg: format!(
+                "weight {value} is out of range [{CGROUP_WEIGHT_MIN}, {CGROUP_WEIGHT_MAX}]"
+            ),
+        });
+    }
+    Ok(value)
+}
+
+fn parse_cpu_quota(value: &str) -> Result<u64> {
+    let percent = match value.trim().strip_suffix('%') {
+        Some(v) => v.trim().parse::<u64>().map_err(|_| Error::ConfigureError {
+            msg: format!("invalid CPUQuota: {value}"),
+        })?,
+        None => {
+            return Err(Error::ConfigureError {
+                msg: format!("CPUQuota must end with '%': {value}"),
+            })
+        }
+    };
+
+    if percent == 0 {
+        return Err(Error::ConfigureError {
+            msg: "CPUQuota must be greater than 0%".to_string(),
+        });
+    }
+
+    // 1% means 10ms cpu time per second
+    Ok(percent * 10000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{slice_cg_path, slice_parent, CgroupContext, CGROUP_LIMIT_MAX};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_cgroup_context() {
+        let ctx = CgroupContext::default();
+        assert!(ctx.is_empty());
+
+        assert!(ctx.set_memory_max(Some("1G")).is_ok());
+        assert_eq!(ctx.memory_max(), Some(1024 * 1024 * 1024));
+        assert!(ctx.set_memory_high(Some("infinity")).is_ok());
+        assert_eq!(ctx.memory_high(), Some(CGROUP_LIMIT_MAX));
+        assert!(ctx.set_memory_max(Some("foo")).is_err());
+
+        assert!(ctx.set_cpu_weight(Some(100)).is_ok());
+        assert_eq!(ctx.cpu_weight(), Some(100));
+        assert!(ctx.set_cpu_weight(Some(0)).is_err());
+        assert!(ctx.set_io_weight(Some(10001)).is_err());
+
+        assert!(ctx.set_cpu_quota(Some("20%")).is_ok());
+        assert_eq!(ctx.cpu_quota_per_sec_usec(), Some(200000));
+        assert!(ctx.set_cpu_quota(Some("200%")).is_ok());
+        assert_eq!(ctx.cpu_quota_per_sec_usec(), Some(2000000));
+        assert!(ctx.set_cpu_quota(Some("20")).is_err());
+        assert!(ctx.set_cpu_quota(Some("0%")).is_err());
+
+        assert!(ctx.set_tasks_max(Some("512")).is_ok());
+        assert_eq!(ctx.tasks_max(), Some(512));
+        assert!(!ctx.is_empty());
+
+        assert!(ctx.set_memory_max(None).is_ok());
+        assert_eq!(ctx.memory_max(), None);
+
+        assert!(ctx.set_slice(Some("foo-bar.slice")).is_ok());
+        assert_eq!(ctx.slice(), Some("foo-bar.slice".to_string()));
+        assert!(ctx.set_slice(Some("foo.service")).is_err());
+        assert!(ctx.set_slice(Some("foo--bar.slice")).is_err());
+    }
+
+    #[test]
+    fn test_cgroup_context_set_property() {
+        let ctx = CgroupContext::default();
+        assert!(ctx.set_property("MemoryMax", "1M").is_ok());
+        assert_eq!(ctx.memory_max(), Some(1024 * 1024));
+        assert!(ctx.set_property("CPUWeight", "200").is_ok());
+        assert_eq!(ctx.cpu_weight(), Some(200));
+        assert!(ctx.set_property("CPUWeight", "foo").is_err());
+        assert!(ctx.set_property("IOWeight", "0").is_err());
+        assert!(ctx.set_property("TasksMax", "infinity").is_ok());
+        assert_eq!(ctx.tasks_max(), Some(CGROUP_LIMIT_MAX));
+        assert!(ctx.set_property("Slice", "foo.slice").is_err());
+        assert_eq!(ctx.slice(), None);
+    }
+
+    #[test]
+    fn test_slice_path() {
+        assert_eq!(slice_parent("a-b-c.slice"), Some("a-b.slice".to_string()));
+        assert_eq!(slice_parent("a.slice"), Some("-.slice".to_string()));
+        assert_eq!(slice_parent("-.slice"), None);
+        assert_eq!(slice_parent("a.service"), None);
+
+        assert_eq!(
+            slice_cg_path("a-b-c.slice"),
+            PathBuf::from("a.slice/a-b.slice/a-b-c.slice")
+        );
+        assert_eq!(slice_cg_path("system.slice"), PathBuf::from("system.slice"));
+        assert_eq!(slice_cg_path("-.slice"), PathBuf::from(""));
+    }
+}
@@ -114,6 +114,12 @@ pub enum UnitType {
     UnitTarget,
     UnitSocket,
     UnitMount,
+    UnitSlice,
+    UnitTimer,
+    UnitPath,
+    UnitDevice,
+    UnitSwap,
+    UnitAutomount,
     UnitTypeMax,
     UnitTypeInvalid,
     UnitTypeErrnoMax,
@@ -127,6 +133,12 @@ impl UnitType {
             UnitType::UnitTarget,
             UnitType::UnitSocket,
             UnitType::UnitMount,
+            UnitType::UnitSlice,
+            UnitType::UnitTimer,
+            UnitType::UnitPath,
+            UnitType::UnitDevice,
+            UnitType::UnitSwap,
+            UnitType::UnitAutomount,
         ]
         .iter()
         .copied()
@@ -142,6 +154,12 @@ impl FromStr for UnitType {
             "target" => UnitType::UnitTarget,
             "socket" => UnitType::UnitSocket,
             "mount" => UnitType::UnitMount,
+            "slice" => UnitType::UnitSlice,
+            "timer" => UnitType::UnitTimer,
+            "path" => UnitType::UnitPath,
+            "device" => UnitType::UnitDevice,
+            "swap" => UnitType::UnitSwap,
+            "automount" => UnitType::UnitAutomount,
             _ => UnitType::UnitTypeInvalid,
         };
         Ok(ret)
@@ -155,6 +173,12 @@ impl From<UnitType> for String {
             UnitType::UnitTarget => "target".into(),
             UnitType::UnitSocket => "socket".into(),
             UnitType::UnitMount => "mount".into(),
+            UnitType::UnitSlice => "slice".into(),
+            UnitType::UnitTimer => "timer".into(),
+            UnitType::UnitPath => "path".into(),
+            UnitType::UnitDevice => "device".into(),
+            UnitType::UnitSwap => "swap".into(),
+            UnitType::UnitAutomount => "automount".into(),
             UnitType::UnitTypeMax => null_str!(""),
             UnitType::UnitTypeInvalid => null_str!(""),
             UnitType::UnitTypeErrnoMax => null_str!(""),
@@ -170,6 +194,12 @@ impl TryFrom<u32> for UnitType {
             1 => Ok(UnitType::UnitTarget),
             2 => Ok(UnitType::UnitSocket),
             3 => Ok(UnitType::UnitMount),
+            4 => Ok(UnitType::UnitSlice),
+            5 => Ok(UnitType::UnitTimer),
+            6 => Ok(UnitType::UnitPath),
+            7 => Ok(UnitType::UnitDevice),
+            8 => Ok(UnitType::UnitSwap),
+            9 => Ok(UnitType::UnitAutomount),
             v => Err(format!("input {v} is invalid")),
         }
     }
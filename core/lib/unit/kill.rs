@@ -41,6 +41,17 @@ impl KillOperation {
     }
 }
 
+/// the processes of the unit that the signal is sent to on request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KillWhom {
+    /// only the main process
+    Main,
+    /// only the control process
+    Control,
+    /// all the processes in the cgroup of the unit
+    All,
+}
+
 /// the method to kill the process
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum KillMode {
@@ -12,11 +12,13 @@
 
 //!
 pub use base::{SubUnit, UnitBase};
+pub use cgroup::{slice_cg_path, slice_parent, CgroupContext, CGROUP_LIMIT_MAX, CGROUP_PROPERTIES};
 pub use deps::{UnitDependencyMask, UnitRelationAtom, UnitRelations, UnitType};
-pub use kill::{KillContext, KillMode, KillOperation};
-pub use state::{UnitActiveState, UnitNotifyFlags, UnitStatus};
+pub use kill::{KillContext, KillMode, KillOperation, KillWhom};
+pub use state::{UnitActiveState, UnitNotifyFlags};
 pub use umif::{UmIf, UnitManagerObj, UnitMngUtil};
 mod base;
+mod cgroup;
 mod deps;
 mod kill;
 mod state;
@@ -92,6 +92,11 @@ pub trait UmIf {
         false
     }
 
+    /// check if there is a job which will start the unit, like start, restart
+    fn has_start_like_job(&self, _name: &str) -> bool {
+        false
+    }
+
     /// check the unit that will be triggered by {name} is in active or activating state
     fn relation_active_or_pending(&self, _name: &str) -> bool {
         false
@@ -101,6 +106,11 @@ pub trait UmIf {
         Ok(())
     }
 
+    /// stopping a unit by pushing it to job queue
+    fn unit_stop_by_job(&self, _name: &str) -> Result<()> {
+        Ok(())
+    }
+
     /// starting a unit by calling unit.start()
     fn unit_start_directly(&self, _name: &str) -> Result<()> {
         Ok(())
@@ -154,6 +164,16 @@ pub trait UmIf {
         Vec::new()
     }
 
+    /// check whether the log collector is enabled
+    fn log_collector_enabled(&self) -> bool {
+        false
+    }
+
+    /// return the fds that trigger the unit {name}, along with the socket unit they belong to;
+    fn collect_named_socket_fds(&self, _name: &str) -> Vec<(String, i32)> {
+        Vec::new()
+    }
+
     /// get all unit in sysmaster
     fn units_get_all(&self, _unit_type: Option<UnitType>) -> Vec<String> {
         Vec::new()
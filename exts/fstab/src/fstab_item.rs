@@ -16,6 +16,21 @@ use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 
+/// the file systems that depend on the network
+const NETWORK_FS_TYPES: [&str; 11] = [
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "sshfs",
+    "fuse.sshfs",
+    "ncpfs",
+    "ceph",
+    "glusterfs",
+    "davfs",
+];
+
 /// FSTabItem structure
 pub struct FSTabItem {
     /// * device_spec: mount device
@@ -52,6 +67,31 @@ impl FSTabItem {
             state: 0,
         }
     }
+
+    /// whether the option is configured, like "nofail"
+    pub fn has_option(&self, name: &str) -> bool {
+        self.options.split(',').any(|o| o == name)
+    }
+
+    /// the values of the option configured as "name=value", the option can be repeated
+    pub fn option_values(&self, name: &str) -> Vec<String> {
+        self.options
+            .split(',')
+            .filter_map(|o| o.split_once('='))
+            .filter(|(k, _)| *k == name)
+            .map(|(_, v)| v.to_string())
+            .collect()
+    }
+
+    /// the last value of the option configured as "name=value"
+    pub fn option_value(&self, name: &str) -> Option<String> {
+        self.option_values(name).pop()
+    }
+
+    /// whether the file system is mounted over the network
+    pub fn is_network(&self) -> bool {
+        self.has_option("_netdev") || NETWORK_FS_TYPES.contains(&self.fs_type.as_str())
+    }
 }
 
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
@@ -89,7 +129,7 @@ mod tests {
     use std::io::prelude::*;
     use std::path::Path;
 
-    use super::parse;
+    use super::{parse, FSTabItem};
 
     #[test]
     fn it_works() {
@@ -157,4 +197,34 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_options() {
+        let item = FSTabItem::new(vec![
+            "server:/export",
+            "/mnt/nfs",
+            "nfs",
+            "rw,nofail,x-systemd.requires=a.service,x-systemd.requires=/srv,x-systemd.device-timeout=10s",
+            "0",
+            "0",
+        ]);
+        assert!(item.has_option("nofail"));
+        assert!(!item.has_option("noauto"));
+        assert!(!item.has_option("x-systemd.requires"));
+        assert_eq!(
+            item.option_values("x-systemd.requires"),
+            vec!["a.service".to_string(), "/srv".to_string()]
+        );
+        assert_eq!(
+            item.option_value("x-systemd.device-timeout"),
+            Some("10s".to_string())
+        );
+        assert!(item.is_network());
+
+        let item = FSTabItem::new(vec!["/dev/sdb1", "/data", "ext4", "_netdev", "0", "2"]);
+        assert!(item.is_network());
+        let item = FSTabItem::new(vec!["/dev/sdb1", "/data", "ext4", "defaults", "0", "2"]);
+        assert!(!item.is_network());
+        assert_eq!(item.option_value("pri"), None);
+    }
 }
@@ -0,0 +1,417 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! generator writes the mount and swap units for the entries of /etc/fstab, so that
+//! the file systems are mounted by sysmaster with the dependencies between the units.
+
+use crate::fstab_item::FSTabItem;
+use basic::special::{LOCAL_FS_TARGET, NETWORK_ONLINE_TARGET, REMOTE_FS_TARGET, SWAP_TARGET};
+use basic::time_util::{parse_timespan, USEC_PER_SEC};
+use basic::unit_name::unit_name_from_path;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::os::unix::fs::symlink;
+use std::path::Path;
+
+const DEVICE_TIMEOUT_DROPIN: &str = "50-device-timeout.conf";
+
+/// the content of a generated unit file
+struct UnitFile {
+    content: String,
+}
+
+impl UnitFile {
+    fn new() -> Self {
+        UnitFile {
+            content: String::from("# Automatically generated by fstab\n"),
+        }
+    }
+
+    fn section(&mut self, name: &str) {
+        self.content += &format!("\n[{name}]\n");
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        // the unit file is toml, and '%' is the prefix of the specifiers
+        let value = value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('%', "%%");
+        self.content += &format!("{key}=\"{value}\"\n");
+    }
+
+    fn set_list(&mut self, key: &str, values: &[String]) {
+        if !values.is_empty() {
+            self.set(key, &values.join(";"));
+        }
+    }
+
+    fn set_number(&mut self, key: &str, value: impl std::fmt::Display) {
+        self.content += &format!("{key}={value}\n");
+    }
+
+    fn write(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, &self.content)
+    }
+}
+
+/// the generator of the units for /etc/fstab, the units are written to dest
+pub struct Generator<'a> {
+    dest: &'a Path,
+    units: HashSet<String>,
+}
+
+impl<'a> Generator<'a> {
+    /// create
+    pub fn new(dest: &'a Path) -> Self {
+        Generator {
+            dest,
+            units: HashSet::new(),
+        }
+    }
+
+    /// generate the units for all the entries, the bad entries are skipped
+    pub fn generate(&mut self, fstab_items: &[FSTabItem]) -> io::Result<()> {
+        fs::create_dir_all(self.dest)?;
+
+        let mut ret = Ok(());
+        for fstab_item in fstab_items {
+            let r = match fstab_item.fs_type.as_str() {
+                "swap" => self.add_swap(fstab_item),
+                _ => self.add_mount(fstab_item),
+            };
+            if let Err(e) = r {
+                log::error!(
+                    "Failed to generate unit for {}: {}",
+                    fstab_item.device_spec,
+                    e
+                );
+                ret = Err(e);
+            }
+        }
+        ret
+    }
+
+    fn add_mount(&mut self, fstab_item: &FSTabItem) -> io::Result<()> {
+        let mount_where = &fstab_item.mount_point;
+        if !mount_where.starts_with('/') {
+            log::warn!("Mount point {} is not a valid path, ignoring.", mount_where);
+            return Ok(());
+        }
+        // The root file system is mounted before sysmaster starts.
+        if mount_where == "/" {
+            log::debug!("Skipping the root file system.");
+            return Ok(());
+        }
+
+        let name = unit_name_from_path(mount_where, ".mount");
+        if !self.units.insert(name.clone()) {
+            log::warn!("Duplicated mount point {}, ignoring.", mount_where);
+            return Ok(());
+        }
+
+        let nofail = fstab_item.has_option("nofail");
+        let noauto = fstab_item.has_option("noauto");
+        let automount = fstab_item.has_option("x-systemd.automount");
+        let target = if fstab_item.is_network() {
+            REMOTE_FS_TARGET
+        } else {
+            LOCAL_FS_TARGET
+        };
+
+        let mut wants = Vec::new();
+        let mut requires = Vec::new();
+        let mut binds_to = Vec::new();
+        let mut after = Vec::new();
+        let mut before = Vec::new();
+        if fstab_item.is_network() {
+            wants.push(NETWORK_ONLINE_TARGET.to_string());
+            after.push(NETWORK_ONLINE_TARGET.to_string());
+        }
+        // The mount unit doesn't depend on the device by itself.
+        if let Some(device) = self.add_device(fstab_item)? {
+            binds_to.push(device.clone());
+            after.push(device);
+        }
+        for unit in requires_units(fstab_item) {
+            requires.push(unit.clone());
+            after.push(unit);
+        }
+        // The automount unit is ordered before the target instead.
+        if !nofail && !automount {
+            before.push(target.to_string());
+        }
+
+        let mut unit = UnitFile::new();
+        unit.section("Unit");
+        unit.set("Description", &format!("Mount {mount_where}"));
+        unit.set("Documentation", "man:fstab(5)");
+        unit.set_list("Wants", &wants);
+        unit.set_list("Requires", &requires);
+        unit.set_list("BindsTo", &binds_to);
+        unit.set_list("After", &after);
+        unit.set_list("Before", &before);
+        unit.section("Mount");
+        unit.set("What", &fstab_item.device_spec);
+        unit.set("Where", mount_where);
+        if fstab_item.fs_type != "auto" {
+            unit.set("Type", &fstab_item.fs_type);
+        }
+        unit.set("Options", &fstab_item.options);
+        unit.write(&self.dest.join(&name))?;
+
+        if automount {
+            self.add_automount(fstab_item, target, nofail)?;
+        } else if !noauto {
+            self.add_link(target, &name, nofail)?;
+        }
+        Ok(())
+    }
+
+    fn add_automount(
+        &mut self,
+        fstab_item: &FSTabItem,
+        target: &str,
+        nofail: bool,
+    ) -> io::Result<()> {
+        let mount_where = &fstab_item.mount_point;
+        let name = unit_name_from_path(mount_where, ".automount");
+
+        let mut unit = UnitFile::new();
+        unit.section("Unit");
+        unit.set("Description", &format!("Automount {mount_where}"));
+        unit.set("Documentation", "man:fstab(5)");
+        if !nofail {
+            unit.set("Before", target);
+        }
+        unit.section("Automount");
+        unit.set("Where", mount_where);
+        if let Some(timeout) = option_timeout_sec(fstab_item, "x-systemd.idle-timeout") {
+            unit.set_number("TimeoutIdleSec", timeout);
+        }
+        unit.write(&self.dest.join(&name))?;
+
+        // the mount unit is started by the automount unit on access
+        if !fstab_item.has_option("noauto") {
+            self.add_link(target, &name, nofail)?;
+        }
+        Ok(())
+    }
+
+    fn add_swap(&mut self, fstab_item: &FSTabItem) -> io::Result<()> {
+        let what = &fstab_item.device_spec;
+        if !what.starts_with('/') {
+            log::warn!("Swap {} is not a valid path, ignoring.", what);
+            return Ok(());
+        }
+
+        let name = unit_name_from_path(what, ".swap");
+        if !self.units.insert(name.clone()) {
+            log::warn!("Duplicated swap {}, ignoring.", what);
+            return Ok(());
+        }
+
+        // The swap unit binds to the device by itself.
+        self.add_device(fstab_item)?;
+
+        let mut requires = Vec::new();
+        let mut after = Vec::new();
+        for unit in requires_units(fstab_item) {
+            requires.push(unit.clone());
+            after.push(unit);
+        }
+
+        // "pri=" is passed as Priority, the others are passed to swapon as they are.
+        let options: Vec<&str> = fstab_item
+            .options
+            .split(',')
+            .filter(|o| !o.is_empty() && !o.starts_with("pri=") && !o.starts_with("x-systemd."))
+            .collect();
+
+        let mut unit = UnitFile::new();
+        unit.section("Unit");
+        unit.set("Description", &format!("Swap {what}"));
+        unit.set("Documentation", "man:fstab(5)");
+        unit.set_list("Requires", &requires);
+        unit.set_list("After", &after);
+        unit.section("Swap");
+        unit.set("What", what);
+        if let Some(priority) = fstab_item.option_value("pri") {
+            match priority.parse::<i32>() {
+                Ok(priority) => unit.set_number("Priority", priority),
+                Err(_) => log::warn!("Invalid swap priority {}, ignoring.", priority),
+            }
+        }
+        unit.set("Options", &options.join(","));
+        unit.write(&self.dest.join(&name))?;
+
+        if !fstab_item.has_option("noauto") {
+            self.add_link(SWAP_TARGET, &name, fstab_item.has_option("nofail"))?;
+        }
+        Ok(())
+    }
+
+    /// the device unit of the block device, with the timeout of waiting for the device
+    fn add_device(&self, fstab_item: &FSTabItem) -> io::Result<Option<String>> {
+        let what = &fstab_item.device_spec;
+        if !what.starts_with("/dev/") {
+            return Ok(None);
+        }
+
+        let device = unit_name_from_path(what, ".device");
+        if let Some(timeout) = option_timeout_sec(fstab_item, "x-systemd.device-timeout") {
+            let dir = self.dest.join(format!("{device}.d"));
+            fs::create_dir_all(&dir)?;
+
+            let mut unit = UnitFile::new();
+            unit.section("Unit");
+            unit.set_number("JobTimeoutSec", timeout);
+            unit.write(&dir.join(DEVICE_TIMEOUT_DROPIN))?;
+        }
+        Ok(Some(device))
+    }
+
+    /// pull the unit in by the target, nofail only wants the unit
+    fn add_link(&self, target: &str, name: &str, nofail: bool) -> io::Result<()> {
+        let suffix = if nofail { "wants" } else { "requires" };
+        let dir = self.dest.join(format!("{target}.{suffix}"));
+        fs::create_dir_all(&dir)?;
+
+        let link = dir.join(name);
+        if link.is_symlink() {
+            fs::remove_file(&link)?;
+        }
+        symlink(Path::new("..").join(name), link)
+    }
+}
+
+/// the units required by "x-systemd.requires=", the absolute path means the mount unit of it
+fn requires_units(fstab_item: &FSTabItem) -> Vec<String> {
+    fstab_item
+        .option_values("x-systemd.requires")
+        .into_iter()
+        .map(|unit| match unit.starts_with('/') {
+            true => unit_name_from_path(&unit, ".mount"),
+            false => unit,
+        })
+        .collect()
+}
+
+/// the timeout option in seconds, "0" means no timeout
+fn option_timeout_sec(fstab_item: &FSTabItem, name: &str) -> Option<u64> {
+    let value = fstab_item.option_value(name)?;
+    match parse_timespan(&value) {
+        Ok(u64::MAX) => Some(0),
+        // round up, the timeout shorter than 1s is not discarded
+        Ok(usec) => Some(usec / USEC_PER_SEC + u64::from(usec % USEC_PER_SEC != 0)),
+        Err(_) => {
+            log::warn!("Invalid {}={}, ignoring.", name, value);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Generator;
+    use crate::fstab_item::FSTabItem;
+    use std::fs;
+    use std::path::Path;
+
+    fn read(dest: &Path, name: &str) -> String {
+        fs::read_to_string(dest.join(name)).unwrap()
+    }
+
+    #[test]
+    fn test_generate() {
+        let dest = Path::new("/tmp/fstab_generator_test");
+        let _ = fs::remove_dir_all(dest);
+
+        let items = vec![
+            FSTabItem::new(vec!["/dev/sda1", "/", "ext4", "defaults", "1", "1"]),
+            FSTabItem::new(vec![
+                "/dev/sdb1",
+                "/data",
+                "ext4",
+                "defaults,x-systemd.requires=/srv,x-systemd.device-timeout=1min",
+                "0",
+                "2",
+            ]),
+            FSTabItem::new(vec![
+                "server:/export",
+                "/mnt/nfs",
+                "nfs",
+                "nofail",
+                "0",
+                "0",
+            ]),
+            FSTabItem::new(vec![
+                "/dev/sdc1",
+                "/mnt/auto",
+                "xfs",
+                "x-systemd.automount,x-systemd.idle-timeout=30",
+                "0",
+                "0",
+            ]),
+            FSTabItem::new(vec!["/dev/sdd1", "/mnt/manual", "auto", "noauto", "0", "0"]),
+            FSTabItem::new(vec![
+                "/dev/sda2",
+                "none",
+                "swap",
+                "pri=10,discard",
+                "0",
+                "0",
+            ]),
+        ];
+        Generator::new(dest).generate(&items).unwrap();
+
+        assert!(!dest.join("-.mount").exists());
+
+        let data = read(dest, "data.mount");
+        assert!(data.contains("Requires=\"srv.mount\"\n"));
+        assert!(data.contains("BindsTo=\"dev-sdb1.device\"\n"));
+        assert!(data.contains("After=\"dev-sdb1.device;srv.mount\"\n"));
+        assert!(data.contains("Before=\"local-fs.target\"\n"));
+        assert!(data.contains("What=\"/dev/sdb1\"\nWhere=\"/data\"\nType=\"ext4\"\n"));
+        assert!(dest.join("local-fs.target.requires/data.mount").exists());
+        assert!(
+            read(dest, "dev-sdb1.device.d/50-device-timeout.conf").contains("JobTimeoutSec=60\n")
+        );
+
+        let nfs = read(dest, "mnt-nfs.mount");
+        assert!(nfs.contains("Wants=\"network-online.target\"\n"));
+        assert!(!nfs.contains("Before="));
+        assert!(dest.join("remote-fs.target.wants/mnt-nfs.mount").exists());
+
+        let auto = read(dest, "mnt-auto.automount");
+        assert!(auto.contains("Where=\"/mnt/auto\"\nTimeoutIdleSec=30\n"));
+        assert!(dest
+            .join("local-fs.target.requires/mnt-auto.automount")
+            .exists());
+        assert!(!dest
+            .join("local-fs.target.requires/mnt-auto.mount")
+            .exists());
+        assert!(!read(dest, "mnt-auto.mount").contains("Before="));
+
+        assert!(!read(dest, "mnt-manual.mount").contains("Type="));
+        assert!(!dest
+            .join("local-fs.target.requires/mnt-manual.mount")
+            .exists());
+
+        let swap = read(dest, "dev-sda2.swap");
+        assert!(swap.contains("What=\"/dev/sda2\"\nPriority=10\nOptions=\"discard\"\n"));
+        assert!(dest.join("swap.target.requires/dev-sda2.swap").exists());
+
+        fs::remove_dir_all(dest).unwrap();
+    }
+}
@@ -16,16 +16,23 @@
 //! 2. According to the configuration order of /etc/fstab, try to mount in turn.
 //! 3. If the corresponding device directory can be found, it will be mounted directly.
 //! 4. If the relevant directory cannot be found, create an inotify detection and mount it after the device is ready.
+//! # Generator
+//! When started with the output directories as arguments, like "fstab normal-dir [early-dir late-dir]",
+//! fstab doesn't mount anything, but writes the mount and swap units for /etc/fstab to normal-dir instead,
+//! so that the other units can be ordered after the file systems they need.
 //! # limit
 //! 1. The current device only supports configuration as full directory or UUID.
 
+use basic::logger;
 use inotify::{EventMask, Inotify, WatchMask};
 use std::collections::HashSet;
 use std::path::Path;
-use std::process::Command;
+use std::process::{self, Command};
 
 pub mod fstab_item;
+mod generator;
 use fstab_item::FSTabItem;
+use generator::Generator;
 
 const MOUNT_BIN: &str = "/usr/bin/mount";
 const SWAP_BIN: &str = "/usr/sbin/swapon";
@@ -132,6 +139,19 @@ fn watch_devices(fstab_items: &Vec<FSTabItem>) -> (Inotify, HashSet<String>) {
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() > 1 {
+        logger::init_log_to_console("fstab", log::LevelFilter::Info);
+        let fstab_items: Vec<FSTabItem> = fstab_item::parse(FSTAB_PATH);
+        if Generator::new(Path::new(&args[1]))
+            .generate(&fstab_items)
+            .is_err()
+        {
+            process::exit(1);
+        }
+        return;
+    }
+
     let mut fstab_items: Vec<FSTabItem> = fstab_item::parse(FSTAB_PATH);
 
     // inotify: monitor, watch_set: what we care.
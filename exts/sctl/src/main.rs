@@ -12,20 +12,27 @@
 
 //!
 
-use clap::Parser;
-use cmdproto::{
-    error::ERROR_CODE_MASK_PRINT_STDOUT,
-    proto::{
-        abi::{sys_comm, unit_comm, CommandRequest},
-        mngr_comm, unit_file, ProstClientStream,
-    },
+use basic::log_store::{self, LogFollower, LOG_STORE_DIR};
+use clap::{ArgEnum, Parser};
+use cmdproto::proto::{
+    abi::{command_response::ResponseData, kill_param, sys_comm, unit_comm, CommandRequest},
+    job_comm, mngr_comm, unit_file, ProstClientStream, SCTL_SOCKET,
 };
+use nix::libc;
+use nix::sys::signal::Signal;
+use output::Output;
 use std::io::Write;
 use std::{
-    net::{SocketAddr, TcpStream},
+    os::unix::net::UnixStream,
+    path::Path,
     process::{ExitCode, Termination},
+    str::FromStr,
+    thread,
+    time::Duration,
 };
 
+mod output;
+
 /// parse program arguments
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -37,6 +44,10 @@ struct Args {
     /// Number of times
     #[clap(short, long, default_value_t = 1)]
     count: u8,
+
+    /// Print the result of the query commands as a table or in json
+    #[clap(long, global = true, arg_enum, default_value = "table")]
+    output: Output,
 }
 
 #[derive(Parser, Debug)]
@@ -73,9 +84,107 @@ enum SubCmd {
         units: Vec<String>,
     },
 
+    /// [units ...] show the properties of the units
+    #[clap(display_order = 6)]
+    Show {
+        #[clap(required = true)]
+        units: Vec<String>,
+
+        /// Show only the given properties, separated by ',' or by repeating the option
+        #[clap(short, long, use_value_delimiter = true)]
+        property: Vec<String>,
+    },
+
+    /// [unit] change the properties of the unit at runtime and save them in the dropins
+    #[clap(display_order = 7)]
+    SetProperty {
+        unit: String,
+
+        /// The properties to set, in the form of KEY=VALUE
+        #[clap(required = true, parse(try_from_str = parse_property))]
+        properties: Vec<(String, String)>,
+
+        /// Save the properties under /run, they are lost after reboot
+        #[clap(long)]
+        runtime: bool,
+    },
+
+    /// [units ...] send the signal to the processes of the units
+    #[clap(display_order = 8)]
+    Kill {
+        #[clap(required = true)]
+        units: Vec<String>,
+
+        /// The signal to send, by name like TERM, SIGTERM and RTMIN+1, or by number
+        #[clap(short, long, default_value = "SIGTERM", parse(try_from_str = parse_signal))]
+        signal: i32,
+
+        /// The processes of the units to send the signal to
+        #[clap(long, arg_enum, default_value = "all")]
+        kill_whom: KillWhom,
+
+        /// Queue the signal with the value by sigqueue, only the main and control processes
+        /// receive it then
+        #[clap(long)]
+        value: Option<i32>,
+    },
+
+    /// [units ...] reset the failed state of the units
+    #[clap(display_order = 9)]
+    ResetFailed {
+        #[clap(required = true)]
+        units: Vec<String>,
+    },
+
+    /// [unit] run the command in a transient service
+    Run {
+        /// Name of the transient service, run-<pid>.service by default
+        #[clap(long)]
+        unit: Option<String>,
+
+        /// Description of the transient service, the command line by default
+        #[clap(long)]
+        description: Option<String>,
+
+        /// Keep the service after the command exits, until it is stopped
+        #[clap(long)]
+        remain_after_exit: bool,
+
+        /// Set the property of the service, in the form of KEY=VALUE
+        #[clap(short, long, parse(try_from_str = parse_property))]
+        property: Vec<(String, String)>,
+
+        /// The command to run and its arguments
+        #[clap(required = true, last = true, parse(try_from_str = parse_exec_arg))]
+        command: Vec<String>,
+    },
+
     /// [manager] list all units
     ListUnits {},
 
+    /// [job] list the jobs in progress
+    ListJobs {},
+
+    /// [job] cancel the waiting or running jobs, all of them are cancelled if no job id is given
+    Cancel { job_ids: Vec<String> },
+
+    /// [manager] show the state changes of the units and the jobs added or removed live
+    Monitor {},
+
+    /// [unit] show the logs of the unit captured by the log collector
+    Log {
+        #[clap(required = true)]
+        unit: String,
+
+        /// Keep printing the new logs
+        #[clap(short, long, required = false)]
+        follow: bool,
+
+        /// Number of the latest lines to show, all the lines are shown by default
+        #[clap(short = 'n', long)]
+        lines: Option<usize>,
+    },
+
     /// [system] shutdown the system
     Shutdown {
         #[clap(short, required = false)]
@@ -131,6 +240,98 @@ enum SubCmd {
     },
 }
 
+/// the processes of the unit that the signal is sent to
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum KillWhom {
+    /// the main process
+    Main,
+    /// the control process
+    Control,
+    /// all the processes in the cgroup of the unit
+    All,
+}
+
+impl From<KillWhom> for kill_param::Whom {
+    fn from(whom: KillWhom) -> Self {
+        match whom {
+            KillWhom::Main => kill_param::Whom::Main,
+            KillWhom::Control => kill_param::Whom::Control,
+            KillWhom::All => kill_param::Whom::All,
+        }
+    }
+}
+
+/// parse the signal name with or without the SIG prefix, RTMIN+N and RTMAX-N are accepted
+/// for the real-time signals
+fn parse_signal(s: &str) -> std::result::Result<i32, String> {
+    let signo = match s.parse::<i32>() {
+        Ok(signo) => Some(signo),
+        Err(_) => {
+            let name = s.strip_prefix("SIG").unwrap_or(s);
+            if let Some(offset) = name.strip_prefix("RTMIN") {
+                parse_rt_offset(offset, '+').map(|n| libc::SIGRTMIN() + n)
+            } else if let Some(offset) = name.strip_prefix("RTMAX") {
+                parse_rt_offset(offset, '-').map(|n| libc::SIGRTMAX() - n)
+            } else {
+                Signal::from_str(&format!("SIG{name}"))
+                    .ok()
+                    .map(|sig| sig as i32)
+            }
+        }
+    };
+
+    match signo {
+        Some(signo) if signo > 0 && signo <= libc::SIGRTMAX() => Ok(signo),
+        _ => Err(format!("invalid signal '{s}'")),
+    }
+}
+
+fn parse_rt_offset(offset: &str, sign: char) -> Option<i32> {
+    if offset.is_empty() {
+        return Some(0);
+    }
+    offset.strip_prefix(sign)?.parse::<i32>().ok()
+}
+
+fn parse_property(s: &str) -> std::result::Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("invalid property '{s}', KEY=VALUE is expected")),
+    }
+}
+
+/// the arguments with whitespaces are quoted in the ExecStart, so the quote and the
+/// separator of the commands can not be used
+fn parse_exec_arg(s: &str) -> std::result::Result<String, String> {
+    if s.is_empty() || s.contains('\'') || s.contains(';') {
+        return Err(format!("unsupported argument '{s}'"));
+    }
+    if s.contains(char::is_whitespace) {
+        return Ok(format!("'{s}'"));
+    }
+    Ok(s.to_string())
+}
+
+/// the properties of the transient service that runs the command
+fn transient_properties(
+    command: Vec<String>,
+    description: Option<String>,
+    remain_after_exit: bool,
+    mut property: Vec<(String, String)>,
+) -> Vec<(String, String)> {
+    let exec_start = command.join(" ");
+    let description = description.unwrap_or_else(|| exec_start.clone());
+    let mut properties = vec![
+        ("Description".to_string(), description),
+        ("ExecStart".to_string(), exec_start),
+    ];
+    if remain_after_exit {
+        properties.push(("RemainAfterExit".to_string(), "true".to_string()));
+    }
+    properties.append(&mut property);
+    properties
+}
+
 /// Generate CommandRequest based on parsed args
 /// clap Args => protobuf based CommandRequest
 fn generate_command_request(args: Args) -> Option<CommandRequest> {
@@ -144,6 +345,25 @@ fn generate_command_request(args: Args) -> Option<CommandRequest> {
 
         SubCmd::Reload { units } => CommandRequest::new_unitcomm(unit_comm::Action::Reload, units),
 
+        SubCmd::Show { units, .. } => CommandRequest::new_unitcomm(unit_comm::Action::Show, units),
+
+        SubCmd::ResetFailed { units } => {
+            CommandRequest::new_unitcomm(unit_comm::Action::ResetFailed, units)
+        }
+
+        SubCmd::SetProperty {
+            unit,
+            properties,
+            runtime,
+        } => CommandRequest::new_set_property(unit, properties, runtime),
+
+        SubCmd::Kill {
+            units,
+            signal,
+            kill_whom,
+            value,
+        } => CommandRequest::new_kill(units, signal, kill_whom.into(), value),
+
         SubCmd::Mask { unit_file } => {
             CommandRequest::new_unitfile(unit_file::Action::Mask, unit_file)
         }
@@ -174,10 +394,111 @@ fn generate_command_request(args: Args) -> Option<CommandRequest> {
         SubCmd::DaemonReexec {} => CommandRequest::new_mngrcomm(mngr_comm::Action::Reexec),
 
         SubCmd::ListUnits {} => CommandRequest::new_mngrcomm(mngr_comm::Action::Listunits),
+
+        SubCmd::ListJobs {} => CommandRequest::new_jobcomm(job_comm::Action::List, vec![]),
+
+        SubCmd::Cancel { job_ids } => {
+            CommandRequest::new_jobcomm(job_comm::Action::Cancel, job_ids)
+        }
+
+        SubCmd::Run {
+            unit,
+            description,
+            remain_after_exit,
+            property,
+            command,
+        } => CommandRequest::new_transient(
+            unit.unwrap_or_else(|| format!("run-{}.service", std::process::id())),
+            transient_properties(command, description, remain_after_exit, property),
+        ),
+
+        // the logs are read from the log store directly
+        SubCmd::Log { .. } => return None,
+
+        // the events are received on a subscription
+        SubCmd::Monitor {} => return None,
     };
     Some(command_request)
 }
 
+/// print the logs of the unit from the log store, and keep printing the new ones if follow is set
+fn show_log(unit: &str, follow: bool, lines: Option<usize>) -> Result {
+    let unit = if unit.contains('.') {
+        unit.to_string()
+    } else {
+        format!("{unit}.service")
+    };
+    let dir = Path::new(LOG_STORE_DIR);
+
+    // start following before reading the old logs, a line written in between may be
+    // shown twice, but never missed
+    let mut follower = LogFollower::new(dir, &unit);
+    match log_store::tail(dir, &unit, lines) {
+        Ok(lines) => {
+            for line in lines {
+                let _ = writeln!(std::io::stdout(), "{line}");
+            }
+        }
+        Err(e) => {
+            return Result::Failure(format!("Failed to read the logs of {unit}: {e}"), 1);
+        }
+    }
+
+    if !follow {
+        return Result::OK;
+    }
+
+    loop {
+        match follower.read_lines() {
+            Ok(lines) => {
+                for line in lines {
+                    let _ = writeln!(std::io::stdout(), "{line}");
+                }
+            }
+            Err(e) => {
+                return Result::Failure(format!("Failed to read the logs of {unit}: {e}"), 1);
+            }
+        }
+        thread::sleep(Duration::from_millis(200));
+    }
+}
+
+/// subscribe the changes of the units and jobs, and print them until sysmaster disconnects
+fn monitor(output: Output) -> Result {
+    let stream = match UnixStream::connect(SCTL_SOCKET) {
+        Err(e) => {
+            let err_msg = format!("Failed to connect to sysmaster: {}", e);
+            return Result::Failure(err_msg, e.raw_os_error().unwrap() as u32);
+        }
+        Ok(v) => v,
+    };
+
+    let mut client = ProstClientStream::new(stream);
+    match client.execute(CommandRequest::new_subscribe()) {
+        Err(e) => {
+            return Result::Failure(format!("Failed to subscribe the events: {e}"), 1);
+        }
+        Ok(data) if data.error_code != 0 => {
+            return Result::Failure(data.message, data.error_code);
+        }
+        Ok(_) => {}
+    }
+
+    loop {
+        match client.next_event() {
+            Ok(event) => {
+                if let Some(data) = &event.response_data {
+                    let rendered = output::render(data, output);
+                    let _ = writeln!(std::io::stdout(), "{rendered}");
+                }
+            }
+            Err(e) => {
+                return Result::Failure(format!("Failed to receive the events: {e}"), 1);
+            }
+        }
+    }
+}
+
 /// Result used in sctl
 pub enum Result {
     ///
@@ -198,12 +519,9 @@ impl Termination for Result {
         match self {
             Result::OK => ExitCode::SUCCESS,
             Result::Failure(s, error_code) => {
-                if error_code & ERROR_CODE_MASK_PRINT_STDOUT != 0 {
-                    let _ = writeln!(std::io::stdout(), "{s}");
-                } else {
+                if !s.is_empty() {
                     let _ = writeln!(std::io::stderr(), "{s}");
                 }
-                let error_code = error_code ^ ERROR_CODE_MASK_PRINT_STDOUT;
                 if error_code > u8::MAX.into() {
                     return ExitCode::FAILURE;
                 }
@@ -216,6 +534,25 @@ impl Termination for Result {
 fn main() -> Result {
     let args = Args::parse();
 
+    if let SubCmd::Log {
+        unit,
+        follow,
+        lines,
+    } = &args.subcmd
+    {
+        return show_log(unit, *follow, *lines);
+    }
+
+    if let SubCmd::Monitor {} = &args.subcmd {
+        return monitor(args.output);
+    }
+
+    let output = args.output;
+    // the properties are filtered on the client side, sysmaster always returns all of them
+    let filter = match &args.subcmd {
+        SubCmd::Show { property, .. } => property.clone(),
+        _ => Vec::new(),
+    };
     let command_request = match generate_command_request(args) {
         None => {
             return Result::Failure(
@@ -226,12 +563,7 @@ fn main() -> Result {
         Some(v) => v,
     };
 
-    let addrs = [
-        SocketAddr::from(([127, 0, 0, 1], 9526)),
-        SocketAddr::from(([127, 0, 0, 1], 9527)),
-    ];
-
-    let stream = match TcpStream::connect(&addrs[..]) {
+    let stream = match UnixStream::connect(SCTL_SOCKET) {
         Err(e) => {
             let err_msg = format!("Failed to connect to sysmaster: {}", e);
             return Result::Failure(err_msg, e.raw_os_error().unwrap() as u32);
@@ -241,7 +573,7 @@ fn main() -> Result {
 
     let mut client = ProstClientStream::new(stream);
 
-    let data = match client.execute(command_request) {
+    let mut data = match client.execute(command_request) {
         Err(e) => {
             let err_msg = format!("Failed to execute the given command: {}", e);
             // don't want to map all kinds of errors the message is clear, just return 1.
@@ -250,8 +582,21 @@ fn main() -> Result {
         Ok(v) => v,
     };
 
+    if let Some(ResponseData::Uprops(list)) = &mut data.response_data {
+        if !filter.is_empty() {
+            for unit in list.units.iter_mut() {
+                unit.properties.retain(|p| filter.contains(&p.key));
+            }
+        }
+    }
+
+    if let Some(response_data) = &data.response_data {
+        let rendered = output::render(response_data, output);
+        let _ = writeln!(std::io::stdout(), "{rendered}");
+    }
+
     /* We should always print the error message if the returned error code is not 0. */
-    if data.message.is_empty() {
+    if data.message.is_empty() && data.error_code == 0 {
         return Result::OK;
     }
 
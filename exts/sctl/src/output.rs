@@ -0,0 +1,308 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! Render the structured response of sysmaster on the client side
+
+use basic::show_table::{CellAlign, CellColor, ShowTable};
+use basic::time_util::format_timespan;
+use clap::ArgEnum;
+use cmdproto::proto::abi::{
+    command_response::ResponseData, event, Event, JobList, UnitList, UnitProperties, UnitStatus,
+};
+
+/// the format that the response is printed in
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Output {
+    /// human readable tables
+    Table,
+    /// pretty printed json, used by scripts
+    Json,
+}
+
+/// render the response data in the given format
+pub(crate) fn render(data: &ResponseData, output: Output) -> String {
+    match output {
+        Output::Table => render_table(data),
+        Output::Json => render_json(data),
+    }
+}
+
+fn render_json(data: &ResponseData) -> String {
+    let res = match data {
+        ResponseData::Ustatus(list) => serde_json::to_string_pretty(&list.units),
+        ResponseData::Ulist(list) => serde_json::to_string_pretty(&list.units),
+        ResponseData::Jlist(list) => serde_json::to_string_pretty(&list.jobs),
+        ResponseData::Uprops(list) => serde_json::to_string_pretty(&list.units),
+        // one line for each event, so that the stream can be parsed line by line
+        ResponseData::Event(e) => serde_json::to_string(&e.event),
+    };
+    /* Serializing the generated messages never fails, there are no maps or non-string keys. */
+    res.unwrap_or_default()
+}
+
+fn render_table(data: &ResponseData) -> String {
+    match data {
+        ResponseData::Ustatus(list) => list
+            .units
+            .iter()
+            .map(unit_status_table)
+            .collect::<Vec<String>>()
+            .join("\n\n"),
+        ResponseData::Ulist(list) => unit_list_table(list),
+        ResponseData::Jlist(list) => job_list_table(list),
+        ResponseData::Uprops(list) => list
+            .units
+            .iter()
+            .map(unit_properties_lines)
+            .collect::<Vec<String>>()
+            .join("\n\n"),
+        ResponseData::Event(e) => event_line(e),
+    }
+}
+
+fn unit_status_table(status: &UnitStatus) -> String {
+    let mut status_table = ShowTable::new();
+    let full_active_state = status.active_state.to_string() + " (" + &status.sub_state + ")";
+
+    let mut color = CellColor::Empty;
+    if status.active_state == "active" {
+        color = CellColor::Green;
+    } else if status.active_state == "failed" {
+        color = CellColor::Red;
+    }
+
+    let cgroup_path = if status.cgroup_path.is_empty() {
+        "Empty cgroup path".to_string()
+    } else {
+        status.cgroup_path.to_string()
+    };
+    let pids = if status.processes.is_empty() {
+        "No process".to_string()
+    } else {
+        status
+            .processes
+            .iter()
+            .map(|p| format!("{} {}", p.pid, p.cmdline))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    status_table.add_line(vec!["Loaded:", &status.load_state]);
+    status_table.add_line(vec!["Active:", &full_active_state]);
+    if !status.status_text.is_empty() {
+        status_table.add_line(vec!["Status:", &format!("\"{}\"", status.status_text)]);
+    }
+    status_table.add_line(vec!["CGroup:", &cgroup_path]);
+    if !status.documentation.is_empty() {
+        status_table.add_line(vec!["Docs:", &status.documentation]);
+    }
+    status_table.add_line(vec!["PID:", &pids]);
+    status_table.set_one_col_align(0, CellAlign::Right);
+    /* The first column: keep the left space, delete the right space. */
+    status_table.set_one_col_space(0, true, false);
+    /* Cell (1, 1) is used to show the unit state, make it colored. */
+    status_table.set_one_cell_color(1, 1, color);
+
+    let mut res = "\x1b".to_string() + &String::from(color) + "● " + "\x1b[0m" + &status.name;
+    if !status.description.is_empty() {
+        res = res + " - " + &status.description;
+    }
+    res = res + "\n" + &status_table.to_string();
+
+    /* The latest logs of the unit, if they are captured by the log collector. */
+    if !status.logs.is_empty() {
+        res = res + "\n\n" + &status.logs.join("\n");
+    }
+    res
+}
+
+/// the type of the unit, the units are listed in groups of the type
+fn unit_type_suffix(name: &str) -> &str {
+    name.rsplit_once('.').map_or("", |(_, suffix)| suffix)
+}
+
+fn unit_list_table(list: &UnitList) -> String {
+    let mut list_units_table = ShowTable::new();
+    list_units_table.add_line(vec!["UNIT", "LOAD", "ACTIVE", "SUB", "DESCRIPTION"]);
+    list_units_table.set_current_row_underline(true);
+    let mut last_type = None;
+    for unit in list.units.iter() {
+        let unit_type = unit_type_suffix(&unit.name);
+        if matches!(last_type, Some(t) if t != unit_type) {
+            list_units_table.set_current_row_underline(true);
+        }
+        last_type = Some(unit_type);
+        list_units_table.add_line(vec![
+            &unit.name,
+            &unit.load_state,
+            &unit.active_state,
+            &unit.sub_state,
+            &unit.description,
+        ]);
+        if unit.active_state == "failed" {
+            list_units_table.set_current_row_color(CellColor::Red);
+        }
+    }
+    list_units_table.to_string()
+}
+
+fn job_list_table(list: &JobList) -> String {
+    let mut list_jobs_table = ShowTable::new();
+    list_jobs_table.add_line(vec!["JOB", "UNIT", "TYPE", "STATE", "WAITING"]);
+    list_jobs_table.set_current_row_underline(true);
+    for job in list.jobs.iter() {
+        list_jobs_table.add_line(vec![
+            &job.id.to_string(),
+            &job.unit,
+            &job.kind,
+            &job.stage,
+            &format_timespan(job.waiting_usec),
+        ]);
+    }
+    list_jobs_table.to_string()
+}
+
+fn unit_properties_lines(props: &UnitProperties) -> String {
+    props
+        .properties
+        .iter()
+        .map(|p| format!("{}={}", p.key, p.value))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn event_line(e: &Event) -> String {
+    match &e.event {
+        Some(event::Event::UnitState(change)) => format!(
+            "{}: {} -> {}",
+            change.unit, change.old_state, change.new_state
+        ),
+        Some(event::Event::JobNew(job)) => format!("job {} {} {}: new", job.id, job.kind, job.unit),
+        Some(event::Event::JobRemoved(job)) => {
+            format!("job {} {} {}: removed", job.id, job.kind, job.unit)
+        }
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, Output};
+    use cmdproto::proto::abi::{
+        command_response::ResponseData, event, Event, JobInfo, JobList, UnitInfo, UnitList,
+        UnitProperties, UnitPropertiesList, UnitProperty, UnitStateChange, UnitStatus,
+        UnitStatusList,
+    };
+
+    fn unit_list() -> ResponseData {
+        let unit = |name: &str, active_state: &str| UnitInfo {
+            name: name.to_string(),
+            load_state: "loaded".to_string(),
+            active_state: active_state.to_string(),
+            sub_state: "dead".to_string(),
+            description: "Foo".to_string(),
+        };
+        ResponseData::Ulist(UnitList {
+            units: vec![
+                unit("foo.service", "inactive"),
+                unit("bar.socket", "failed"),
+            ],
+        })
+    }
+
+    fn unit_props() -> ResponseData {
+        let prop = |key: &str, value: &str| UnitProperty {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        ResponseData::Uprops(UnitPropertiesList {
+            units: vec![UnitProperties {
+                unit: "foo.service".to_string(),
+                properties: vec![prop("Id", "foo.service"), prop("Description", "Foo")],
+            }],
+        })
+    }
+
+    fn state_event() -> ResponseData {
+        ResponseData::Event(Event {
+            event: Some(event::Event::UnitState(UnitStateChange {
+                unit: "foo.service".to_string(),
+                old_state: "inactive".to_string(),
+                new_state: "active".to_string(),
+            })),
+        })
+    }
+
+    #[test]
+    fn test_render_json() {
+        let value: serde_json::Value =
+            serde_json::from_str(&render(&unit_list(), Output::Json)).unwrap();
+        assert_eq!(value[0]["name"], "foo.service");
+        assert_eq!(value[1]["active_state"], "failed");
+
+        let jobs = ResponseData::Jlist(JobList {
+            jobs: vec![JobInfo {
+                id: 1,
+                unit: "foo.service".to_string(),
+                kind: "start".to_string(),
+                stage: "running".to_string(),
+                waiting_usec: 0,
+            }],
+        });
+        let value: serde_json::Value = serde_json::from_str(&render(&jobs, Output::Json)).unwrap();
+        assert_eq!(value[0]["id"], 1);
+        assert_eq!(value[0]["kind"], "start");
+
+        let value: serde_json::Value =
+            serde_json::from_str(&render(&unit_props(), Output::Json)).unwrap();
+        assert_eq!(value[0]["properties"][1]["value"], "Foo");
+
+        // the event is printed in one line
+        let rendered = render(&state_event(), Output::Json);
+        assert!(!rendered.contains('\n'));
+        assert!(rendered.contains("\"new_state\":\"active\""));
+    }
+
+    #[test]
+    fn test_render_table() {
+        let rendered = render(&unit_list(), Output::Table);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert!(lines[0].contains("UNIT") && lines[0].contains("DESCRIPTION"));
+        assert!(rendered.contains("foo.service") && rendered.contains("bar.socket"));
+
+        assert_eq!(
+            render(&unit_props(), Output::Table),
+            "Id=foo.service\nDescription=Foo"
+        );
+        assert_eq!(
+            render(&state_event(), Output::Table),
+            "foo.service: inactive -> active"
+        );
+
+        let status = ResponseData::Ustatus(UnitStatusList {
+            units: vec![UnitStatus {
+                name: "foo.service".to_string(),
+                description: "Foo".to_string(),
+                load_state: "loaded".to_string(),
+                active_state: "inactive".to_string(),
+                sub_state: "dead".to_string(),
+                logs: vec!["foo: started".to_string()],
+                ..Default::default()
+            }],
+        });
+        let rendered = render(&status, Output::Table);
+        assert!(rendered.contains("foo.service - Foo\n"));
+        assert!(rendered.contains("inactive (dead)"));
+        assert!(rendered.contains("No process"));
+        assert!(rendered.ends_with("\n\nfoo: started"));
+    }
+}
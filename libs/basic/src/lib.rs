@@ -21,6 +21,7 @@ pub mod fd_util;
 pub mod file_util;
 pub mod fs_util;
 pub mod io_util;
+pub mod log_store;
 pub mod logger;
 pub mod macros;
 pub mod mount_util;
@@ -33,9 +34,11 @@ pub mod security;
 pub mod show_table;
 pub mod socket_util;
 pub mod special;
+pub mod specifier;
 pub mod stat_util;
 pub mod string;
 pub mod time_util;
+pub mod unit_name;
 pub mod user_group_util;
 pub mod virtualize;
 pub use error::*;
@@ -0,0 +1,271 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! The store of the logs that sysmaster collects from the units. The logs of each
+//! unit are written into the file "current" under the directory of the unit, the
+//! file is rotated to "current.1", "current.2", ... when it exceeds the size limit,
+//! and the oldest rotated file is dropped when there are too many of them.
+use crate::error::*;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+/// the directory where the logs of the units are stored
+pub const LOG_STORE_DIR: &str = "/var/log/sysmaster";
+
+const LOG_FILE: &str = "current";
+
+/// the size-bounded, rotating per-unit log store
+pub struct LogStore {
+    dir: PathBuf,
+    max_size: u64,
+    max_files: u32,
+}
+
+impl LogStore {
+    /// create the log store under dir, each log file is limited to max_size bytes,
+    /// and at most max_files rotated files are kept for each unit
+    pub fn new(dir: &Path, max_size: u64, max_files: u32) -> LogStore {
+        LogStore {
+            dir: dir.to_path_buf(),
+            max_size,
+            max_files,
+        }
+    }
+
+    /// append the lines to the log of the unit, rotate the log file if it's full
+    pub fn write(&self, unit: &str, lines: &[String]) -> Result<()> {
+        let unit_dir = self.dir.join(unit);
+        if !unit_dir.exists() {
+            fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o750)
+                .create(&unit_dir)
+                .context(IoSnafu)?;
+        }
+
+        let path = unit_dir.join(LOG_FILE);
+        let mut file = open_append(&path)?;
+        let mut size = file.metadata().context(IoSnafu)?.len();
+        for line in lines {
+            let len = line.len() as u64 + 1;
+            if size > 0 && size + len > self.max_size {
+                self.rotate(&unit_dir)?;
+                file = open_append(&path)?;
+                size = 0;
+            }
+
+            writeln!(file, "{line}").context(IoSnafu)?;
+            size += len;
+        }
+
+        Ok(())
+    }
+
+    fn rotate(&self, unit_dir: &Path) -> Result<()> {
+        let current = unit_dir.join(LOG_FILE);
+        if self.max_files == 0 {
+            return fs::remove_file(current).context(IoSnafu);
+        }
+
+        // renaming to an existing file replaces it, so the oldest one is dropped
+        for i in (1..self.max_files).rev() {
+            let from = rotated_file(unit_dir, i);
+            if from.exists() {
+                fs::rename(&from, rotated_file(unit_dir, i + 1)).context(IoSnafu)?;
+            }
+        }
+        fs::rename(&current, rotated_file(unit_dir, 1)).context(IoSnafu)
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o640)
+        .open(path)
+        .context(IoSnafu)
+}
+
+fn rotated_file(unit_dir: &Path, index: u32) -> PathBuf {
+    unit_dir.join(format!("{LOG_FILE}.{index}"))
+}
+
+/// return the file that the logs of the unit are being written to
+pub fn log_file(dir: &Path, unit: &str) -> PathBuf {
+    dir.join(unit).join(LOG_FILE)
+}
+
+/// return the last n lines of the logs of the unit in time order,
+/// all the lines are returned if n is None
+pub fn tail(dir: &Path, unit: &str, n: Option<usize>) -> Result<Vec<String>> {
+    let unit_dir = dir.join(unit);
+    let mut files = vec![unit_dir.join(LOG_FILE)];
+    let mut index = 1;
+    loop {
+        let path = rotated_file(&unit_dir, index);
+        if !path.exists() {
+            break;
+        }
+        files.push(path);
+        index += 1;
+    }
+
+    // read from the newest file until there are enough lines
+    let mut lines = Vec::new();
+    for path in files {
+        let file = match File::open(&path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(Error::Io { source: e }),
+        };
+        let mut older = BufReader::new(file)
+            .lines()
+            .collect::<io::Result<Vec<String>>>()
+            .context(IoSnafu)?;
+        older.append(&mut lines);
+        lines = older;
+
+        if matches!(n, Some(n) if lines.len() >= n) {
+            break;
+        }
+    }
+
+    if let Some(n) = n {
+        if lines.len() > n {
+            lines.drain(..lines.len() - n);
+        }
+    }
+    Ok(lines)
+}
+
+/// follow the log file of the unit, return the lines appended since last read
+pub struct LogFollower {
+    path: PathBuf,
+    file: Option<File>,
+    ino: u64,
+    partial: String,
+}
+
+impl LogFollower {
+    /// create the follower from the end of the current log file of the unit
+    pub fn new(dir: &Path, unit: &str) -> LogFollower {
+        let mut follower = LogFollower {
+            path: log_file(dir, unit),
+            file: None,
+            ino: 0,
+            partial: String::new(),
+        };
+        if follower.reopen() {
+            if let Some(f) = follower.file.as_mut() {
+                let _ = f.seek(SeekFrom::End(0));
+            }
+        }
+        follower
+    }
+
+    fn reopen(&mut self) -> bool {
+        match File::open(&self.path) {
+            Ok(f) => {
+                self.ino = f.metadata().map(|m| m.ino()).unwrap_or(0);
+                self.file = Some(f);
+                true
+            }
+            Err(_) => {
+                self.file = None;
+                false
+            }
+        }
+    }
+
+    /// return the complete lines appended since last read,
+    /// the rotated log file is finished reading before switching to the new one
+    pub fn read_lines(&mut self) -> Result<Vec<String>> {
+        let mut content = String::new();
+        if let Some(f) = self.file.as_mut() {
+            f.read_to_string(&mut content).context(IoSnafu)?;
+        }
+
+        let rotated = match fs::metadata(&self.path) {
+            Ok(m) => self.file.is_none() || m.ino() != self.ino,
+            Err(_) => false,
+        };
+        if rotated && self.reopen() {
+            if let Some(f) = self.file.as_mut() {
+                f.read_to_string(&mut content).context(IoSnafu)?;
+            }
+        }
+
+        self.partial += &content;
+        let mut lines: Vec<String> = self.partial.split('\n').map(String::from).collect();
+        // the last one is not finished yet
+        self.partial = lines.pop().unwrap_or_default();
+        Ok(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(range: std::ops::Range<usize>) -> Vec<String> {
+        range.map(|i| format!("line {i:02}")).collect()
+    }
+
+    #[test]
+    fn test_write_and_tail() {
+        let dir = std::env::temp_dir().join(format!("log_store_test_{}", std::process::id()));
+        let store = LogStore::new(&dir, 1024, 2);
+        store.write("foo.service", &lines(0..5)).unwrap();
+        store.write("foo.service", &lines(5..10)).unwrap();
+
+        assert_eq!(tail(&dir, "foo.service", Some(3)).unwrap(), lines(7..10));
+        assert_eq!(tail(&dir, "foo.service", None).unwrap(), lines(0..10));
+        assert!(tail(&dir, "bar.service", None).unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotate() {
+        let dir = std::env::temp_dir().join(format!("log_store_rotate_{}", std::process::id()));
+        // each line takes 8 bytes, so that each file holds 4 lines
+        let store = LogStore::new(&dir, 32, 2);
+        store.write("foo.service", &lines(0..20)).unwrap();
+
+        let unit_dir = dir.join("foo.service");
+        assert!(rotated_file(&unit_dir, 2).exists());
+        assert!(!rotated_file(&unit_dir, 3).exists());
+        // only the current file and 2 rotated files are kept
+        assert_eq!(tail(&dir, "foo.service", None).unwrap(), lines(8..20));
+        assert_eq!(tail(&dir, "foo.service", Some(6)).unwrap(), lines(14..20));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_follow() {
+        let dir = std::env::temp_dir().join(format!("log_store_follow_{}", std::process::id()));
+        let store = LogStore::new(&dir, 32, 1);
+        let mut follower = LogFollower::new(&dir, "foo.service");
+        assert!(follower.read_lines().unwrap().is_empty());
+
+        store.write("foo.service", &lines(0..2)).unwrap();
+        assert_eq!(follower.read_lines().unwrap(), lines(0..2));
+
+        // the lines written before and after the rotation are not lost
+        store.write("foo.service", &lines(2..6)).unwrap();
+        assert_eq!(follower.read_lines().unwrap(), lines(2..6));
+        assert!(follower.read_lines().unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -12,10 +12,19 @@
 
 //!
 use crate::error::*;
+use libmount::mountinfo;
 use nix::{
     fcntl::AtFlags,
-    sys::stat::{fstatat, SFlag},
+    mount::{mount, MsFlags},
+    sys::{
+        stat::{fstatat, SFlag},
+        statvfs::{statvfs, FsFlags},
+    },
 };
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MOUNT_INFO: &str = "/proc/self/mountinfo";
 
 ///
 pub fn mount_point_fd_valid(fd: i32, file_name: &str, flags: AtFlags) -> Result<bool> {
@@ -40,3 +49,119 @@ pub fn mount_point_fd_valid(fd: i32, file_name: &str, flags: AtFlags) -> Result<
 
     Ok(f_stat.st_dev != d_stat.st_dev)
 }
+
+/// bind mount the source to the target, the submounts of the source are bound too if recursive
+pub fn bind_mount(source: &Path, target: &Path, recursive: bool) -> Result<()> {
+    let mut flags = MsFlags::MS_BIND;
+    if recursive {
+        flags |= MsFlags::MS_REC;
+    }
+    mount::<Path, Path, str, str>(Some(source), target, None, flags, None).context(NixSnafu)
+}
+
+/// mount a new tmpfs on the target, the root directory of the tmpfs is created with the mode
+pub fn mount_tmpfs(target: &Path, mode: u32, flags: MsFlags) -> Result<()> {
+    let data = format!("mode={mode:04o}");
+    mount::<str, Path, str, str>(Some("tmpfs"), target, Some("tmpfs"), flags, Some(&data))
+        .context(NixSnafu)
+}
+
+/// move the mount on the source to the target
+pub fn move_mount(source: &Path, target: &Path) -> Result<()> {
+    mount::<Path, Path, str, str>(Some(source), target, None, MsFlags::MS_MOVE, None)
+        .context(NixSnafu)
+}
+
+/// change the propagation of the mount on the path and all the mounts under it to slave,
+/// so that the mounts made later are not propagated to the peers
+pub fn mount_slave_recursive(path: &Path) -> Result<()> {
+    mount::<str, Path, str, str>(None, path, None, MsFlags::MS_SLAVE | MsFlags::MS_REC, None)
+        .context(NixSnafu)
+}
+
+// collect the mount points under the path from the content of mountinfo
+fn mount_points_in(mountinfo: &[u8], path: &Path) -> Vec<PathBuf> {
+    let mut points = Vec::new();
+    let parser = mountinfo::Parser::new(mountinfo);
+    for mount in parser.flatten() {
+        let point = PathBuf::from(&mount.mount_point);
+        if point.starts_with(path) && !points.contains(&point) {
+            points.push(point);
+        }
+    }
+    points
+}
+
+/// return the mount points of the path itself and all the mounts under it,
+/// in the order they are mounted
+pub fn mount_points_under(path: &Path) -> Result<Vec<PathBuf>> {
+    let content = fs::read(MOUNT_INFO).context(IoSnafu)?;
+    Ok(mount_points_in(&content, path))
+}
+
+// the flags that must be kept when remounting, or the remounting is rejected
+// for the mounts locked in the user namespace
+fn locked_flags(path: &Path) -> Result<MsFlags> {
+    let fs_flags = statvfs(path).context(NixSnafu)?.flags();
+    let mut flags = MsFlags::empty();
+    for (fs_flag, flag) in [
+        (FsFlags::ST_NOSUID, MsFlags::MS_NOSUID),
+        (FsFlags::ST_NODEV, MsFlags::MS_NODEV),
+        (FsFlags::ST_NOEXEC, MsFlags::MS_NOEXEC),
+        (FsFlags::ST_NOATIME, MsFlags::MS_NOATIME),
+        (FsFlags::ST_NODIRATIME, MsFlags::MS_NODIRATIME),
+        (FsFlags::ST_RELATIME, MsFlags::MS_RELATIME),
+    ] {
+        if fs_flags.contains(fs_flag) {
+            flags |= flag;
+        }
+    }
+    Ok(flags)
+}
+
+/// remount the mount on the path and all the mounts under it read-only or read-write,
+/// the extra flags are added and the other flags of each mount are kept.
+/// the path must be a mount point, the failures of the submounts are ignored.
+pub fn bind_remount_recursive(path: &Path, readonly: bool, extra: MsFlags) -> Result<()> {
+    let mut points = mount_points_under(path)?;
+    if !points.iter().any(|p| p == path) {
+        points.insert(0, path.to_path_buf());
+    }
+
+    for point in points {
+        let ret = locked_flags(&point).and_then(|mut flags| {
+            flags |= extra | MsFlags::MS_BIND | MsFlags::MS_REMOUNT;
+            if readonly {
+                flags |= MsFlags::MS_RDONLY;
+            }
+            mount::<str, Path, str, str>(None, &point, None, flags, None).context(NixSnafu)
+        });
+
+        match ret {
+            Ok(_) => {}
+            Err(e) if point == path => return Err(e),
+            Err(e) => log::debug!("Failed to remount {:?}, ignoring: {}", point, e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mount_points_in;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_mount_points_in() {
+        let mountinfo = b"22 1 8:1 / / rw - ext4 /dev/sda1 rw\n\
+            36 22 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw\n\
+            40 36 0:35 / /mnt2/a\\040b rw shared:2 - tmpfs tmpfs rw\n\
+            41 22 0:36 / /mnt20 rw - tmpfs tmpfs rw\n";
+        assert_eq!(
+            mount_points_in(mountinfo, Path::new("/mnt2")),
+            vec![PathBuf::from("/mnt2"), PathBuf::from("/mnt2/a b")]
+        );
+        assert!(mount_points_in(mountinfo, Path::new("/mnt3")).is_empty());
+        assert_eq!(mount_points_in(mountinfo, Path::new("/")).len(), 4);
+    }
+}
@@ -19,6 +19,26 @@ pub const ETC_SYSTEM_PATH: &str = "/etc/sysmaster";
 pub const RUN_SYSTEM_PATH: &str = "/run/sysmaster";
 /// unit lookup path in /usr/lib
 pub const LIB_SYSTEM_PATH: &str = "/usr/lib/sysmaster";
+/// the output directory of the generators, between /run and /usr/lib
+pub const GENERATOR_PATH: &str = "/run/sysmaster/generator";
+/// the output directory of the generators, before /etc
+pub const GENERATOR_EARLY_PATH: &str = "/run/sysmaster/generator.early";
+/// the output directory of the generators, after /usr/lib
+pub const GENERATOR_LATE_PATH: &str = "/run/sysmaster/generator.late";
+/// the directory of the transient units created over the control API, before the others
+pub const TRANSIENT_PATH: &str = "/run/sysmaster/transient";
+/// the directory of the dropins written by set-property, before the transient units
+pub const PERSISTENT_CONTROL_PATH: &str = "/etc/sysmaster/system.control";
+/// the directory of the dropins written by set-property --runtime
+pub const RUNTIME_CONTROL_PATH: &str = "/run/sysmaster/system.control";
+/// the directory of the template files copied for the instances, with the specifiers expanded
+pub const INSTANCE_PATH: &str = "/run/sysmaster/instance";
+/// the directories of the generators, the former overrides the latter with the same name
+pub const SYSTEM_GENERATOR_PATH: [&str; 3] = [
+    "/etc/sysmaster/system-generators",
+    "/run/sysmaster/system-generators",
+    "/usr/lib/sysmaster/system-generators",
+];
 
 /// struct LookupPaths
 #[derive(Debug, Clone)]
@@ -35,6 +55,12 @@ pub struct LookupPaths {
     pub generator_late: String,
     /// transient paths
     pub transient: String,
+    /// the dropins written by set-property
+    pub persistent_control: String,
+    /// the dropins written by set-property, lost after reboot
+    pub runtime_control: String,
+    /// the template files copied for the instances
+    pub instance: String,
     /// transient paths
     pub persistent_path: String,
 }
@@ -47,6 +73,9 @@ impl LookupPaths {
             generator_early: String::from(""),
             generator_late: String::from(""),
             transient: String::from(""),
+            persistent_control: String::from(""),
+            runtime_control: String::from(""),
+            instance: String::from(""),
             search_path: Vec::new(),
             persistent_path: String::from(""),
             preset_path: Vec::new(),
@@ -77,9 +106,24 @@ impl LookupPaths {
             self.search_path.push(tmp_str[0].to_string());
             self.preset_path.push(tmp_str[0].to_string());
         }
-        self.search_path.push(LIB_SYSTEM_PATH.to_string());
-        self.search_path.push(RUN_SYSTEM_PATH.to_string());
+        // the former has the higher priority
+        self.search_path.push(PERSISTENT_CONTROL_PATH.to_string());
+        self.search_path.push(RUNTIME_CONTROL_PATH.to_string());
+        self.search_path.push(TRANSIENT_PATH.to_string());
+        self.search_path.push(GENERATOR_EARLY_PATH.to_string());
         self.search_path.push(ETC_SYSTEM_PATH.to_string());
+        self.search_path.push(RUN_SYSTEM_PATH.to_string());
+        self.search_path.push(GENERATOR_PATH.to_string());
+        self.search_path.push(LIB_SYSTEM_PATH.to_string());
+        self.search_path.push(GENERATOR_LATE_PATH.to_string());
+
+        self.generator = GENERATOR_PATH.to_string();
+        self.generator_early = GENERATOR_EARLY_PATH.to_string();
+        self.generator_late = GENERATOR_LATE_PATH.to_string();
+        self.transient = TRANSIENT_PATH.to_string();
+        self.persistent_control = PERSISTENT_CONTROL_PATH.to_string();
+        self.runtime_control = RUNTIME_CONTROL_PATH.to_string();
+        self.instance = INSTANCE_PATH.to_string();
 
         self.preset_path
             .push(format!("{}/{}", ETC_SYSTEM_PATH, "system-preset"));
@@ -103,7 +147,7 @@ mod tests {
 
     use crate::logger;
 
-    use super::LookupPaths;
+    use super::*;
     #[test]
     fn test_init_lookup_paths() {
         logger::init_log_to_console("test_init_lookup_paths", log::LevelFilter::Trace);
@@ -112,6 +156,17 @@ mod tests {
         for item in _lp.search_path.iter() {
             log::info!("lookup path is{:?}", item);
         }
+
+        let pos = |path: &str| _lp.search_path.iter().position(|p| p == path).unwrap();
+        assert!(pos(PERSISTENT_CONTROL_PATH) < pos(RUNTIME_CONTROL_PATH));
+        assert!(pos(RUNTIME_CONTROL_PATH) < pos(TRANSIENT_PATH));
+        assert!(pos(TRANSIENT_PATH) < pos(GENERATOR_EARLY_PATH));
+        assert!(pos(GENERATOR_EARLY_PATH) < pos(ETC_SYSTEM_PATH));
+        assert!(pos(RUN_SYSTEM_PATH) < pos(GENERATOR_PATH));
+        assert!(pos(GENERATOR_PATH) < pos(LIB_SYSTEM_PATH));
+        assert!(pos(LIB_SYSTEM_PATH) < pos(GENERATOR_LATE_PATH));
+        assert_eq!(_lp.generator, GENERATOR_PATH);
+        assert_eq!(_lp.transient, TRANSIENT_PATH);
         let tmp_dir = env::var("OUT_DIR");
         if tmp_dir.is_err() {
             return;
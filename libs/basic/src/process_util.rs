@@ -207,6 +207,37 @@ pub fn kill_and_cont(pid: Pid, sig: Signal) -> Result<(), Errno> {
     }
 }
 
+extern "C" {
+    fn sigqueue(
+        pid: nix::libc::pid_t,
+        sig: nix::libc::c_int,
+        value: nix::libc::sigval,
+    ) -> nix::libc::c_int;
+}
+
+/// send the signal number to pid, which may be a real-time signal not covered by [`Signal`],
+/// the signal is queued with the value by sigqueue if it's given
+pub fn kill_with_value(pid: Pid, signal: i32, value: Option<i32>) -> Result<(), Errno> {
+    let ret = match value {
+        None => unsafe { kill(pid.as_raw(), signal) },
+        Some(v) => unsafe { sigqueue(pid.as_raw(), signal, int_sigval(v)) },
+    };
+    Errno::result(ret).map(drop)
+}
+
+// sigval is a union of sival_int and sival_ptr, but only sival_ptr is exposed by libc
+fn int_sigval(value: i32) -> nix::libc::sigval {
+    #[allow(unused_mut)]
+    let mut raw = value as u32 as usize;
+    #[cfg(all(target_endian = "big", target_pointer_width = "64"))]
+    {
+        raw <<= 32;
+    }
+    nix::libc::sigval {
+        sival_ptr: raw as *mut nix::libc::c_void,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use nix::libc::kill;
@@ -214,7 +245,9 @@ mod tests {
     use std::process::Command;
     use std::thread;
 
-    use crate::process_util::wait_pids;
+    use crate::process_util::{kill_with_value, wait_pids};
+    use nix::errno::Errno;
+    use nix::unistd::Pid;
     #[test]
     fn test_wait_pids() {
         let mut pids: HashSet<i32> = HashSet::new();
@@ -239,4 +272,17 @@ mod tests {
         let res = wait_pids(pids, 10000000);
         assert_eq!(res.len(), 0);
     }
+
+    #[test]
+    fn test_kill_with_value() {
+        // the signal 0 only checks whether the process exists
+        let pid = nix::unistd::getpid();
+        assert!(kill_with_value(pid, 0, None).is_ok());
+        assert!(kill_with_value(pid, 0, Some(1)).is_ok());
+        assert_eq!(
+            kill_with_value(Pid::from_raw(i32::MAX), 0, Some(1)),
+            Err(Errno::ESRCH)
+        );
+        assert_eq!(kill_with_value(pid, -1, None), Err(Errno::EINVAL));
+    }
 }
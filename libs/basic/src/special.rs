@@ -18,6 +18,18 @@ pub const DEFAULT_TARGET: &str = "default.target";
 pub const SHUTDOWN_TARGET: &str = "shutdown.target";
 /// the socketc target
 pub const SOCKETS_TARGET: &str = "sockets.target";
+/// the timers target
+pub const TIMERS_TARGET: &str = "timers.target";
+/// the paths target
+pub const PATHS_TARGET: &str = "paths.target";
+/// the swap target
+pub const SWAP_TARGET: &str = "swap.target";
+/// the local file systems target
+pub const LOCAL_FS_TARGET: &str = "local-fs.target";
+/// the remote file systems target
+pub const REMOTE_FS_TARGET: &str = "remote-fs.target";
+/// the network is configured and online
+pub const NETWORK_ONLINE_TARGET: &str = "network-online.target";
 
 /// early boot targets
 pub const SYSINIT_TARGET: &str = "sysinit.target";
@@ -31,6 +43,8 @@ pub const MULTI_USER_TARGET: &str = "multi-user.target";
 pub const INIT_SCOPE: &str = "init.scope";
 /// sysmaster service slice
 pub const SYSMASTER_SLICE: &str = "system.slice";
+/// the root slice
+pub const ROOT_SLICE: &str = "-.slice";
 
 /// the unit store sysmaster itself
 pub const CGROUP_SYSMASTER: &str = "sysmaster";
@@ -0,0 +1,169 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! the specifiers in the unit files, like "%i" and "%n", which are expanded
+//! according to the unit name and the manager
+use crate::unit_name::{unit_name_instance, unit_name_prefix, unit_name_unescape};
+use nix::unistd::{Uid, User};
+
+// the specifiers of the manager
+struct ManagerInfo {
+    user: String,
+    home: String,
+    runtime_dir: String,
+}
+
+impl ManagerInfo {
+    fn new() -> ManagerInfo {
+        let uid = Uid::current();
+        let (user, home) = match User::from_uid(uid) {
+            Ok(Some(user)) => (user.name, user.dir.to_string_lossy().to_string()),
+            _ => (uid.to_string(), String::new()),
+        };
+        let runtime_dir = if uid.is_root() {
+            "/run".to_string()
+        } else {
+            std::env::var("XDG_RUNTIME_DIR").unwrap_or_default()
+        };
+        ManagerInfo {
+            user,
+            home,
+            runtime_dir,
+        }
+    }
+}
+
+/// expand the specifiers in the text for the unit:
+/// "%n": the full unit name, "%N": the unit name without the suffix,
+/// "%p": the prefix, "%i": the instance, "%I": the unescaped instance,
+/// "%u": the user of the manager, "%h": the home of the user,
+/// "%t": the runtime directory, "%%": "%".
+/// the unknown specifiers are kept as they are.
+pub fn specifier_expand(text: &str, unit: &str) -> String {
+    expand(text, unit, |v| v.to_string())
+}
+
+/// expand the specifiers in the unit file written in toml, the same as specifier_expand(),
+/// except that the expanded values are escaped, so that they are kept as they are in the
+/// quoted strings, like the "\x2d" in the escaped instance.
+pub fn specifier_expand_toml(text: &str, unit: &str) -> String {
+    expand(text, unit, toml_escape)
+}
+
+fn toml_escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            '\r' => result.push_str("\\r"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04X}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn expand(text: &str, unit: &str, escape: fn(&str) -> String) -> String {
+    if !text.contains('%') {
+        return text.to_string();
+    }
+
+    let mut manager: Option<ManagerInfo> = None;
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+
+        let spec = match chars.next() {
+            Some(spec) => spec,
+            None => {
+                result.push(c);
+                break;
+            }
+        };
+        let instance = unit_name_instance(unit).unwrap_or_default();
+        match spec {
+            'n' => result.push_str(&escape(unit)),
+            'N' => result.push_str(&escape(
+                unit.rsplit_once('.').map_or(unit, |(stem, _)| stem),
+            )),
+            'p' => result.push_str(&escape(unit_name_prefix(unit))),
+            'i' => result.push_str(&escape(instance)),
+            'I' => result.push_str(&escape(&unit_name_unescape(instance))),
+            'u' | 'h' | 't' => {
+                let info = manager.get_or_insert_with(ManagerInfo::new);
+                result.push_str(&escape(match spec {
+                    'u' => &info.user,
+                    'h' => &info.home,
+                    _ => &info.runtime_dir,
+                }));
+            }
+            '%' => result.push('%'),
+            _ => {
+                result.push(c);
+                result.push(spec);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{specifier_expand, specifier_expand_toml};
+
+    #[test]
+    fn test_specifier_expand() {
+        let unit = "getty@tty\\x2d1-a.service";
+        assert_eq!(
+            specifier_expand("ExecStart=/sbin/agetty %I", unit),
+            "ExecStart=/sbin/agetty tty-1/a"
+        );
+        assert_eq!(
+            specifier_expand("%n %N", unit),
+            format!("{unit} getty@tty\\x2d1-a")
+        );
+        assert_eq!(specifier_expand("%p-%i", unit), "getty-tty\\x2d1-a");
+        assert_eq!(specifier_expand("100%% %s %", unit), "100% %s %");
+        assert_eq!(specifier_expand("%i", "foo.service"), "");
+        assert_eq!(specifier_expand("%p", "foo.service"), "foo");
+        assert_eq!(specifier_expand("no specifier", unit), "no specifier");
+
+        if nix::unistd::Uid::current().is_root() {
+            assert_eq!(specifier_expand("%u:%t", unit), "root:/run");
+        }
+    }
+
+    #[test]
+    fn test_specifier_expand_toml() {
+        let unit = "getty@tty\\x2d1.service";
+        let text = "[Service]\nExecStart = \"/sbin/agetty %i %I\"\nDescription = \"%n\"";
+        assert_eq!(
+            specifier_expand_toml(text, unit),
+            "[Service]\nExecStart = \"/sbin/agetty tty\\\\x2d1 tty-1\"\nDescription = \"getty@tty\\\\x2d1.service\""
+        );
+
+        // the quote and the newline can't end the string and add other keys
+        let unit = "foo@a\\x22\\x0aUser\\x3droot.service";
+        assert_eq!(
+            specifier_expand_toml("Description = \"%I\"", unit),
+            "Description = \"a\\\"\\nUser=root\""
+        );
+        assert_eq!(specifier_expand_toml("%%i", unit), "%i");
+    }
+}
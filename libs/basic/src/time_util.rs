@@ -11,12 +11,24 @@
 // See the Mulan PSL v2 for more details.
 
 //!
+use crate::error::*;
+use nix::time::{clock_gettime, ClockId};
 use std::time::SystemTime;
 
 const USEC_INFINITY: u128 = u128::MAX;
 
+/// usec per msec
+pub const USEC_PER_MSEC: u64 = 1000;
 /// usec per sec
 pub const USEC_PER_SEC: u64 = 1000000;
+/// usec per minute
+pub const USEC_PER_MINUTE: u64 = 60 * USEC_PER_SEC;
+/// usec per hour
+pub const USEC_PER_HOUR: u64 = 60 * USEC_PER_MINUTE;
+/// usec per day
+pub const USEC_PER_DAY: u64 = 24 * USEC_PER_HOUR;
+/// usec per week
+pub const USEC_PER_WEEK: u64 = 7 * USEC_PER_DAY;
 
 ///
 pub fn timespec_load(systime: SystemTime) -> u128 {
@@ -25,3 +37,175 @@ pub fn timespec_load(systime: SystemTime) -> u128 {
         Err(_) => USEC_INFINITY,
     }
 }
+
+/// return the current time of the clock in usec
+pub fn now_clockid(clock: ClockId) -> u64 {
+    match clock_gettime(clock) {
+        Ok(ts) => ts.tv_sec() as u64 * USEC_PER_SEC + ts.tv_nsec() as u64 / 1000,
+        Err(_) => 0,
+    }
+}
+
+/// parse the time span to usec, like "1h 30min", "90s" or "500ms",
+/// the number without unit is treated as seconds, "infinity" means u64::MAX
+pub fn parse_timespan(t: &str) -> Result<u64> {
+    let t = t.trim();
+    if t == "infinity" {
+        return Ok(u64::MAX);
+    }
+    if t.is_empty() {
+        return Err(Error::Invalid {
+            what: "empty time span".to_string(),
+        });
+    }
+
+    let units = [
+        ("usec", 1),
+        ("us", 1),
+        ("msec", USEC_PER_MSEC),
+        ("ms", USEC_PER_MSEC),
+        ("seconds", USEC_PER_SEC),
+        ("second", USEC_PER_SEC),
+        ("sec", USEC_PER_SEC),
+        ("s", USEC_PER_SEC),
+        ("minutes", USEC_PER_MINUTE),
+        ("minute", USEC_PER_MINUTE),
+        ("min", USEC_PER_MINUTE),
+        ("m", USEC_PER_MINUTE),
+        ("hours", USEC_PER_HOUR),
+        ("hour", USEC_PER_HOUR),
+        ("hr", USEC_PER_HOUR),
+        ("h", USEC_PER_HOUR),
+        ("days", USEC_PER_DAY),
+        ("day", USEC_PER_DAY),
+        ("d", USEC_PER_DAY),
+        ("weeks", USEC_PER_WEEK),
+        ("week", USEC_PER_WEEK),
+        ("w", USEC_PER_WEEK),
+        ("", USEC_PER_SEC),
+    ];
+
+    let invalid = || Error::Invalid {
+        what: format!("time span {t}"),
+    };
+
+    let mut total: u64 = 0;
+    let mut rest = t;
+    while !rest.is_empty() {
+        let num_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        if num_len == 0 {
+            return Err(invalid());
+        }
+        let num = rest[..num_len].parse::<f64>().map_err(|_| invalid())?;
+        rest = rest[num_len..].trim_start();
+
+        let unit_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let multiplier = units
+            .iter()
+            .find(|(name, _)| *name == &rest[..unit_len])
+            .map(|(_, m)| *m)
+            .ok_or_else(invalid)?;
+        rest = rest[unit_len..].trim_start();
+
+        total = total.saturating_add((num * multiplier as f64) as u64);
+    }
+
+    Ok(total)
+}
+
+/// format the time span in usec, like "1h 30min 5s", the part less than a second is
+/// dropped unless the time span is shorter than a second
+pub fn format_timespan(usec: u64) -> String {
+    if usec < USEC_PER_SEC {
+        return format!("{}ms", usec / USEC_PER_MSEC);
+    }
+
+    let units = [
+        ("w", USEC_PER_WEEK),
+        ("d", USEC_PER_DAY),
+        ("h", USEC_PER_HOUR),
+        ("min", USEC_PER_MINUTE),
+        ("s", USEC_PER_SEC),
+    ];
+    let mut parts = Vec::new();
+    let mut rest = usec;
+    for (name, unit) in units {
+        if rest >= unit {
+            parts.push(format!("{}{name}", rest / unit));
+            rest %= unit;
+        }
+    }
+    parts.join(" ")
+}
+
+/// format the realtime in usec to the local time, like "2023-01-01T08:00:00.000000+08:00"
+pub fn format_timestamp(usec: u64) -> String {
+    let secs = (usec / USEC_PER_SEC) as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&secs, &mut tm) }.is_null() {
+        return format!("@{usec}");
+    }
+
+    let offset = tm.tm_gmtoff / 60;
+    let sign = if offset < 0 { '-' } else { '+' };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}{}{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec,
+        usec % USEC_PER_SEC,
+        sign,
+        offset.abs() / 60,
+        offset.abs() % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timespan() {
+        assert_eq!(parse_timespan("90").unwrap(), 90 * USEC_PER_SEC);
+        assert_eq!(parse_timespan("5min").unwrap(), 5 * USEC_PER_MINUTE);
+        assert_eq!(
+            parse_timespan("1h 30min").unwrap(),
+            USEC_PER_HOUR + 30 * USEC_PER_MINUTE
+        );
+        assert_eq!(parse_timespan("1.5s").unwrap(), 1500 * USEC_PER_MSEC);
+        assert_eq!(
+            parse_timespan("2d1h").unwrap(),
+            2 * USEC_PER_DAY + USEC_PER_HOUR
+        );
+        assert_eq!(parse_timespan("100ms").unwrap(), 100 * USEC_PER_MSEC);
+        assert_eq!(parse_timespan("infinity").unwrap(), u64::MAX);
+        assert!(parse_timespan("").is_err());
+        assert!(parse_timespan("5 apples").is_err());
+        assert!(parse_timespan("min").is_err());
+    }
+
+    #[test]
+    fn test_format_timespan() {
+        assert_eq!(format_timespan(500 * USEC_PER_MSEC), "500ms");
+        assert_eq!(format_timespan(90 * USEC_PER_SEC), "1min 30s");
+        assert_eq!(
+            format_timespan(USEC_PER_DAY + USEC_PER_HOUR + 5 * USEC_PER_SEC + 42),
+            "1d 1h 5s"
+        );
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        let s = format_timestamp(1_000_000 * 86400 * 365 + 42);
+        assert_eq!(s.len(), "1971-01-01T00:00:00.000042+00:00".len());
+        assert!(s.starts_with("1971-01-0") || s.starts_with("1970-12-31"));
+        assert_eq!(&s[19..26], ".000042");
+    }
+}
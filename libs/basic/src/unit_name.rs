@@ -0,0 +1,244 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! the unit name functions, especially for the template "foo@.service"
+//! and the instance "foo@bar.service"
+
+/// the max length of the unit name
+const UNIT_NAME_MAX: usize = 255;
+
+/// whether the unit name is valid, like "foo.service", "foo@.service" or "foo@bar.service",
+/// only the alphanumerics and ":-_.\@" are allowed, so the name never contains "/"
+pub fn unit_name_is_valid(name: &str) -> bool {
+    if name.is_empty() || name.len() > UNIT_NAME_MAX {
+        return false;
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || ":-_.\\@".contains(c))
+    {
+        return false;
+    }
+
+    let (prefix, instance, suffix) = unit_name_split(name);
+    !prefix.is_empty() && !suffix.is_empty() && !matches!(instance, Some(i) if i.contains('@'))
+}
+
+/// split the unit name into the prefix, the instance and the suffix,
+/// like "foo@bar.service" => ("foo", Some("bar"), "service"),
+/// the instance of the template "foo@.service" is Some("")
+pub fn unit_name_split(name: &str) -> (&str, Option<&str>, &str) {
+    let (stem, suffix) = match name.rfind('.') {
+        Some(pos) => (&name[..pos], &name[pos + 1..]),
+        None => (name, ""),
+    };
+    match stem.find('@') {
+        Some(pos) => (&stem[..pos], Some(&stem[pos + 1..]), suffix),
+        None => (stem, None, suffix),
+    }
+}
+
+/// return the prefix of the unit name, like "foo" of "foo@bar.service"
+pub fn unit_name_prefix(name: &str) -> &str {
+    unit_name_split(name).0
+}
+
+/// return the instance of the unit name, like "bar" of "foo@bar.service",
+/// None is returned if the unit is not an instance
+pub fn unit_name_instance(name: &str) -> Option<&str> {
+    match unit_name_split(name).1 {
+        Some(instance) if !instance.is_empty() => Some(instance),
+        _ => None,
+    }
+}
+
+/// whether the unit name is a template, like "foo@.service"
+pub fn unit_name_is_template(name: &str) -> bool {
+    matches!(unit_name_split(name), (prefix, Some(""), _) if !prefix.is_empty())
+}
+
+/// return the template of the instance, like "foo@.service" of "foo@bar.service",
+/// None is returned if the unit is not an instance
+pub fn unit_name_template(name: &str) -> Option<String> {
+    let (prefix, instance, suffix) = unit_name_split(name);
+    match instance {
+        Some(instance) if !instance.is_empty() && !prefix.is_empty() => {
+            Some(format!("{prefix}@.{suffix}"))
+        }
+        _ => None,
+    }
+}
+
+/// return the instance of the template, like "foo@bar.service" of "foo@.service" and "bar"
+pub fn unit_name_replace_instance(template: &str, instance: &str) -> Option<String> {
+    let (prefix, _, suffix) = unit_name_split(template);
+    if !unit_name_is_template(template) || instance.is_empty() {
+        return None;
+    }
+    Some(format!("{prefix}@{instance}.{suffix}"))
+}
+
+/// unescape the string in the unit name, "-" is converted to "/",
+/// and "\xNN" is converted to the character with the hex value
+pub fn unit_name_unescape(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'-' => result.push(b'/'),
+            b'\\' if i + 4 <= bytes.len() && bytes[i + 1] == b'x' => {
+                let hex = std::str::from_utf8(&bytes[i + 2..i + 4]).unwrap_or_default();
+                match u8::from_str_radix(hex, 16) {
+                    Ok(c) => {
+                        result.push(c);
+                        i += 4;
+                        continue;
+                    }
+                    Err(_) => result.push(b'\\'),
+                }
+            }
+            c => result.push(c),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&result).to_string()
+}
+
+/// escape the string to be used in the unit name, "/" is converted to "-",
+/// and the characters other than "[a-zA-Z0-9:_.]" are converted to "\xNN",
+/// so is the leading "."
+pub fn unit_name_escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for (i, c) in s.bytes().enumerate() {
+        match c {
+            b'/' => result.push('-'),
+            b'.' if i == 0 => result.push_str("\\x2e"),
+            c if c.is_ascii_alphanumeric() || c == b':' || c == b'_' || c == b'.' => {
+                result.push(c as char)
+            }
+            c => result.push_str(&format!("\\x{c:02x}")),
+        }
+    }
+    result
+}
+
+/// escape the path to be used in the unit name, the redundant "/" are removed,
+/// like "/dev/sda1" => "dev-sda1", and the root directory is escaped to "-"
+pub fn unit_name_path_escape(path: &str) -> String {
+    let path = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    if path.is_empty() {
+        return "-".to_string();
+    }
+    unit_name_escape(&path)
+}
+
+/// generate the unit name from the path and the suffix,
+/// like ("/dev/sda1", ".device") => "dev-sda1.device"
+pub fn unit_name_from_path(path: &str, suffix: &str) -> String {
+    unit_name_path_escape(path) + suffix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_name_is_valid() {
+        assert!(unit_name_is_valid("foo.service"));
+        assert!(unit_name_is_valid("foo@.service"));
+        assert!(unit_name_is_valid("foo@bar.service"));
+        assert!(unit_name_is_valid("dev-sda\\x2d1.device"));
+        assert!(!unit_name_is_valid(""));
+        assert!(!unit_name_is_valid("foo"));
+        assert!(!unit_name_is_valid("foo."));
+        assert!(!unit_name_is_valid(".service"));
+        assert!(!unit_name_is_valid("@bar.service"));
+        assert!(!unit_name_is_valid("foo@bar@baz.service"));
+        assert!(!unit_name_is_valid("../../etc/x.service"));
+        assert!(!unit_name_is_valid("foo bar.service"));
+        assert!(!unit_name_is_valid(&format!("{}.service", "a".repeat(250))));
+    }
+
+    #[test]
+    fn test_unit_name_split() {
+        assert_eq!(
+            unit_name_split("foo@bar.service"),
+            ("foo", Some("bar"), "service")
+        );
+        assert_eq!(
+            unit_name_split("foo@.service"),
+            ("foo", Some(""), "service")
+        );
+        assert_eq!(unit_name_split("foo.service"), ("foo", None, "service"));
+        assert_eq!(
+            unit_name_split("getty@tty1.a.b.service"),
+            ("getty", Some("tty1.a.b"), "service")
+        );
+
+        assert_eq!(unit_name_prefix("foo@bar.service"), "foo");
+        assert_eq!(unit_name_instance("foo@bar.service"), Some("bar"));
+        assert_eq!(unit_name_instance("foo@.service"), None);
+        assert_eq!(unit_name_instance("foo.service"), None);
+    }
+
+    #[test]
+    fn test_unit_name_template() {
+        assert!(unit_name_is_template("foo@.service"));
+        assert!(!unit_name_is_template("foo@bar.service"));
+        assert!(!unit_name_is_template("@.service"));
+        assert!(!unit_name_is_template("foo.service"));
+
+        assert_eq!(
+            unit_name_template("foo@bar.service"),
+            Some("foo@.service".to_string())
+        );
+        assert_eq!(unit_name_template("foo@.service"), None);
+        assert_eq!(unit_name_template("foo.service"), None);
+
+        assert_eq!(
+            unit_name_replace_instance("foo@.service", "bar"),
+            Some("foo@bar.service".to_string())
+        );
+        assert_eq!(unit_name_replace_instance("foo.service", "bar"), None);
+        assert_eq!(unit_name_replace_instance("foo@.service", ""), None);
+    }
+
+    #[test]
+    fn test_unit_name_unescape() {
+        assert_eq!(unit_name_unescape("dev-sda1"), "dev/sda1");
+        assert_eq!(unit_name_unescape("a\\x2db\\x20c"), "a-b c");
+        assert_eq!(unit_name_unescape("a\\xzz"), "a\\xzz");
+        assert_eq!(unit_name_unescape("tty1"), "tty1");
+    }
+
+    #[test]
+    fn test_unit_name_escape() {
+        assert_eq!(unit_name_escape("dev/sda1"), "dev-sda1");
+        assert_eq!(unit_name_escape("a-b c"), "a\\x2db\\x20c");
+        assert_eq!(unit_name_escape(".hidden"), "\\x2ehidden");
+        assert_eq!(unit_name_path_escape("/"), "-");
+        assert_eq!(unit_name_path_escape("//dev//sda1/"), "dev-sda1");
+        assert_eq!(
+            unit_name_from_path("/dev/disk/by-label/root", ".device"),
+            "dev-disk-by\\x2dlabel-root.device"
+        );
+        assert_eq!(
+            unit_name_unescape(&unit_name_path_escape("/dev/a-b c")),
+            "dev/a-b c"
+        );
+    }
+}
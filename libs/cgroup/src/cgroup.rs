@@ -187,6 +187,42 @@ pub fn cg_get_pids(cg_path: &PathBuf) -> Vec<Pid> {
     }
 }
 
+/// return the cgroup path of the pid in the hierarchy used by sysmaster, read from /proc/<pid>/cgroup.
+/// the returned path is relative to the cgroup root, like the cg_path of the units.
+pub fn cg_pid_get_path(pid: Pid) -> Result<PathBuf> {
+    let content = fs::read_to_string(format!("/proc/{}/cgroup", pid)).context(IoSnafu)?;
+
+    parse_proc_cgroup(&content, cg_type()?).ok_or(Error::NotFound {
+        what: format!("cgroup of pid {}", pid),
+    })
+}
+
+fn parse_proc_cgroup(content: &str, cg_type: CgType) -> Option<PathBuf> {
+    // the unified hierarchy has no controllers, like "0::/system.slice/foo.service"
+    let controller = match cg_type {
+        CgType::None => return None,
+        CgType::UnifiedV1 | CgType::UnifiedV2 => "",
+        CgType::Legacy => "name=sysmaster",
+        CgType::LegacySystemd => "name=systemd",
+    };
+
+    for line in content.lines() {
+        let mut fields = line.splitn(3, ':');
+        let (_, controllers, path) = (fields.next()?, fields.next()?, fields.next()?);
+        let matched = if controller.is_empty() {
+            controllers.is_empty()
+        } else {
+            controllers.split(',').any(|c| c == controller)
+        };
+
+        if matched {
+            return Some(PathBuf::from(path.trim_start_matches('/')));
+        }
+    }
+
+    None
+}
+
 fn remove_dir(cg_path: &PathBuf) -> Result<()> {
     let abs_cg_path: PathBuf = cg_abs_path(cg_path, &PathBuf::from(""))?;
 
@@ -460,6 +496,130 @@ pub fn cg_is_empty_recursive(cg_path: &PathBuf) -> Result<bool> {
     return Ok(true);
 }
 
+#[cfg(feature = "linux")]
+fn cg_controller_path(controller: &str, cg_path: &PathBuf, suffix: &PathBuf) -> Result<PathBuf> {
+    match cg_type()? {
+        CgType::UnifiedV2 => Ok(PathBuf::from(CG_BASE_DIR).join(cg_path).join(suffix)),
+        CgType::None => Err(Error::NotFound {
+            what: "cgroup is not mounted".to_string(),
+        }),
+        // the controllers are mounted to /sys/fs/cgroup/<controller> in legacy and hybrid mode
+        _ => Ok(PathBuf::from(CG_BASE_DIR)
+            .join(controller)
+            .join(cg_path)
+            .join(suffix)),
+    }
+}
+
+#[cfg(feature = "hongmeng")]
+fn cg_controller_path(_controller: &str, cg_path: &PathBuf, suffix: &PathBuf) -> Result<PathBuf> {
+    cg_abs_path(cg_path, suffix)
+}
+
+/// write the value to the attribute of the cg_path,
+/// the controller is used to find the hierarchy when the controllers are not unified.
+pub fn cg_set_attr(controller: &str, cg_path: &PathBuf, attr: &str, value: &str) -> Result<()> {
+    let dir = cg_controller_path(controller, cg_path, &PathBuf::from(""))?;
+    if !dir.exists() {
+        fs::create_dir_all(&dir).context(IoSnafu)?;
+    }
+
+    log::debug!("set cgroup attribute {:?}/{} to {}", dir, attr, value);
+    fs::write(dir.join(attr), value).context(IoSnafu)?;
+    Ok(())
+}
+
+/// read the value of the attribute of the cg_path.
+pub fn cg_get_attr(controller: &str, cg_path: &PathBuf, attr: &str) -> Result<String> {
+    let path = cg_controller_path(controller, cg_path, &PathBuf::from(attr))?;
+    let value = fs::read_to_string(path).context(IoSnafu)?;
+    Ok(value.trim().to_string())
+}
+
+/// attach the pid to the cg_path in the hierarchy of the controller,
+/// only needed when the controllers are not unified.
+pub fn cg_attach_controller(controller: &str, pid: Pid, cg_path: &PathBuf) -> Result<()> {
+    let dir = cg_controller_path(controller, cg_path, &PathBuf::from(""))?;
+    if !dir.exists() {
+        fs::create_dir_all(&dir).context(IoSnafu)?;
+    }
+
+    let p = if pid == Pid::from_raw(0) {
+        nix::unistd::getpid()
+    } else {
+        pid
+    };
+
+    fs::write(dir.join(CGROUP_PROCS), format!("{p}\n")).context(IoSnafu)?;
+    Ok(())
+}
+
+/// enable the controllers in the cgroup.subtree_control of all the ancestors of the cg_path,
+/// only works on the unified hierarchy.
+pub fn cg_enable_controllers(cg_path: &PathBuf, controllers: &[&str]) -> Result<()> {
+    if cg_type()? != CgType::UnifiedV2 {
+        return Ok(());
+    }
+
+    let mut dir = PathBuf::from(CG_BASE_DIR);
+    let mut ancestors = vec![dir.clone()];
+    if let Some(parent) = cg_path.parent() {
+        for c in parent.components() {
+            dir = dir.join(c);
+            ancestors.push(dir.clone());
+        }
+    }
+
+    for ancestor in ancestors {
+        if !ancestor.exists() {
+            fs::create_dir_all(&ancestor).context(IoSnafu)?;
+        }
+
+        let subtree = ancestor.join("cgroup.subtree_control");
+        let enabled = fs::read_to_string(&subtree).context(IoSnafu)?;
+        for controller in controllers {
+            if enabled.split_whitespace().any(|c| c == *controller) {
+                continue;
+            }
+            // enable one by one, the write fails entirely if any controller is not available.
+            if let Err(e) = fs::write(&subtree, format!("+{controller}")) {
+                log::warn!(
+                    "Failed to enable controller {} in {:?}: {}",
+                    controller,
+                    subtree,
+                    e
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// remove the cgroup of the cg_path, it fails if there are processes or sub cgroups left
+/// in it, the cgroup which doesn't exist is ignored.
+pub fn cg_remove(cg_path: &PathBuf) -> Result<()> {
+    let dir = cg_abs_path(cg_path, &PathBuf::from(""))?;
+    log::debug!("cgroup remove path {:?}", dir);
+    match fs::remove_dir(&dir) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e).context(IoSnafu),
+    }
+}
+
+/// remove the cgroup of the cg_path in the hierarchy of the controller,
+/// only needed when the controllers are not unified, the cgroup which doesn't exist is ignored.
+pub fn cg_remove_controller(controller: &str, cg_path: &PathBuf) -> Result<()> {
+    let dir = cg_controller_path(controller, cg_path, &PathBuf::from(""))?;
+    log::debug!("cgroup remove path {:?}", dir);
+    match fs::remove_dir(&dir) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e).context(IoSnafu),
+    }
+}
+
 /// create cgroup path and attach pid to this cgroup
 pub fn cg_create_and_attach(cg_path: &PathBuf, pid: Pid) -> Result<bool> {
     cg_create(cg_path)?;
@@ -789,4 +949,25 @@ mod tests {
             format!("{}/{}/{}", CG_BASE_DIR, controller, "test")
         );
     }
+
+    #[test]
+    fn test_parse_proc_cgroup() {
+        let unified = "0::/system.slice/foo.service\n";
+        assert_eq!(
+            parse_proc_cgroup(unified, CgType::UnifiedV2),
+            Some(PathBuf::from("system.slice/foo.service"))
+        );
+        assert_eq!(parse_proc_cgroup(unified, CgType::Legacy), None);
+
+        let legacy = "12:pids:/system.slice\n1:name=sysmaster:/system.slice/foo.service\n0::/\n";
+        assert_eq!(
+            parse_proc_cgroup(legacy, CgType::Legacy),
+            Some(PathBuf::from("system.slice/foo.service"))
+        );
+        assert_eq!(
+            parse_proc_cgroup(legacy, CgType::UnifiedV1),
+            Some(PathBuf::from(""))
+        );
+        assert_eq!(parse_proc_cgroup(legacy, CgType::None), None);
+    }
 }
@@ -20,13 +20,20 @@ use bitflags::bitflags;
 mod cgroup;
 pub mod error;
 pub use crate::cgroup::cg_attach;
+pub use crate::cgroup::cg_attach_controller;
 pub use crate::cgroup::cg_controllers;
 pub use crate::cgroup::cg_create;
 pub use crate::cgroup::cg_create_and_attach;
+pub use crate::cgroup::cg_enable_controllers;
 pub use crate::cgroup::cg_escape;
+pub use crate::cgroup::cg_get_attr;
 pub use crate::cgroup::cg_get_pids;
 pub use crate::cgroup::cg_is_empty_recursive;
 pub use crate::cgroup::cg_kill_recursive;
+pub use crate::cgroup::cg_pid_get_path;
+pub use crate::cgroup::cg_remove;
+pub use crate::cgroup::cg_remove_controller;
+pub use crate::cgroup::cg_set_attr;
 pub use crate::cgroup::cg_type;
 pub use crate::cgroup::CgController;
 pub use crate::cgroup::CG_BASE_DIR;
@@ -18,6 +18,7 @@ fn main() {
     let mut config = prost_build::Config::new();
     config.bytes(["."]);
     config.type_attribute(".", "#[rustfmt::skip]");
+    config.type_attribute(".", "#[derive(serde::Serialize)]");
     config
         .out_dir("src/proto")
         .compile_protos(&["abi.proto"], &["./src/proto"])
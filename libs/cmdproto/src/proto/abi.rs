@@ -1,13 +1,15 @@
 /// Command request from client
 #[rustfmt::skip]
+#[derive(serde::Serialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CommandRequest {
-    #[prost(oneof="command_request::RequestData", tags="1, 2, 3, 4, 5")]
+    #[prost(oneof="command_request::RequestData", tags="1, 2, 3, 4, 5, 6, 7, 8")]
     pub request_data: ::core::option::Option<command_request::RequestData>,
 }
 /// Nested message and enum types in `CommandRequest`.
 pub mod command_request {
     #[rustfmt::skip]
+    #[derive(serde::Serialize)]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum RequestData {
         ///unit lifecycle
@@ -25,10 +27,20 @@ pub mod command_request {
         ///system commands, reboot/shutdown/halt
         #[prost(message, tag="5")]
         Syscomm(super::SysComm),
+        ///transient units
+        #[prost(message, tag="6")]
+        Trans(super::TransientUnit),
+        ///subscribe the changes of units and jobs
+        #[prost(message, tag="7")]
+        Subscribe(super::Subscribe),
+        ///change the properties of a unit at runtime
+        #[prost(message, tag="8")]
+        Setprop(super::SetProperty),
     }
 }
 /// Command Response from server
 #[rustfmt::skip]
+#[derive(serde::Serialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CommandResponse {
     /// stat code; HTTP 2xx/4xx/5xx
@@ -40,18 +52,149 @@ pub struct CommandResponse {
     /// if not 2xx，message include more information
     #[prost(string, tag="3")]
     pub message: ::prost::alloc::string::String,
+    /// the structured result of the query commands, rendered by the client
+    #[prost(oneof="command_response::ResponseData", tags="4, 5, 6, 7, 8")]
+    pub response_data: ::core::option::Option<command_response::ResponseData>,
+}
+/// Nested message and enum types in `CommandResponse`.
+pub mod command_response {
+    /// the structured result of the query commands, rendered by the client
+    #[rustfmt::skip]
+    #[derive(serde::Serialize)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum ResponseData {
+        #[prost(message, tag="4")]
+        Ustatus(super::UnitStatusList),
+        #[prost(message, tag="5")]
+        Ulist(super::UnitList),
+        #[prost(message, tag="6")]
+        Jlist(super::JobList),
+        #[prost(message, tag="7")]
+        Uprops(super::UnitPropertiesList),
+        /// pushed to the subscribers
+        #[prost(message, tag="8")]
+        Event(super::Event),
+    }
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnitStatus {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub description: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub documentation: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub load_state: ::prost::alloc::string::String,
+    #[prost(string, tag="5")]
+    pub active_state: ::prost::alloc::string::String,
+    #[prost(string, tag="6")]
+    pub sub_state: ::prost::alloc::string::String,
+    #[prost(string, tag="7")]
+    pub status_text: ::prost::alloc::string::String,
+    #[prost(string, tag="8")]
+    pub cgroup_path: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="9")]
+    pub processes: ::prost::alloc::vec::Vec<UnitProcess>,
+    /// the latest logs captured by the log collector
+    #[prost(string, repeated, tag="10")]
+    pub logs: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 #[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnitProcess {
+    #[prost(uint32, tag="1")]
+    pub pid: u32,
+    #[prost(string, tag="2")]
+    pub cmdline: ::prost::alloc::string::String,
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnitStatusList {
+    #[prost(message, repeated, tag="1")]
+    pub units: ::prost::alloc::vec::Vec<UnitStatus>,
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnitInfo {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub load_state: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub active_state: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub sub_state: ::prost::alloc::string::String,
+    #[prost(string, tag="5")]
+    pub description: ::prost::alloc::string::String,
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnitList {
+    #[prost(message, repeated, tag="1")]
+    pub units: ::prost::alloc::vec::Vec<UnitInfo>,
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JobInfo {
+    #[prost(uint32, tag="1")]
+    pub id: u32,
+    #[prost(string, tag="2")]
+    pub unit: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub kind: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub stage: ::prost::alloc::string::String,
+    /// the time since the job is created
+    #[prost(uint64, tag="5")]
+    pub waiting_usec: u64,
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JobList {
+    #[prost(message, repeated, tag="1")]
+    pub jobs: ::prost::alloc::vec::Vec<JobInfo>,
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnitProperties {
+    #[prost(string, tag="1")]
+    pub unit: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="2")]
+    pub properties: ::prost::alloc::vec::Vec<UnitProperty>,
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnitPropertiesList {
+    #[prost(message, repeated, tag="1")]
+    pub units: ::prost::alloc::vec::Vec<UnitProperties>,
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UnitComm {
     #[prost(enumeration="unit_comm::Action", tag="1")]
     pub action: i32,
     #[prost(string, repeated, tag="2")]
     pub units: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// only used by KILL
+    #[prost(message, optional, tag="3")]
+    pub kill: ::core::option::Option<KillParam>,
 }
 /// Nested message and enum types in `UnitComm`.
 pub mod unit_comm {
     #[rustfmt::skip]
+    #[derive(serde::Serialize)]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum Action {
@@ -61,9 +204,38 @@ pub mod unit_comm {
         Restart = 3,
         Reload = 4,
         Kill = 5,
+        Show = 6,
+        ResetFailed = 7,
+    }
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct KillParam {
+    #[prost(int32, tag="1")]
+    pub signal: i32,
+    #[prost(enumeration="kill_param::Whom", tag="2")]
+    pub whom: i32,
+    /// queue the signal with the value by sigqueue
+    #[prost(bool, tag="3")]
+    pub queue: bool,
+    #[prost(int32, tag="4")]
+    pub value: i32,
+}
+/// Nested message and enum types in `KillParam`.
+pub mod kill_param {
+    #[rustfmt::skip]
+    #[derive(serde::Serialize)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Whom {
+        All = 0,
+        Main = 1,
+        Control = 2,
     }
 }
 #[rustfmt::skip]
+#[derive(serde::Serialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UnitFile {
     #[prost(enumeration="unit_file::Action", tag="1")]
@@ -74,6 +246,7 @@ pub struct UnitFile {
 /// Nested message and enum types in `UnitFile`.
 pub mod unit_file {
     #[rustfmt::skip]
+    #[derive(serde::Serialize)]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum Action {
@@ -87,16 +260,18 @@ pub mod unit_file {
     }
 }
 #[rustfmt::skip]
+#[derive(serde::Serialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct JobComm {
     #[prost(enumeration="job_comm::Action", tag="1")]
     pub action: i32,
-    #[prost(string, tag="2")]
-    pub job_id: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag="2")]
+    pub job_id: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 /// Nested message and enum types in `JobComm`.
 pub mod job_comm {
     #[rustfmt::skip]
+    #[derive(serde::Serialize)]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum Action {
@@ -105,6 +280,7 @@ pub mod job_comm {
     }
 }
 #[rustfmt::skip]
+#[derive(serde::Serialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct MngrComm {
     #[prost(enumeration="mngr_comm::Action", tag="1")]
@@ -113,6 +289,7 @@ pub struct MngrComm {
 /// Nested message and enum types in `MngrComm`.
 pub mod mngr_comm {
     #[rustfmt::skip]
+    #[derive(serde::Serialize)]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum Action {
@@ -122,6 +299,7 @@ pub mod mngr_comm {
     }
 }
 #[rustfmt::skip]
+#[derive(serde::Serialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SysComm {
     #[prost(enumeration="sys_comm::Action", tag="1")]
@@ -132,6 +310,7 @@ pub struct SysComm {
 /// Nested message and enum types in `SysComm`.
 pub mod sys_comm {
     #[rustfmt::skip]
+    #[derive(serde::Serialize)]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum Action {
@@ -143,3 +322,82 @@ pub mod sys_comm {
         Hibernate = 5,
     }
 }
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TransientUnit {
+    #[prost(string, tag="1")]
+    pub unit: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="2")]
+    pub properties: ::prost::alloc::vec::Vec<UnitProperty>,
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnitProperty {
+    #[prost(string, tag="1")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub value: ::prost::alloc::string::String,
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetProperty {
+    #[prost(string, tag="1")]
+    pub unit: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="2")]
+    pub properties: ::prost::alloc::vec::Vec<UnitProperty>,
+    /// the dropin is written under /run and lost after reboot
+    #[prost(bool, tag="3")]
+    pub runtime: bool,
+}
+/// the events are sent on the connection after the response, until the client disconnects
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Subscribe {
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Event {
+    #[prost(oneof="event::Event", tags="1, 2, 3")]
+    pub event: ::core::option::Option<event::Event>,
+}
+/// Nested message and enum types in `Event`.
+pub mod event {
+    #[rustfmt::skip]
+    #[derive(serde::Serialize)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Event {
+        #[prost(message, tag="1")]
+        UnitState(super::UnitStateChange),
+        #[prost(message, tag="2")]
+        JobNew(super::JobEvent),
+        #[prost(message, tag="3")]
+        JobRemoved(super::JobEvent),
+    }
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnitStateChange {
+    #[prost(string, tag="1")]
+    pub unit: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub old_state: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub new_state: ::prost::alloc::string::String,
+}
+#[rustfmt::skip]
+#[derive(serde::Serialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JobEvent {
+    #[prost(uint32, tag="1")]
+    pub id: u32,
+    #[prost(string, tag="2")]
+    pub unit: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub kind: ::prost::alloc::string::String,
+}
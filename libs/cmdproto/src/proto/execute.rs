@@ -12,14 +12,16 @@
 
 //! Convert the command request into the corresponding execution action
 use super::{
-    mngr_comm, sys_comm, unit_comm, CommandRequest, CommandResponse, MngrComm, RequestData,
-    SysComm, UnitComm, UnitFile,
+    command_response::ResponseData, job_comm, kill_param, mngr_comm, sys_comm, unit_comm,
+    CommandRequest, CommandResponse, JobComm, JobList, MngrComm, RequestData, SetProperty,
+    Subscribe, SysComm, TransientUnit, UnitComm, UnitFile, UnitList, UnitProperties,
+    UnitPropertiesList, UnitStatus, UnitStatusList,
 };
 
 use crate::error::*;
 use http::StatusCode;
-use nix;
-use std::{fmt::Display, rc::Rc};
+use nix::{self, sys::socket::UnixCredentials};
+use std::{fmt::Display, os::unix::net::UnixStream, rc::Rc};
 
 pub(crate) trait Executer {
     /// deal Command，return Response
@@ -34,8 +36,6 @@ pub(crate) trait Executer {
 pub trait ExecuterAction {
     #[allow(missing_docs)]
     type Error: Display + Into<nix::Error>;
-    #[allow(missing_docs)]
-    type Status: Display + Into<nix::Error>;
     /// start the unit_name
     fn start(&self, unit_name: &str) -> Result<(), Self::Error>;
     /// stop the unit_name
@@ -44,10 +44,34 @@ pub trait ExecuterAction {
     fn restart(&self, unit_name: &str) -> Result<(), Self::Error>;
     /// reload the unit_name
     fn reload(&self, unit_name: &str) -> Result<(), Self::Error>;
+    /// reset the failed state of the unit_name
+    fn reset_failed(&self, unit_name: &str) -> Result<(), Self::Error>;
+    /// send the signal to the processes of unit_name, queue it with the value if it's given
+    fn kill(
+        &self,
+        unit_name: &str,
+        whom: kill_param::Whom,
+        signal: i32,
+        value: Option<i32>,
+    ) -> Result<(), Self::Error>;
     /// show the status of unit_name
-    fn status(&self, unit_name: &str) -> Result<Self::Status, Self::Error>;
+    fn status(&self, unit_name: &str) -> Result<UnitStatus, Self::Error>;
+    /// show all the properties of unit_name
+    fn show(&self, unit_name: &str) -> Result<UnitProperties, Self::Error>;
+    /// change the properties of unit_name and save them in a dropin,
+    /// the dropin is lost after reboot if runtime is true
+    fn set_property(
+        &self,
+        unit_name: &str,
+        properties: &[(String, String)],
+        runtime: bool,
+    ) -> Result<(), Self::Error>;
     /// list all units
-    fn list_units(&self) -> Result<String, Self::Error>;
+    fn list_units(&self) -> Result<UnitList, Self::Error>;
+    /// list all jobs
+    fn list_jobs(&self) -> Result<JobList, Self::Error>;
+    /// cancel the job, all the waiting jobs are cancelled if job_id is none
+    fn cancel_job(&self, job_id: Option<u32>) -> Result<(), Self::Error>;
     /// suspend host
     fn suspend(&self) -> Result<i32, Self::Error>;
     /// poweroff host
@@ -64,18 +88,45 @@ pub trait ExecuterAction {
     fn mask(&self, unit_name: &str) -> Result<(), Self::Error>;
     /// unmask unit_name
     fn unmask(&self, unit_name: &str) -> Result<(), Self::Error>;
+    /// create the transient unit_name from the properties and start it
+    fn start_transient(
+        &self,
+        unit_name: &str,
+        properties: &[(String, String)],
+    ) -> Result<(), Self::Error>;
+    /// send the changes of units and jobs on the stream, until the peer disconnects
+    fn subscribe(&self, stream: UnixStream);
     /// daemon-reload
     fn daemon_reload(&self);
     /// daemon-reexec
     fn daemon_reexec(&self);
+    /// check whether the peer is allowed to send the mutating requests
+    fn is_privileged(&self, cred: &UnixCredentials) -> bool;
 }
 
 /// Depending on the type of request
-pub(crate) fn dispatch<T>(cmd: CommandRequest, manager: Rc<T>) -> CommandResponse
+pub(crate) fn dispatch<T>(
+    cmd: CommandRequest,
+    manager: Rc<T>,
+    cred: Option<UnixCredentials>,
+) -> CommandResponse
 where
     T: ExecuterAction,
 {
     println!("commandRequest :{cmd:?}");
+    let privileged = match cred {
+        Some(c) => manager.is_privileged(&c),
+        None => false,
+    };
+    if !cmd.is_read_only() && !privileged {
+        return CommandResponse {
+            status: StatusCode::FORBIDDEN.as_u16() as _,
+            error_code: nix::Error::EPERM as u32,
+            message: String::from("Access denied: root privileges are required."),
+            ..Default::default()
+        };
+    }
+
     let call_back = |unit_name: &str| {
         // If users didn't specify the unit type, treat it as
 
@@ -89,8 +140,12 @@ where
     let res = match cmd.request_data {
         Some(RequestData::Ucomm(param)) => param.execute(manager, Some(call_back)),
         Some(RequestData::Mcomm(param)) => param.execute(manager, None),
+        Some(RequestData::Jcomm(param)) => param.execute(manager, None),
         Some(RequestData::Syscomm(param)) => param.execute(manager, Some(call_back)),
         Some(RequestData::Ufile(param)) => param.execute(manager, Some(call_back)),
+        Some(RequestData::Trans(param)) => param.execute(manager, Some(call_back)),
+        Some(RequestData::Subscribe(param)) => param.execute(manager, None),
+        Some(RequestData::Setprop(param)) => param.execute(manager, Some(call_back)),
         _ => CommandResponse::default(),
     };
     println!("CommandResponse :{res:?}");
@@ -112,6 +167,7 @@ impl Executer for UnitComm {
         let mut reply = String::new();
         let mut units: Vec<String> = Vec::new();
         let mut error_code: u32 = 0;
+        let mut response_data = None;
         for unit_name in &self.units {
             if call_back.is_none() {
                 units.push(unit_name.to_string());
@@ -123,19 +179,41 @@ impl Executer for UnitComm {
 
         match self.action() {
             unit_comm::Action::Status => {
+                let mut status_list = Vec::new();
                 for unit in units {
-                    new_line_break(&mut reply);
                     match manager.status(&unit) {
                         Ok(status) => {
-                            reply += &status.to_string();
-                            error_code = status.into() as u32 | ERROR_CODE_MASK_PRINT_STDOUT;
+                            // systemd will return 3 if the unit's state is failed or inactive.
+                            if status.active_state == "failed" || status.active_state == "inactive"
+                            {
+                                error_code = 3;
+                            }
+                            status_list.push(status);
                         }
                         Err(e) => {
+                            new_line_break(&mut reply);
                             reply = format!("{reply}Failed to show the status of {unit}: {e}");
                             error_code = e.into() as u32;
                         }
                     }
                 }
+                response_data = Some(ResponseData::Ustatus(UnitStatusList { units: status_list }));
+            }
+            unit_comm::Action::Show => {
+                let mut props_list = Vec::new();
+                for unit in units {
+                    match manager.show(&unit) {
+                        Ok(props) => props_list.push(props),
+                        Err(e) => {
+                            new_line_break(&mut reply);
+                            reply = format!("{reply}Failed to show {unit}: {e}");
+                            error_code = e.into() as u32;
+                        }
+                    }
+                }
+                response_data = Some(ResponseData::Uprops(UnitPropertiesList {
+                    units: props_list,
+                }));
             }
             unit_comm::Action::Start => {
                 for unit in units {
@@ -173,12 +251,38 @@ impl Executer for UnitComm {
                     }
                 }
             }
-            _ => todo!(),
+            unit_comm::Action::ResetFailed => {
+                for unit in units {
+                    if let Err(e) = manager.reset_failed(&unit) {
+                        new_line_break(&mut reply);
+                        reply = format!("{reply}Failed to reset {unit}: {e}");
+                        error_code = e.into() as u32;
+                    }
+                }
+            }
+            unit_comm::Action::Kill => match &self.kill {
+                Some(param) => {
+                    for unit in units {
+                        if let Err(e) =
+                            manager.kill(&unit, param.whom(), param.signal, param.queued_value())
+                        {
+                            new_line_break(&mut reply);
+                            reply = format!("{reply}Failed to kill {unit}: {e}");
+                            error_code = e.into() as u32;
+                        }
+                    }
+                }
+                None => {
+                    reply = "The signal to send is not specified".to_string();
+                    error_code = nix::Error::EINVAL as u32;
+                }
+            },
         }
         CommandResponse {
             status: StatusCode::OK.as_u16() as _,
             error_code,
             message: reply,
+            response_data,
         }
     }
 }
@@ -209,10 +313,11 @@ impl Executer for MngrComm {
             }
 
             mngr_comm::Action::Listunits => match manager.list_units() {
-                Ok(m) => CommandResponse {
+                Ok(list) => CommandResponse {
                     status: StatusCode::OK.as_u16() as _,
                     error_code: 0,
-                    message: m,
+                    response_data: Some(ResponseData::Ulist(list)),
+                    ..Default::default()
                 },
                 Err(e) => {
                     let error_message = format!("Failed to list all units:{e}");
@@ -220,9 +325,83 @@ impl Executer for MngrComm {
                         status: StatusCode::INTERNAL_SERVER_ERROR.as_u16() as _,
                         error_code: e.into() as u32,
                         message: error_message,
+                        ..Default::default()
+                    }
+                }
+            },
+        }
+    }
+}
+
+impl Executer for JobComm {
+    fn execute(
+        self,
+        manager: Rc<impl ExecuterAction>,
+        _call_back: Option<fn(&str) -> String>,
+    ) -> CommandResponse {
+        match self.action() {
+            job_comm::Action::List => match manager.list_jobs() {
+                Ok(list) => CommandResponse {
+                    status: StatusCode::OK.as_u16() as _,
+                    error_code: 0,
+                    response_data: Some(ResponseData::Jlist(list)),
+                    ..Default::default()
+                },
+                Err(e) => {
+                    let error_message = format!("Failed to list all jobs:{e}");
+                    CommandResponse {
+                        status: StatusCode::INTERNAL_SERVER_ERROR.as_u16() as _,
+                        error_code: e.into() as u32,
+                        message: error_message,
+                        ..Default::default()
                     }
                 }
             },
+            job_comm::Action::Cancel => {
+                if self.job_id.is_empty() {
+                    return match manager.cancel_job(None) {
+                        Ok(_) => CommandResponse {
+                            status: StatusCode::OK.as_u16() as _,
+                            error_code: 0,
+                            ..Default::default()
+                        },
+                        Err(e) => {
+                            let error_message = format!("Failed to cancel all jobs: {e}");
+                            CommandResponse {
+                                status: StatusCode::INTERNAL_SERVER_ERROR.as_u16() as _,
+                                error_code: e.into() as u32,
+                                message: error_message,
+                                ..Default::default()
+                            }
+                        }
+                    };
+                }
+
+                let mut reply = String::new();
+                let mut error_code: u32 = 0;
+                for job_id in &self.job_id {
+                    let id = match job_id.parse::<u32>() {
+                        Ok(id) => id,
+                        Err(_) => {
+                            new_line_break(&mut reply);
+                            reply = format!("{reply}Failed to parse job id {job_id}");
+                            error_code = nix::Error::EINVAL as u32;
+                            continue;
+                        }
+                    };
+                    if let Err(e) = manager.cancel_job(Some(id)) {
+                        new_line_break(&mut reply);
+                        reply = format!("{reply}Failed to cancel job {id}: {e}");
+                        error_code = e.into() as u32;
+                    }
+                }
+                CommandResponse {
+                    status: StatusCode::OK.as_u16() as _,
+                    error_code,
+                    message: reply,
+                    ..Default::default()
+                }
+            }
         }
     }
 }
@@ -260,6 +439,7 @@ impl Executer for SysComm {
                 status: StatusCode::INTERNAL_SERVER_ERROR.as_u16() as _,
                 error_code: e.into() as u32,
                 message: String::from("error."),
+                ..Default::default()
             },
         }
     }
@@ -319,13 +499,258 @@ impl Executer for UnitFile {
                     }
                 }
             }
-            _ => todo!(),
+            action => {
+                return CommandResponse {
+                    status: StatusCode::NOT_IMPLEMENTED.as_u16() as _,
+                    error_code: nix::Error::ENOTSUP as u32,
+                    message: format!("{action:?} is not supported"),
+                    ..Default::default()
+                };
+            }
         };
 
         CommandResponse {
             status: StatusCode::OK.as_u16() as _,
             error_code,
             message: reply,
+            ..Default::default()
+        }
+    }
+}
+
+impl Executer for TransientUnit {
+    fn execute(
+        self,
+        manager: Rc<impl ExecuterAction>,
+        call_back: Option<fn(&str) -> String>,
+    ) -> CommandResponse {
+        let unit = match call_back {
+            Some(call_back) => call_back(&self.unit),
+            None => self.unit,
+        };
+        let properties: Vec<(String, String)> = self
+            .properties
+            .into_iter()
+            .map(|p| (p.key, p.value))
+            .collect();
+
+        match manager.start_transient(&unit, &properties) {
+            Ok(_) => CommandResponse {
+                status: StatusCode::OK.as_u16() as _,
+                error_code: 0,
+                message: format!("Running as unit: {unit}"),
+                ..Default::default()
+            },
+            Err(e) => {
+                let message = format!("Failed to start transient unit {unit}: {e}");
+                CommandResponse {
+                    status: StatusCode::INTERNAL_SERVER_ERROR.as_u16() as _,
+                    error_code: e.into() as u32,
+                    message,
+                    ..Default::default()
+                }
+            }
+        }
+    }
+}
+
+impl Executer for SetProperty {
+    fn execute(
+        self,
+        manager: Rc<impl ExecuterAction>,
+        call_back: Option<fn(&str) -> String>,
+    ) -> CommandResponse {
+        let unit = match call_back {
+            Some(call_back) => call_back(&self.unit),
+            None => self.unit,
+        };
+        let properties: Vec<(String, String)> = self
+            .properties
+            .into_iter()
+            .map(|p| (p.key, p.value))
+            .collect();
+
+        match manager.set_property(&unit, &properties, self.runtime) {
+            Ok(_) => CommandResponse {
+                status: StatusCode::OK.as_u16() as _,
+                error_code: 0,
+                ..Default::default()
+            },
+            Err(e) => {
+                let message = format!("Failed to set the properties of {unit}: {e}");
+                CommandResponse {
+                    status: StatusCode::INTERNAL_SERVER_ERROR.as_u16() as _,
+                    error_code: e.into() as u32,
+                    message,
+                    ..Default::default()
+                }
+            }
+        }
+    }
+}
+
+impl Executer for Subscribe {
+    fn execute(
+        self,
+        _manager: Rc<impl ExecuterAction>,
+        _call_back: Option<fn(&str) -> String>,
+    ) -> CommandResponse {
+        // the stream is handed over to the manager after the response is sent
+        CommandResponse {
+            status: StatusCode::OK.as_u16() as _,
+            error_code: 0,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the manager with the units in the given active states
+    struct MockManager {
+        units: Vec<(&'static str, &'static str)>,
+    }
+
+    impl ExecuterAction for MockManager {
+        type Error = nix::Error;
+
+        fn start(&self, _unit_name: &str) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn stop(&self, _unit_name: &str) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn restart(&self, _unit_name: &str) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn reload(&self, _unit_name: &str) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn reset_failed(&self, _unit_name: &str) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn kill(
+            &self,
+            _unit_name: &str,
+            _whom: kill_param::Whom,
+            _signal: i32,
+            _value: Option<i32>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn status(&self, unit_name: &str) -> Result<UnitStatus, Self::Error> {
+            match self.units.iter().find(|(name, _)| *name == unit_name) {
+                Some((name, active_state)) => Ok(UnitStatus {
+                    name: name.to_string(),
+                    active_state: active_state.to_string(),
+                    ..Default::default()
+                }),
+                None => Err(nix::Error::ENOENT),
+            }
+        }
+        fn show(&self, _unit_name: &str) -> Result<UnitProperties, Self::Error> {
+            Err(nix::Error::ENOTSUP)
+        }
+        fn set_property(
+            &self,
+            _unit_name: &str,
+            _properties: &[(String, String)],
+            _runtime: bool,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn list_units(&self) -> Result<UnitList, Self::Error> {
+            Ok(UnitList::default())
+        }
+        fn list_jobs(&self) -> Result<JobList, Self::Error> {
+            Ok(JobList::default())
+        }
+        fn cancel_job(&self, _job_id: Option<u32>) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn suspend(&self) -> Result<i32, Self::Error> {
+            Ok(0)
+        }
+        fn poweroff(&self) -> Result<i32, Self::Error> {
+            Ok(0)
         }
+        fn reboot(&self) -> Result<i32, Self::Error> {
+            Ok(0)
+        }
+        fn halt(&self) -> Result<i32, Self::Error> {
+            Ok(0)
+        }
+        fn disable(&self, _unit_name: &str) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn enable(&self, _unit_name: &str) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn mask(&self, _unit_name: &str) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn unmask(&self, _unit_name: &str) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn start_transient(
+            &self,
+            _unit_name: &str,
+            _properties: &[(String, String)],
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn subscribe(&self, _stream: UnixStream) {}
+        fn daemon_reload(&self) {}
+        fn daemon_reexec(&self) {}
+        fn is_privileged(&self, _cred: &UnixCredentials) -> bool {
+            false
+        }
+    }
+
+    fn status(units: &[&str]) -> CommandResponse {
+        let manager = Rc::new(MockManager {
+            units: vec![
+                ("running.service", "active"),
+                ("dead.service", "inactive"),
+                ("failed.service", "failed"),
+            ],
+        });
+        let units = units.iter().map(|u| u.to_string()).collect();
+        let req = CommandRequest::new_unitcomm(unit_comm::Action::Status, units);
+        // the status is read only, it's allowed without the credentials
+        dispatch(req, manager, None)
+    }
+
+    fn status_names(res: &CommandResponse) -> Vec<String> {
+        match &res.response_data {
+            Some(ResponseData::Ustatus(list)) => {
+                list.units.iter().map(|u| u.name.clone()).collect()
+            }
+            _ => panic!("no status in the response"),
+        }
+    }
+
+    #[test]
+    fn test_status_error_code() {
+        // the unit type defaults to service
+        let res = status(&["running"]);
+        assert_eq!(res.error_code, 0);
+        assert_eq!(status_names(&res), vec!["running.service"]);
+
+        // 3 is returned if any of the units is inactive or failed, like systemctl
+        for unit in ["dead.service", "failed.service"] {
+            let res = status(&["running.service", unit]);
+            assert_eq!(res.status, StatusCode::OK.as_u16() as u32);
+            assert_eq!(res.error_code, 3);
+            assert_eq!(status_names(&res), vec!["running.service", unit]);
+        }
+
+        // the status of the other units is still returned if one is not found
+        let res = status(&["dead.service", "none.service"]);
+        assert_eq!(res.error_code, nix::Error::ENOENT as u32);
+        assert!(res.message.contains("none.service"));
+        assert_eq!(status_names(&res), vec!["dead.service"]);
     }
 }
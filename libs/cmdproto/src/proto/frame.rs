@@ -12,6 +12,7 @@
 
 //! Encapsulate the command request into a frame
 use crate::error::*;
+use nix::sys::socket::UnixCredentials;
 use prost::bytes::{BufMut, BytesMut};
 use prost::Message;
 use std::{
@@ -20,12 +21,14 @@ use std::{
 };
 
 use super::execute::ExecuterAction;
-use super::{execute, CommandRequest, CommandResponse};
+use super::{execute, CommandRequest, CommandResponse, RequestData};
 
 /// Reading buffer size used in `fn read` of `std::io::Read`
 const MAX_FRAME: usize = 1024;
 /// The length of u8 to represent usize
 const USIZE_TO_U8_LENGTH: usize = 8;
+/// The max length of a message, the longer ones are rejected before reading them
+const MAX_MSG_LEN: usize = 16 * 1024 * 1024;
 
 /// Frame : encode/decode
 pub trait FrameCoder
@@ -57,18 +60,26 @@ where
     let mut msg_len = [0_u8; USIZE_TO_U8_LENGTH];
     stream.read_exact(&mut msg_len).context(IoSnafu)?;
     let msg_len = get_msg_len(msg_len);
+    if msg_len > MAX_MSG_LEN {
+        return Err(Error::ReadStream {
+            msg: format!("the message length {msg_len} exceeds the limit {MAX_MSG_LEN}"),
+        });
+    }
 
-    // 2. Got the message
+    // 2. Got the message, never read beyond it, the next frame may follow in the stream
     let mut tmp = vec![0; MAX_FRAME];
     let mut cur_len: usize = 0;
-    loop {
-        match stream.read(&mut tmp) {
+    while cur_len < msg_len {
+        let want = std::cmp::min(MAX_FRAME, msg_len - cur_len);
+        match stream.read(&mut tmp[..want]) {
+            Ok(0) => {
+                return Err(Error::ReadStream {
+                    msg: "unexpected end of the stream".to_string(),
+                });
+            }
             Ok(len) => {
                 cur_len += len;
                 buf.put_slice(&tmp[..len]);
-                if len < MAX_FRAME || cur_len >= msg_len {
-                    break;
-                }
             }
             Err(e) => {
                 return Err(Error::ReadStream { msg: e.to_string() });
@@ -78,6 +89,68 @@ where
     Ok(())
 }
 
+/// take the first whole frame out of the received data, None is returned if the frame is
+/// not received completely yet, so that the stream can be read in pieces without blocking
+pub fn take_frame(buf: &mut Vec<u8>) -> Result<Option<Vec<u8>>> {
+    if buf.len() < USIZE_TO_U8_LENGTH {
+        return Ok(None);
+    }
+    let mut msg_len = [0_u8; USIZE_TO_U8_LENGTH];
+    msg_len.copy_from_slice(&buf[..USIZE_TO_U8_LENGTH]);
+    let msg_len = get_msg_len(msg_len);
+    if msg_len > MAX_MSG_LEN {
+        return Err(Error::ReadStream {
+            msg: format!("the message length {msg_len} exceeds the limit {MAX_MSG_LEN}"),
+        });
+    }
+    if buf.len() < USIZE_TO_U8_LENGTH + msg_len {
+        return Ok(None);
+    }
+
+    let frame = buf[USIZE_TO_U8_LENGTH..USIZE_TO_U8_LENGTH + msg_len].to_vec();
+    buf.drain(..USIZE_TO_U8_LENGTH + msg_len);
+    Ok(Some(frame))
+}
+
+/// encode the message into a frame with its length ahead
+pub fn encode_frame<M: FrameCoder>(msg: &M) -> Result<Vec<u8>> {
+    let mut buf = BytesMut::new();
+    msg.encode_frame(&mut buf)?;
+    let mut frame = msg_len_vec(buf.len()).to_vec();
+    frame.extend_from_slice(&buf);
+    Ok(frame)
+}
+
+/// handle the request frame in server-side, cred is the credentials of the peer process,
+/// return the response frame and whether the client subscribes the events
+pub fn handle_request<T: ExecuterAction>(
+    frame: &[u8],
+    manager: Rc<T>,
+    cred: Option<UnixCredentials>,
+) -> Result<(Vec<u8>, bool)> {
+    let cmd = CommandRequest::decode_frame(&mut BytesMut::from(frame))?;
+    let subscribe = matches!(cmd.request_data, Some(RequestData::Subscribe(_)));
+    let res = execute::dispatch(cmd, manager, cred);
+    let subscribed = subscribe && res.error_code == 0;
+    Ok((encode_frame(&res)?, subscribed))
+}
+
+/// write frame to stream
+pub fn write_frame<S, M>(stream: &mut S, msg: &M) -> Result<()>
+where
+    S: Write,
+    M: FrameCoder,
+{
+    let mut buf = BytesMut::new();
+    msg.encode_frame(&mut buf)?;
+    let encoded = buf.freeze();
+    let msg_len = msg_len_vec(encoded.len());
+    stream.write_all(&msg_len).context(IoSnafu)?;
+    stream.write_all(&encoded).context(IoSnafu)?;
+    stream.flush().context(IoSnafu)?;
+    Ok(())
+}
+
 fn msg_len_vec(len: usize) -> [u8; USIZE_TO_U8_LENGTH] {
     let res = len.to_le_bytes();
     assert_eq!(res.len(), USIZE_TO_U8_LENGTH);
@@ -92,6 +165,7 @@ fn get_msg_len(message: [u8; USIZE_TO_U8_LENGTH]) -> usize {
 pub struct ProstServerStream<S, T> {
     inner: S,
     manager: Rc<T>,
+    cred: Option<UnixCredentials>,
 }
 
 /// Handle read and write of client-side socket
@@ -104,39 +178,28 @@ where
     S: Read + Write + Unpin + Send,
     T: ExecuterAction,
 {
-    /// new ProstServerStream
-    pub fn new(stream: S, manager: Rc<T>) -> Self {
+    /// new ProstServerStream, cred is the credentials of the peer process
+    pub fn new(stream: S, manager: Rc<T>, cred: Option<UnixCredentials>) -> Self {
         Self {
             inner: stream,
             manager,
+            cred,
         }
     }
 
-    /// process frame in server-side
-    pub fn process(mut self) -> Result<()> {
-        if let Ok(cmd) = self.recv() {
-            let res = execute::dispatch(cmd, Rc::clone(&self.manager));
-            self.send(res)?;
-        };
-        Ok(())
-    }
-
-    fn send(&mut self, msg: CommandResponse) -> Result<()> {
-        let mut buf = BytesMut::new();
-        msg.encode_frame(&mut buf)?;
-        let encoded = buf.freeze();
-        let msg_len = msg_len_vec(encoded.len());
-        self.inner.write_all(&msg_len).context(IoSnafu)?;
-        self.inner.write_all(&encoded).context(IoSnafu)?;
-        self.inner.flush().context(IoSnafu)?;
-        Ok(())
-    }
-
-    fn recv(&mut self) -> Result<CommandRequest> {
+    /// process frame in server-side, the stream is returned if the client subscribes
+    /// the events, which are sent on it later
+    pub fn process(mut self) -> Result<Option<S>> {
         let mut buf = BytesMut::new();
-        let stream = &mut self.inner;
-        read_frame(stream, &mut buf)?;
-        CommandRequest::decode_frame(&mut buf)
+        if read_frame(&mut self.inner, &mut buf).is_ok() {
+            let (res, subscribed) = handle_request(&buf, Rc::clone(&self.manager), self.cred)?;
+            self.inner.write_all(&res).context(IoSnafu)?;
+            self.inner.flush().context(IoSnafu)?;
+            if subscribed {
+                return Ok(Some(self.inner));
+            }
+        };
+        Ok(None)
     }
 }
 
@@ -156,15 +219,13 @@ where
         self.recv()
     }
 
+    /// receive the next event pushed by the server after subscribing
+    pub fn next_event(&mut self) -> Result<CommandResponse> {
+        self.recv()
+    }
+
     fn send(&mut self, msg: CommandRequest) -> Result<()> {
-        let mut buf = BytesMut::new();
-        msg.encode_frame(&mut buf)?;
-        let encoded = buf.freeze();
-        let msg_len = msg_len_vec(encoded.len());
-        self.inner.write_all(&msg_len).context(IoSnafu)?;
-        self.inner.write_all(&encoded).context(IoSnafu)?;
-        self.inner.flush().context(IoSnafu)?;
-        Ok(())
+        write_frame(&mut self.inner, &msg)
     }
 
     fn recv(&mut self) -> Result<CommandResponse> {
@@ -179,37 +240,73 @@ where
 mod tests {
     use super::super::abi::unit_comm::Action as UnitAction;
     use super::*;
-    use std::net::{SocketAddr, TcpStream};
-    use std::thread;
-    use std::time::Duration;
+    use std::os::unix::net::UnixStream;
 
     #[test]
     fn test_send_and_recv() {
-        thread::spawn(move || {
-            thread::sleep(Duration::from_secs(1));
-            let addrs = [
-                SocketAddr::from(([127, 0, 0, 1], 9528)),
-                SocketAddr::from(([127, 0, 0, 1], 9529)),
-            ];
-            let stream = TcpStream::connect(&addrs[..]).unwrap();
-            let mut client = ProstClientStream::new(stream);
-            let cmd =
-                CommandRequest::new_unitcomm(UnitAction::Start, vec!["test.service".to_string()]);
-            let _ = client.execute(cmd).unwrap();
-        });
+        let (client_sock, mut server_sock) = UnixStream::pair().unwrap();
+        let mut client = ProstClientStream::new(client_sock);
+        let cmd = CommandRequest::new_unitcomm(UnitAction::Start, vec!["test.service".to_string()]);
+        client.send(cmd.clone()).unwrap();
 
-        let addrs = [
-            SocketAddr::from(([127, 0, 0, 1], 9528)),
-            SocketAddr::from(([127, 0, 0, 1], 9529)),
-        ];
-        let fd = std::net::TcpListener::bind(&addrs[..]).unwrap();
-        loop {
-            for stream in fd.incoming() {
-                match stream {
-                    Err(e) => panic!("failed: {e}"),
-                    Ok(_stream) => return,
-                }
-            }
+        let mut buf = BytesMut::new();
+        read_frame(&mut server_sock, &mut buf).unwrap();
+        let req = CommandRequest::decode_frame(&mut buf).unwrap();
+        assert_eq!(req, cmd);
+    }
+
+    #[test]
+    fn test_read_frames_in_stream() {
+        let (mut client_sock, mut server_sock) = UnixStream::pair().unwrap();
+        let first = CommandRequest::new_unitcomm(UnitAction::Start, vec!["a.service".to_string()]);
+        let second = CommandRequest::new_unitcomm(UnitAction::Stop, vec!["b.service".to_string()]);
+        write_frame(&mut client_sock, &first).unwrap();
+        write_frame(&mut client_sock, &second).unwrap();
+
+        let mut buf = BytesMut::new();
+        read_frame(&mut server_sock, &mut buf).unwrap();
+        assert_eq!(CommandRequest::decode_frame(&mut buf).unwrap(), first);
+        let mut buf = BytesMut::new();
+        read_frame(&mut server_sock, &mut buf).unwrap();
+        assert_eq!(CommandRequest::decode_frame(&mut buf).unwrap(), second);
+
+        drop(client_sock);
+        let mut buf = BytesMut::new();
+        assert!(read_frame(&mut server_sock, &mut buf).is_err());
+    }
+
+    #[test]
+    fn test_take_frame() {
+        let cmd = CommandRequest::new_unitcomm(UnitAction::Start, vec!["a.service".to_string()]);
+        let frame = encode_frame(&cmd).unwrap();
+
+        // the frame received in pieces is taken once it's complete
+        let mut buf = Vec::new();
+        for b in &frame[..frame.len() - 1] {
+            buf.push(*b);
+            assert!(take_frame(&mut buf).unwrap().is_none());
         }
+        buf.extend_from_slice(&frame[frame.len() - 1..]);
+        buf.extend_from_slice(&frame[..3]);
+        let msg = take_frame(&mut buf).unwrap().unwrap();
+        let msg = CommandRequest::decode_frame(&mut BytesMut::from(&msg[..])).unwrap();
+        assert_eq!(msg, cmd);
+        // the beginning of the next frame is kept
+        assert_eq!(&buf[..], &frame[..3]);
+
+        let mut buf = msg_len_vec(MAX_MSG_LEN + 1).to_vec();
+        assert!(take_frame(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_read_frame_too_long() {
+        let (mut client_sock, mut server_sock) = UnixStream::pair().unwrap();
+        client_sock
+            .write_all(&msg_len_vec(MAX_MSG_LEN + 1))
+            .unwrap();
+
+        let mut buf = BytesMut::new();
+        assert!(read_frame(&mut server_sock, &mut buf).is_err());
+        assert!(buf.is_empty());
     }
 }
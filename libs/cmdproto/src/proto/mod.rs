@@ -24,6 +24,9 @@ pub use frame::ProstServerStream;
 pub use http::StatusCode;
 use std::fmt;
 
+/// the unix socket that sysmaster listens on for the control commands
+pub const SCTL_SOCKET: &str = "/run/sysmaster/sctl";
+
 impl CommandRequest {
     /// Create a new command request for unit
     pub fn new_unitcomm(action: unit_comm::Action, units: Vec<String>) -> Self {
@@ -31,6 +34,29 @@ impl CommandRequest {
             request_data: Some(RequestData::Ucomm(UnitComm {
                 action: action.into(),
                 units,
+                kill: None,
+            })),
+        }
+    }
+
+    /// Create a new command request to send the signal to the processes of the units,
+    /// the signal is queued with the value if it's given
+    pub fn new_kill(
+        units: Vec<String>,
+        signal: i32,
+        whom: kill_param::Whom,
+        value: Option<i32>,
+    ) -> Self {
+        Self {
+            request_data: Some(RequestData::Ucomm(UnitComm {
+                action: unit_comm::Action::Kill.into(),
+                units,
+                kill: Some(KillParam {
+                    signal,
+                    whom: whom.into(),
+                    queue: value.is_some(),
+                    value: value.unwrap_or_default(),
+                }),
             })),
         }
     }
@@ -63,6 +89,81 @@ impl CommandRequest {
             })),
         }
     }
+
+    /// Create a new command request for job
+    pub fn new_jobcomm(action: job_comm::Action, job_id: Vec<String>) -> Self {
+        Self {
+            request_data: Some(RequestData::Jcomm(JobComm {
+                action: action.into(),
+                job_id,
+            })),
+        }
+    }
+
+    /// Create a new command request for transient unit
+    pub fn new_transient(unit: String, properties: Vec<(String, String)>) -> Self {
+        Self {
+            request_data: Some(RequestData::Trans(TransientUnit {
+                unit,
+                properties: properties
+                    .into_iter()
+                    .map(|(key, value)| UnitProperty { key, value })
+                    .collect(),
+            })),
+        }
+    }
+
+    /// Create a new command request to change the properties of the unit,
+    /// they are lost after reboot if runtime is true
+    pub fn new_set_property(
+        unit: String,
+        properties: Vec<(String, String)>,
+        runtime: bool,
+    ) -> Self {
+        Self {
+            request_data: Some(RequestData::Setprop(SetProperty {
+                unit,
+                properties: properties
+                    .into_iter()
+                    .map(|(key, value)| UnitProperty { key, value })
+                    .collect(),
+                runtime,
+            })),
+        }
+    }
+
+    /// Create a new command request for subscribing the events
+    pub fn new_subscribe() -> Self {
+        Self {
+            request_data: Some(RequestData::Subscribe(Subscribe {})),
+        }
+    }
+}
+
+impl CommandRequest {
+    /// Whether the request only queries the state, read-only requests are allowed for everyone
+    pub fn is_read_only(&self) -> bool {
+        match &self.request_data {
+            Some(RequestData::Ucomm(param)) => matches!(
+                param.action(),
+                unit_comm::Action::Status | unit_comm::Action::Show
+            ),
+            Some(RequestData::Mcomm(param)) => param.action() == mngr_comm::Action::Listunits,
+            Some(RequestData::Jcomm(param)) => param.action() == job_comm::Action::List,
+            _ => false,
+        }
+    }
+}
+
+impl KillParam {
+    /// the value to queue with the signal, None if the signal is sent by kill
+    pub fn queued_value(&self) -> Option<i32> {
+        if self.queue {
+            Some(self.value)
+        } else {
+            None
+        }
+    }
 }
 
 impl fmt::Display for sys_comm::Action {
@@ -70,3 +171,68 @@ impl fmt::Display for sys_comm::Action {
         write!(f, "{}", format!("{self:?}").to_lowercase())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_read_only() {
+        let req = CommandRequest::new_unitcomm(unit_comm::Action::Status, vec![]);
+        assert!(req.is_read_only());
+        let req = CommandRequest::new_unitcomm(unit_comm::Action::Start, vec![]);
+        assert!(!req.is_read_only());
+        let req = CommandRequest::new_unitcomm(unit_comm::Action::Show, vec![]);
+        assert!(req.is_read_only());
+        let req = CommandRequest::new_unitcomm(unit_comm::Action::ResetFailed, vec![]);
+        assert!(!req.is_read_only());
+        let req = CommandRequest::new_mngrcomm(mngr_comm::Action::Listunits);
+        assert!(req.is_read_only());
+        let req = CommandRequest::new_mngrcomm(mngr_comm::Action::Reexec);
+        assert!(!req.is_read_only());
+        let req = CommandRequest::new_syscomm(sys_comm::Action::Poweroff, false);
+        assert!(!req.is_read_only());
+        let req = CommandRequest::new_jobcomm(job_comm::Action::List, vec![]);
+        assert!(req.is_read_only());
+        let req = CommandRequest::new_jobcomm(job_comm::Action::Cancel, vec!["1".to_string()]);
+        assert!(!req.is_read_only());
+        let req = CommandRequest::new_transient("run-1.service".to_string(), vec![]);
+        assert!(!req.is_read_only());
+        assert!(!CommandRequest::new_subscribe().is_read_only());
+        let props = vec![("MemoryMax".to_string(), "1G".to_string())];
+        let req = CommandRequest::new_set_property("a.service".to_string(), props, true);
+        assert!(!req.is_read_only());
+        let req = CommandRequest::new_kill(vec![], 15, kill_param::Whom::All, None);
+        assert!(!req.is_read_only());
+        let req = CommandRequest::new_unitfile(unit_file::Action::Cat, vec![]);
+        assert!(!req.is_read_only());
+        assert!(!CommandRequest::default().is_read_only());
+    }
+
+    fn kill_param(req: CommandRequest) -> KillParam {
+        match req.request_data {
+            Some(RequestData::Ucomm(param)) => {
+                assert_eq!(param.action(), unit_comm::Action::Kill);
+                param.kill.unwrap()
+            }
+            _ => panic!("not a unit command"),
+        }
+    }
+
+    #[test]
+    fn test_new_kill() {
+        let req = CommandRequest::new_kill(
+            vec!["a.service".to_string()],
+            34,
+            kill_param::Whom::Main,
+            Some(0),
+        );
+        let kill = kill_param(req);
+        assert_eq!(kill.signal, 34);
+        assert_eq!(kill.whom(), kill_param::Whom::Main);
+        assert_eq!(kill.queued_value(), Some(0));
+
+        let req = CommandRequest::new_kill(vec![], 15, kill_param::Whom::All, None);
+        assert_eq!(kill_param(req).queued_value(), None);
+    }
+}
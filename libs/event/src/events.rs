@@ -108,7 +108,11 @@ impl Events {
         }
 
         let top = first.unwrap();
-        let state = self.data.borrow().source_state(&top).unwrap();
+        // the source may be deleted after it's pushed to the pending queue
+        let state = match self.data.borrow().source_state(&top) {
+            Some(state) => state,
+            None => return Ok(0),
+        };
         match state {
             EventState::Off => {
                 println!("set_enabled Off: {top:?}");
@@ -126,8 +126,8 @@ impl HardwareWatchdog {
         Ok(self.file.as_ref().unwrap().as_raw_fd())
     }
 
-    #[allow(dead_code)]
-    fn set_device(&mut self, device: String) {
+    /// set the path of the device, the default is "/dev/watchdog0"
+    pub fn set_device(&mut self, device: String) {
         if self.device != device {
             self.device = device;
             self.file = None
@@ -169,7 +169,7 @@ impl Watchdog for HardwareWatchdog {
                     "Not Support Zero Timeout",
                 ));
             }
-            let secs = min(time.as_secs() as i32, i32::MAX);
+            let secs = min(time.as_secs(), i32::MAX as u64) as i32;
             match self.set_timeout(secs) {
                 Ok(_) => {
                     self.timeout = Some(Duration::from_secs(secs as u64));
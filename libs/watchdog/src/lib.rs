@@ -13,11 +13,8 @@
 //! hardware watchdog
 pub mod hardware;
 
-/// register timer
-pub fn register_timer() {}
+pub use hardware::{HardwareWatchdog, Watchdog};
 
-/// set enable state
-pub fn event_source_set_enabled(_enable: bool) {}
 #[cfg(test)]
 mod tests {
     #[test]
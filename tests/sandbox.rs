@@ -0,0 +1,187 @@
+// Copyright (c) 2022 Huawei Technologies Co.,Ltd. All rights reserved.
+//
+// sysMaster is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan
+// PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//         http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY
+// KIND, EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO
+// NON-INFRINGEMENT, MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+
+//! the sandbox is set up in a forked child, which is only safe in a single-threaded
+//! process, so these tests run without the test harness, one after another.
+use nix::sched::{unshare, CloneFlags};
+use nix::sys::wait::{waitpid, WaitStatus};
+use nix::unistd::{self, ForkResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+use sysmaster::exec::{
+    private_tmp_create, private_tmp_dir, private_tmp_remove, ExecSandbox, ProtectSystem,
+};
+
+// the exit code of the child if the user namespace is not supported
+const SKIP: i32 = 77;
+
+// the device nodes that are kept by PrivateDevices=
+const PRIVATE_DEVICES: [&str; 6] = ["null", "zero", "full", "random", "urandom", "tty"];
+
+fn main() {
+    let tests: [(&str, fn()); 3] = [
+        ("test_sandbox_paths", test_sandbox_paths),
+        ("test_sandbox_protect", test_sandbox_protect),
+        ("test_sandbox_private_tmp", test_sandbox_private_tmp),
+    ];
+    for (name, test) in tests {
+        println!("test {name} ...");
+        test();
+    }
+    println!("test result: ok. {} passed", tests.len());
+}
+
+fn enter_user_namespace() -> bool {
+    let uid = unistd::getuid();
+    let gid = unistd::getgid();
+    if unshare(CloneFlags::CLONE_NEWUSER).is_err() {
+        return false;
+    }
+    fs::write("/proc/self/setgroups", "deny").is_ok()
+        && fs::write("/proc/self/uid_map", format!("0 {uid} 1")).is_ok()
+        && fs::write("/proc/self/gid_map", format!("0 {gid} 1")).is_ok()
+}
+
+// run the check in a child process which sets up the sandbox in a new user namespace,
+// so that it can be tested without privilege, return false if it's skipped
+fn run_in_sandbox<F: FnOnce() -> bool>(sandbox: &ExecSandbox, check: F) -> bool {
+    match unsafe { unistd::fork() }.unwrap() {
+        ForkResult::Child => {
+            let code = if !enter_user_namespace() {
+                SKIP
+            } else if let Err(e) = sandbox.setup() {
+                eprintln!("Failed to set up the sandbox: {e}");
+                1
+            } else if check() {
+                0
+            } else {
+                1
+            };
+            unsafe { libc::_exit(code) };
+        }
+        ForkResult::Parent { child } => match waitpid(child, None).unwrap() {
+            WaitStatus::Exited(_, SKIP) => {
+                println!("user namespace is not supported, skip");
+                false
+            }
+            status => {
+                assert_eq!(status, WaitStatus::Exited(child, 0));
+                true
+            }
+        },
+    }
+}
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("sandbox-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn writable(path: &Path) -> bool {
+    fs::write(path, "foo").is_ok()
+}
+
+fn test_sandbox_paths() {
+    let dir = test_dir("paths");
+    for sub in ["ro/rw", "hidden", "src", "dst"] {
+        fs::create_dir_all(dir.join(sub)).unwrap();
+    }
+    fs::write(dir.join("hidden/secret"), "foo").unwrap();
+    fs::write(dir.join("file"), "foo").unwrap();
+    fs::write(dir.join("src/data"), "bar").unwrap();
+
+    let path = |sub: &str| dir.join(sub).to_str().unwrap().to_string();
+    let mut sandbox = ExecSandbox::new();
+    sandbox.add_read_only_paths(&[path("ro")]).unwrap();
+    sandbox.add_read_write_paths(&[path("ro/rw")]).unwrap();
+    sandbox
+        .add_inaccessible_paths(&[path("hidden"), path("file"), format!("-{}", path("none"))])
+        .unwrap();
+    sandbox
+        .add_bind_paths(&[format!("{}:{}", path("src"), path("dst"))])
+        .unwrap();
+
+    let ran = run_in_sandbox(&sandbox, || {
+        !writable(&dir.join("ro/foo"))
+            && writable(&dir.join("ro/rw/foo"))
+            && !dir.join("hidden/secret").exists()
+            && !writable(&dir.join("hidden/foo"))
+            && fs::read_to_string(dir.join("file")).is_err()
+            && fs::read_to_string(dir.join("dst/data")).unwrap() == "bar"
+            && writable(&dir.join("dst/foo"))
+    });
+
+    if ran {
+        // the mounts are not propagated to the host
+        assert!(dir.join("hidden/secret").exists());
+        assert!(dir.join("ro/rw/foo").exists());
+        assert!(dir.join("src/foo").exists());
+        assert!(!dir.join("dst/data").exists());
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn test_sandbox_protect() {
+    let dir = test_dir("protect");
+    fs::create_dir_all(dir.join("rw")).unwrap();
+
+    let mut sandbox = ExecSandbox::new();
+    sandbox.set_protect_system(ProtectSystem::Strict);
+    sandbox.set_private_devices(true);
+    sandbox.set_runtime_dir(dir.join("run"));
+    sandbox
+        .add_read_write_paths(&[dir.join("rw").to_str().unwrap().to_string()])
+        .unwrap();
+
+    let ran = run_in_sandbox(&sandbox, || {
+        let devices = ["pts", "shm", "fd", "stdin", "stdout", "stderr"];
+        !writable(&dir.join("foo"))
+            && writable(&dir.join("rw/foo"))
+            && writable(Path::new("/dev/null"))
+            && fs::read_dir("/dev").unwrap().flatten().all(|e| {
+                let name = e.file_name().to_str().unwrap().to_string();
+                PRIVATE_DEVICES.contains(&name.as_str()) || devices.contains(&name.as_str())
+            })
+    });
+
+    if ran {
+        assert!(dir.join("rw/foo").exists());
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn test_sandbox_private_tmp() {
+    let dir = test_dir("tmp");
+    let private_tmp = private_tmp_dir(&dir, "foo.service");
+    private_tmp_create(&private_tmp).unwrap();
+
+    let mut sandbox = ExecSandbox::new();
+    sandbox.set_private_tmp(Some(private_tmp.clone()));
+
+    // the private /tmp is shared by the processes of the unit
+    let name = format!("sandbox-{}", std::process::id());
+    let ran = run_in_sandbox(&sandbox, || writable(&Path::new("/tmp").join(&name)));
+    if ran {
+        let ran = run_in_sandbox(&sandbox, || {
+            fs::read_dir("/tmp").unwrap().count() == 1
+                && fs::read_to_string(Path::new("/tmp").join(&name)).unwrap() == "foo"
+        });
+        assert!(ran);
+        assert!(private_tmp.join("tmp").join(&name).exists());
+        assert!(!Path::new("/tmp").join(&name).exists());
+    }
+
+    private_tmp_remove(&private_tmp).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+}